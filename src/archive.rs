@@ -1,22 +1,153 @@
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, Read};
+use std::io::{self, Cursor, Read};
 
+use crate::math::{Vec3, Vec4};
+use crate::parse::ParseContext;
 use crate::CfxResult;
 
+/// Default for the most elements `read_array_*` accept, whatever the
+/// remaining data allows.
+pub const MAX_ARRAY_LEN: usize = 1 << 20;
+
 pub trait FArchive {
     fn read_bytes(&mut self, buffer: &mut [u8]) -> CfxResult<usize>;
     fn set_position(&mut self, pos: u64) -> CfxResult<()>;
+    fn position(&self) -> u64;
+    /// Length of the data the position currently points into.
+    fn len(&self) -> u64;
+
+    /// Bytes left between the position and [`FArchive::len`].
+    fn remaining(&self) -> u64 {
+        self.len().saturating_sub(self.position())
+    }
 }
 
 pub trait FArchiveExt: FArchive {
+    fn read_bytes_exact(&mut self, buffer: &mut [u8]) -> CfxResult<()>;
+    #[allow(dead_code)]
+    fn read_remaining(&mut self) -> CfxResult<Vec<u8>>;
+    #[allow(dead_code)]
+    fn read_bool(&mut self, context: &mut ParseContext) -> CfxResult<bool>;
     fn read_uint(&mut self) -> CfxResult<u32>;
     fn read_int(&mut self) -> CfxResult<i32>;
+    #[allow(dead_code)]
+    fn read_varint(&mut self) -> CfxResult<u64>;
+    #[allow(dead_code)]
+    fn read_svarint(&mut self) -> CfxResult<i64>;
+    fn skip(&mut self, n: u64) -> CfxResult<()>;
+    #[allow(dead_code)]
+    fn seek_back(&mut self, count: u64) -> CfxResult<()>;
+    #[allow(dead_code)]
+    fn align_to(&mut self, alignment: u64) -> CfxResult<()>;
+    #[allow(dead_code)]
+    fn expect_padding_zero(&mut self, n: u64, context: &mut ParseContext) -> CfxResult<()>;
+    fn read_vec3(&mut self) -> CfxResult<Vec3>;
+    fn read_vec4(&mut self) -> CfxResult<Vec4>;
+    #[allow(dead_code)]
+    fn read_array_u32(&mut self, count: usize, context: &mut ParseContext) -> CfxResult<Vec<u32>>;
+    #[allow(dead_code)]
+    fn read_array_u16(&mut self, count: usize, context: &mut ParseContext) -> CfxResult<Vec<u16>>;
+    #[allow(dead_code)]
+    fn read_utf16_string(&mut self, char_count: usize) -> CfxResult<String>;
+}
+
+/// Reads `N` little-endian `f32`s.
+fn read_floats<const N: usize>(archive: &mut impl FArchive) -> CfxResult<[f32; N]> {
+    let mut result = [0f32; N];
+    for value in result.iter_mut() {
+        let mut buffer = [0u8; 4];
+        archive.read_bytes(&mut buffer)?;
+        *value = f32::from_le_bytes(buffer);
+    }
+
+    Ok(result)
+}
+
+/// Reads `count` elements of `size` bytes, checking `count` against the
+/// `max_items` option and the remaining data before allocating anything. An
+/// over-reported count is a deviation, leniently truncated to the elements
+/// that are there.
+pub fn read_array<T>(
+    archive: &mut impl FArchive,
+    count: usize,
+    size: usize,
+    context: &mut ParseContext,
+    decode: impl Fn(&[u8]) -> T,
+) -> CfxResult<Vec<T>> {
+    let max_items = context.options.max_items;
+    if count > max_items {
+        return Err(format!("array of {count} elements exceeds the limit of {max_items}").into());
+    }
+
+    let mut len = count * size;
+    let remaining = archive.remaining();
+    if len as u64 > remaining {
+        context.deviation(
+            archive.position(),
+            format!("array of {count} elements needs {len} bytes but there were only {remaining} bytes left"),
+        )?;
+        len = remaining as usize / size * size;
+    }
+
+    let mut buffer = vec![0u8; len];
+    archive.read_bytes(&mut buffer)?;
+    Ok(buffer.chunks_exact(size).map(decode).collect())
 }
 
 impl<Archive> FArchiveExt for Archive
 where
     Archive: FArchive,
 {
+    /// Fills all of `buffer`, failing on a short read where
+    /// [`FArchive::read_bytes`] returns how much it read.
+    fn read_bytes_exact(&mut self, buffer: &mut [u8]) -> CfxResult<()> {
+        let start = self.position();
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let read = self.read_bytes(&mut buffer[filled..])?;
+            if read == 0 {
+                return Err(format!(
+                    "short read at {start:#x}: got {filled} of {} bytes",
+                    buffer.len()
+                )
+                .into());
+            }
+
+            filled += read;
+        }
+
+        Ok(())
+    }
+
+    /// Reads everything from the position to [`FArchive::len`], leaving the
+    /// position at the end. Streamed archives have no known end and fail.
+    fn read_remaining(&mut self) -> CfxResult<Vec<u8>> {
+        if self.len() == u64::MAX {
+            return Err(format!(
+                "cannot read the rest of an unbounded archive at {:#x}",
+                self.position()
+            )
+            .into());
+        }
+
+        let mut buffer = vec![0u8; self.remaining() as usize];
+        self.read_bytes_exact(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Reads a one-byte boolean, where any non-zero byte is `true`. Bytes
+    /// other than 0 and 1 are a deviation, rejected in strict mode.
+    fn read_bool(&mut self, context: &mut ParseContext) -> CfxResult<bool> {
+        let offset = self.position();
+        let mut buffer = [0u8];
+        self.read_bytes_exact(&mut buffer)?;
+        if buffer[0] > 1 {
+            context.deviation(offset, format!("boolean byte is {:#04x}", buffer[0]))?;
+        }
+
+        Ok(buffer[0] != 0)
+    }
+
     fn read_uint(&mut self) -> CfxResult<u32> {
         let mut buffer = [0u8; 4];
         self.read_bytes(&mut buffer)?;
@@ -36,6 +167,146 @@ where
 
         Ok(result)
     }
+
+    /// Reads an unsigned LEB128 integer, rejecting encodings that are longer
+    /// than needed or overflow 64 bits.
+    fn read_varint(&mut self) -> CfxResult<u64> {
+        // A `u64` never takes more than ten 7-bit groups
+        const MAX_VARINT_LEN: usize = 10;
+
+        let mut result = 0u64;
+        for i in 0..MAX_VARINT_LEN {
+            let mut byte = [0u8; 1];
+            self.read_bytes(&mut byte)?;
+
+            let group = (byte[0] & 0x7F) as u64;
+            if i == MAX_VARINT_LEN - 1 && group > 1 {
+                return Err("varint overflows 64 bits".into());
+            }
+
+            result |= group << (7 * i);
+            if byte[0] & 0x80 == 0 {
+                if i > 0 && byte[0] == 0 {
+                    return Err(format!("overlong varint encoding ({} bytes)", i + 1).into());
+                }
+
+                return Ok(result);
+            }
+        }
+
+        Err("varint overflows 64 bits".into())
+    }
+
+    /// Reads a zig-zag encoded signed LEB128 integer.
+    fn read_svarint(&mut self) -> CfxResult<i64> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Advances the position by `n` bytes without reading them.
+    fn skip(&mut self, n: u64) -> CfxResult<()> {
+        let remaining = self.remaining();
+        if n > remaining {
+            return Err(format!(
+                "tried to skip {n} bytes at {:#x} but there were only {remaining} bytes left",
+                self.position()
+            )
+            .into());
+        }
+
+        self.set_position(self.position() + n)
+    }
+
+    /// Moves the position back by `count` bytes, to re-read what was peeked.
+    fn seek_back(&mut self, count: u64) -> CfxResult<()> {
+        let position = self.position();
+        if count > position {
+            return Err(format!("tried to seek back {count} bytes at {position:#x}").into());
+        }
+
+        self.set_position(position - count)
+    }
+
+    /// Skips to the next multiple of `alignment`, staying put when aligned.
+    fn align_to(&mut self, alignment: u64) -> CfxResult<()> {
+        if alignment == 0 {
+            return Err("alignment must not be 0".into());
+        }
+
+        let misalignment = self.position() % alignment;
+        if misalignment == 0 {
+            return Ok(());
+        }
+
+        self.skip(alignment - misalignment)
+    }
+
+    /// Skips `n` bytes of padding. Padding that is not all zero, which usually
+    /// means the structure before was parsed with the wrong layout, or that
+    /// runs past the end is a deviation.
+    fn expect_padding_zero(&mut self, n: u64, context: &mut ParseContext) -> CfxResult<()> {
+        let start = self.position();
+        let remaining = self.remaining();
+        let mut padding = vec![0u8; n.min(remaining) as usize];
+        if n > remaining {
+            context.deviation(
+                start,
+                format!("{n} bytes of padding but there were only {remaining} bytes left"),
+            )?;
+        }
+
+        self.read_bytes(&mut padding)?;
+        if let Some(offset) = padding.iter().position(|&byte| byte != 0) {
+            context.deviation(
+                start + offset as u64,
+                "padding is not zero, the preceding structure may be misaligned",
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn read_vec3(&mut self) -> CfxResult<Vec3> {
+        Ok(read_floats::<3>(self)?.into())
+    }
+
+    fn read_vec4(&mut self) -> CfxResult<Vec4> {
+        Ok(read_floats::<4>(self)?.into())
+    }
+
+    fn read_array_u32(&mut self, count: usize, context: &mut ParseContext) -> CfxResult<Vec<u32>> {
+        read_array(self, count, 4, context, |bytes| {
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        })
+    }
+
+    fn read_array_u16(&mut self, count: usize, context: &mut ParseContext) -> CfxResult<Vec<u16>> {
+        read_array(self, count, 2, context, |bytes| {
+            u16::from_le_bytes([bytes[0], bytes[1]])
+        })
+    }
+
+    /// Reads `char_count` little-endian UTF-16 code units, checked against
+    /// the remaining data before allocating. Unpaired surrogates decode to
+    /// U+FFFD.
+    fn read_utf16_string(&mut self, char_count: usize) -> CfxResult<String> {
+        let remaining = self.remaining();
+        let len = char_count
+            .checked_mul(2)
+            .filter(|&len| len as u64 <= remaining)
+            .ok_or_else(|| {
+                format!("string of {char_count} UTF-16 units needs more than the {remaining} bytes left")
+            })?;
+
+        let mut buffer = vec![0u8; len];
+        self.read_bytes_exact(&mut buffer)?;
+        let units = buffer
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect::<Vec<_>>();
+
+        Ok(String::from_utf16_lossy(&units))
+    }
 }
 
 pub struct FMemoryArchive<Data>
@@ -63,11 +334,11 @@ where
     Data: AsRef<[u8]>,
 {
     fn read_bytes(&mut self, buffer: &mut [u8]) -> CfxResult<usize> {
-        let buffer_len = buffer.len();
-        let total_len = self.cursor.position() as usize + buffer_len;
-        if total_len > self.len {
+        let remaining = self.remaining();
+        if buffer.len() as u64 > remaining {
             return Err(format!(
-                "tried to read {buffer_len} bytes but there were only {total_len} bytes left"
+                "tried to read {} bytes but there were only {remaining} bytes left",
+                buffer.len()
             )
             .into());
         }
@@ -80,10 +351,92 @@ where
         self.cursor.set_position(pos);
         Ok(())
     }
+
+    fn position(&self) -> u64 {
+        self.cursor.position()
+    }
+
+    fn len(&self) -> u64 {
+        self.len as u64
+    }
+}
+
+/// Forward-only archive over a stream such as an inflating page, for reading
+/// headers without holding the whole data in memory. Positions start at
+/// `base`, seeking forward skips over the data in between, and seeking back
+/// is an error: data that needs random access has to be read into memory.
+pub struct FStreamArchive<Inner: Read> {
+    inner: Inner,
+    pos: u64,
+}
+
+impl<Inner: Read> FStreamArchive<Inner> {
+    pub fn new(inner: Inner, base: u64) -> Self {
+        Self { inner, pos: base }
+    }
+
+    /// Reads the rest of the stream, returning the position at its end.
+    pub fn finish(mut self) -> CfxResult<u64> {
+        self.pos += io::copy(&mut self.inner, &mut io::sink())?;
+        Ok(self.pos)
+    }
+}
+
+impl<Inner: Read> FArchive for FStreamArchive<Inner> {
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> CfxResult<usize> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.inner.read(&mut buffer[filled..]) {
+                Ok(0) => {
+                    return Err(format!(
+                        "tried to read {} bytes at {:#x} but the stream ended after {filled}",
+                        buffer.len(),
+                        self.pos
+                    )
+                    .into())
+                }
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        self.pos += filled as u64;
+        Ok(filled)
+    }
+
+    fn set_position(&mut self, pos: u64) -> CfxResult<()> {
+        if pos < self.pos {
+            return Err(format!(
+                "cannot move back from {:#x} to {pos:#x} in a streamed page",
+                self.pos
+            )
+            .into());
+        }
+
+        let skipped = io::copy(&mut (&mut self.inner).take(pos - self.pos), &mut io::sink())?;
+        self.pos += skipped;
+        if self.pos != pos {
+            return Err(format!("the stream ends at {:#x}, before {pos:#x}", self.pos).into());
+        }
+
+        Ok(())
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Unknown until the stream is read to its end, so unbounded.
+    fn len(&self) -> u64 {
+        u64::MAX
+    }
 }
 
-const VIRTUAL_BASE: u64 = 0x50000000;
-const PHYSICAL_BASE: u64 = 0x60000000;
+/// Address of the start of the virtual segment, where a resource's structure
+/// begins.
+pub const VIRTUAL_BASE: u64 = 0x50000000;
+pub const PHYSICAL_BASE: u64 = 0x60000000;
 
 pub struct FResourceArchive<Data>
 where
@@ -112,6 +465,48 @@ where
 
         Ok(u64::from_le_bytes(buffer))
     }
+
+    /// Reads a pointer field, checking that it addresses the virtual or the
+    /// physical segment.
+    #[allow(dead_code)]
+    pub fn follow_pointer(&mut self) -> CfxResult<u64> {
+        let start = self.pos;
+        let pointer = self.read_ulong()?;
+        if pointer & VIRTUAL_BASE != VIRTUAL_BASE && pointer & PHYSICAL_BASE != PHYSICAL_BASE {
+            return Err(format!(
+                "pointer {pointer:#x} read at {start:#x} is not a resource address"
+            )
+            .into());
+        }
+
+        Ok(pointer)
+    }
+
+    /// Runs `read` at `pointer` and moves back to the current position
+    /// afterwards, whether `read` succeeds or not.
+    pub fn read_at<T>(
+        &mut self,
+        pointer: u64,
+        read: impl FnOnce(&mut Self) -> CfxResult<T>,
+    ) -> CfxResult<T> {
+        let saved = self.pos;
+        self.pos = pointer;
+        let result = read(self);
+        self.pos = saved;
+
+        result
+    }
+
+    /// Base address and stream of the segment the position points into.
+    fn segment(&self) -> Option<(u64, &Cursor<Data>)> {
+        if (self.pos & VIRTUAL_BASE) == VIRTUAL_BASE {
+            Some((VIRTUAL_BASE, &self.virtual_stream))
+        } else if (self.pos & PHYSICAL_BASE) == PHYSICAL_BASE {
+            Some((PHYSICAL_BASE, &self.physical_stream))
+        } else {
+            None
+        }
+    }
 }
 
 impl<Data> FArchive for FResourceArchive<Data>
@@ -119,20 +514,27 @@ where
     Data: AsRef<[u8]>,
 {
     fn read_bytes(&mut self, buffer: &mut [u8]) -> CfxResult<usize> {
-        let mut base_position = 0x0;
-        let mut cursor = if (self.pos & VIRTUAL_BASE) == VIRTUAL_BASE {
-            base_position = VIRTUAL_BASE;
-            &mut self.virtual_stream
+        let remaining = self.remaining();
+        let (base_position, cursor) = if (self.pos & VIRTUAL_BASE) == VIRTUAL_BASE {
+            (VIRTUAL_BASE, &mut self.virtual_stream)
         } else if (self.pos & PHYSICAL_BASE) == PHYSICAL_BASE {
-            base_position = PHYSICAL_BASE;
-            &mut self.physical_stream
+            (PHYSICAL_BASE, &mut self.physical_stream)
         } else {
             return Err(format!("Invalid position: {}", self.pos).into());
         };
 
-        cursor.set_position((self.pos & !base_position));
+        if buffer.len() as u64 > remaining {
+            return Err(format!(
+                "tried to read {} bytes at {:#x} but there were only {remaining} bytes left",
+                buffer.len(),
+                self.pos
+            )
+            .into());
+        }
+
+        cursor.set_position(self.pos & !base_position);
         let read = cursor.read(buffer)?;
-        self.pos = self.pos | base_position;
+        self.pos += read as u64;
 
         Ok(read)
     }
@@ -141,11 +543,62 @@ where
         self.pos = pos;
         Ok(())
     }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Length of the virtual or physical segment the position points into,
+    /// 0 outside of both.
+    fn len(&self) -> u64 {
+        self.segment()
+            .map_or(0, |(_, cursor)| cursor.get_ref().as_ref().len() as u64)
+    }
+
+    fn remaining(&self) -> u64 {
+        self.segment().map_or(0, |(base, cursor)| {
+            (cursor.get_ref().as_ref().len() as u64).saturating_sub(self.pos & !base)
+        })
+    }
 }
 
 #[cfg(test)]
 mod archive_tests {
     use super::*;
+    use crate::writer::{write_rsc7, FWriterExt};
+
+    /// Deterministic pseudo-random bits for round-trip properties.
+    fn random_bits(seed: &mut u64) -> u32 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*seed >> 32) as u32
+    }
+
+    #[test]
+    fn stream_archive_test() {
+        let data = (0u8..32).collect::<Vec<u8>>();
+        let mut archive = FStreamArchive::new(data.as_slice(), VIRTUAL_BASE);
+        assert_eq!(archive.read_uint().unwrap(), 0x03020100);
+
+        archive.set_position(VIRTUAL_BASE + 0x10).unwrap();
+        assert_eq!(archive.read_uint().unwrap(), 0x13121110);
+        let err = archive.set_position(VIRTUAL_BASE).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot move back from 0x50000014 to 0x50000000 in a streamed page"
+        );
+        assert_eq!(archive.finish().unwrap(), VIRTUAL_BASE + 32);
+
+        let mut archive = FStreamArchive::new(data.as_slice(), 0);
+        let err = archive.set_position(40).unwrap_err();
+        assert_eq!(err.to_string(), "the stream ends at 0x20, before 0x28");
+        let err = archive.read_uint().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "tried to read 4 bytes at 0x20 but the stream ended after 0"
+        );
+    }
 
     #[test]
     fn archive_len_test() {
@@ -177,4 +630,401 @@ mod archive_tests {
 
         assert!(result.is_err(), "read_bytes did not return an error");
     }
+
+    /// Hands out at most `chunk` bytes per read, and nothing past `len`.
+    struct ChunkedArchive {
+        data: Vec<u8>,
+        pos: usize,
+        chunk: usize,
+    }
+
+    impl FArchive for ChunkedArchive {
+        fn read_bytes(&mut self, buffer: &mut [u8]) -> CfxResult<usize> {
+            let read = buffer.len().min(self.chunk).min(self.data.len() - self.pos);
+            buffer[..read].copy_from_slice(&self.data[self.pos..self.pos + read]);
+            self.pos += read;
+            Ok(read)
+        }
+
+        fn set_position(&mut self, pos: u64) -> CfxResult<()> {
+            self.pos = pos as usize;
+            Ok(())
+        }
+
+        fn position(&self) -> u64 {
+            self.pos as u64
+        }
+
+        fn len(&self) -> u64 {
+            self.data.len() as u64
+        }
+    }
+
+    #[test]
+    fn archive_read_bytes_exact_test() {
+        let mut archive = ChunkedArchive {
+            data: vec![1, 2, 3, 4, 5],
+            pos: 0,
+            chunk: 2,
+        };
+
+        let mut buffer = [0u8; 4];
+        archive.read_bytes_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [1, 2, 3, 4]);
+
+        let err = archive.read_bytes_exact(&mut buffer).unwrap_err();
+        assert_eq!(err.to_string(), "short read at 0x4: got 1 of 4 bytes");
+
+        let mut archive = FMemoryArchive::new([1u8, 2]);
+        assert!(archive.read_bytes_exact(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn archive_read_utf16_string_test() {
+        // "Cfx€" followed by an unpaired high surrogate
+        let bytes = [0x43, 0x00, 0x66, 0x00, 0x78, 0x00, 0xac, 0x20, 0x3d, 0xd8];
+        let mut archive = FMemoryArchive::new(bytes);
+        assert_eq!(archive.read_utf16_string(4).unwrap(), "Cfx€");
+        assert_eq!(archive.read_utf16_string(1).unwrap(), "\u{fffd}");
+
+        let mut archive = FMemoryArchive::new(bytes);
+        let err = archive.read_utf16_string(6).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "string of 6 UTF-16 units needs more than the 10 bytes left"
+        );
+        assert_eq!(archive.position(), 0);
+        assert!(archive.read_utf16_string(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn archive_read_bool_test() {
+        let mut archive = FMemoryArchive::new([0u8, 1, 2]);
+        let mut context = ParseContext::strict();
+        assert!(!archive.read_bool(&mut context).unwrap());
+        assert!(archive.read_bool(&mut context).unwrap());
+
+        let err = archive.read_bool(&mut context).unwrap_err();
+        assert!(err.to_string().starts_with("boolean byte is 0x02 (at 0x2"));
+
+        archive.set_position(2).unwrap();
+        let mut lenient = ParseContext::default();
+        assert!(archive.read_bool(&mut lenient).unwrap());
+        assert_eq!(lenient.warnings.len(), 1);
+        assert!(archive.read_bool(&mut lenient).is_err());
+    }
+
+    #[test]
+    fn archive_position_test() {
+        let mut archive = FMemoryArchive::new([0u8; 8]);
+        assert_eq!(archive.position(), 0);
+
+        archive.read_uint().unwrap();
+        assert_eq!(archive.position(), 4);
+    }
+
+    #[test]
+    fn read_remaining_test() {
+        let mut archive = FMemoryArchive::new((0u8..10).collect::<Vec<u8>>());
+        archive.read_uint().unwrap();
+        assert_eq!(archive.read_remaining().unwrap(), [4, 5, 6, 7, 8, 9]);
+        assert_eq!((archive.position(), archive.remaining()), (10, 0));
+        assert!(archive.read_remaining().unwrap().is_empty());
+
+        let data = [1u8; 4];
+        let mut archive = FStreamArchive::new(data.as_slice(), 0);
+        assert!(archive.read_remaining().is_err());
+    }
+
+    #[test]
+    fn archive_remaining_test() {
+        let mut archive = FMemoryArchive::new([0u8; 10]);
+        assert_eq!((archive.position(), archive.remaining()), (0, 10));
+
+        archive.read_uint().unwrap();
+        assert_eq!((archive.position(), archive.remaining()), (4, 6));
+
+        archive.set_position(8).unwrap();
+        assert_eq!((archive.position(), archive.remaining()), (8, 2));
+        assert!(archive.read_uint().is_err());
+
+        archive.set_position(12).unwrap();
+        assert_eq!(archive.remaining(), 0);
+    }
+
+    #[test]
+    fn resource_archive_remaining_test() {
+        let mut archive = FResourceArchive::new(vec![1u8; 16], vec![2u8; 8]);
+        archive.set_position(VIRTUAL_BASE).unwrap();
+        assert_eq!((archive.len(), archive.remaining()), (16, 16));
+
+        assert_eq!(archive.read_ulong().unwrap(), 0x0101010101010101);
+        assert_eq!(archive.position(), VIRTUAL_BASE + 8);
+        assert_eq!(archive.remaining(), 8);
+
+        archive.set_position(PHYSICAL_BASE + 2).unwrap();
+        assert_eq!((archive.len(), archive.remaining()), (8, 6));
+        assert!(archive.read_ulong().is_err());
+
+        archive.set_position(0).unwrap();
+        assert_eq!((archive.len(), archive.remaining()), (0, 0));
+    }
+
+    #[test]
+    fn archive_skip_align_test() {
+        let mut archive = FMemoryArchive::new([0u8; 40]);
+
+        archive.skip(3).unwrap();
+        assert_eq!(archive.position(), 3);
+        archive.align_to(16).unwrap();
+        assert_eq!(archive.position(), 16);
+        archive.align_to(16).unwrap();
+        assert_eq!(archive.position(), 16);
+
+        assert!(archive.skip(25).is_err());
+        assert_eq!(archive.position(), 16);
+        archive.skip(24).unwrap();
+        assert!(archive.align_to(0).is_err());
+
+        let mut archive = FMemoryArchive::new([0u8; 17]);
+        archive.skip(1).unwrap();
+        assert!(archive.align_to(32).is_err());
+    }
+
+    #[test]
+    fn archive_seek_back_test() {
+        let mut archive = FMemoryArchive::new([1u8, 0, 0, 0, 2, 0, 0, 0]);
+        let mut buffer = [0u8; 8];
+        archive.read_bytes(&mut buffer).unwrap();
+
+        archive.seek_back(4).unwrap();
+        assert_eq!(archive.position(), 4);
+        assert_eq!(archive.read_uint().unwrap(), 2);
+
+        let err = archive.seek_back(9).unwrap_err();
+        assert_eq!(err.to_string(), "tried to seek back 9 bytes at 0x8");
+        assert_eq!(archive.position(), 8);
+        archive.seek_back(8).unwrap();
+        assert_eq!(archive.read_uint().unwrap(), 1);
+    }
+
+    #[test]
+    fn archive_padding_test() {
+        let mut context = ParseContext::default();
+        let mut archive = FMemoryArchive::new([0u8, 0, 0, 0, 0, 7, 0, 0]);
+        archive.expect_padding_zero(4, &mut context).unwrap();
+        assert_eq!(archive.position(), 4);
+        assert!(context.warnings.is_empty());
+
+        // Non-zero padding only warns
+        archive.expect_padding_zero(4, &mut context).unwrap();
+        assert_eq!(archive.position(), 8);
+        assert_eq!(context.warnings[0].offset, 5);
+        archive.expect_padding_zero(1, &mut context).unwrap();
+        assert_eq!(context.warnings.len(), 2);
+
+        archive.set_position(4).unwrap();
+        assert!(archive
+            .expect_padding_zero(4, &mut ParseContext::strict())
+            .is_err());
+
+        let mut archive = FResourceArchive::new(vec![0u8; 32], vec![]);
+        archive.set_position(VIRTUAL_BASE + 4).unwrap();
+        archive.align_to(16).unwrap();
+        assert_eq!(archive.position(), VIRTUAL_BASE + 16);
+        assert!(archive.skip(17).is_err());
+    }
+
+    #[test]
+    fn archive_read_rsc7_header_test() {
+        let data = write_rsc7(2, b"virtual", b"physical").unwrap();
+        let mut archive = FMemoryArchive::new(&data);
+
+        assert_eq!(archive.read_uint().unwrap(), 0x37435352);
+        assert_eq!(archive.read_uint().unwrap(), 0);
+        archive.set_position(16).unwrap();
+        assert_eq!(archive.read_int().unwrap(), 2);
+    }
+
+    #[test]
+    fn archive_read_varint_test() {
+        let cases: [(&[u8], u64); 8] = [
+            (&[0x00], 0),
+            (&[0x7F], 0x7F),
+            (&[0x80, 0x01], 0x80),
+            (&[0xFF, 0x7F], 0x3FFF),
+            (&[0x80, 0x80, 0x01], 0x4000),
+            (&[0xFF, 0xFF, 0x7F], 0x1FFFFF),
+            (&[0x80, 0x80, 0x80, 0x01], 0x200000),
+            (
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+                u64::MAX,
+            ),
+        ];
+
+        for (data, expected) in cases {
+            let mut archive = FMemoryArchive::new(data);
+            assert_eq!(archive.read_varint().unwrap(), expected, "{data:02x?}");
+            assert_eq!(archive.position(), data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn archive_read_varint_invalid_test() {
+        let cases: [&[u8]; 4] = [
+            // Overlong: 0 padded with a continuation byte
+            &[0x80, 0x00],
+            // Eleven bytes
+            &[0xFF; 11],
+            // Overflows 64 bits in the tenth byte
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02],
+            // Truncated
+            &[0x80, 0x80],
+        ];
+
+        for data in cases {
+            let mut archive = FMemoryArchive::new(data);
+            assert!(archive.read_varint().is_err(), "{data:02x?}");
+        }
+    }
+
+    #[test]
+    fn archive_read_svarint_test() {
+        let cases: [(&[u8], i64); 5] = [
+            (&[0x00], 0),
+            (&[0x01], -1),
+            (&[0x02], 1),
+            (&[0xFF, 0x7F], -0x2000),
+            (&[0x80, 0x80, 0x01], 0x2000),
+        ];
+
+        for (data, expected) in cases {
+            let mut archive = FMemoryArchive::new(data);
+            assert_eq!(archive.read_svarint().unwrap(), expected, "{data:02x?}");
+        }
+    }
+
+    #[test]
+    fn archive_vectors_round_trip_test() {
+        let mut seed = 0x5EED;
+        for _ in 0..256 {
+            let mut floats = [0f32; 4];
+            for float in floats.iter_mut() {
+                *float = f32::from_bits(random_bits(&mut seed));
+            }
+
+            let vec3 = Vec3::from([floats[0], floats[1], floats[2]]);
+            let vec4 = Vec4::from(floats);
+            let mut data = vec![];
+            data.write_vec3(vec3);
+            data.write_vec4(vec4);
+
+            let mut archive = FMemoryArchive::new(&data);
+            let read3 = <[f32; 3]>::from(archive.read_vec3().unwrap());
+            let read4 = <[f32; 4]>::from(archive.read_vec4().unwrap());
+
+            // Compared as bits, so NaNs round-trip too
+            assert_eq!(
+                read3.map(f32::to_bits),
+                [floats[0], floats[1], floats[2]].map(f32::to_bits)
+            );
+            assert_eq!(read4.map(f32::to_bits), floats.map(f32::to_bits));
+            assert_eq!(archive.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn archive_arrays_round_trip_test() {
+        let mut seed = 0xA11A;
+        for count in 0..64 {
+            let words = (0..count)
+                .map(|_| random_bits(&mut seed))
+                .collect::<Vec<u32>>();
+            let halves = (0..count)
+                .map(|_| random_bits(&mut seed) as u16)
+                .collect::<Vec<u16>>();
+
+            let mut data = vec![];
+            data.write_array_u32(&words);
+            data.write_array_u16(&halves);
+
+            let mut archive = FMemoryArchive::new(&data);
+            let mut context = ParseContext::strict();
+            assert_eq!(archive.read_array_u32(count, &mut context).unwrap(), words);
+            assert_eq!(archive.read_array_u16(count, &mut context).unwrap(), halves);
+        }
+    }
+
+    #[test]
+    fn archive_array_bounds_test() {
+        let mut archive = FMemoryArchive::new([0u8; 8]);
+        let mut context = ParseContext::strict();
+
+        assert!(archive
+            .read_array_u32(MAX_ARRAY_LEN + 1, &mut ParseContext::default())
+            .is_err());
+        assert!(archive.read_array_u32(3, &mut context).is_err());
+        assert_eq!(archive.position(), 0);
+        assert_eq!(archive.read_array_u16(4, &mut context).unwrap(), vec![0; 4]);
+    }
+
+    /// A count of 8 followed by only three elements.
+    #[test]
+    fn overcounted_array_fixture_test() {
+        let data = include_bytes!("../fixtures/deviations/overcounted_array.bin");
+
+        let mut archive = FMemoryArchive::new(data);
+        let count = archive.read_uint().unwrap() as usize;
+        let mut lenient = ParseContext::default();
+        assert_eq!(
+            archive.read_array_u32(count, &mut lenient).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(lenient.warnings.len(), 1);
+        assert_eq!(lenient.warnings[0].offset, 4);
+
+        archive.set_position(4).unwrap();
+        let err = archive
+            .read_array_u32(count, &mut ParseContext::strict())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("array of 8 elements needs 32 bytes"));
+    }
+
+    #[test]
+    fn resource_archive_pointer_test() {
+        let mut virtual_data = vec![0u8; 32];
+        virtual_data[..8].copy_from_slice(&(VIRTUAL_BASE + 0x10).to_le_bytes());
+        virtual_data[8..16].copy_from_slice(&(PHYSICAL_BASE + 4).to_le_bytes());
+        virtual_data[0x10..0x14].copy_from_slice(&0xCAFEu32.to_le_bytes());
+        let mut physical_data = vec![0u8; 8];
+        physical_data[4..].copy_from_slice(&0xBEEFu32.to_le_bytes());
+
+        let mut archive = FResourceArchive::new(virtual_data, physical_data);
+        archive.set_position(VIRTUAL_BASE).unwrap();
+
+        let pointer = archive.follow_pointer().unwrap();
+        let value = archive
+            .read_at(pointer, |archive| archive.read_uint())
+            .unwrap();
+        assert_eq!(value, 0xCAFE);
+        assert_eq!(archive.position(), VIRTUAL_BASE + 8);
+
+        let pointer = archive.follow_pointer().unwrap();
+        assert_eq!(
+            archive
+                .read_at(pointer, |archive| archive.read_uint())
+                .unwrap(),
+            0xBEEF
+        );
+        assert_eq!(archive.position(), VIRTUAL_BASE + 16);
+
+        // Restored after a failed read too, and null pointers are rejected
+        assert!(archive
+            .read_at(pointer, |archive| archive.read_ulong())
+            .is_err());
+        assert_eq!(archive.position(), VIRTUAL_BASE + 16);
+        assert!(archive.follow_pointer().is_err());
+    }
 }
@@ -0,0 +1,136 @@
+use std::fs::{copy, remove_file, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static DURABLE: AtomicBool = AtomicBool::new(false);
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes every later atomic write fsync the file and its directory before
+/// returning, set from `--durable`.
+pub fn set_durable(durable: bool) {
+    DURABLE.store(durable, Ordering::Relaxed);
+}
+
+/// A hidden sibling of `path` to write into before renaming. The process id
+/// and a counter keep concurrent runs and writes apart.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{name}.{}-{count}.tmp", std::process::id()))
+}
+
+/// Writes `bytes` to `path` so readers see either the old file or the whole
+/// new one, never a half-written file when the disk fills up.
+pub fn atomic_write(path: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> io::Result<()> {
+    atomic_write_with(path.as_ref(), |file| file.write_all(bytes.as_ref()))
+}
+
+/// Like `atomic_write`, for callers that stream into the file. When `write`
+/// fails the temp file is removed and `path` is left untouched.
+pub fn atomic_write_with<T>(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<T>,
+) -> io::Result<T> {
+    let temp = temp_path(path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)?;
+
+    let result = write(&mut file).and_then(|value| {
+        if DURABLE.load(Ordering::Relaxed) {
+            file.sync_all()?;
+        }
+
+        Ok(value)
+    });
+    drop(file);
+
+    let result = result.and_then(|value| persist(&temp, path).map(|_| value));
+    if result.is_err() {
+        let _ = remove_file(&temp);
+    }
+
+    result
+}
+
+fn persist(temp: &Path, path: &Path) -> io::Result<()> {
+    match std::fs::rename(temp, path) {
+        // The temp file is a sibling, but `path` may be a mount point of its own
+        Err(err) if err.kind() == ErrorKind::CrossesDevices => {
+            copy(temp, path)?;
+            remove_file(temp)?;
+        }
+        result => result?,
+    }
+
+    if DURABLE.load(Ordering::Relaxed) {
+        sync_parent(path)?;
+    }
+
+    Ok(())
+}
+
+/// Flushes the directory entry of the rename. Windows has no way to open a
+/// directory for this, and NTFS journals renames anyway.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read, read_dir, read_to_string, write};
+
+    #[test]
+    fn atomic_write_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fxmanifest.lua");
+        write(&path, "old").unwrap();
+
+        atomic_write(&path, "new").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "new");
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failed_write_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fxmanifest.lua");
+        write(&path, "old").unwrap();
+
+        let result = atomic_write_with(&path, |file| {
+            file.write_all(b"half")?;
+            Err::<(), _>(io::Error::other("disk full"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(read(&path).unwrap(), b"old");
+        assert_eq!(read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn temp_path_test() {
+        let path = Path::new("resource/fxmanifest.lua");
+        let first = temp_path(path);
+        let second = temp_path(path);
+
+        assert_eq!(first.parent(), path.parent());
+        assert!(first
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(".fxmanifest.lua."));
+        assert_ne!(first, second);
+    }
+}
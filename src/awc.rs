@@ -0,0 +1,89 @@
+use crate::archive::{FArchiveExt, FMemoryArchive};
+use crate::magic::{AWC_MAGIC, AWC_MAGIC_BIG_ENDIAN};
+use crate::CfxResult;
+
+/// Magic, version and flags, stream count and data offset.
+const AWC_HEADER_SIZE: usize = 16;
+
+/// Header of an audio wave container (`.awc`).
+#[derive(Debug, PartialEq)]
+pub struct AwcHeader {
+    pub version: u16,
+    pub flags: u16,
+    pub stream_count: u32,
+    pub data_offset: u32,
+}
+
+impl AwcHeader {
+    pub fn parse(data: &[u8]) -> CfxResult<Self> {
+        if data.len() < AWC_HEADER_SIZE {
+            return Err(format!("too short for an AWC header ({} bytes)", data.len()).into());
+        }
+
+        let mut archive = FMemoryArchive::new(data);
+        match archive.read_uint()? {
+            AWC_MAGIC => {}
+            AWC_MAGIC_BIG_ENDIAN => {
+                return Err("big-endian AWC containers are not supported".into())
+            }
+            magic => return Err(format!("invalid magic {magic:#010x}").into()),
+        }
+
+        let version_flags = archive.read_uint()?;
+        let header = Self {
+            version: (version_flags & 0xFFFF) as u16,
+            flags: (version_flags >> 16) as u16,
+            stream_count: archive.read_uint()?,
+            data_offset: archive.read_uint()?,
+        };
+
+        if header.version != 1 {
+            return Err(format!("unsupported version {}", header.version).into());
+        }
+
+        if header.data_offset as usize > data.len() {
+            return Err(format!(
+                "data offset {:#x} is past the end of the file ({:#x} bytes)",
+                header.data_offset,
+                data.len()
+            )
+            .into());
+        }
+
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_header(magic: u32, version_flags: u32, data_offset: u32) -> Vec<u8> {
+        let mut result = vec![];
+        for value in [magic, version_flags, 2, data_offset] {
+            result.extend_from_slice(&value.to_le_bytes());
+        }
+
+        result.resize(0x40, 0);
+        result
+    }
+
+    #[test]
+    fn awc_header_test() {
+        let header = AwcHeader::parse(&build_header(AWC_MAGIC, 0xFF00_0001, 0x20)).unwrap();
+        assert_eq!(
+            header,
+            AwcHeader {
+                version: 1,
+                flags: 0xFF00,
+                stream_count: 2,
+                data_offset: 0x20
+            }
+        );
+
+        assert!(AwcHeader::parse(&build_header(AWC_MAGIC_BIG_ENDIAN, 1, 0x20)).is_err());
+        assert!(AwcHeader::parse(&build_header(AWC_MAGIC, 2, 0x20)).is_err());
+        assert!(AwcHeader::parse(&build_header(AWC_MAGIC, 1, 0x100)).is_err());
+        assert!(AwcHeader::parse(b"ADAT").is_err());
+    }
+}
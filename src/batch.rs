@@ -0,0 +1,75 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Totals of a run over several files, accumulated while iterating and
+/// reported once at the end.
+#[derive(Debug, Serialize)]
+pub struct BatchStats {
+    pub processed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Plain text files copied instead of unpacked
+    pub passed_through: usize,
+    /// Decompressed bytes produced by the successful files
+    pub bytes: u64,
+    pub elapsed_secs: f64,
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl BatchStats {
+    pub fn start() -> Self {
+        Self {
+            processed: 0,
+            succeeded: 0,
+            failed: 0,
+            passed_through: 0,
+            bytes: 0,
+            elapsed_secs: 0.0,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn record_success(&mut self, bytes: u64) {
+        self.processed += 1;
+        self.succeeded += 1;
+        self.bytes += bytes;
+    }
+
+    pub fn record_passthrough(&mut self) {
+        self.processed += 1;
+        self.passed_through += 1;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.processed += 1;
+        self.failed += 1;
+    }
+
+    pub fn finish(&mut self) {
+        self.elapsed_secs = self.started.elapsed().as_secs_f64();
+    }
+}
+
+impl fmt::Display for BatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elapsed = Duration::from_millis((self.elapsed_secs * 1000.0) as u64);
+        write!(
+            f,
+            "Processed {} file(s): {} succeeded, {} failed, ",
+            self.processed, self.succeeded, self.failed
+        )?;
+        if self.passed_through > 0 {
+            write!(f, "{} passed through, ", self.passed_through)?;
+        }
+
+        write!(
+            f,
+            "{} bytes decompressed in {}",
+            self.bytes,
+            humantime::format_duration(elapsed)
+        )
+    }
+}
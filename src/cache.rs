@@ -0,0 +1,350 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::atomic::atomic_write;
+use crate::config::{cache_dir, Config};
+use crate::lock::{lock_path, FileLock};
+use crate::CfxResult;
+
+/// Size the cache is trimmed back to when the config sets no `cache_max_mb`.
+pub const DEFAULT_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+const INDEX_FILE: &str = "index.json";
+const BLOBS_DIR: &str = "blobs";
+
+/// A logical name in the index, such as `template:<url>@<rev>`, and the blob
+/// it currently points to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// SHA-256 of the blob, in hex
+    pub hash: String,
+    pub size: u64,
+    /// Unix timestamp of the last put or get, eviction drops the oldest first
+    pub last_access: u64,
+}
+
+type Index = BTreeMap<String, CacheEntry>;
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct GcReport {
+    /// Names that were evicted or pointed to a missing blob
+    pub removed_entries: Vec<String>,
+    pub removed_blobs: usize,
+    pub freed: u64,
+}
+
+/// Content-addressed store for downloads. Blobs live under `blobs/` named by
+/// their SHA-256, so names sharing content share one file, and `index.json`
+/// maps names to blobs. The index is only changed while holding its lock.
+pub struct Cache {
+    root: PathBuf,
+    max_size: u64,
+}
+
+impl Cache {
+    /// The store below the cache directory, limited to the configured size.
+    pub fn open() -> CfxResult<Self> {
+        let max_size = Config::load()?
+            .cache_max_mb
+            .map_or(DEFAULT_MAX_SIZE, |mb| mb * 1024 * 1024);
+
+        Ok(Self::at(cache_dir()?.join("store"), max_size))
+    }
+
+    pub fn at(root: PathBuf, max_size: u64) -> Self {
+        Self { root, max_size }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join(BLOBS_DIR).join(&hash[..2]).join(hash)
+    }
+
+    /// Stores `bytes` under `name` and evicts the least recently used other
+    /// names while the cache is over its size limit.
+    pub fn put(&self, name: &str, bytes: &[u8]) -> CfxResult<CacheEntry> {
+        let entry = CacheEntry {
+            hash: hex(&Sha256::digest(bytes)),
+            size: bytes.len() as u64,
+            last_access: now(),
+        };
+
+        // Written under the lock, a concurrent gc would delete it as unused
+        self.update(|cache, index| {
+            let blob = cache.blob_path(&entry.hash);
+            if !blob.exists() {
+                create_dir_all(blob.parent().unwrap())?;
+                atomic_write(&blob, bytes)?;
+            }
+
+            index.insert(name.to_owned(), entry.clone());
+            cache.evict(index, Some(name))
+        })?;
+
+        Ok(entry)
+    }
+
+    /// Path of the blob stored under `name`, marking it as recently used.
+    /// A name whose blob has gone missing counts as not cached.
+    pub fn get(&self, name: &str) -> CfxResult<Option<PathBuf>> {
+        self.update(|cache, index| {
+            let Some(entry) = index.get_mut(name) else {
+                return Ok(None);
+            };
+
+            let blob = cache.blob_path(&entry.hash);
+            if !blob.is_file() {
+                index.remove(name);
+                return Ok(None);
+            }
+
+            entry.last_access = now();
+            Ok(Some(blob))
+        })
+    }
+
+    pub fn entries(&self) -> CfxResult<Index> {
+        self.load_index()
+    }
+
+    /// Deletes every blob and the index.
+    pub fn clear(&self) -> CfxResult<GcReport> {
+        self.update(|cache, index| {
+            let sizes = cache.blob_sizes()?;
+            let blobs = cache.root.join(BLOBS_DIR);
+            if blobs.exists() {
+                remove_dir_all(blobs)?;
+            }
+
+            Ok(GcReport {
+                removed_entries: std::mem::take(index).into_keys().collect(),
+                removed_blobs: sizes.len(),
+                freed: sizes.values().sum(),
+            })
+        })
+    }
+
+    /// Drops names whose blob is missing, deletes blobs no name points to,
+    /// then evicts down to the size limit.
+    pub fn gc(&self) -> CfxResult<GcReport> {
+        self.update(|cache, index| {
+            let mut report = GcReport::default();
+            index.retain(|name, entry| {
+                let exists = cache.blob_path(&entry.hash).is_file();
+                if !exists {
+                    report.removed_entries.push(name.clone());
+                }
+
+                exists
+            });
+
+            let evicted = cache.evict(index, None)?;
+            report.removed_entries.extend(evicted.removed_entries);
+            report.removed_blobs += evicted.removed_blobs;
+            report.freed += evicted.freed;
+
+            let referenced = index
+                .values()
+                .map(|entry| entry.hash.as_str())
+                .collect::<BTreeSet<_>>();
+            for (hash, size) in cache.blob_sizes()? {
+                if !referenced.contains(hash.as_str()) {
+                    remove_file(cache.blob_path(&hash))?;
+                    report.removed_blobs += 1;
+                    report.freed += size;
+                }
+            }
+
+            Ok(report)
+        })
+    }
+
+    /// Removes the least recently used names, and the blobs only they used,
+    /// until the blobs fit in the size limit. `keep` is never evicted.
+    fn evict(&self, index: &mut Index, keep: Option<&str>) -> CfxResult<GcReport> {
+        let mut report = GcReport::default();
+        let mut size = unique_size(index);
+
+        let mut by_access = index
+            .iter()
+            .filter(|(name, _)| Some(name.as_str()) != keep)
+            .map(|(name, entry)| (entry.last_access, name.clone()))
+            .collect::<Vec<_>>();
+        by_access.sort();
+
+        for (_, name) in by_access {
+            if size <= self.max_size {
+                break;
+            }
+
+            let entry = index.remove(&name).unwrap();
+            report.removed_entries.push(name);
+            if index.values().any(|other| other.hash == entry.hash) {
+                continue;
+            }
+
+            let blob = self.blob_path(&entry.hash);
+            if blob.exists() {
+                remove_file(blob)?;
+            }
+
+            report.removed_blobs += 1;
+            report.freed += entry.size;
+            size -= entry.size;
+        }
+
+        Ok(report)
+    }
+
+    /// Sizes of the blobs on disk by hash, whether or not a name uses them.
+    fn blob_sizes(&self) -> CfxResult<BTreeMap<String, u64>> {
+        let mut sizes = BTreeMap::new();
+        let blobs = self.root.join(BLOBS_DIR);
+        if !blobs.is_dir() {
+            return Ok(sizes);
+        }
+
+        for prefix in read_dir(blobs)? {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+
+            for blob in read_dir(prefix)? {
+                let blob = blob?;
+                let name = blob.file_name().to_string_lossy().to_string();
+                // Temp files of interrupted writes start with a dot
+                if !name.starts_with('.') {
+                    sizes.insert(name, blob.metadata()?.len());
+                }
+            }
+        }
+
+        Ok(sizes)
+    }
+
+    fn load_index(&self) -> CfxResult<Index> {
+        let path = self.root.join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Index::new());
+        }
+
+        serde_json::from_str(&read_to_string(&path)?)
+            .map_err(|err| format!("Invalid cache index {path:?}: {err}").into())
+    }
+
+    /// Loads, changes and saves the index while holding its lock.
+    fn update<T>(&self, change: impl FnOnce(&Self, &mut Index) -> CfxResult<T>) -> CfxResult<T> {
+        let path = self.root.join(INDEX_FILE);
+        let _lock = FileLock::acquire(&lock_path(&path))?;
+        let mut index = self.load_index()?;
+        let result = change(self, &mut index)?;
+        atomic_write(&path, serde_json::to_string_pretty(&index)?)?;
+
+        Ok(result)
+    }
+}
+
+/// Total size of the distinct blobs the index points to.
+fn unique_size(index: &Index) -> u64 {
+    let mut seen = BTreeSet::new();
+    index
+        .values()
+        .filter(|entry| seen.insert(entry.hash.as_str()))
+        .map(|entry| entry.size)
+        .sum()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::read;
+
+    fn set_access(cache: &Cache, name: &str, last_access: u64) {
+        cache
+            .update(|_, index| {
+                index.get_mut(name).unwrap().last_access = last_access;
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn put_get_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf(), DEFAULT_MAX_SIZE);
+
+        let first = cache.put("template:a@1", b"content").unwrap();
+        let second = cache.put("template:b@1", b"content").unwrap();
+        assert_eq!(first.hash, second.hash);
+        assert_eq!(
+            first.hash,
+            "ed7002b439e9ac845f22357d822bac1444730fbdb6016d3ec9432297b9ec9f73"
+        );
+
+        let blob = cache.get("template:a@1").unwrap().unwrap();
+        assert_eq!(read(blob).unwrap(), b"content");
+        assert!(cache.get("template:missing").unwrap().is_none());
+        assert_eq!(cache.entries().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn eviction_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf(), 8);
+
+        cache.put("old", b"1234").unwrap();
+        cache.put("recent", b"5678").unwrap();
+        set_access(&cache, "old", 1);
+        set_access(&cache, "recent", 3);
+        // Reading `old` makes `recent` the least recently used one
+        cache.get("old").unwrap();
+
+        cache.put("new", b"abcd").unwrap();
+        let names = cache.entries().unwrap().into_keys().collect::<Vec<_>>();
+        assert_eq!(names, ["new", "old"]);
+        assert_eq!(cache.blob_sizes().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn gc_clear_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::at(dir.path().to_path_buf(), DEFAULT_MAX_SIZE);
+
+        let kept = cache.put("kept", b"kept").unwrap();
+        let gone = cache.put("gone", b"gone").unwrap();
+        remove_file(cache.blob_path(&gone.hash)).unwrap();
+        let orphan = cache.put("orphan", b"orphan").unwrap();
+        cache.update(|_, index| Ok(index.remove("orphan"))).unwrap();
+
+        let report = cache.gc().unwrap();
+        assert_eq!(report.removed_entries, ["gone"]);
+        assert_eq!(report.removed_blobs, 1);
+        assert_eq!(report.freed, orphan.size);
+        assert!(cache.blob_path(&kept.hash).is_file());
+
+        let report = cache.clear().unwrap();
+        assert_eq!(report.removed_entries, ["kept"]);
+        assert_eq!(report.freed, kept.size);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+}
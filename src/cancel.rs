@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+
+/// Cooperative cancellation, checked by long operations between entries and
+/// chunks so that they can stop without leaving torn files behind.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Token of the cancellable section Ctrl-C currently stops, if any.
+static SCOPE: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+/// Whether Ctrl-C was pressed at any point of the run.
+static PRESSED: AtomicBool = AtomicBool::new(false);
+
+/// A cancellable section, during which Ctrl-C cancels its token. Outside of
+/// one, Ctrl-C exits with status 130 as it would without a handler.
+pub struct CtrlcScope(CancelToken);
+
+impl CtrlcScope {
+    fn new() -> Self {
+        let token = CancelToken::new();
+        *SCOPE.lock().unwrap() = Some(token.clone());
+        Self(token)
+    }
+}
+
+impl Deref for CtrlcScope {
+    type Target = CancelToken;
+
+    fn deref(&self) -> &CancelToken {
+        &self.0
+    }
+}
+
+impl Drop for CtrlcScope {
+    fn drop(&mut self) {
+        *SCOPE.lock().unwrap() = None;
+    }
+}
+
+/// Starts a section that Ctrl-C cancels, until the returned scope is
+/// dropped. The handler itself is installed once per process.
+pub fn install_ctrlc() -> CtrlcScope {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let handler = || {
+            if !on_ctrlc() {
+                std::process::exit(130);
+            }
+        };
+        if let Err(err) = ctrlc::set_handler(handler) {
+            log::warn!("Could not install the Ctrl-C handler: {err}");
+        }
+    });
+
+    CtrlcScope::new()
+}
+
+/// Cancels the token of the current scope, returning false outside of one.
+fn on_ctrlc() -> bool {
+    PRESSED.store(true, Ordering::Relaxed);
+    match SCOPE.lock().unwrap().as_ref() {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether Ctrl-C was pressed, so `main` does not wait for enter after it.
+pub fn ctrlc_pressed() -> bool {
+    PRESSED.load(Ordering::Relaxed)
+}
+
+/// Error of an operation stopped through its [`CancelToken`], which `main`
+/// exits with status 130 for.
+#[derive(Debug, PartialEq)]
+pub struct Cancelled {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled after {} of {} entries", self.done, self.total)
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Error of an interactive command left with Ctrl-C or Esc at one of its
+/// prompts, before anything was written.
+#[derive(Debug, PartialEq)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "interrupted, nothing was created")
+    }
+}
+
+impl Error for Interrupted {}
+
+/// Whether `err` means the user stopped the command rather than it failing.
+pub fn is_cancellation(err: &(dyn Error + 'static)) -> bool {
+    err.is::<Cancelled>() || err.is::<Interrupted>()
+}
+
+/// Fails reads once its token is cancelled.
+pub struct CancelReader<'a, Inner: Read> {
+    inner: Inner,
+    token: &'a CancelToken,
+}
+
+impl<'a, Inner: Read> CancelReader<'a, Inner> {
+    pub fn new(inner: Inner, token: &'a CancelToken) -> Self {
+        Self { inner, token }
+    }
+}
+
+impl<Inner: Read> Read for CancelReader<'_, Inner> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // Not `Interrupted`, which `io::copy` would retry
+        if self.token.is_cancelled() {
+            return Err(std::io::Error::other("cancelled"));
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctrlc_scope_test() {
+        let scope = CtrlcScope::new();
+        assert!(!scope.is_cancelled());
+        assert!(on_ctrlc());
+        assert!(scope.is_cancelled());
+        assert!(ctrlc_pressed());
+
+        let token = CancelToken::clone(&scope);
+        drop(scope);
+        assert!(!on_ctrlc());
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::atomic::atomic_write;
+use crate::commands::create::{
+    library_convars, library_import, render_script_section, SectionStyle,
+};
+use crate::diff::unified_diff;
+use crate::manifest::Manifest;
+use crate::project::{ProjectConfig, PROJECT_FILE};
+use crate::wizard::confirm;
+use crate::CfxResult;
+
+/// Comment marking the next script section as safe for the tool to rewrite.
+pub const MANAGED_MARKER: &str = "-- cfx:managed";
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Library to add, e.g. `ox_lib`
+    pub library: String,
+
+    /// Resource directory containing the fxmanifest.lua
+    #[arg(long, default_value = ".")]
+    pub path: PathBuf,
+
+    /// Never rewrite an existing section, add a singular directive after it
+    #[arg(long)]
+    pub append_only: bool,
+}
+
+/// How the manifest was changed to import a library.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddMode {
+    /// The import was already there, or the library has nothing to import
+    Unchanged,
+    /// No section for the runtime existed, one was appended
+    NewSection,
+    /// A tool-generated section was rewritten
+    InPlace,
+    /// A hand-edited section was rewritten after confirmation
+    Rewritten,
+    /// A singular directive was added after the existing section
+    Appended,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AddPlan {
+    pub content: String,
+    pub mode: AddMode,
+}
+
+#[derive(Serialize)]
+pub struct AddSummary {
+    pub library: String,
+    pub mode: AddMode,
+}
+
+pub fn handle_add_command(args: &AddArgs) -> CfxResult<AddSummary> {
+    let (section, import) = library_import(&args.library)
+        .ok_or_else(|| format!("Unknown library `{}`", args.library))?;
+
+    let manifest_path = args.path.join("fxmanifest.lua");
+    let content = read_to_string(&manifest_path)
+        .map_err(|err| format!("Could not read {}: {err}", manifest_path.display()))?;
+
+    let mode = match import {
+        None => {
+            log::info!(
+                "{} is used through its exports, nothing to import",
+                args.library
+            );
+            AddMode::Unchanged
+        }
+        Some(import) => {
+            let plan = plan_add(&content, section, import, args.append_only)?;
+            if plan.mode == AddMode::Rewritten {
+                let diff = unified_diff(
+                    &content,
+                    &plan.content,
+                    "a/fxmanifest.lua",
+                    "b/fxmanifest.lua",
+                );
+                log::info!("The {section} scripts section was edited by hand:\n{diff}");
+
+                let confirmed = confirm("Rewrite the section as shown?", false)?;
+                if !confirmed {
+                    return Err("Left fxmanifest.lua untouched, use --append-only to add a separate line instead".into());
+                }
+            }
+
+            if plan.mode != AddMode::Unchanged {
+                atomic_write(&manifest_path, &plan.content)?;
+            }
+
+            plan.mode
+        }
+    };
+
+    if args.path.join(PROJECT_FILE).is_file() {
+        let mut project = ProjectConfig::load(&args.path)?;
+        if !project.libraries.contains(&args.library) {
+            project.libraries.push(args.library.clone());
+        }
+
+        for convar in library_convars(&project.libraries) {
+            if !project.required_convars.contains(&convar) {
+                project.required_convars.push(convar);
+            }
+        }
+
+        project.save(&args.path)?;
+    }
+
+    log::info!("Added {} ({mode:?})", args.library);
+    Ok(AddSummary {
+        library: args.library.clone(),
+        mode,
+    })
+}
+
+/// Works out the manifest with `import` added to the `section` scripts. A
+/// section counts as tool-generated when it follows [`MANAGED_MARKER`] or is
+/// formatted exactly as `create` writes it; anything else is hand-edited.
+pub fn plan_add(
+    content: &str,
+    section: &str,
+    import: &str,
+    append_only: bool,
+) -> CfxResult<AddPlan> {
+    let manifest = Manifest::parse(content)?;
+    let names = [format!("{section}_scripts"), format!("{section}_script")];
+    let sections = manifest
+        .directives
+        .iter()
+        .filter(|directive| names.contains(&directive.name))
+        .collect::<Vec<_>>();
+
+    if sections
+        .iter()
+        .any(|directive| directive.values.iter().any(|value| value == import))
+    {
+        return Ok(AddPlan {
+            content: content.to_owned(),
+            mode: AddMode::Unchanged,
+        });
+    }
+
+    let Some(directive) = sections.first() else {
+        let rendered = render_script_section(section, &[import.to_owned()], SectionStyle::Table)?
+            .unwrap_or_default();
+        let separator = if content.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+
+        return Ok(AddPlan {
+            content: format!("{content}{separator}{rendered}\n"),
+            mode: AddMode::NewSection,
+        });
+    };
+
+    let span = directive.span.clone();
+    if append_only {
+        return Ok(AddPlan {
+            content: format!(
+                "{}\n{section}_script \"{import}\"{}",
+                &content[..span.end],
+                &content[span.end..]
+            ),
+            mode: AddMode::Appended,
+        });
+    }
+
+    let original = &content[span.clone()];
+    let generated = [SectionStyle::Table, SectionStyle::Inline]
+        .into_iter()
+        .filter_map(|style| {
+            render_script_section(section, &directive.values, style)
+                .ok()
+                .flatten()
+        })
+        .any(|rendered| rendered == original);
+    let marked = content[..span.start]
+        .trim_end()
+        .lines()
+        .last()
+        .is_some_and(|line| line.trim() == MANAGED_MARKER);
+
+    let mut scripts = vec![import.to_owned()];
+    scripts.extend(directive.values.iter().cloned());
+    let rendered =
+        render_script_section(section, &scripts, SectionStyle::Table)?.unwrap_or_default();
+
+    Ok(AddPlan {
+        content: format!(
+            "{}{rendered}{}",
+            &content[..span.start],
+            &content[span.end..]
+        ),
+        mode: if generated || marked {
+            AddMode::InPlace
+        } else {
+            AddMode::Rewritten
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMPORT: &str = "@ox_lib/init.lua";
+
+    #[test]
+    fn add_generated_section_test() {
+        let content = "fx_version \"cerulean\"\n\nshared_scripts {\n    \"config.lua\"\n}\n";
+        let plan = plan_add(content, "shared", IMPORT, false).unwrap();
+
+        assert_eq!(plan.mode, AddMode::InPlace);
+        assert_eq!(
+            plan.content,
+            "fx_version \"cerulean\"\n\nshared_scripts {\n    \"@ox_lib/init.lua\",\n    \"config.lua\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn add_marked_section_test() {
+        let content = "-- cfx:managed\nshared_scripts { 'config.lua' }\n";
+        let plan = plan_add(content, "shared", IMPORT, false).unwrap();
+
+        assert_eq!(plan.mode, AddMode::InPlace);
+    }
+
+    #[test]
+    fn add_hand_edited_section_test() {
+        let content = "shared_scripts {\n    'config.lua', -- keep first\n}\n";
+        let plan = plan_add(content, "shared", IMPORT, false).unwrap();
+
+        assert_eq!(plan.mode, AddMode::Rewritten);
+        assert!(plan
+            .content
+            .starts_with("shared_scripts {\n    \"@ox_lib/init.lua\","));
+
+        let plan = plan_add(content, "shared", IMPORT, true).unwrap();
+        assert_eq!(plan.mode, AddMode::Appended);
+        assert_eq!(
+            plan.content,
+            "shared_scripts {\n    'config.lua', -- keep first\n}\nshared_script \"@ox_lib/init.lua\"\n"
+        );
+    }
+
+    #[test]
+    fn add_new_section_test() {
+        let content = "fx_version \"cerulean\"\n";
+        let plan = plan_add(content, "shared", IMPORT, false).unwrap();
+
+        assert_eq!(plan.mode, AddMode::NewSection);
+        assert_eq!(
+            plan.content,
+            "fx_version \"cerulean\"\n\nshared_scripts {\n    \"@ox_lib/init.lua\"\n}\n"
+        );
+
+        let plan = plan_add(&plan.content, "shared", IMPORT, false).unwrap();
+        assert_eq!(plan.mode, AddMode::Unchanged);
+    }
+}
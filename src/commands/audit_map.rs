@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::archive::{FArchive, FArchiveExt, FMemoryArchive, FResourceArchive};
+use crate::commands::unpack::{
+    inflate_page, read_input, ArchiveHeader, ResourceChunkFlags, HEADER_SIZE,
+};
+use crate::joaat::HashDictionary;
+use crate::magic::RSC7_MAGIC;
+use crate::manifest::expand_pattern;
+use crate::map::{Ymap, Ytyp};
+use crate::parse::ParseContext;
+use crate::progress::NoProgress;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct AuditMapArgs {
+    /// Map resource holding the .ymap and .ytyp files
+    #[arg(default_value = ".")]
+    pub resource: PathBuf,
+
+    /// Text file of archetypes the game already defines, one name or 0x hash
+    /// per line, that entities may use without a .ytyp in the resource. Can
+    /// be repeated
+    #[arg(long, value_name = "FILE")]
+    pub archetypes: Vec<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct YmapReport {
+    /// Path relative to the resource
+    pub file: String,
+    pub name: String,
+    pub entities: usize,
+    /// Entities that are interior instances
+    pub mlo_instances: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MloReport {
+    /// The .ytyp defining the interior
+    pub file: String,
+    pub name: String,
+    pub rooms: u16,
+    pub portals: u16,
+    pub entities: usize,
+}
+
+/// An archetype that neither a loaded .ytyp nor an `--archetypes` file
+/// defines.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MissingArchetype {
+    pub archetype: String,
+    /// Files with entities using it, once per entity
+    pub files: Vec<String>,
+}
+
+/// An entity GUID used more than once.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DuplicateGuid {
+    pub guid: u32,
+    /// The .ymap of every entity using it
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct UnreadableFile {
+    pub file: String,
+    pub error: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MapAudit {
+    pub ymaps: Vec<YmapReport>,
+    pub ytyps: usize,
+    pub mlos: Vec<MloReport>,
+    pub missing_archetypes: Vec<MissingArchetype>,
+    pub duplicate_guids: Vec<DuplicateGuid>,
+    pub unreadable: Vec<UnreadableFile>,
+}
+
+/// Loads the .ymap and .ytyp files of a map resource and prints its entity
+/// and interior counts, followed by the entities whose archetype is not
+/// defined anywhere and the entity GUIDs used more than once.
+pub fn handle_audit_map_command(args: &AuditMapArgs) -> CfxResult<MapAudit> {
+    let audit = audit_map(&args.resource, &args.archetypes)?;
+
+    if !audit.ymaps.is_empty() {
+        log::info!("{:<40} {:>8} {:>9}", "YMAP", "ENTITIES", "INTERIORS");
+    }
+    for ymap in &audit.ymaps {
+        log::info!(
+            "{:<40} {:>8} {:>9}",
+            ymap.file,
+            ymap.entities,
+            ymap.mlo_instances
+        );
+    }
+
+    if !audit.mlos.is_empty() {
+        log::info!(
+            "{:<40} {:>8} {:>9} {:>8}",
+            "INTERIOR",
+            "ROOMS",
+            "PORTALS",
+            "ENTITIES"
+        );
+    }
+    for mlo in &audit.mlos {
+        log::info!(
+            "{:<40} {:>8} {:>9} {:>8}",
+            mlo.name,
+            mlo.rooms,
+            mlo.portals,
+            mlo.entities
+        );
+    }
+
+    for file in &audit.unreadable {
+        log::warn!("Could not read {}: {}", file.file, file.error);
+    }
+
+    for missing in &audit.missing_archetypes {
+        log::warn!(
+            "Archetype {} is not defined, used {} times in {}",
+            missing.archetype,
+            missing.files.len(),
+            dedup(&missing.files).join(", ")
+        );
+    }
+
+    for duplicate in &audit.duplicate_guids {
+        log::warn!(
+            "GUID {:#010x} is used by {} entities in {}",
+            duplicate.guid,
+            duplicate.files.len(),
+            dedup(&duplicate.files).join(", ")
+        );
+    }
+
+    log::info!(
+        "Audited {} ymaps and {} ytyps: {} undefined archetypes, {} duplicate GUIDs",
+        audit.ymaps.len(),
+        audit.ytyps,
+        audit.missing_archetypes.len(),
+        audit.duplicate_guids.len()
+    );
+
+    Ok(audit)
+}
+
+/// `files` in order without repeats.
+fn dedup(files: &[String]) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    files
+        .iter()
+        .map(String::as_str)
+        .filter(|file| seen.insert(*file))
+        .collect()
+}
+
+pub fn audit_map(resource_dir: &Path, archetype_files: &[PathBuf]) -> CfxResult<MapAudit> {
+    if !resource_dir.is_dir() {
+        return Err(format!("{} is not a directory", resource_dir.display()).into());
+    }
+
+    // Names come from the file names in the resource, which are what the
+    // archetypes and maps in it are usually named after
+    let mut dictionary = HashDictionary::default();
+    for path in expand_pattern(resource_dir, "**/*")? {
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            dictionary.insert(stem.split('.').next().unwrap_or(stem));
+        }
+    }
+
+    let mut defined = HashSet::new();
+    for path in archetype_files {
+        defined.extend(dictionary.load(path)?);
+    }
+
+    let mut unreadable = vec![];
+    let mut ytyps = 0;
+    let mut mlos = vec![];
+    let mut references = vec![];
+    for path in find_files(resource_dir, "ytyp")? {
+        let file = relative(resource_dir, &path);
+        let ytyp = match load(&path)
+            .and_then(|mut archive| Ytyp::read(&mut archive, &mut ParseContext::default()))
+        {
+            Ok(ytyp) => ytyp,
+            Err(err) => {
+                unreadable.push(UnreadableFile {
+                    file,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        ytyps += 1;
+        for archetype in ytyp.archetypes {
+            defined.insert(archetype.name);
+            let Some(mlo) = archetype.mlo else {
+                continue;
+            };
+
+            references.extend(mlo.entities.iter().map(|&entity| (entity, file.clone())));
+            mlos.push(MloReport {
+                file: file.clone(),
+                name: dictionary.name(archetype.name),
+                rooms: mlo.room_count,
+                portals: mlo.portal_count,
+                entities: mlo.entities.len(),
+            });
+        }
+    }
+
+    let mut ymaps = vec![];
+    let mut guids: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for path in find_files(resource_dir, "ymap")? {
+        let file = relative(resource_dir, &path);
+        let ymap = match load(&path)
+            .and_then(|mut archive| Ymap::read(&mut archive, &mut ParseContext::default()))
+        {
+            Ok(ymap) => ymap,
+            Err(err) => {
+                unreadable.push(UnreadableFile {
+                    file,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        for entity in &ymap.entities {
+            references.push((entity.archetype, file.clone()));
+            if entity.guid != 0 {
+                guids.entry(entity.guid).or_default().push(file.clone());
+            }
+        }
+
+        ymaps.push(YmapReport {
+            file,
+            name: dictionary.name(ymap.name),
+            entities: ymap.entities.len(),
+            mlo_instances: ymap.entities.iter().filter(|entity| entity.mlo).count(),
+        });
+    }
+
+    let mut missing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (archetype, file) in references {
+        if !defined.contains(&archetype) {
+            missing
+                .entry(dictionary.name(archetype))
+                .or_default()
+                .push(file);
+        }
+    }
+
+    Ok(MapAudit {
+        ymaps,
+        ytyps,
+        mlos,
+        missing_archetypes: missing
+            .into_iter()
+            .map(|(archetype, files)| MissingArchetype { archetype, files })
+            .collect(),
+        duplicate_guids: guids
+            .into_iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(guid, files)| DuplicateGuid { guid, files })
+            .collect(),
+        unreadable,
+    })
+}
+
+/// Files with `extension` anywhere in the resource, gzipped or not, in
+/// path order.
+fn find_files(resource_dir: &Path, extension: &str) -> CfxResult<Vec<PathBuf>> {
+    let mut files = expand_pattern(resource_dir, &format!("**/*.{extension}"))?;
+    files.extend(expand_pattern(
+        resource_dir,
+        &format!("**/*.{extension}.gz"),
+    )?);
+    files.sort();
+    Ok(files)
+}
+
+fn relative(resource_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(resource_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Decompresses both pages of the RSC7 file at `path`.
+fn load(path: &Path) -> CfxResult<FResourceArchive<Vec<u8>>> {
+    let buffer = read_input(path)?;
+    let mut archive = FMemoryArchive::new(&buffer);
+    if archive.read_uint()? != RSC7_MAGIC {
+        return Err("not an RSC7 file".into());
+    }
+
+    let header = ArchiveHeader::from(&mut archive)?;
+    let virtual_size = ResourceChunkFlags::new(header.virtual_page_flags).get_size()? as usize;
+    let physical_size = ResourceChunkFlags::new(header.physical_page_flags).get_size()? as usize;
+    if (virtual_size + physical_size) as u64 > archive.remaining() {
+        return Err("the file is truncated".into());
+    }
+
+    let virtual_end = HEADER_SIZE + virtual_size;
+    Ok(FResourceArchive::new(
+        inflate_page(&buffer[HEADER_SIZE..virtual_end], &mut NoProgress)?,
+        inflate_page(
+            &buffer[virtual_end..virtual_end + physical_size],
+            &mut NoProgress,
+        )?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::dev::FixtureDescription;
+    use crate::joaat::joaat;
+    use crate::map::ymap_page;
+    use crate::writer::write_rsc7;
+    use std::fs;
+
+    #[test]
+    fn audit_map_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let stream = dir.path().join("stream");
+        fs::create_dir_all(stream.join("interior")).unwrap();
+
+        let fixture = |path: &str, asset: serde_json::Value| {
+            let description = serde_json::json!({ "asset": asset });
+            let fixture: FixtureDescription = serde_json::from_value(description).unwrap();
+            fs::write(stream.join(path), fixture.build().unwrap()).unwrap();
+        };
+        fixture(
+            "bank.ytyp",
+            serde_json::json!({
+                "kind": "ytyp",
+                "name": "bank",
+                "archetypes": ["bank_counter"],
+                "mlos": [{
+                    "name": "bank_vault",
+                    "rooms": 3,
+                    "portals": 4,
+                    "entities": ["bank_counter", "prop_safe_01"]
+                }]
+            }),
+        );
+        fixture(
+            "bank_exterior.ymap",
+            serde_json::json!({
+                "kind": "ymap",
+                "name": "bank_exterior",
+                "entities": [
+                    { "archetype": "bank_counter", "guid": 7 },
+                    { "archetype": "prop_bench_01a", "guid": 8 },
+                    { "archetype": "prop_bench_01a" }
+                ],
+                "mlo_instances": [{ "archetype": "bank_vault", "guid": 9 }]
+            }),
+        );
+        fixture(
+            "interior/bank_interior.ymap",
+            serde_json::json!({
+                "kind": "ymap",
+                "name": "bank_interior",
+                "entities": [{ "archetype": "bank_lamp", "guid": 7 }]
+            }),
+        );
+        fs::write(stream.join("broken.ymap"), b"\0not a resource").unwrap();
+        // Out of spec, so laid out by hand
+        let mut overflowing = ymap_page(joaat("overflowing"), &[], &[]);
+        overflowing[0x78..0x80].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(
+            stream.join("overflowing.ymap"),
+            write_rsc7(2, &overflowing, &[]).unwrap(),
+        )
+        .unwrap();
+
+        let base_game = dir.path().join("base_game.txt");
+        fs::write(&base_game, "prop_bench_01a\nprop_safe_01\n").unwrap();
+
+        let audit = audit_map(dir.path(), &[base_game]).unwrap();
+        assert_eq!(
+            audit.ymaps,
+            [
+                YmapReport {
+                    file: "stream/bank_exterior.ymap".into(),
+                    name: "bank_exterior".into(),
+                    entities: 4,
+                    mlo_instances: 1,
+                },
+                YmapReport {
+                    file: "stream/interior/bank_interior.ymap".into(),
+                    name: "bank_interior".into(),
+                    entities: 1,
+                    mlo_instances: 0,
+                },
+            ]
+        );
+        assert_eq!(audit.ytyps, 1);
+        assert_eq!(
+            audit.mlos,
+            [MloReport {
+                file: "stream/bank.ytyp".into(),
+                name: format!("{:#010x}", joaat("bank_vault")),
+                rooms: 3,
+                portals: 4,
+                entities: 2,
+            }]
+        );
+        assert_eq!(
+            audit.missing_archetypes,
+            [MissingArchetype {
+                archetype: format!("{:#010x}", joaat("bank_lamp")),
+                files: vec!["stream/interior/bank_interior.ymap".into()],
+            }]
+        );
+        assert_eq!(
+            audit.duplicate_guids,
+            [DuplicateGuid {
+                guid: 7,
+                files: vec![
+                    "stream/bank_exterior.ymap".into(),
+                    "stream/interior/bank_interior.ymap".into()
+                ],
+            }]
+        );
+        assert_eq!(
+            audit.unreadable,
+            [
+                UnreadableFile {
+                    file: "stream/broken.ymap".into(),
+                    error: "not an RSC7 file".into(),
+                },
+                UnreadableFile {
+                    file: "stream/overflowing.ymap".into(),
+                    error: "structure at 0xffffffffffffffff runs past the address space".into(),
+                },
+            ]
+        );
+
+        // Without the base game list, the props the resource uses from it
+        // are undefined too
+        let audit = audit_map(dir.path(), &[]).unwrap();
+        let missing = audit
+            .missing_archetypes
+            .iter()
+            .map(|missing| (missing.archetype.clone(), missing.files.len()))
+            .collect::<Vec<_>>();
+        let mut expected = [("bank_lamp", 1), ("prop_bench_01a", 2), ("prop_safe_01", 1)]
+            .map(|(name, count)| (format!("{:#010x}", joaat(name)), count));
+        expected.sort();
+        assert_eq!(missing, expected);
+    }
+}
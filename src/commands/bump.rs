@@ -0,0 +1,188 @@
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+use crate::atomic::atomic_write;
+use crate::commands::create::VERSION_FILE;
+use crate::manifest::Manifest;
+use crate::CfxResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum VersionPart {
+    Major,
+    Minor,
+    Patch,
+}
+
+#[derive(Args)]
+pub struct BumpArgs {
+    /// Version component to increment, lower ones are reset to 0
+    #[arg(value_enum)]
+    pub part: VersionPart,
+
+    /// Resource directory containing the fxmanifest.lua
+    #[arg(long, default_value = ".")]
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn parse(value: &str) -> CfxResult<Self> {
+        let parts = value
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>();
+
+        match parts.as_deref() {
+            Some(&[major, minor, patch]) => Ok(Self {
+                major,
+                minor,
+                patch,
+            }),
+            _ => Err(format!("Version `{value}` is not of the form x.y.z").into()),
+        }
+    }
+
+    /// The next version, failing when the bumped part is already at its
+    /// largest value.
+    pub fn bump(self, part: VersionPart) -> CfxResult<Self> {
+        let next = |value: u64, name: &str| {
+            value.checked_add(1).ok_or_else(|| {
+                format!("Cannot bump the {name} of {self}, it is at its largest value")
+            })
+        };
+
+        Ok(match part {
+            VersionPart::Major => Self {
+                major: next(self.major, "major")?,
+                minor: 0,
+                patch: 0,
+            },
+            VersionPart::Minor => Self {
+                minor: next(self.minor, "minor")?,
+                patch: 0,
+                ..self
+            },
+            VersionPart::Patch => Self {
+                patch: next(self.patch, "patch")?,
+                ..self
+            },
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BumpSummary {
+    pub previous: String,
+    pub version: String,
+}
+
+pub fn handle_bump_command(args: &BumpArgs) -> CfxResult<BumpSummary> {
+    let manifest_path = args.path.join("fxmanifest.lua");
+    let content = read_to_string(&manifest_path)
+        .map_err(|err| format!("Could not read {}: {err}", manifest_path.display()))?;
+
+    let (content, previous, version) = bump_manifest(&content, args.part)?;
+    atomic_write(&manifest_path, content)?;
+
+    let version_path = args.path.join(VERSION_FILE);
+    if version_path.is_file() {
+        atomic_write(&version_path, format!("{version}\n"))?;
+    }
+
+    log::info!("Bumped {previous} to {version}");
+    Ok(BumpSummary {
+        previous: previous.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// Increments `part` of the `version` directive, rewriting only its value so
+/// the rest of the manifest stays byte-for-byte the same.
+pub fn bump_manifest(content: &str, part: VersionPart) -> CfxResult<(String, Version, Version)> {
+    let manifest = Manifest::parse(content)?;
+    let directive = manifest
+        .get("version")
+        .ok_or("The manifest has no version directive")?;
+    let value = directive
+        .values
+        .first()
+        .ok_or("The version directive has no value")?;
+
+    let previous = Version::parse(value)?;
+    let version = previous.bump(part)?;
+
+    // The value is the only string in the statement, so its first occurrence
+    // after the directive name is the one to replace
+    let statement = &content[directive.span.clone()];
+    let offset = directive.span.start
+        + directive.name.len()
+        + statement[directive.name.len()..]
+            .find(value.as_str())
+            .ok_or("Could not locate the version value")?;
+
+    let content = format!(
+        "{}{version}{}",
+        &content[..offset],
+        &content[offset + value.len()..]
+    );
+    Ok((content, previous, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str =
+        "fx_version 'cerulean'\r\ngame 'gta5'\r\n\r\nversion   '1.4.2' -- keep\r\nauthor 'cfx'\r\n";
+
+    #[test]
+    fn bump_each_part_test() {
+        for (part, expected) in [
+            (VersionPart::Major, "2.0.0"),
+            (VersionPart::Minor, "1.5.0"),
+            (VersionPart::Patch, "1.4.3"),
+        ] {
+            let (content, previous, version) = bump_manifest(MANIFEST, part).unwrap();
+
+            assert_eq!(previous.to_string(), "1.4.2");
+            assert_eq!(version.to_string(), expected);
+            assert_eq!(content, MANIFEST.replace("1.4.2", expected));
+        }
+    }
+
+    #[test]
+    fn bump_invalid_version_test() {
+        assert!(bump_manifest("fx_version 'cerulean'\n", VersionPart::Patch).is_err());
+        assert!(bump_manifest("version '1.2'\n", VersionPart::Patch).is_err());
+        assert!(bump_manifest("version 'v1.2.3'\n", VersionPart::Patch).is_err());
+
+        let max = u64::MAX;
+        let err = bump_manifest(&format!("version '1.2.{max}'\n"), VersionPart::Patch)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            format!("Cannot bump the patch of 1.2.{max}, it is at its largest value")
+        );
+        let (content, _, version) =
+            bump_manifest(&format!("version '1.2.{max}'\n"), VersionPart::Minor).unwrap();
+        assert_eq!(version.to_string(), "1.3.0");
+        assert_eq!(content, "version '1.3.0'\n");
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::cache::{Cache, CacheEntry, GcReport};
+use crate::output::is_machine;
+use crate::CfxResult;
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// List cached downloads, least recently used first
+    List,
+    /// Delete everything in the cache
+    Clear,
+    /// Delete unused blobs and evict down to the configured size
+    Gc,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CachedItem {
+    pub name: String,
+    #[serde(flatten)]
+    pub entry: CacheEntry,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum CacheSummary {
+    List(Vec<CachedItem>),
+    Removed(GcReport),
+}
+
+pub fn handle_cache_command(command: &CacheCommand) -> CfxResult<CacheSummary> {
+    let cache = Cache::open()?;
+
+    match command {
+        CacheCommand::List => {
+            let mut items = cache
+                .entries()?
+                .into_iter()
+                .map(|(name, entry)| CachedItem { name, entry })
+                .collect::<Vec<_>>();
+            items.sort_by_key(|item| item.entry.last_access);
+
+            if items.is_empty() {
+                log::info!("Nothing cached in {}", cache.root().display());
+            }
+
+            // The envelope carries the items in machine mode
+            if !is_machine() {
+                for item in &items {
+                    let accessed = UNIX_EPOCH + Duration::from_secs(item.entry.last_access);
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        item.name,
+                        &item.entry.hash[..12],
+                        item.entry.size,
+                        humantime::format_rfc3339_seconds(accessed)
+                    );
+                }
+            }
+
+            Ok(CacheSummary::List(items))
+        }
+        CacheCommand::Clear => {
+            let report = cache.clear()?;
+            log_report(&report);
+            Ok(CacheSummary::Removed(report))
+        }
+        CacheCommand::Gc => {
+            let report = cache.gc()?;
+            log_report(&report);
+            Ok(CacheSummary::Removed(report))
+        }
+    }
+}
+
+fn log_report(report: &GcReport) {
+    for name in &report.removed_entries {
+        log::info!("Removed {name}");
+    }
+
+    log::info!(
+        "Freed {} bytes in {} blob(s)",
+        report.freed,
+        report.removed_blobs
+    );
+}
@@ -0,0 +1,346 @@
+use std::collections::BTreeSet;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::commands::doctor::find_in_path;
+use crate::i18n::tf;
+use crate::lua::check_syntax;
+use crate::manifest::{expand_pattern, Manifest, SCRIPT_DIRECTIVES};
+use crate::output::FailedWith;
+use crate::project::{Language, ProjectConfig, PROJECT_FILE};
+use crate::scan::read_script;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Resource directory
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+/// A problem found in one of the resource's files.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    /// Path relative to the resource directory
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "{}:{line}:{column}: {}", self.file, self.message)
+            }
+            _ => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CheckSummary {
+    /// Number of Lua files parsed
+    pub checked: usize,
+    pub errors: Vec<Diagnostic>,
+    /// Checks that could not run, such as the type check without `tsc`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+pub fn handle_check_command(args: &CheckArgs) -> CfxResult<CheckSummary> {
+    let summary = check_resource(&args.path)?;
+    for warning in &summary.warnings {
+        log::warn!("{warning}");
+    }
+
+    for error in &summary.errors {
+        log::error!("{error}");
+    }
+
+    if !summary.errors.is_empty() {
+        let count = summary.errors.len().to_string();
+        let message = tf("check.found", &[("count", &count)]);
+        return Err(FailedWith::new(message, &summary).into());
+    }
+
+    let count = summary.checked.to_string();
+    log::info!("{}", tf("check.checked", &[("count", &count)]));
+    Ok(summary)
+}
+
+/// Parses every Lua file the manifest of `resource_dir` references, scripts
+/// as well as files and data files, reporting the first syntax error of each.
+/// JavaScript and TypeScript projects are checked with [`check_bundle`].
+pub fn check_resource(resource_dir: &Path) -> CfxResult<CheckSummary> {
+    let manifest_path = resource_dir.join("fxmanifest.lua");
+    if !manifest_path.is_file() {
+        return Err(format!("No fxmanifest.lua found in {resource_dir:?}").into());
+    }
+
+    let manifest = Manifest::parse(&read_to_string(manifest_path)?)?;
+    let mut files = BTreeSet::new();
+    for pattern in manifest.referenced_patterns() {
+        files.extend(
+            expand_pattern(resource_dir, &pattern)?
+                .into_iter()
+                .filter(|file| file.extension().is_some_and(|ext| ext == "lua")),
+        );
+    }
+
+    let mut summary = CheckSummary::default();
+    for file in files {
+        summary.checked += 1;
+        let source = read_script(&file)?;
+        if let Err(error) = check_syntax(&source) {
+            summary.errors.push(Diagnostic {
+                file: relative(resource_dir, &file),
+                line: Some(error.line),
+                column: Some(error.column),
+                message: error.message,
+            });
+        }
+    }
+
+    if resource_dir.join(PROJECT_FILE).is_file() {
+        let language = ProjectConfig::load(resource_dir)?.language;
+        if language != Language::Lua {
+            let tsc = find_tsc(resource_dir);
+            check_bundle(
+                resource_dir,
+                &manifest,
+                language,
+                tsc.as_deref(),
+                &mut summary,
+            )?;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn relative(resource_dir: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(resource_dir).unwrap_or(file);
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// The project's own TypeScript compiler, or the one on `PATH`.
+fn find_tsc(resource_dir: &Path) -> Option<PathBuf> {
+    let local = resource_dir.join("node_modules").join(".bin");
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs = vec![local];
+    dirs.extend(std::env::split_paths(&path_var));
+
+    find_in_path("tsc", &std::env::join_paths(dirs).ok()?)
+}
+
+/// Checks a bundled project: the built scripts the manifest lists have to
+/// exist, and TypeScript projects have to declare the citizenfx typings of
+/// their runtimes and pass `tsc --noEmit` when `tsc` is available.
+fn check_bundle(
+    resource_dir: &Path,
+    manifest: &Manifest,
+    language: Language,
+    tsc: Option<&Path>,
+    summary: &mut CheckSummary,
+) -> CfxResult<()> {
+    for script in manifest.values(&SCRIPT_DIRECTIVES) {
+        let local = !script.starts_with('@') && !script.contains("://");
+        if local && script.ends_with(".js") && expand_pattern(resource_dir, script)?.is_empty() {
+            summary.errors.push(Diagnostic {
+                file: script.to_owned(),
+                line: None,
+                column: None,
+                message: "does not exist, run `npm run build` first".to_owned(),
+            });
+        }
+    }
+
+    if language != Language::Ts {
+        return Ok(());
+    }
+
+    let package_path = resource_dir.join("package.json");
+    let package: serde_json::Value = match read_to_string(&package_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| format!("Invalid {package_path:?}: {err}"))?,
+        Err(_) => serde_json::Value::Null,
+    };
+    for runtime in ["client", "server"] {
+        let used = !manifest
+            .values(&[&format!("{runtime}_script"), &format!("{runtime}_scripts")])
+            .is_empty();
+        let typings = format!("@citizenfx/{runtime}");
+        let declared = ["dependencies", "devDependencies"]
+            .iter()
+            .any(|section| package[section].get(&typings).is_some());
+        if used && !declared {
+            summary.errors.push(Diagnostic {
+                file: "package.json".to_owned(),
+                line: None,
+                column: None,
+                message: format!("{typings} is not declared, install it for the {runtime} typings"),
+            });
+        }
+    }
+
+    let Some(tsc) = tsc else {
+        summary.warnings.push(
+            "tsc was not found, skipping the type check; install typescript to enable it"
+                .to_owned(),
+        );
+        return Ok(());
+    };
+
+    let output = Command::new(tsc)
+        .args(["--noEmit", "--pretty", "false"])
+        .current_dir(resource_dir)
+        .output()
+        .map_err(|err| format!("Could not run tsc: {err}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let diagnostics = parse_tsc_output(&stdout);
+    if !output.status.success() && diagnostics.is_empty() {
+        return Err(format!("tsc failed ({}): {}", output.status, stdout.trim()).into());
+    }
+
+    summary.errors.extend(diagnostics);
+    Ok(())
+}
+
+/// Parses `tsc --pretty false` lines such as
+/// `src/client/main.ts(3,5): error TS2304: Cannot find name 'x'.`, skipping
+/// the indented lines that continue a message.
+fn parse_tsc_output(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let (location, message) = line.split_once("): ")?;
+            let (file, position) = location.rsplit_once('(')?;
+            let (line, column) = position.split_once(',')?;
+            Some(Diagnostic {
+                file: file.replace('\\', "/"),
+                line: Some(line.parse().ok()?),
+                column: Some(column.parse().ok()?),
+                message: message.to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn check_resource_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+        write(
+            dir.path().join("fxmanifest.lua"),
+            "fx_version 'cerulean'\nshared_script '@ox_lib/init.lua'\nclient_scripts { 'client/*.lua' }\nfiles { 'data/*.json' }",
+        )
+        .unwrap();
+        write(dir.path().join("client/good.lua"), "local hash = `adder`\n").unwrap();
+        write(
+            dir.path().join("client/bad.lua"),
+            "if true then\n  print('x')\n",
+        )
+        .unwrap();
+        write(dir.path().join("client/ignored.txt"), "if").unwrap();
+        write(
+            dir.path().join("client/latin1.lua"),
+            b"-- Versi\xf3n\nprint('Canci\xf3n')\n",
+        )
+        .unwrap();
+
+        let summary = check_resource(dir.path()).unwrap();
+        assert_eq!(summary.checked, 3);
+        assert_eq!(
+            summary.errors,
+            vec![Diagnostic {
+                file: "client/bad.lua".to_owned(),
+                line: Some(3),
+                column: Some(1),
+                message: "`end` expected (to close `if` at line 1) near the end of the file"
+                    .to_owned(),
+            }]
+        );
+        assert_eq!(
+            summary.errors[0].to_string(),
+            "client/bad.lua:3:1: `end` expected (to close `if` at line 1) near the end of the file"
+        );
+
+        let args = CheckArgs {
+            path: dir.path().to_path_buf(),
+        };
+        assert!(handle_check_command(&args).is_err());
+    }
+
+    #[test]
+    fn check_bundle_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("dist")).unwrap();
+        write(dir.path().join("dist/server.js"), "").unwrap();
+        write(
+            dir.path().join("package.json"),
+            r#"{ "devDependencies": { "@citizenfx/server": "latest" } }"#,
+        )
+        .unwrap();
+        let manifest = Manifest::parse(
+            "client_script 'dist/client.js'\nserver_script 'dist/server.js'\nshared_script '@ox_lib/init.lua'",
+        )
+        .unwrap();
+
+        let mut summary = CheckSummary::default();
+        check_bundle(dir.path(), &manifest, Language::Ts, None, &mut summary).unwrap();
+        assert_eq!(
+            summary
+                .errors
+                .iter()
+                .map(Diagnostic::to_string)
+                .collect::<Vec<String>>(),
+            vec![
+                "dist/client.js: does not exist, run `npm run build` first",
+                "package.json: @citizenfx/client is not declared, install it for the client typings"
+            ]
+        );
+        assert_eq!(summary.warnings.len(), 1);
+
+        // JavaScript projects need neither typings nor tsc
+        let mut summary = CheckSummary::default();
+        check_bundle(dir.path(), &manifest, Language::Js, None, &mut summary).unwrap();
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_tsc_output_test() {
+        let output = "src/client/main.ts(3,5): error TS2304: Cannot find name 'x'.\n  Continued message.\nsrc\\server\\main.ts(10,1): error TS1005: ';' expected.\n";
+        assert_eq!(
+            parse_tsc_output(output),
+            vec![
+                Diagnostic {
+                    file: "src/client/main.ts".to_owned(),
+                    line: Some(3),
+                    column: Some(5),
+                    message: "error TS2304: Cannot find name 'x'.".to_owned(),
+                },
+                Diagnostic {
+                    file: "src/server/main.ts".to_owned(),
+                    line: Some(10),
+                    column: Some(1),
+                    message: "error TS1005: ';' expected.".to_owned(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::scan::{find_resources, scan_resources, Location, EVENT_REGISTRATION, EVENT_TRIGGER};
+use crate::CfxResult;
+
+/// Events raised by the server or game itself, which are neither orphaned
+/// when only handled nor collisions when handled by many resources.
+const BUILTIN_EVENTS: [&str; 18] = [
+    "chatMessage",
+    "entityCreated",
+    "entityCreating",
+    "entityRemoved",
+    "explosionEvent",
+    "gameEventTriggered",
+    "onClientResourceStart",
+    "onClientResourceStop",
+    "onResourceStart",
+    "onResourceStarting",
+    "onResourceStop",
+    "onServerResourceStart",
+    "onServerResourceStop",
+    "playerConnecting",
+    "playerDropped",
+    "playerJoining",
+    "playerSpawned",
+    "populationPedCreating",
+];
+
+#[derive(Args)]
+pub struct CheckEventsArgs {
+    /// Directory containing the resources to cross-reference
+    #[arg(default_value = "resources")]
+    pub resources_dir: PathBuf,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventIssue {
+    /// Triggered somewhere but never registered
+    Orphan,
+    /// Registered as a net event by more than one resource
+    Collision,
+    /// Missing the conventional `resource:` prefix
+    Unprefixed,
+}
+
+impl fmt::Display for EventIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventIssue::Orphan => write!(f, "triggered but never registered"),
+            EventIssue::Collision => write!(f, "registered by several resources"),
+            EventIssue::Unprefixed => write!(f, "no resource prefix"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EventUsage {
+    pub resource: String,
+    pub function: String,
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EventReport {
+    pub name: String,
+    pub issues: Vec<EventIssue>,
+    pub registrations: Vec<EventUsage>,
+    pub triggers: Vec<EventUsage>,
+}
+
+#[derive(Serialize)]
+pub struct CheckEventsSummary {
+    pub resources: usize,
+    pub events: usize,
+    pub reports: Vec<EventReport>,
+}
+
+pub fn handle_check_events_command(args: &CheckEventsArgs) -> CfxResult<CheckEventsSummary> {
+    let summary = check_events(&args.resources_dir)?;
+
+    for report in &summary.reports {
+        let issues = report
+            .issues
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+
+        log::warn!("{}: {}", report.name, issues.join(", "));
+        for usage in report.registrations.iter().chain(&report.triggers) {
+            log::warn!(
+                "    {} {} ({})",
+                usage.function,
+                usage.location,
+                usage.resource
+            );
+        }
+    }
+
+    log::info!(
+        "Checked {} events across {} resources, {} with issues",
+        summary.events,
+        summary.resources,
+        summary.reports.len()
+    );
+
+    Ok(summary)
+}
+
+pub fn check_events(resources_dir: &Path) -> CfxResult<CheckEventsSummary> {
+    let resources = find_resources(resources_dir)?;
+
+    let mut registrations: BTreeMap<String, Vec<EventUsage>> = BTreeMap::new();
+    let mut triggers: BTreeMap<String, Vec<EventUsage>> = BTreeMap::new();
+
+    for (target, regex) in [
+        (&mut registrations, &*EVENT_REGISTRATION),
+        (&mut triggers, &*EVENT_TRIGGER),
+    ] {
+        for (resource, found) in scan_resources(&resources, regex)? {
+            let function = found.captures[0].clone().unwrap_or_default();
+            let name = found.captures[1].clone().unwrap_or_default();
+
+            target.entry(name).or_default().push(EventUsage {
+                resource,
+                function,
+                location: found.location,
+            });
+        }
+    }
+
+    let names = registrations
+        .keys()
+        .chain(triggers.keys())
+        .cloned()
+        .collect::<BTreeSet<String>>();
+    let events = names.len();
+
+    let mut reports = vec![];
+    for name in names {
+        if BUILTIN_EVENTS.contains(&name.as_str()) {
+            continue;
+        }
+
+        let registered = registrations.remove(&name).unwrap_or_default();
+        let triggered = triggers.remove(&name).unwrap_or_default();
+
+        let mut issues = vec![];
+        if registered.is_empty() {
+            issues.push(EventIssue::Orphan);
+        }
+
+        let net_resources = registered
+            .iter()
+            .filter(|usage| usage.function != "AddEventHandler")
+            .map(|usage| usage.resource.as_str())
+            .collect::<BTreeSet<&str>>();
+        if net_resources.len() > 1 {
+            issues.push(EventIssue::Collision);
+        }
+
+        if !name.contains(':') {
+            issues.push(EventIssue::Unprefixed);
+        }
+
+        if !issues.is_empty() {
+            reports.push(EventReport {
+                name,
+                issues,
+                registrations: registered,
+                triggers: triggered,
+            });
+        }
+    }
+
+    Ok(CheckEventsSummary {
+        resources: resources.len(),
+        events,
+        reports,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn check_events_test() {
+        let root = tempfile::tempdir().unwrap();
+        for (name, script) in [
+            (
+                "bank",
+                r#"RegisterNetEvent('bank:deposit', function() end)
+AddEventHandler('onResourceStart', function() end)
+RegisterNetEvent('giveMoney')"#,
+            ),
+            (
+                "shop",
+                r#"TriggerServerEvent('bank:deposit', 10)
+TriggerServerEvent('bank:withdraw', 10)
+RegisterNetEvent("giveMoney")"#,
+            ),
+        ] {
+            let dir = root.path().join(name);
+            create_dir_all(&dir).unwrap();
+            write(dir.join("fxmanifest.lua"), "").unwrap();
+            write(dir.join("main.lua"), script).unwrap();
+        }
+
+        let summary = check_events(root.path()).unwrap();
+        let issues = summary
+            .reports
+            .iter()
+            .map(|report| (report.name.as_str(), &report.issues))
+            .collect::<Vec<_>>();
+
+        assert_eq!(summary.events, 4);
+        assert_eq!(
+            issues,
+            vec![
+                ("bank:withdraw", &vec![EventIssue::Orphan]),
+                (
+                    "giveMoney",
+                    &vec![EventIssue::Collision, EventIssue::Unprefixed]
+                ),
+            ]
+        );
+
+        let withdraw = &summary.reports[0].triggers[0];
+        assert_eq!(withdraw.resource, "shop");
+        assert_eq!(withdraw.location.line, 2);
+    }
+}
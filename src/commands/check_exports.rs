@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::scan::{
+    find_resources, manifest_exports, scan_resources, Location, EXPORT_CALL, EXPORT_REGISTRATION,
+};
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct CheckExportsArgs {
+    /// Directory containing the resources to cross-reference
+    #[arg(default_value = "resources")]
+    pub resources_dir: PathBuf,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportIssueKind {
+    UnknownResource,
+    UnknownExport,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ExportIssue {
+    pub kind: ExportIssueKind,
+    pub resource: String,
+    pub export: String,
+    pub location: Location,
+}
+
+#[derive(Serialize)]
+pub struct CheckExportsSummary {
+    pub resources: usize,
+    pub calls: usize,
+    pub issues: Vec<ExportIssue>,
+}
+
+pub fn handle_check_exports_command(args: &CheckExportsArgs) -> CfxResult<CheckExportsSummary> {
+    let config = Config::load()?;
+    let summary = check_exports(&args.resources_dir, &config.ignored_exports)?;
+
+    for issue in &summary.issues {
+        let reason = match issue.kind {
+            ExportIssueKind::UnknownResource => "unknown resource",
+            ExportIssueKind::UnknownExport => "unknown export",
+        };
+
+        log::warn!(
+            "{}: exports['{}']:{} ({reason})",
+            issue.location,
+            issue.resource,
+            issue.export
+        );
+    }
+
+    log::info!(
+        "Checked {} export calls across {} resources, {} issues",
+        summary.calls,
+        summary.resources,
+        summary.issues.len()
+    );
+
+    Ok(summary)
+}
+
+/// Cross-references export calls against the exports every resource declares.
+/// `ignored` entries are either `resource` or `resource:export`.
+pub fn check_exports(resources_dir: &Path, ignored: &[String]) -> CfxResult<CheckExportsSummary> {
+    let resources = find_resources(resources_dir)?;
+    let ignored = ignored
+        .iter()
+        .map(String::as_str)
+        .collect::<HashSet<&str>>();
+
+    let mut declared: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for resource in &resources {
+        let exports = declared.entry(resource.name.clone()).or_default();
+        if let Some(manifest) = resource.manifest_path() {
+            exports.extend(manifest_exports(&read_to_string(manifest)?));
+        }
+    }
+
+    for (resource, registration) in scan_resources(&resources, &EXPORT_REGISTRATION)? {
+        let exports = declared.entry(resource).or_default();
+        exports.extend(registration.captures[0].clone());
+    }
+
+    let calls = scan_resources(&resources, &EXPORT_CALL)?
+        .into_iter()
+        .map(|(_, call)| call)
+        .collect::<Vec<_>>();
+
+    let mut issues = vec![];
+    for call in &calls {
+        let resource = call.captures[0]
+            .clone()
+            .or_else(|| call.captures[1].clone())
+            .unwrap_or_default();
+        let export = call.captures[2].clone().unwrap_or_default();
+
+        if ignored.contains(resource.as_str())
+            || ignored.contains(format!("{resource}:{export}").as_str())
+        {
+            continue;
+        }
+
+        let kind = match declared.get(&resource) {
+            None => ExportIssueKind::UnknownResource,
+            Some(exports) if !exports.contains(&export) => ExportIssueKind::UnknownExport,
+            _ => continue,
+        };
+
+        issues.push(ExportIssue {
+            kind,
+            resource,
+            export,
+            location: call.location.clone(),
+        });
+    }
+
+    Ok(CheckExportsSummary {
+        resources: resources.len(),
+        calls: calls.len(),
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn check_exports_test() {
+        let root = tempfile::tempdir().unwrap();
+        let bank = root.path().join("[core]/bank");
+        let shop = root.path().join("shop");
+        create_dir_all(&bank).unwrap();
+        create_dir_all(shop.join("client")).unwrap();
+
+        write(bank.join("fxmanifest.lua"), "server_exports { 'getMoney' }").unwrap();
+        write(
+            bank.join("server.lua"),
+            "exports('addMoney', function() end)",
+        )
+        .unwrap();
+        write(shop.join("fxmanifest.lua"), "").unwrap();
+        write(
+            shop.join("client/main.lua"),
+            r#"exports.bank:getMoney()
+exports['bank']:addMoney()
+exports['bank']:removeMoney()
+exports['ox_lib']:notify()
+exports['qb-core']:GetCoreObject()"#,
+        )
+        .unwrap();
+
+        let summary = check_exports(root.path(), &["qb-core".to_owned()]).unwrap();
+        let file = shop.join("client/main.lua");
+
+        assert_eq!(summary.resources, 2);
+        assert_eq!(summary.calls, 5);
+        assert_eq!(
+            summary.issues,
+            vec![
+                ExportIssue {
+                    kind: ExportIssueKind::UnknownExport,
+                    resource: "bank".to_owned(),
+                    export: "removeMoney".to_owned(),
+                    location: Location {
+                        file: file.clone(),
+                        line: 3
+                    },
+                },
+                ExportIssue {
+                    kind: ExportIssueKind::UnknownResource,
+                    resource: "ox_lib".to_owned(),
+                    export: "notify".to_owned(),
+                    location: Location { file, line: 4 },
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::diff::{diff_manifests, ManifestDiff};
+use crate::manifest::Manifest;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct CompareManifestsArgs {
+    /// Old fxmanifest.lua, or the resource directory holding it
+    pub old: PathBuf,
+
+    /// New fxmanifest.lua, or the resource directory holding it
+    pub new: PathBuf,
+
+    /// Also report scripts and files whose load order changed
+    #[arg(long)]
+    pub ordered: bool,
+}
+
+pub fn handle_compare_manifests_command(args: &CompareManifestsArgs) -> CfxResult<ManifestDiff> {
+    let old = load(&args.old)?;
+    let new = load(&args.new)?;
+
+    let diff = diff_manifests(&old, &new, args.ordered);
+    if diff.is_empty() {
+        log::info!("The manifests are equivalent");
+    }
+
+    for line in diff.to_string().lines() {
+        log::info!("{line}");
+    }
+
+    Ok(diff)
+}
+
+fn load(path: &Path) -> CfxResult<Manifest> {
+    let path = match path.is_dir() {
+        true => path.join("fxmanifest.lua"),
+        false => path.to_path_buf(),
+    };
+
+    let content =
+        read_to_string(&path).map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+    Manifest::parse(&content)
+}
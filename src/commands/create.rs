@@ -1,12 +1,26 @@
 use std::collections::HashMap;
-use std::fs::{create_dir_all, File};
+use std::fmt;
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, rename, File};
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use inquire::validator::Validation;
-use inquire::{Confirm, MultiSelect, Text};
+use clap::{Args, ValueEnum};
+use glob::Pattern;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use string_builder::Builder;
 
+use crate::atomic::atomic_write;
+use crate::cancel::{CancelToken, Interrupted};
+use crate::commands::check::check_resource;
+use crate::config::Config;
+use crate::i18n::{t, tf};
+use crate::icon::{check_icon, placeholder_png, ICON_FILE};
+use crate::manifest::{expand_pattern, Manifest, SCRIPT_DIRECTIVES};
+use crate::meta::{to_xml, PedInitData, PedInitDatas, PedsMeta, ShopPedApparel};
+use crate::project::{Language, ProjectConfig, PROJECT_FILE};
+use crate::template::{parse_variable, render, resolve, substitute, variables};
+use crate::wizard::{run_wizard, terminal_prompter, Answer, Prompter, Step};
 use crate::CfxResult;
 
 lazy_static! {
@@ -14,14 +28,34 @@ lazy_static! {
         (
             "es_extended",
             Library::new("@es_extended/imports.lua", ScriptRuntime::Shared)
+                .with_games(&[Game::Gta5])
+                .with_load_order(20)
+                .with_notes("Import the es_extended SQL files and ensure it after oxmysql")
         ),
         (
             "ox_lib",
             Library::new("@ox_lib/init.lua", ScriptRuntime::Shared)
+                .with_load_order(0)
+                .with_client_init("@ox_lib/init.lua")
         ),
         (
             "oxmysql",
             Library::new("@oxmysql/lib/MySQL.lua", ScriptRuntime::Server)
+                .with_load_order(10)
+                .with_required_convars(&["mysql_connection_string"])
+                .with_notes("Ensure oxmysql before any resource that queries the database")
+        ),
+        (
+            "ox_target",
+            Library::exports_only()
+                .with_games(&[Game::Gta5])
+                .with_example(OX_TARGET_CLIENT_EXAMPLE, TARGET_SERVER_EXAMPLE)
+        ),
+        (
+            "qb-target",
+            Library::exports_only()
+                .with_games(&[Game::Gta5])
+                .with_example(QB_TARGET_CLIENT_EXAMPLE, TARGET_SERVER_EXAMPLE)
         )
     ]
     .iter()
@@ -29,6 +63,186 @@ lazy_static! {
     .collect();
 }
 
+// Example snippets use `{{resource}}` for the project name, which also prefixes
+// their event names.
+const OX_TARGET_CLIENT_EXAMPLE: &str = r#"exports.ox_target:addBoxZone({
+    coords = vec3(0.0, 0.0, 0.0),
+    size = vec3(2.0, 2.0, 2.0),
+    rotation = 0.0,
+    options = {
+        {
+            name = '{{resource}}:interact',
+            icon = 'fa-solid fa-hand',
+            label = 'Interact',
+            onSelect = function()
+                TriggerServerEvent('{{resource}}:interact')
+            end,
+        },
+    },
+})
+"#;
+
+const QB_TARGET_CLIENT_EXAMPLE: &str = r#"exports['qb-target']:AddBoxZone('{{resource}}:interact', vector3(0.0, 0.0, 0.0), 2.0, 2.0, {
+    name = '{{resource}}:interact',
+    heading = 0.0,
+    minZ = -1.0,
+    maxZ = 1.0,
+}, {
+    options = {
+        {
+            icon = 'fas fa-hand',
+            label = 'Interact',
+            action = function()
+                TriggerServerEvent('{{resource}}:interact')
+            end,
+        },
+    },
+    distance = 2.0,
+})
+"#;
+
+const TARGET_SERVER_EXAMPLE: &str = r#"RegisterNetEvent('{{resource}}:interact', function()
+    local source = source
+    print(('%s interacted with {{resource}}'):format(GetPlayerName(source)))
+end)
+"#;
+
+/// Page the manifest of a loading screen points `loadscreen` at.
+const LOADSCREEN_PAGE: &str = "html/index.html";
+
+/// Files of a loading screen's NUI page. `{{name}}` is the project name.
+const LOADSCREEN_FILES: [(&str, &str); 3] = [
+    (LOADSCREEN_PAGE, LOADSCREEN_HTML),
+    ("html/style.css", LOADSCREEN_CSS),
+    ("html/script.js", LOADSCREEN_JS),
+];
+
+const LOADSCREEN_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>{{name}}</title>
+    <link rel="stylesheet" href="style.css">
+</head>
+<body>
+    <main>
+        <h1>{{name}}</h1>
+        <div class="progress"><div id="progress-bar"></div></div>
+    </main>
+    <script src="script.js"></script>
+</body>
+</html>
+"#;
+
+const LOADSCREEN_CSS: &str = r#"body {
+    margin: 0;
+    height: 100vh;
+    display: flex;
+    align-items: center;
+    justify-content: center;
+    background: #111;
+    color: #fff;
+    font-family: sans-serif;
+}
+
+.progress {
+    width: 40vw;
+    height: 6px;
+    background: #333;
+}
+
+#progress-bar {
+    width: 0;
+    height: 100%;
+    background: #f40552;
+}
+"#;
+
+const LOADSCREEN_JS: &str = r#"// The game posts its loading progress to the loading screen as it goes
+window.addEventListener('message', (event) => {
+    if (event.data.eventName === 'loadProgress') {
+        const percent = Math.round(event.data.loadFraction * 100);
+        document.getElementById('progress-bar').style.width = `${percent}%`;
+    }
+});
+"#;
+
+/// Data files of a vehicle add-on in the order they have to load, with their
+/// `data_file` type. `{{model}}` is the spawn name of the vehicle.
+const VEHICLE_DATA_FILES: [(&str, &str, &str); 4] = [
+    ("HANDLING_FILE", "data/handling.meta", VEHICLE_HANDLING_META),
+    (
+        "VEHICLE_METADATA_FILE",
+        "data/vehicles.meta",
+        VEHICLE_METADATA_META,
+    ),
+    ("CARCOLS_FILE", "data/carcols.meta", VEHICLE_CARCOLS_META),
+    (
+        "VEHICLE_VARIATION_FILE",
+        "data/carvariations.meta",
+        VEHICLE_VARIATIONS_META,
+    ),
+];
+
+const VEHICLE_HANDLING_META: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CHandlingDataMgr>
+  <HandlingData>
+    <Item type="CHandlingData">
+      <handlingName>{{model}}</handlingName>
+    </Item>
+  </HandlingData>
+</CHandlingDataMgr>
+"#;
+
+const VEHICLE_METADATA_META: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CVehicleModelInfo__InitDataList>
+  <residentTxd>vehshare</residentTxd>
+  <InitDatas>
+    <Item>
+      <modelName>{{model}}</modelName>
+      <txdName>{{model}}</txdName>
+      <handlingId>{{model}}</handlingId>
+      <gameName>{{model}}</gameName>
+      <vehicleMakeName />
+      <type>VEHICLE_TYPE_CAR</type>
+      <vehicleClass>VC_SPORT</vehicleClass>
+    </Item>
+  </InitDatas>
+</CVehicleModelInfo__InitDataList>
+"#;
+
+const VEHICLE_CARCOLS_META: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CVehicleModelInfoVarGlobal>
+  <Kits />
+  <Lights />
+</CVehicleModelInfoVarGlobal>
+"#;
+
+const VEHICLE_VARIATIONS_META: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CVehicleModelInfoVariation>
+  <variationData>
+    <Item>
+      <modelName>{{model}}</modelName>
+      <colors>
+        <Item>
+          <indices content="char_array">0 0 0 0</indices>
+          <liveries />
+        </Item>
+      </colors>
+      <kits>
+        <Item>0_default_modkit</Item>
+      </kits>
+      <windowsWithExposedEdges />
+      <plateProbabilities>
+        <Probabilities />
+      </plateProbabilities>
+      <lightSettings value="1" />
+      <sirenSettings value="0" />
+    </Item>
+  </variationData>
+</CVehicleModelInfoVariation>
+"#;
+
 #[derive(Debug, Clone, PartialEq)]
 enum ScriptRuntime {
     Server,
@@ -36,218 +250,4246 @@ enum ScriptRuntime {
     Shared,
 }
 
-#[derive(Debug, Clone)]
-struct Library {
-    import: String,
-    runtime: ScriptRuntime,
+const RDR3_WARNING: &str = "I acknowledge that this is a prerelease build of RedM, and I am aware my resources *will* become incompatible once RedM ships.";
+
+/// Games a resource can declare support for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Game {
+    Gta5,
+    Rdr3,
 }
 
-impl Library {
-    pub fn new(import: impl Into<String>, runtime: ScriptRuntime) -> Self {
-        Self {
-            import: import.into(),
-            runtime,
+impl Game {
+    fn name(&self) -> &'static str {
+        match self {
+            Game::Gta5 => "gta5",
+            Game::Rdr3 => "rdr3",
         }
     }
 }
 
-struct ScriptSectionBuilder {
-    name: String,
-    scripts: Vec<String>,
+/// Which runtimes a generated resource is meant to load on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    #[default]
+    Both,
+    Client,
+    Server,
 }
 
-impl ScriptSectionBuilder {
-    pub fn new(name: impl Into<String>) -> Self {
-        Self {
-            name: name.into(),
-            scripts: vec![],
-        }
+impl Scope {
+    fn includes(&self, runtime: &ScriptRuntime) -> bool {
+        !matches!(
+            (self, runtime),
+            (Scope::Client, ScriptRuntime::Server) | (Scope::Server, ScriptRuntime::Client)
+        )
     }
+}
 
-    pub fn append(&mut self, path: impl Into<String>) -> &mut Self {
-        self.scripts.push(path.into());
-        self
+/// What kind of resource `create` scaffolds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ResourceKind {
+    /// Client and server scripts with optional libraries
+    Script,
+    /// A vehicle add-on with `stream/` and the vehicle meta files
+    Vehicle,
+    /// An add-on ped or a clothing pack with `stream/` and its meta files
+    Ped,
+    /// A sound pack with audio config and a wavepack folder
+    Audio,
+    /// A loading screen: an NUI page shown while the game loads, with no
+    /// scripts
+    Loadscreen,
+}
+
+/// What a ped add-on adds to the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PedAddon {
+    /// A new ped model registered through `peds.meta`
+    FullPed,
+    /// Clothing components for an existing ped, registered through shop meta
+    Components,
+}
+
+impl fmt::Display for PedAddon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PedAddon::FullPed => write!(f, "{}", t("create.ped_addon.full_ped")),
+            PedAddon::Components => write!(f, "{}", t("create.ped_addon.components")),
+        }
     }
+}
 
-    pub fn build(&self) -> CfxResult<String> {
-        let mut string_builder = Builder::default();
-        string_builder.append(format!("{}_scripts {{\n", self.name));
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Kind of resource to scaffold
+    #[arg(long, value_enum, default_value_t = ResourceKind::Script)]
+    pub kind: ResourceKind,
 
-        for (i, script) in self.scripts.iter().enumerate() {
-            string_builder.append(format!("    \"{script}\""));
-            if i < self.scripts.len() - 1 {
-                string_builder.append(",");
-            }
+    /// Runtimes the resource loads on; `server` also marks the manifest `server_only`
+    #[arg(long, value_enum, default_value_t = Scope::Both)]
+    pub scope: Scope,
 
-            string_builder.append("\n")
-        }
+    /// How script sections with a single entry are written
+    #[arg(long, value_enum, default_value_t = SectionStyle::Table)]
+    pub section_style: SectionStyle,
 
-        string_builder.append("}");
-        Ok(string_builder.string()?)
+    /// Minimum FXServer build required to start the resource
+    #[arg(long, value_parser = parse_server_version)]
+    pub server_version: Option<u32>,
+
+    /// Require OneSync, emitting a `/onesync` dependency that `cfx validate`
+    /// checks server.cfg against
+    #[arg(long)]
+    pub onesync: bool,
+
+    /// Minimum game build required to start the resource
+    #[arg(long, value_parser = parse_game_build)]
+    pub game_build: Option<u32>,
+
+    /// Emit a `KEY "VALUE"` manifest directive, such as
+    /// `use_experimental_fxv2_oal=yes`. Keys outside the known toggles only
+    /// warn unless `--strict` is given. Can be repeated
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_manifest_flag)]
+    pub manifest_flag: Vec<(String, String)>,
+
+    /// Reject `--manifest-flag` keys that are not known manifest toggles
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Add an icon.png for marketplace listings and txAdmin, copied from this
+    /// square PNG or, without a path, a placeholder with the project initials
+    #[arg(long, value_name = "PNG", num_args = 0..=1)]
+    pub icon: Option<Option<PathBuf>>,
+
+    /// Label each block of the manifest with a Lua comment explaining it
+    #[arg(long)]
+    pub comments: bool,
+
+    /// Comma-separated games the resource supports; several emit a `games` table
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "gta5")]
+    pub games: Vec<Game>,
+
+    /// Also list the client init of libraries that ship one, such as ox_lib,
+    /// in `client_scripts` for client-side library modules
+    #[arg(long)]
+    pub client_init: bool,
+
+    /// Create the project in a `[category]` folder below the resources root
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Comma-separated server modules, each written to
+    /// `src/server/modules/<name>.lua` and loaded before `main.lua`
+    #[arg(long, value_delimiter = ',', value_parser = parse_module_name)]
+    pub modules: Vec<String>,
+
+    /// Comma-separated client modules, like --modules
+    #[arg(long, value_delimiter = ',', value_parser = parse_module_name)]
+    pub client_modules: Vec<String>,
+
+    /// Comma-separated libraries or modules to list at the top of their
+    /// section, such as a polyfill that has to load first
+    #[arg(long, value_delimiter = ',')]
+    pub priority: Vec<String>,
+
+    /// Client function other resources can call, listed in the `exports`
+    /// block of the manifest. Can be repeated
+    #[arg(long = "export", value_name = "NAME", value_parser = parse_export_name)]
+    pub exports: Vec<String>,
+
+    /// Server function other resources can call, listed in `server_exports`.
+    /// Can be repeated
+    #[arg(long = "server-export", value_name = "NAME", value_parser = parse_export_name)]
+    pub server_exports: Vec<String>,
+
+    /// Define each export in the `main.lua` of its side with an empty
+    /// `exports('name', function() end)` stub
+    #[arg(long)]
+    pub export_stubs: bool,
+
+    /// Language of the entry scripts. JavaScript and TypeScript are bundled
+    /// with esbuild into `dist/`, which the manifest lists instead of `src/`
+    #[arg(long, value_enum, default_value_t = Language::Lua)]
+    pub language: Language,
+
+    /// Write a CHANGELOG.md following Keep a Changelog, with an Unreleased
+    /// section and one for the initial version
+    #[arg(long)]
+    pub changelog: bool,
+
+    /// Check the syntax of the generated Lua scripts once they are written,
+    /// warning about any errors like `cfx check`
+    #[arg(long)]
+    pub check: bool,
+
+    /// When the project directory exists, replace the existing files matching
+    /// this glob, relative to the project. Every other existing file is left
+    /// untouched. Can be repeated
+    #[arg(long, value_name = "GLOB")]
+    pub allow_overwrite: Vec<Pattern>,
+
+    /// Project name, instead of prompting for it
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Author name, instead of prompting for it
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// TOML file answering any of the prompts, with the keys of the `[create]`
+    /// section of cfx.toml. Only the missing answers are prompted for
+    #[arg(long, value_name = "FILE")]
+    pub answers: Option<PathBuf>,
+
+    /// Write the answers used to this file, for regenerating the project with
+    /// `--answers`
+    #[arg(long, value_name = "FILE")]
+    pub save_answers: Option<PathBuf>,
+
+    /// Render this registered template instead of generating the scripts and
+    /// manifest, see `cfx template add`. Only the name and author are asked
+    #[arg(long, value_name = "NAME", conflicts_with = "kind")]
+    pub template: Option<String>,
+
+    /// Scaffold a loading screen, the same as `--kind loadscreen`
+    #[arg(long, conflicts_with_all = ["kind", "template"])]
+    pub loadscreen: bool,
+
+    /// Keep the loading screen up until a client script calls
+    /// `ShutdownLoadingScreenNui`, emitting `loadscreen_manual_shutdown`
+    #[arg(long)]
+    pub manual_shutdown: bool,
+
+    /// Value for the `{{KEY}}` placeholders in the --template files, next to
+    /// the built-in project_name and author. Can be repeated
+    #[arg(long = "var", alias = "template-var", value_name = "KEY=VALUE", value_parser = parse_variable, requires = "template")]
+    pub vars: Vec<(String, String)>,
+}
+
+/// Where a new project goes: `<root>/[<category>]/<name>`, where both the root
+/// and the category are optional. A category given with brackets keeps them.
+pub fn project_dir(root: Option<&Path>, category: Option<&str>, name: &str) -> PathBuf {
+    let mut result = root.map(Path::to_path_buf).unwrap_or_default();
+    if let Some(category) = category {
+        let category = category.trim_start_matches('[').trim_end_matches(']');
+        result.push(format!("[{category}]"));
     }
+
+    result.push(name);
+    result
 }
 
-struct ScriptManifest {
-    author: String,
-    use_data_files: bool,
-    libraries: Vec<Library>,
+fn parse_module_name(value: &str) -> Result<String, String> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("`{value}` is not a valid module name"));
+    }
+
+    Ok(value.to_owned())
 }
 
-impl ScriptManifest {
-    pub fn new(author: impl Into<String>, use_data_files: bool, libraries: Vec<Library>) -> Self {
-        Self {
-            author: author.into(),
-            use_data_files,
-            libraries,
-        }
+fn parse_export_name(value: &str) -> Result<String, String> {
+    let valid = value
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("`{value}` is not a valid export name"));
     }
 
-    pub fn build(&self) -> CfxResult<String> {
-        let server_scripts = self.build_script_section("server", ScriptRuntime::Server)?;
-        let client_scripts = self.build_script_section("client", ScriptRuntime::Client)?;
-        let shared_scripts = self.build_script_section("shared", ScriptRuntime::Shared)?;
+    Ok(value.to_owned())
+}
 
-        let mut builder = Builder::default();
-        builder.append(format!(
-            r#"fx_version "cerulean"
-game "gta5"
-lua54 "yes"
+/// Boolean manifest directives `--manifest-flag` accepts without a warning.
+const KNOWN_MANIFEST_FLAGS: [&str; 8] = [
+    "clr_disable_task_scheduler",
+    "clr_experimental_2021",
+    "disable_lazy_natives",
+    "loadscreen_cursor",
+    "loadscreen_manual_shutdown",
+    "this_is_a_map",
+    "use_experimental_fxv2_oal",
+    "use_fxv2_oal",
+];
 
-author "{}"
-version "0.0.0"
+/// Directives `create` writes itself, which a `--manifest-flag` would repeat.
+const GENERATED_DIRECTIVES: [&str; 12] = [
+    "fx_version",
+    "game",
+    "games",
+    "rdr3_warning",
+    "lua54",
+    "server_only",
+    "name",
+    "author",
+    "description",
+    "version",
+    "dependency",
+    "provides",
+];
 
-{server_scripts}
+fn parse_manifest_flag(value: &str) -> Result<(String, String), String> {
+    let Some((key, flag)) = value.split_once('=') else {
+        return Err(format!("`{value}` is not KEY=VALUE"));
+    };
 
-{client_scripts}
+    let valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid_key {
+        return Err(format!("`{key}` is not a valid manifest directive"));
+    }
 
-{shared_scripts}
-        "#,
-            self.author
-        ));
+    if GENERATED_DIRECTIVES.contains(&key) {
+        return Err(format!("`{key}` is written by create itself"));
+    }
 
-        if self.use_data_files {
-            builder.append(
-                r#"
-data_files {
-    "data/*.lua"
+    if flag.is_empty() || flag.contains(['"', '\\', '\n', '\r']) {
+        return Err(format!("`{flag}` is not a valid value for `{key}`"));
+    }
+
+    Ok((key.to_owned(), flag.to_owned()))
 }
-            "#,
-            )
+
+/// Warns about `--manifest-flag` keys outside [`KNOWN_MANIFEST_FLAGS`], or
+/// fails on them when `strict`.
+fn check_manifest_flags(flags: &[(String, String)], strict: bool) -> CfxResult<()> {
+    for (key, _) in flags {
+        if KNOWN_MANIFEST_FLAGS.contains(&key.as_str()) {
+            continue;
         }
 
-        let result = builder.string()?.trim().to_owned();
-        Ok(result)
+        let message = format!("`{key}` is not a known manifest toggle");
+        if strict {
+            return Err(message.into());
+        }
+
+        log::warn!("{message}, writing it anyway");
     }
 
-    fn build_script_section(&self, name: &str, runtime: ScriptRuntime) -> CfxResult<String> {
-        let mut builder = ScriptSectionBuilder::new(name);
-        for library in self.get_runtime_libraries(&runtime) {
-            builder.append(&library.import);
+    Ok(())
+}
+
+fn parse_build_number(value: &str, range: std::ops::RangeInclusive<u32>) -> Result<u32, String> {
+    let number = value
+        .parse::<u32>()
+        .map_err(|_| format!("`{value}` is not a build number"))?;
+
+    if !range.contains(&number) {
+        return Err(format!(
+            "{number} is outside the plausible range {}-{}",
+            range.start(),
+            range.end()
+        ));
+    }
+
+    Ok(number)
+}
+
+fn parse_server_version(value: &str) -> Result<u32, String> {
+    parse_build_number(value, 1000..=99999)
+}
+
+fn parse_game_build(value: &str) -> Result<u32, String> {
+    parse_build_number(value, 1604..=9999)
+}
+
+#[derive(Debug, Clone)]
+struct Library {
+    import: Option<String>,
+    runtime: ScriptRuntime,
+    required_convars: Vec<String>,
+    notes: Option<String>,
+    example: Option<Example>,
+    /// Listed in `client_scripts` as well when `--client-init` is given
+    client_init: Option<String>,
+    /// Imports are emitted in ascending order, so libraries others build on
+    /// (ox_lib, oxmysql) load first
+    load_order: i32,
+    /// Listed at the top of its section, before everything else in it
+    priority: bool,
+    /// Games the library runs on
+    games: &'static [Game],
+}
+
+/// Plain-text file some projects keep their version in, next to the manifest.
+pub const VERSION_FILE: &str = "VERSION";
+
+/// Changelog in the Keep a Changelog format, see [`changelog`].
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Version new projects start out at.
+const INITIAL_VERSION: &str = "0.0.0";
+
+/// Load order of libraries nothing else depends on.
+const DEFAULT_LOAD_ORDER: i32 = 100;
+
+/// Starter code written into the generated `main.lua` files.
+#[derive(Debug, Clone)]
+struct Example {
+    client: &'static str,
+    server: &'static str,
+}
+
+impl Library {
+    pub fn new(import: impl Into<String>, runtime: ScriptRuntime) -> Self {
+        Self {
+            import: Some(import.into()),
+            runtime,
+            ..Self::exports_only()
         }
+    }
 
-        match &runtime {
-            ScriptRuntime::Server => {
-                builder.append("src/server/main.lua");
-            }
-            ScriptRuntime::Client => {
-                builder.append("src/client/main.lua");
-            }
-            _ => {}
+    /// A library used through its exports alone, with nothing to import.
+    pub fn exports_only() -> Self {
+        Self {
+            import: None,
+            runtime: ScriptRuntime::Shared,
+            required_convars: vec![],
+            notes: None,
+            example: None,
+            client_init: None,
+            load_order: DEFAULT_LOAD_ORDER,
+            priority: false,
+            games: &[Game::Gta5, Game::Rdr3],
         }
+    }
 
-        Ok(builder.build()?)
+    pub fn with_games(mut self, games: &'static [Game]) -> Self {
+        self.games = games;
+        self
     }
 
-    fn get_runtime_libraries(&self, runtime: &ScriptRuntime) -> Vec<&Library> {
-        self.libraries
+    /// A warning naming the `games` the library does not run on, if any.
+    fn unsupported_games(&self, name: &str, games: &[Game]) -> Option<String> {
+        let unsupported = games
             .iter()
-            .filter(|x| x.runtime == *runtime)
-            .collect::<Vec<&Library>>()
+            .filter(|game| !self.games.contains(game))
+            .map(|game| game.name())
+            .collect::<Vec<&str>>();
+        match unsupported.as_slice() {
+            [] => None,
+            names => Some(format!("{name} does not support {}", names.join(", "))),
+        }
+    }
+
+    pub fn with_load_order(mut self, load_order: i32) -> Self {
+        self.load_order = load_order;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: bool) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_required_convars(mut self, convars: &[&str]) -> Self {
+        self.required_convars = convars.iter().map(|&convar| convar.to_owned()).collect();
+        self
+    }
+
+    pub fn with_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    pub fn with_client_init(mut self, import: impl Into<String>) -> Self {
+        self.client_init = Some(import.into());
+        self
+    }
+
+    pub fn with_example(mut self, client: &'static str, server: &'static str) -> Self {
+        self.example = Some(Example { client, server });
+        self
     }
 }
 
-pub fn handle_create_command() -> CfxResult<()> {
-    let library_names = LIBRARIES.keys().cloned().collect::<Vec<&str>>();
+/// Joins the examples of `libraries` for `runtime`, naming events after `resource`.
+fn render_examples(
+    libraries: &[Library],
+    runtime: &ScriptRuntime,
+    resource: &str,
+) -> CfxResult<String> {
+    let variables = HashMap::from([("resource".to_owned(), resource.to_owned())]);
+    let mut snippets = vec![];
 
-    let min_length_validator = |input: &str| {
-        if input.chars().count() < 1 {
-            Ok(Validation::Invalid("Invalid input".into()))
-        } else {
-            Ok(Validation::Valid)
+    for example in libraries
+        .iter()
+        .filter_map(|library| library.example.as_ref())
+    {
+        let snippet = match runtime {
+            ScriptRuntime::Client => example.client,
+            ScriptRuntime::Server => example.server,
+            ScriptRuntime::Shared => continue,
+        };
+
+        let snippet = substitute(snippet, &variables)
+            .map_err(|var| format!("Example uses unknown variable `{var}`"))?;
+        if !snippets.contains(&snippet) {
+            snippets.push(snippet);
         }
+    }
+
+    Ok(snippets.join("\n"))
+}
+
+/// Where a module of `runtime` is written, relative to the project.
+fn module_path(runtime: &ScriptRuntime, name: &str) -> String {
+    let dir = match runtime {
+        ScriptRuntime::Server => "server",
+        ScriptRuntime::Client => "client",
+        ScriptRuntime::Shared => "shared",
     };
 
-    let project_name = Text::new("What is your project name?")
-        .with_validator(min_length_validator)
-        .prompt()?;
+    format!("src/{dir}/modules/{name}.lua")
+}
 
-    let author_name = Text::new("What is the authors name?")
-        .with_validator(min_length_validator)
-        .prompt()?;
+/// Writes a stub for each module, which registers itself in the global
+/// `Modules` table, returning the written files relative to `base_path`.
+fn write_modules(
+    base_path: &Path,
+    runtime: &ScriptRuntime,
+    names: &[String],
+) -> CfxResult<Vec<String>> {
+    let mut files = vec![];
+    for name in names {
+        let relative = module_path(runtime, name);
+        let path = base_path.join(&relative);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
 
-    let use_data_files = Confirm::new("Do you want to use data files?")
-        .with_default(false)
-        .prompt()?;
+        let stub = format!(
+            "local module = {{}}\n\nfunction module.init()\nend\n\nModules = Modules or {{}}\nModules['{name}'] = module\n"
+        );
+        File::create(path)?.write_all(stub.as_bytes())?;
+        files.push(relative);
+    }
 
-    let libraries: Vec<_> = MultiSelect::new(
-        "What libraries/frameworks do you want to use?",
-        library_names,
-    )
-    .prompt()?
-    .iter()
-    .map(|&name| LIBRARIES.get(name).expect("Invalid library").clone())
-    .collect::<Vec<Library>>();
+    Ok(files)
+}
 
-    let manifest = ScriptManifest::new(&author_name, use_data_files, libraries);
-    let manifest_str = manifest.build()?;
+/// Locale files ox_lib's `lib.locale()` loads, shipped to clients via `files`.
+const LOCALES_PATTERN: &str = "locales/*.json";
+const LOCALE_FILE: &str = "locales/en.json";
 
-    let base_path = format!("{project_name}");
-    if use_data_files {
-        create_dir_all(format!("{base_path}/data"))?;
+/// Whether the selected libraries can load locale files, which takes ox_lib.
+fn supports_locales(selected_names: &[&str]) -> bool {
+    selected_names.contains(&"ox_lib")
+}
+
+/// Lists the locale files in the manifest and prefixes `example` with the
+/// `lib.locale()` call that loads them.
+fn setup_locales(manifest: &mut ScriptManifest, example: String) -> String {
+    manifest.file(LOCALES_PATTERN);
+    if example.is_empty() {
+        "lib.locale()\n".to_owned()
+    } else {
+        format!("lib.locale()\n\n{example}")
     }
+}
 
-    create_dir_all(format!("{base_path}/src/client"))?;
-    create_dir_all(format!("{base_path}/src/server"))?;
-    create_dir_all(format!("{base_path}/src/shared"))?;
+/// Writes `locales/en.json` with a sample key, returning its relative path.
+fn write_locales(base_path: &Path, project_name: &str) -> CfxResult<String> {
+    let locale = serde_json::json!({ "greeting": format!("Hello from {project_name}!") });
+    create_dir_all(base_path.join("locales"))?;
+    atomic_write(
+        base_path.join(LOCALE_FILE),
+        serde_json::to_string_pretty(&locale)? + "\n",
+    )?;
 
-    File::create(format!("{base_path}/src/client/main.lua"))?;
-    File::create(format!("{base_path}/src/server/main.lua"))?;
+    Ok(LOCALE_FILE.to_owned())
+}
 
-    let mut manifest_file = File::create(format!("{base_path}/fxmanifest.lua"))?;
-    manifest_file.write_all(manifest_str.as_bytes())?;
+/// Entry scripts esbuild bundles into `dist/`, for the runtimes of `scope`:
+/// the runtime name, source entry and bundle path.
+fn bundle_entries(scope: Scope, language: Language) -> Vec<(&'static str, String, String)> {
+    let extension = match language {
+        Language::Ts => "ts",
+        _ => "js",
+    };
 
-    Ok(())
+    [
+        ("client", ScriptRuntime::Client),
+        ("server", ScriptRuntime::Server),
+    ]
+    .into_iter()
+    .filter(|(_, runtime)| scope.includes(runtime))
+    .map(|(name, _)| {
+        (
+            name,
+            format!("src/{name}/main.{extension}"),
+            bundle_path(name),
+        )
+    })
+    .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn bundle_path(runtime: &str) -> String {
+    format!("dist/{runtime}.js")
+}
 
-    #[test]
-    fn script_section_multiple_test() {
-        let mut builder = ScriptSectionBuilder::new("server");
-        builder.append("@es_extended/imports.lua");
-        builder.append("src/server/main.lua");
+/// Builds `build.js`, which bundles each entry with esbuild, rebuilding on
+/// changes when run with `--watch`. Clients run a plain V8 isolate and the
+/// server runs Node 16, so each gets its own platform and target.
+fn build_script(scope: Scope, language: Language) -> String {
+    let builds = bundle_entries(scope, language)
+        .into_iter()
+        .map(|(name, entry, outfile)| {
+            let (platform, target) = match name {
+                "server" => ("node", "node16"),
+                _ => ("browser", "es2020"),
+            };
+            format!(
+                "  {{ entryPoints: ['{entry}'], outfile: '{outfile}', platform: '{platform}', target: '{target}' }},"
+            )
+        })
+        .collect::<Vec<String>>();
 
-        let result = builder.build().unwrap();
-        let expected = r#"server_scripts {
-    "@es_extended/imports.lua",
-    "src/server/main.lua"
-}"#;
+    format!(
+        r#"const esbuild = require('esbuild');
 
-        assert_eq!(result, expected);
+const watch = process.argv.includes('--watch');
+const builds = [
+{}
+];
+
+(async () => {{
+  for (const build of builds) {{
+    const options = {{ ...build, bundle: true, format: 'iife', logLevel: 'info' }};
+    if (watch) {{
+      await (await esbuild.context(options)).watch();
+    }} else {{
+      await esbuild.build(options);
+    }}
+  }}
+}})().catch(() => process.exit(1));
+"#,
+        builds.join("\n")
+    )
+}
+
+/// Builds `package.json` with the `build` and `watch` scripts and the
+/// bundler and runtime typings as dev dependencies.
+fn package_json(project_name: &str, scope: Scope) -> CfxResult<String> {
+    let mut dev_dependencies = serde_json::Map::new();
+    for (name, runtime) in [
+        ("client", ScriptRuntime::Client),
+        ("server", ScriptRuntime::Server),
+    ] {
+        if scope.includes(&runtime) {
+            dev_dependencies.insert(format!("@citizenfx/{name}"), "latest".into());
+        }
     }
+    dev_dependencies.insert("esbuild".to_owned(), "^0.24.0".into());
 
-    #[test]
-    fn script_section_single_test() {
-        let mut builder = ScriptSectionBuilder::new("server");
-        builder.append("src/server/main.lua");
+    let package = serde_json::json!({
+        "name": project_name.to_lowercase(),
+        "private": true,
+        "scripts": {
+            "build": "node build.js",
+            "watch": "node build.js --watch"
+        },
+        "devDependencies": dev_dependencies
+    });
 
-        let result = builder.build().unwrap();
-        let expected = r#"server_scripts {
-    "src/server/main.lua"
-}"#;
+    Ok(serde_json::to_string_pretty(&package)? + "\n")
+}
 
-        assert_eq!(result, expected);
+/// Writes the entry scripts, `build.js` and `package.json` of a bundled
+/// project, returning the written files relative to `base_path`.
+fn write_bundle(
+    base_path: &Path,
+    project_name: &str,
+    scope: Scope,
+    language: Language,
+) -> CfxResult<Vec<String>> {
+    let mut files = vec![];
+    for (name, entry, _) in bundle_entries(scope, language) {
+        create_dir_all(base_path.join("src").join(name))?;
+        let example =
+            format!("console.log(`${{GetCurrentResourceName()}} started on the {name}`);\n");
+        File::create(base_path.join(&entry))?.write_all(example.as_bytes())?;
+        files.push(entry);
+    }
+
+    atomic_write(base_path.join("build.js"), build_script(scope, language))?;
+    files.push("build.js".to_owned());
+    atomic_write(
+        base_path.join("package.json"),
+        package_json(project_name, scope)?,
+    )?;
+    files.push("package.json".to_owned());
+
+    Ok(files)
+}
+
+/// Lists `names` in the export block of `runtime` and, with `stubs`, appends
+/// an empty definition of each to `example`.
+fn setup_exports(
+    manifest: &mut ScriptManifest,
+    runtime: ScriptRuntime,
+    names: &[String],
+    stubs: bool,
+    example: String,
+) -> String {
+    for name in names {
+        manifest.export(runtime.clone(), name);
+    }
+
+    if !stubs || names.is_empty() {
+        return example;
+    }
+
+    let stubs = names
+        .iter()
+        .map(|name| format!("exports('{name}', function()\nend)\n"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    match example.trim_end() {
+        "" => stubs,
+        example => format!("{example}\n\n{stubs}"),
+    }
+}
+
+/// Prefixes `example` with the initialization of `modules`, in order. The
+/// manifest lists the modules before `main.lua`, so they are loaded by then.
+fn with_module_loader(modules: &[String], example: String) -> String {
+    if modules.is_empty() {
+        return example;
+    }
+
+    let names = modules
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<String>>();
+    let loader = format!(
+        "for _, name in ipairs({{ {} }}) do\n    Modules[name].init()\nend\n",
+        names.join(", ")
+    );
+
+    if example.is_empty() {
+        loader
+    } else {
+        format!("{loader}\n{example}")
+    }
+}
+
+/// The script section (`shared`, `client` or `server`) and import of a known
+/// library; the import is `None` for libraries used through exports alone.
+pub fn library_import(name: &str) -> Option<(&'static str, Option<&'static str>)> {
+    let library = LIBRARIES.get(name)?;
+    let section = match library.runtime {
+        ScriptRuntime::Server => "server",
+        ScriptRuntime::Client => "client",
+        ScriptRuntime::Shared => "shared",
+    };
+
+    Some((section, library.import.as_deref()))
+}
+
+/// Renders a `<section>_scripts` table, or the singular directive for a
+/// single script with [`SectionStyle::Inline`], the way `create` writes them.
+pub fn render_script_section(
+    section: &str,
+    scripts: &[String],
+    style: SectionStyle,
+) -> CfxResult<Option<String>> {
+    let mut builder = ScriptSectionBuilder::new(section);
+    builder.style(style);
+    for script in scripts {
+        builder.append(script);
+    }
+
+    builder.build()
+}
+
+/// Number of libraries `create` knows how to set up.
+pub fn library_count() -> usize {
+    LIBRARIES.len()
+}
+
+/// Whether `name` is a library `create` knows how to set up.
+pub fn is_known_library(name: &str) -> bool {
+    LIBRARIES.contains_key(name)
+}
+
+/// Convars the known libraries among `names` require, in order and deduplicated.
+pub fn library_convars(names: &[String]) -> Vec<String> {
+    let libraries = names
+        .iter()
+        .filter_map(|name| LIBRARIES.get_key_value(name.as_str()))
+        .map(|(&name, library)| (name, library));
+
+    InstallInstructions::collect(libraries).required_convars
+}
+
+/// Post-install steps gathered from the selected libraries.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct InstallInstructions {
+    pub required_convars: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl InstallInstructions {
+    fn collect<'a>(libraries: impl IntoIterator<Item = (&'a str, &'a Library)>) -> Self {
+        let mut result = Self::default();
+        for (name, library) in libraries {
+            for convar in &library.required_convars {
+                if !result.required_convars.contains(convar) {
+                    result.required_convars.push(convar.clone());
+                }
+            }
+
+            if let Some(notes) = &library.notes {
+                result.notes.push(format!("{name}: {notes}"));
+            }
+        }
+
+        result
+    }
+
+    fn is_empty(&self) -> bool {
+        self.required_convars.is_empty() && self.notes.is_empty()
+    }
+
+    fn build_markdown(&self, project_name: &str) -> String {
+        let mut builder = Builder::default();
+        builder.append(format!("# Installing {project_name}\n"));
+
+        if !self.required_convars.is_empty() {
+            builder.append("\n## Required convars\n\nSet these in your server.cfg:\n\n");
+            for convar in &self.required_convars {
+                builder.append(format!("- `set {convar} \"...\"`\n"));
+            }
+        }
+
+        if !self.notes.is_empty() {
+            builder.append("\n## Notes\n\n");
+            for notes in &self.notes {
+                builder.append(format!("- {notes}\n"));
+            }
+        }
+
+        builder.string().unwrap_or_default()
+    }
+}
+
+/// How a script section with a single entry is written to the manifest.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionStyle {
+    /// Always emit the `*_scripts { ... }` table, one entry per line
+    #[default]
+    Table,
+    /// Emit the singular `*_script "..."` directive for single-entry sections
+    Inline,
+}
+
+struct ScriptSectionBuilder {
+    name: String,
+    scripts: Vec<String>,
+    /// Number of leading `scripts` marked as priority
+    priority: usize,
+    style: SectionStyle,
+}
+
+impl ScriptSectionBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            scripts: vec![],
+            priority: 0,
+            style: SectionStyle::Table,
+        }
+    }
+
+    pub fn append(&mut self, path: impl Into<String>) -> &mut Self {
+        self.scripts.push(path.into());
+        self
+    }
+
+    /// Appends `path` after the priority scripts added so far but before all
+    /// others, so priority scripts keep the order they were added in.
+    pub fn append_priority(&mut self, path: impl Into<String>) -> &mut Self {
+        self.scripts.insert(self.priority, path.into());
+        self.priority += 1;
+        self
+    }
+
+    fn append_with(&mut self, path: impl Into<String>, priority: bool) -> &mut Self {
+        if priority {
+            self.append_priority(path)
+        } else {
+            self.append(path)
+        }
+    }
+
+    pub fn style(&mut self, style: SectionStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Builds the section, or returns `None` when there are no scripts to list.
+    pub fn build(&self) -> CfxResult<Option<String>> {
+        if self.scripts.is_empty() {
+            return Ok(None);
+        }
+
+        if self.style == SectionStyle::Inline && self.scripts.len() == 1 {
+            return Ok(Some(format!(
+                "{}_script \"{}\"",
+                self.name, self.scripts[0]
+            )));
+        }
+
+        let mut string_builder = Builder::default();
+        string_builder.append(format!("{}_scripts {{\n", self.name));
+
+        for (i, script) in self.scripts.iter().enumerate() {
+            string_builder.append(format!("    \"{script}\""));
+            if i < self.scripts.len() - 1 {
+                string_builder.append(",");
+            }
+
+            string_builder.append("\n")
+        }
+
+        string_builder.append("}");
+        Ok(Some(string_builder.string()?))
+    }
+}
+
+/// Keys `ScriptManifest::build` can emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ManifestKey {
+    FxVersion,
+    Games,
+    Rdr3Warning,
+    Lua54,
+    ServerOnly,
+    Flags,
+    Name,
+    Author,
+    Description,
+    Version,
+    Dependencies,
+    Provides,
+    ServerScripts,
+    ClientScripts,
+    SharedScripts,
+    Exports,
+    ServerExports,
+    DataFiles,
+    Loadscreen,
+    LoadscreenManualShutdown,
+    Files,
+    DataFile,
+}
+
+impl ManifestKey {
+    /// Keys sharing a block are emitted on consecutive lines; blocks are
+    /// separated by a blank line.
+    fn block(self) -> usize {
+        match self {
+            Self::FxVersion
+            | Self::Games
+            | Self::Rdr3Warning
+            | Self::Lua54
+            | Self::ServerOnly
+            | Self::Flags => 0,
+            Self::Name | Self::Author | Self::Description | Self::Version => 1,
+            Self::Dependencies | Self::Provides => 2,
+            Self::ServerScripts => 3,
+            Self::ClientScripts => 4,
+            Self::SharedScripts => 5,
+            Self::Exports | Self::ServerExports => 6,
+            Self::DataFiles => 7,
+            Self::Loadscreen | Self::LoadscreenManualShutdown => 8,
+            Self::Files => 9,
+            Self::DataFile => 10,
+        }
+    }
+}
+
+/// Comments `ScriptManifest::build` labels each block with when asked to,
+/// indexed by [`ManifestKey::block`].
+const BLOCK_COMMENTS: [&str; 11] = [
+    "-- What the resource runs on",
+    "-- About the resource",
+    "-- Resources that have to start first, or that this one replaces",
+    "-- Scripts run by the server",
+    "-- Scripts run by every client",
+    "-- Scripts run on both sides, before the server and client ones",
+    "-- Functions other resources can call through exports",
+    "-- Lua files the server can read with LoadResourceFile",
+    "-- NUI page shown while the game loads",
+    "-- Files sent to clients, such as NUI pages",
+    "-- Game data files loaded by clients",
+];
+
+/// Manifest settings from flags that every kind of resource shares.
+struct ManifestExtras<'a> {
+    games: &'a [Game],
+    /// `--manifest-flag` directives and their values
+    flags: &'a [(String, String)],
+    /// Whether icon.png is added to `files`
+    icon: bool,
+    /// Whether blocks are labeled with comments
+    comments: bool,
+}
+
+impl Default for ManifestExtras<'_> {
+    fn default() -> Self {
+        Self {
+            games: &[Game::Gta5],
+            flags: &[],
+            icon: false,
+            comments: false,
+        }
+    }
+}
+
+/// The order `ScriptManifest::build` emits present keys in, whatever order
+/// they were set in: what the resource runs on, who made it, what it needs,
+/// its scripts by runtime, then the files it ships. New keys go into this
+/// list so generated manifests stay stable.
+const MANIFEST_ORDER: [ManifestKey; 22] = [
+    ManifestKey::FxVersion,
+    ManifestKey::Games,
+    ManifestKey::Rdr3Warning,
+    ManifestKey::Lua54,
+    ManifestKey::ServerOnly,
+    ManifestKey::Flags,
+    ManifestKey::Name,
+    ManifestKey::Author,
+    ManifestKey::Description,
+    ManifestKey::Version,
+    ManifestKey::Dependencies,
+    ManifestKey::Provides,
+    ManifestKey::ServerScripts,
+    ManifestKey::ClientScripts,
+    ManifestKey::SharedScripts,
+    ManifestKey::Exports,
+    ManifestKey::ServerExports,
+    ManifestKey::DataFiles,
+    ManifestKey::Loadscreen,
+    ManifestKey::LoadscreenManualShutdown,
+    ManifestKey::Files,
+    ManifestKey::DataFile,
+];
+
+struct ScriptManifest {
+    /// What the resource is; a loading screen has its page instead of scripts
+    kind: ResourceKind,
+    /// Whether a loading screen stays up until a script shuts it down
+    manual_shutdown: bool,
+    name: Option<String>,
+    author: String,
+    description: Option<String>,
+    version: String,
+    use_data_files: bool,
+    libraries: Vec<Library>,
+    scope: Scope,
+    section_style: SectionStyle,
+    dependencies: Vec<String>,
+    /// Resources this one stands in for, emitted as `provides`
+    provides: Vec<String>,
+    games: Vec<Game>,
+    /// `--manifest-flag` directives and their values
+    flags: Vec<(String, String)>,
+    entry_scripts: bool,
+    /// Whether the entry scripts are esbuild bundles in `dist/`
+    bundled: bool,
+    client_init: bool,
+    modules: Vec<(ScriptRuntime, String)>,
+    scripts: Vec<(ScriptRuntime, String)>,
+    /// Module and script paths listed at the top of their section
+    priority_scripts: Vec<String>,
+    /// Functions declared in `exports` and `server_exports`
+    exports: Vec<String>,
+    server_exports: Vec<String>,
+    files: Vec<String>,
+    data_files: Vec<(String, String)>,
+    /// Whether each block is labeled with a comment
+    comments: bool,
+}
+
+impl ScriptManifest {
+    pub fn new(author: impl Into<String>, use_data_files: bool, libraries: Vec<Library>) -> Self {
+        Self {
+            kind: ResourceKind::Script,
+            manual_shutdown: false,
+            name: None,
+            author: author.into(),
+            description: None,
+            version: INITIAL_VERSION.to_owned(),
+            use_data_files,
+            libraries,
+            scope: Scope::Both,
+            section_style: SectionStyle::Table,
+            dependencies: vec![],
+            provides: vec![],
+            games: vec![Game::Gta5],
+            flags: vec![],
+            entry_scripts: true,
+            bundled: false,
+            client_init: false,
+            modules: vec![],
+            scripts: vec![],
+            priority_scripts: vec![],
+            exports: vec![],
+            server_exports: vec![],
+            files: vec![],
+            data_files: vec![],
+            comments: false,
+        }
+    }
+
+    pub fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets what the resource is. Asset and loading screen resources have
+    /// no entry scripts, and a loading screen has no script sections at all.
+    pub fn kind(&mut self, kind: ResourceKind) -> &mut Self {
+        self.kind = kind;
+        self.entry_scripts(kind == ResourceKind::Script)
+    }
+
+    /// Whether a loading screen waits for `ShutdownLoadingScreenNui`.
+    pub fn manual_shutdown(&mut self, manual_shutdown: bool) -> &mut Self {
+        self.manual_shutdown = manual_shutdown;
+        self
+    }
+
+    pub fn version(&mut self, version: impl Into<String>) -> &mut Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn description(&mut self, description: impl Into<String>) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Content of a `VERSION` file holding the same version as the manifest.
+    pub fn version_file(&self) -> String {
+        format!("{}\n", self.version)
+    }
+
+    /// Lists `path` in the section of `runtime`, after the entry scripts.
+    fn script(&mut self, runtime: ScriptRuntime, path: impl Into<String>) -> &mut Self {
+        self.scripts.push((runtime, path.into()));
+        self
+    }
+
+    /// Adds a module script, listed in order before the entry script.
+    fn module(&mut self, runtime: ScriptRuntime, path: impl Into<String>) -> &mut Self {
+        self.modules.push((runtime, path.into()));
+        self
+    }
+
+    /// Lists the module or script at `path` at the top of its section.
+    fn priority_script(&mut self, path: impl Into<String>) -> &mut Self {
+        self.priority_scripts.push(path.into());
+        self
+    }
+
+    /// Whether library client inits are listed in `client_scripts`.
+    pub fn client_init(&mut self, client_init: bool) -> &mut Self {
+        self.client_init = client_init;
+        self
+    }
+
+    /// Whether the generated `src/*/main.lua` scripts are listed.
+    pub fn entry_scripts(&mut self, entry_scripts: bool) -> &mut Self {
+        self.entry_scripts = entry_scripts;
+        self
+    }
+
+    /// Whether the entry scripts are the `dist/*.js` bundles built from
+    /// JavaScript or TypeScript rather than `src/*/main.lua`.
+    pub fn bundled(&mut self, bundled: bool) -> &mut Self {
+        self.bundled = bundled;
+        self
+    }
+
+    /// Declares the function `name` in the export block of `runtime`; shared
+    /// functions are exported on both sides.
+    pub fn export(&mut self, runtime: ScriptRuntime, name: impl Into<String>) -> &mut Self {
+        let name = name.into();
+        if runtime != ScriptRuntime::Server {
+            self.exports.push(name.clone());
+        }
+
+        if runtime != ScriptRuntime::Client {
+            self.server_exports.push(name);
+        }
+
+        self
+    }
+
+    pub fn file(&mut self, path: impl Into<String>) -> &mut Self {
+        self.files.push(path.into());
+        self
+    }
+
+    /// Adds a `data_file` line; lines keep the order they were added in.
+    pub fn data_file(&mut self, kind: impl Into<String>, path: impl Into<String>) -> &mut Self {
+        self.data_files.push((kind.into(), path.into()));
+        self
+    }
+
+    pub fn scope(&mut self, scope: Scope) -> &mut Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn section_style(&mut self, style: SectionStyle) -> &mut Self {
+        self.section_style = style;
+        self
+    }
+
+    pub fn dependency(&mut self, dependency: impl Into<String>) -> &mut Self {
+        self.dependencies.push(dependency.into());
+        self
+    }
+
+    pub fn provides(&mut self, resource: impl Into<String>) -> &mut Self {
+        self.provides.push(resource.into());
+        self
+    }
+
+    pub fn flags(&mut self, flags: &[(String, String)]) -> &mut Self {
+        self.flags.extend_from_slice(flags);
+        self
+    }
+
+    /// Whether each block starts with a comment explaining it.
+    pub fn comments(&mut self, comments: bool) -> &mut Self {
+        self.comments = comments;
+        self
+    }
+
+    pub fn extras(&mut self, extras: &ManifestExtras) -> &mut Self {
+        self.games(extras.games)
+            .flags(extras.flags)
+            .comments(extras.comments);
+        if extras.icon {
+            self.file(ICON_FILE);
+        }
+
+        self
+    }
+
+    pub fn games(&mut self, games: &[Game]) -> &mut Self {
+        self.games.clear();
+        for game in games {
+            if !self.games.contains(game) {
+                self.games.push(*game);
+            }
+        }
+
+        self
+    }
+
+    /// Builds the manifest as blank-line separated blocks, without trailing
+    /// whitespace and terminated by a single newline. With comments on, each
+    /// block starts with its line from [`BLOCK_COMMENTS`].
+    pub fn build(&self) -> CfxResult<String> {
+        let mut blocks: Vec<(usize, Vec<String>)> = vec![];
+        for key in MANIFEST_ORDER {
+            let Some(text) = self.build_key(key)? else {
+                continue;
+            };
+
+            match blocks.last_mut() {
+                Some((block, lines)) if *block == key.block() => lines.push(text),
+                _ if self.comments => blocks.push((
+                    key.block(),
+                    vec![BLOCK_COMMENTS[key.block()].to_owned(), text],
+                )),
+                _ => blocks.push((key.block(), vec![text])),
+            }
+        }
+
+        let blocks = blocks.into_iter().map(|(_, lines)| lines.join("\n"));
+        let mut builder = Builder::default();
+        for block in blocks {
+            if builder.len() > 0 {
+                builder.append("\n\n");
+            }
+
+            builder.append(block);
+        }
+
+        builder.append("\n");
+        Ok(builder.string()?)
+    }
+
+    /// The lines of `key`, or `None` when the manifest does not have it.
+    fn build_key(&self, key: ManifestKey) -> CfxResult<Option<String>> {
+        let lines = |values: &[String], directive: &str| {
+            let lines = values
+                .iter()
+                .map(|value| format!("{directive} \"{value}\""))
+                .collect::<Vec<String>>();
+            Some(lines.join("\n")).filter(|lines| !lines.is_empty())
+        };
+        let table = |values: &[String], directive: &str| {
+            let lines = values
+                .iter()
+                .map(|value| format!("    \"{value}\""))
+                .collect::<Vec<String>>();
+            (!lines.is_empty()).then(|| format!("{directive} {{\n{}\n}}", lines.join(",\n")))
+        };
+
+        let text = match key {
+            ManifestKey::FxVersion => Some("fx_version \"cerulean\"".to_owned()),
+            ManifestKey::Games => Some(match self.games.as_slice() {
+                [game] => format!("game \"{}\"", game.name()),
+                games => {
+                    let names = games
+                        .iter()
+                        .map(|game| format!("\"{}\"", game.name()))
+                        .collect::<Vec<String>>();
+                    format!("games {{ {} }}", names.join(", "))
+                }
+            }),
+            ManifestKey::Rdr3Warning => self
+                .games
+                .contains(&Game::Rdr3)
+                .then(|| format!("rdr3_warning \"{RDR3_WARNING}\"")),
+            ManifestKey::Lua54 => Some("lua54 \"yes\"".to_owned()),
+            ManifestKey::ServerOnly => {
+                (self.scope == Scope::Server).then(|| "server_only \"yes\"".to_owned())
+            }
+            ManifestKey::Flags => {
+                let lines = self
+                    .flags
+                    .iter()
+                    .map(|(key, value)| format!("{key} \"{value}\""))
+                    .collect::<Vec<String>>();
+                Some(lines.join("\n")).filter(|lines| !lines.is_empty())
+            }
+            ManifestKey::Name => self.name.as_ref().map(|name| format!("name \"{name}\"")),
+            ManifestKey::Author => Some(format!("author \"{}\"", self.author)),
+            ManifestKey::Description => self
+                .description
+                .as_ref()
+                .map(|description| format!("description \"{description}\"")),
+            ManifestKey::Version => Some(format!("version \"{}\"", self.version)),
+            ManifestKey::Dependencies => lines(&self.dependencies, "dependency"),
+            ManifestKey::Provides => lines(&self.provides, "provides"),
+            ManifestKey::ServerScripts => {
+                self.build_runtime_section("server", ScriptRuntime::Server)?
+            }
+            ManifestKey::ClientScripts => {
+                self.build_runtime_section("client", ScriptRuntime::Client)?
+            }
+            ManifestKey::SharedScripts => {
+                self.build_runtime_section("shared", ScriptRuntime::Shared)?
+            }
+            ManifestKey::Exports => table(&self.exports, "exports"),
+            ManifestKey::ServerExports => table(&self.server_exports, "server_exports"),
+            ManifestKey::DataFiles => self
+                .use_data_files
+                .then(|| "data_files {\n    \"data/*.lua\"\n}".to_owned()),
+            ManifestKey::Loadscreen => (self.kind == ResourceKind::Loadscreen)
+                .then(|| format!("loadscreen \"{LOADSCREEN_PAGE}\"")),
+            ManifestKey::LoadscreenManualShutdown => (self.kind == ResourceKind::Loadscreen
+                && self.manual_shutdown)
+                .then(|| "loadscreen_manual_shutdown \"yes\"".to_owned()),
+            ManifestKey::Files => table(&self.files, "files"),
+            ManifestKey::DataFile => {
+                let lines = self
+                    .data_files
+                    .iter()
+                    .map(|(kind, path)| format!("data_file \"{kind}\" \"{path}\""))
+                    .collect::<Vec<String>>();
+                Some(lines.join("\n")).filter(|lines| !lines.is_empty())
+            }
+        };
+
+        Ok(text)
+    }
+
+    /// The script section of `runtime`, when the scope includes it.
+    fn build_runtime_section(
+        &self,
+        name: &str,
+        runtime: ScriptRuntime,
+    ) -> CfxResult<Option<String>> {
+        if self.kind == ResourceKind::Loadscreen || !self.scope.includes(&runtime) {
+            return Ok(None);
+        }
+
+        self.build_script_section(name, runtime)
+    }
+
+    fn build_script_section(
+        &self,
+        name: &str,
+        runtime: ScriptRuntime,
+    ) -> CfxResult<Option<String>> {
+        let mut builder = ScriptSectionBuilder::new(name);
+        builder.style(self.section_style);
+        for library in self.get_runtime_libraries(&runtime) {
+            if let Some(import) = &library.import {
+                builder.append_with(import, library.priority);
+            }
+        }
+
+        if self.client_init && runtime == ScriptRuntime::Client {
+            for import in self
+                .ordered_libraries()
+                .filter_map(|x| x.client_init.as_ref())
+            {
+                builder.append(import);
+            }
+        }
+
+        for (_, module) in self.modules.iter().filter(|(x, _)| *x == runtime) {
+            builder.append_with(module, self.priority_scripts.contains(module));
+        }
+
+        match &runtime {
+            ScriptRuntime::Server | ScriptRuntime::Client if self.bundled => {
+                builder.append(bundle_path(name));
+            }
+            ScriptRuntime::Server if self.entry_scripts => {
+                builder.append("src/server/main.lua");
+            }
+            ScriptRuntime::Client if self.entry_scripts => {
+                builder.append("src/client/main.lua");
+            }
+            _ => {}
+        }
+
+        for (_, script) in self.scripts.iter().filter(|(x, _)| *x == runtime) {
+            builder.append_with(script, self.priority_scripts.contains(script));
+        }
+
+        builder.build()
+    }
+
+    fn get_runtime_libraries(&self, runtime: &ScriptRuntime) -> Vec<&Library> {
+        self.ordered_libraries()
+            .filter(|x| x.runtime == *runtime)
+            .collect::<Vec<&Library>>()
+    }
+
+    /// Libraries by load order then import, so the manifest text does not
+    /// depend on the order they were selected in.
+    fn ordered_libraries(&self) -> impl Iterator<Item = &Library> {
+        let mut libraries = self.libraries.iter().collect::<Vec<&Library>>();
+        libraries.sort_by(|a, b| (a.load_order, &a.import).cmp(&(b.load_order, &b.import)));
+        libraries.into_iter()
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreateSummary {
+    pub name: String,
+    pub author: String,
+    pub files: Vec<String>,
+    pub install: InstallInstructions,
+    /// Answers the project was created from, as saved by `--save-answers`
+    pub answers: CreateAnswers,
+}
+
+/// Walks through the [`CREATE_STEPS`] wizard and writes the project. Esc goes
+/// back a question, Ctrl-C or Esc at the first question, or cancelling
+/// `cancel` while files are written, returns [`Interrupted`] without leaving a
+/// project directory behind.
+pub fn handle_create_command(args: &CreateArgs, cancel: &CancelToken) -> CfxResult<CreateSummary> {
+    let summary = create_project(args, cancel)?;
+    if let Some(path) = &args.save_answers {
+        atomic_write(path, toml::to_string_pretty(&summary.answers)?)?;
+        log::info!("Saved the answers to {}", path.display());
+    }
+
+    Ok(summary)
+}
+
+/// Answers to the `create` prompts, `None` until asked. This is the schema of
+/// `--answers` files and of the `[create]` sections of cfx.toml, which records
+/// what a project was created from, and of the user config, which holds
+/// defaults.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CreateAnswers {
+    #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Vehicle or ped model name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ped_addon: Option<PedAddon>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wavepack: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_files: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_file: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub libraries: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locales: Option<bool>,
+}
+
+impl CreateAnswers {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Reads an answers file. A cfx.toml works too, its `[create]` section is
+    /// used then.
+    pub fn load(path: &Path) -> CfxResult<Self> {
+        let content = read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+        let mut table = toml::from_str::<toml::Table>(&content)
+            .map_err(|err| format!("Invalid answers {path:?}: {err}"))?;
+        let answers = match table.remove("create") {
+            Some(create) => create,
+            None => toml::Value::Table(table),
+        };
+
+        answers
+            .try_into()
+            .map_err(|err| format!("Invalid answers {path:?}: {err}").into())
+    }
+
+    /// These answers, with the missing ones taken from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            project_name: self.project_name.or(fallback.project_name),
+            author: self.author.or(fallback.author),
+            model: self.model.or(fallback.model),
+            ped_addon: self.ped_addon.or(fallback.ped_addon),
+            wavepack: self.wavepack.or(fallback.wavepack),
+            data_files: self.data_files.or(fallback.data_files),
+            version_file: self.version_file.or(fallback.version_file),
+            libraries: self.libraries.or(fallback.libraries),
+            locales: self.locales.or(fallback.locales),
+        }
+    }
+}
+
+/// State of the `create` wizard, what the flags decided and the answers so far.
+struct CreateWizard {
+    kind: ResourceKind,
+    bundled: bool,
+    /// Whether a template provides the files, leaving only the name and
+    /// author to ask
+    template: bool,
+    /// Libraries offered for the selected games, in load order
+    library_names: Vec<String>,
+    answers: CreateAnswers,
+}
+
+impl CreateWizard {
+    /// Fails on answers given up front that no prompt could have given.
+    fn check_answers(&self) -> CfxResult<()> {
+        let answers = &self.answers;
+        for (key, text) in [
+            ("name", &answers.project_name),
+            ("author", &answers.author),
+            ("model", &answers.model),
+            ("wavepack", &answers.wavepack),
+        ] {
+            if text.as_ref().is_some_and(|text| text.trim().is_empty()) {
+                return Err(tf("create.empty_answer", &[("key", key)]).into());
+            }
+        }
+
+        if self.generates_script() {
+            for name in answers.libraries.iter().flatten() {
+                if !self.library_names.contains(name) {
+                    return Err(format!(
+                        "`{name}` in the `libraries` answer is not a library for the selected games"
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The answers of the steps that applied, leaving out defaults that were
+    /// never used.
+    fn used_answers(&self) -> CreateAnswers {
+        let mut answers = self.answers.clone();
+        if !matches!(self.kind, ResourceKind::Vehicle | ResourceKind::Ped) {
+            answers.model = None;
+        }
+
+        if self.kind != ResourceKind::Ped {
+            answers.ped_addon = None;
+        }
+
+        if self.kind != ResourceKind::Audio {
+            answers.wavepack = None;
+        }
+
+        if !self.generates_script() {
+            answers.data_files = None;
+            answers.version_file = None;
+            answers.libraries = None;
+        }
+
+        if !CreateStep::Locales.applies(self) {
+            answers.locales = None;
+        }
+
+        answers
+    }
+
+    /// Whether the scripts and manifest are generated rather than rendered
+    /// from a template.
+    fn generates_script(&self) -> bool {
+        self.kind == ResourceKind::Script && !self.template
+    }
+}
+
+/// The questions `create` asks, in order.
+#[derive(Debug, Clone, Copy)]
+enum CreateStep {
+    ProjectName,
+    Author,
+    VehicleModel,
+    PedAddon,
+    PedModel,
+    Wavepack,
+    DataFiles,
+    VersionFile,
+    Libraries,
+    Locales,
+}
+
+const CREATE_STEPS: [&dyn Step<CreateWizard>; 10] = [
+    &CreateStep::ProjectName,
+    &CreateStep::Author,
+    &CreateStep::VehicleModel,
+    &CreateStep::PedAddon,
+    &CreateStep::PedModel,
+    &CreateStep::Wavepack,
+    &CreateStep::DataFiles,
+    &CreateStep::VersionFile,
+    &CreateStep::Libraries,
+    &CreateStep::Locales,
+];
+
+const PED_ADDONS: [PedAddon; 2] = [PedAddon::FullPed, PedAddon::Components];
+
+impl Step<CreateWizard> for CreateStep {
+    fn title(&self) -> &'static str {
+        match self {
+            CreateStep::ProjectName => t("create.step.project_name"),
+            CreateStep::Author => t("create.step.author"),
+            CreateStep::VehicleModel => t("create.step.vehicle_model"),
+            CreateStep::PedAddon => t("create.step.ped_addon"),
+            CreateStep::PedModel => t("create.step.ped_model"),
+            CreateStep::Wavepack => t("create.step.wavepack"),
+            CreateStep::DataFiles => t("create.step.data_files"),
+            CreateStep::VersionFile => t("create.step.version_file"),
+            CreateStep::Libraries => t("create.step.libraries"),
+            CreateStep::Locales => t("create.step.locales"),
+        }
+    }
+
+    fn ask(&self, prompter: &mut dyn Prompter, wizard: &mut CreateWizard) -> CfxResult<Answer<()>> {
+        let answers = &mut wizard.answers;
+        Ok(match self {
+            CreateStep::ProjectName => prompter
+                .text(t("create.project_name"), answers.project_name.as_deref())?
+                .map(|name| answers.project_name = Some(name)),
+            CreateStep::Author => prompter
+                .text(t("create.author"), answers.author.as_deref())?
+                .map(|author| answers.author = Some(author)),
+            CreateStep::VehicleModel => prompter
+                .text(t("create.vehicle_model"), answers.model.as_deref())?
+                .map(|model| answers.model = Some(model)),
+            CreateStep::PedAddon => {
+                let options = PED_ADDONS.map(|addon| addon.to_string());
+                let cursor = PED_ADDONS
+                    .iter()
+                    .position(|&addon| Some(addon) == answers.ped_addon)
+                    .unwrap_or_default();
+                prompter
+                    .select(t("create.ped_addon"), &options, cursor)?
+                    .map(|index| answers.ped_addon = Some(PED_ADDONS[index]))
+            }
+            CreateStep::PedModel => prompter
+                .text(t("create.ped_model"), answers.model.as_deref())?
+                .map(|model| answers.model = Some(model)),
+            CreateStep::Wavepack => prompter
+                .text(t("create.wavepack"), answers.wavepack.as_deref())?
+                .map(|wavepack| answers.wavepack = Some(wavepack)),
+            CreateStep::DataFiles => prompter
+                .confirm(t("create.data_files"), answers.data_files == Some(true))?
+                .map(|yes| answers.data_files = Some(yes)),
+            CreateStep::VersionFile => prompter
+                .confirm(t("create.version_file"), answers.version_file == Some(true))?
+                .map(|yes| answers.version_file = Some(yes)),
+            CreateStep::Libraries => {
+                let names = &wizard.library_names;
+                let selected = answers.libraries.as_deref().unwrap_or_default();
+                let selected = (0..names.len())
+                    .filter(|&index| selected.contains(&names[index]))
+                    .collect::<Vec<_>>();
+                prompter
+                    .multi_select(t("create.libraries"), names, &selected)?
+                    .map(|indices| {
+                        answers.libraries = Some(
+                            indices
+                                .into_iter()
+                                .map(|index| names[index].clone())
+                                .collect(),
+                        )
+                    })
+            }
+            CreateStep::Locales => prompter
+                .confirm(t("create.locales"), answers.locales == Some(true))?
+                .map(|yes| answers.locales = Some(yes)),
+        })
+    }
+
+    fn applies(&self, wizard: &CreateWizard) -> bool {
+        match self {
+            CreateStep::ProjectName | CreateStep::Author => true,
+            CreateStep::VehicleModel => wizard.kind == ResourceKind::Vehicle,
+            CreateStep::PedAddon | CreateStep::PedModel => wizard.kind == ResourceKind::Ped,
+            CreateStep::Wavepack => wizard.kind == ResourceKind::Audio,
+            CreateStep::DataFiles | CreateStep::VersionFile | CreateStep::Libraries => {
+                wizard.generates_script()
+            }
+            CreateStep::Locales => {
+                let libraries = wizard.answers.libraries.as_deref().unwrap_or_default();
+                let libraries = libraries.iter().map(String::as_str).collect::<Vec<_>>();
+                wizard.generates_script() && !wizard.bundled && supports_locales(&libraries)
+            }
+        }
+    }
+
+    fn summary(&self, wizard: &CreateWizard) -> Option<String> {
+        let answers = &wizard.answers;
+        let yes_no = |answer: Option<bool>| {
+            answer.map(|yes| t(if yes { "wizard.yes" } else { "wizard.no" }).to_owned())
+        };
+        match self {
+            CreateStep::ProjectName => answers.project_name.clone(),
+            CreateStep::Author => answers.author.clone(),
+            CreateStep::VehicleModel | CreateStep::PedModel => answers.model.clone(),
+            CreateStep::PedAddon => answers.ped_addon.map(|addon| addon.to_string()),
+            CreateStep::Wavepack => answers.wavepack.clone(),
+            CreateStep::DataFiles => yes_no(answers.data_files),
+            CreateStep::VersionFile => yes_no(answers.version_file),
+            CreateStep::Libraries => {
+                answers
+                    .libraries
+                    .as_ref()
+                    .map(|names| match names.is_empty() {
+                        true => t("wizard.none").to_owned(),
+                        false => names.join(", "),
+                    })
+            }
+            CreateStep::Locales => yes_no(answers.locales),
+        }
+    }
+}
+
+/// Reads an `--icon` image, failing unless it is a PNG that fits as an icon.
+fn read_icon(source: &Path) -> CfxResult<Vec<u8>> {
+    let bytes = std::fs::read(source)
+        .map_err(|err| format!("Could not read {}: {err}", source.display()))?;
+    check_icon(&bytes).map_err(|problem| format!("{} {problem}", source.display()))?;
+
+    Ok(bytes)
+}
+
+/// Writes icon.png, copied from `source` or a placeholder with the initials of
+/// `name`.
+fn write_icon(base_path: &Path, source: Option<&Path>, name: &str) -> CfxResult<()> {
+    let bytes = match source {
+        Some(source) => read_icon(source)?,
+        None => placeholder_png(name),
+    };
+
+    atomic_write(base_path.join(ICON_FILE), bytes)?;
+    Ok(())
+}
+
+/// Builds a Keep a Changelog style `CHANGELOG.md` with an empty Unreleased
+/// section above the section of the initial `version`, released on `date`.
+fn changelog(version: &str, date: &str) -> String {
+    format!(
+        r#"# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+
+## [Unreleased]
+
+## [{version}] - {date}
+
+### Added
+
+- Initial release
+"#
+    )
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+fn today() -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (year, month, day) = civil_date((seconds / 86400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian date, following
+/// Howard Hinnant's `civil_from_days`.
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Fails when a local path in the `*_scripts` sections of `manifest` matches
+/// no file below `base_path`, catching a section that lists a script the
+/// generator never wrote. Imports from other resources (`@res/...`) are left
+/// out, and so are the `dist/` bundles of `bundled` projects, which
+/// `npm run build` writes later.
+fn check_written_scripts(base_path: &Path, manifest: &str, bundled: bool) -> CfxResult<()> {
+    let manifest = Manifest::parse(manifest)?;
+    let mut missing = vec![];
+    for script in manifest.values(&SCRIPT_DIRECTIVES) {
+        if script.starts_with('@') || (bundled && script.starts_with("dist/")) {
+            continue;
+        }
+
+        if expand_pattern(base_path, script)?.is_empty() {
+            missing.push(format!("`{script}`"));
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(format!(
+            "The manifest lists {} but no such file was created",
+            missing.join(", ")
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Logs the errors `cfx check` finds in the new project as warnings.
+fn warn_syntax_errors(base_path: &Path) -> CfxResult<()> {
+    let check = check_resource(base_path)?;
+    for error in &check.errors {
+        log::warn!("{error}");
+    }
+
+    Ok(())
+}
+
+/// Removes the directories a create made for the project unless it is
+/// kept, so a failed or interrupted run does not leave half a project.
+struct CreatedDir {
+    path: Option<PathBuf>,
+}
+
+impl CreatedDir {
+    /// Tracks the outermost missing ancestor of `base_path`, such as a new
+    /// `[category]` folder. Nothing is tracked when the project directory exists.
+    fn new(base_path: &Path) -> Self {
+        let path = base_path
+            .ancestors()
+            .take_while(|path| !path.as_os_str().is_empty() && !path.exists())
+            .last()
+            .map(Path::to_path_buf);
+
+        Self { path }
+    }
+
+    fn keep(mut self) {
+        self.path = None;
+    }
+}
+
+impl Drop for CreatedDir {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            if let Err(err) = remove_dir_all(path) {
+                log::warn!("Could not remove {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Where a create writes its files. A new project is written in place. For
+/// an existing directory the files are written to a sibling temp directory
+/// first and only moved over existing files that `--allow-overwrite` permits.
+struct StagedWrite {
+    target: PathBuf,
+    staging: Option<tempfile::TempDir>,
+}
+
+impl StagedWrite {
+    fn new(target: &Path) -> CfxResult<Self> {
+        let staging = if target.exists() {
+            let parent = target
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            Some(
+                tempfile::Builder::new()
+                    .prefix(".cfx-create-")
+                    .tempdir_in(parent)?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            staging,
+        })
+    }
+
+    /// Directory to write the project files to.
+    fn dir(&self) -> &Path {
+        self.staging
+            .as_ref()
+            .map_or(&self.target, |staging| staging.path())
+    }
+
+    /// Moves staged files into the project, returning the existing files that
+    /// were protected from being overwritten, relative to the project. Once
+    /// `cancel` fires nothing is moved and the staging directory is dropped.
+    fn finish(self, allow_overwrite: &[Pattern], cancel: &CancelToken) -> CfxResult<Vec<String>> {
+        if cancel.is_cancelled() {
+            return Err(Interrupted.into());
+        }
+
+        let mut protected = vec![];
+        if let Some(staging) = &self.staging {
+            merge_dir(
+                staging.path(),
+                staging.path(),
+                &self.target,
+                allow_overwrite,
+                &mut protected,
+            )?;
+        }
+
+        Ok(protected)
+    }
+}
+
+fn merge_dir(
+    root: &Path,
+    dir: &Path,
+    target: &Path,
+    allow_overwrite: &[Pattern],
+    protected: &mut Vec<String>,
+) -> CfxResult<()> {
+    let mut entries = read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for path in entries {
+        let relative = path.strip_prefix(root)?;
+        let destination = target.join(relative);
+        if path.is_dir() {
+            create_dir_all(&destination)?;
+            merge_dir(root, &path, target, allow_overwrite, protected)?;
+            continue;
+        }
+
+        let allowed = allow_overwrite
+            .iter()
+            .any(|pattern| pattern.matches_path(relative));
+        if destination.exists() && !allowed {
+            log::warn!(
+                "Left the existing {} untouched, pass --allow-overwrite to replace it",
+                destination.display()
+            );
+            protected.push(relative.to_string_lossy().to_string());
+            continue;
+        }
+
+        rename(&path, &destination)?;
+    }
+
+    Ok(())
+}
+
+fn create_project(args: &CreateArgs, cancel: &CancelToken) -> CfxResult<CreateSummary> {
+    for entry in &args.priority {
+        let is_module = args.modules.contains(entry) || args.client_modules.contains(entry);
+        if !is_module && !LIBRARIES.contains_key(entry.as_str()) {
+            return Err(format!("--priority `{entry}` is neither a library nor a module").into());
+        }
+    }
+
+    let kind = match args.loadscreen {
+        true => ResourceKind::Loadscreen,
+        false => args.kind,
+    };
+    if args.manual_shutdown && kind != ResourceKind::Loadscreen {
+        return Err("--manual-shutdown only applies to loading screens".into());
+    }
+
+    let exports = !(args.exports.is_empty() && args.server_exports.is_empty());
+    if exports && (kind != ResourceKind::Script || args.template.is_some()) {
+        return Err("--export and --server-export only apply to generated scripts".into());
+    }
+
+    if args.export_stubs && !exports {
+        return Err("--export-stubs needs --export or --server-export".into());
+    }
+
+    for (runtime, names, flag, side) in [
+        (ScriptRuntime::Client, &args.exports, "--export", "client"),
+        (
+            ScriptRuntime::Server,
+            &args.server_exports,
+            "--server-export",
+            "server",
+        ),
+    ] {
+        if !names.is_empty() && !args.scope.includes(&runtime) {
+            return Err(format!("{flag} needs {side} scripts, which the scope leaves out").into());
+        }
+    }
+
+    check_manifest_flags(&args.manifest_flag, args.strict)?;
+    if let Some(Some(source)) = &args.icon {
+        read_icon(source)?;
+    }
+
+    let extras = ManifestExtras {
+        games: &args.games,
+        flags: &args.manifest_flag,
+        icon: args.icon.is_some(),
+        comments: args.comments,
+    };
+
+    let bundled = args.language != Language::Lua;
+    if bundled && !(args.modules.is_empty() && args.client_modules.is_empty()) {
+        return Err(
+            "Modules are Lua scripts, import JavaScript modules from the entry script instead"
+                .into(),
+        );
+    }
+
+    if bundled && args.export_stubs {
+        return Err("Export stubs are Lua, call `exports` from the entry script instead".into());
+    }
+
+    // Only offer libraries that run on at least one of the games
+    let mut library_names = LIBRARIES
+        .iter()
+        .filter(|(_, library)| args.games.iter().any(|game| library.games.contains(game)))
+        .map(|(&name, _)| name)
+        .collect::<Vec<&str>>();
+    library_names.sort_by_key(|&name| (LIBRARIES[name].load_order, name));
+
+    // Flags take precedence over the answers file, which takes precedence
+    // over the defaults in the user config
+    let flags = CreateAnswers {
+        project_name: args.name.clone(),
+        author: args.author.clone(),
+        ..Default::default()
+    };
+    let file = match &args.answers {
+        Some(path) => CreateAnswers::load(path)?,
+        None => CreateAnswers::default(),
+    };
+
+    let mut wizard = CreateWizard {
+        kind,
+        bundled,
+        template: args.template.is_some(),
+        library_names: library_names.iter().map(|&name| name.to_owned()).collect(),
+        answers: flags.or(file).or(Config::load()?.create),
+    };
+    wizard.check_answers()?;
+    run_wizard(&CREATE_STEPS, terminal_prompter().as_mut(), &mut wizard)?;
+    let used = wizard.used_answers();
+    let answers = wizard.answers;
+
+    let project_name = answers.project_name.expect("asked by the wizard");
+    let author_name = answers.author.expect("asked by the wizard");
+    match kind {
+        ResourceKind::Script => {
+            if let Some(template) = &args.template {
+                let variables = variables(&project_name, &author_name, &args.vars);
+                return create_addon(
+                    args,
+                    cancel,
+                    project_name,
+                    author_name,
+                    used,
+                    vec![],
+                    |base_path| write_template(base_path, template, &variables),
+                );
+            }
+        }
+        ResourceKind::Vehicle => {
+            let model = answers.model.expect("asked by the wizard");
+            let notes = vec![format!(
+                "Drop {model}.yft, {model}_hi.yft and {model}.ytd into stream/"
+            )];
+            let (name, author) = (project_name.clone(), author_name.clone());
+            return create_addon(
+                args,
+                cancel,
+                project_name,
+                author_name,
+                used,
+                notes,
+                |base_path| write_vehicle(base_path, &name, &author, &model, &extras),
+            );
+        }
+        ResourceKind::Ped => {
+            let addon = answers.ped_addon.expect("asked by the wizard");
+            let model = answers.model.expect("asked by the wizard");
+
+            let notes = vec![ped_stream_notes(addon, &model, &project_name)];
+            let author = author_name.clone();
+            let collection = project_name.clone();
+            return create_addon(
+                args,
+                cancel,
+                project_name,
+                author_name,
+                used,
+                notes,
+                |base_path| write_ped(base_path, &collection, &author, &model, addon, &extras),
+            );
+        }
+        ResourceKind::Audio => {
+            let wavepack = answers.wavepack.expect("asked by the wizard");
+
+            let notes = audio_notes(&wavepack);
+            let (name, author) = (project_name.clone(), author_name.clone());
+            return create_addon(
+                args,
+                cancel,
+                project_name,
+                author_name,
+                used,
+                notes,
+                |base_path| write_audio(base_path, &name, &author, &wavepack, &extras),
+            );
+        }
+        ResourceKind::Loadscreen => {
+            let notes = match args.manual_shutdown {
+                true => vec!["Call ShutdownLoadingScreenNui from a client script once \
+                              the player is ready, the loading screen stays up until then"
+                    .to_owned()],
+                false => vec![],
+            };
+            let (name, author) = (project_name.clone(), author_name.clone());
+            let manual_shutdown = args.manual_shutdown;
+            return create_addon(
+                args,
+                cancel,
+                project_name,
+                author_name,
+                used,
+                notes,
+                |base_path| write_loadscreen(base_path, &name, &author, manual_shutdown, &extras),
+            );
+        }
+    }
+
+    let use_data_files = answers.data_files == Some(true);
+    let use_version_file = answers.version_file == Some(true);
+    let selected_names = answers.libraries.unwrap_or_default();
+    let selected_names = selected_names
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let use_locales =
+        !bundled && supports_locales(&selected_names) && answers.locales == Some(true);
+
+    let libraries = selected_names
+        .iter()
+        .map(|&name| {
+            let priority = args.priority.iter().any(|entry| entry == name);
+            LIBRARIES
+                .get(name)
+                .expect("Invalid library")
+                .clone()
+                .with_priority(priority)
+        })
+        .collect::<Vec<Library>>();
+    for (name, library) in selected_names.iter().zip(&libraries) {
+        if let Some(warning) = library.unsupported_games(name, &args.games) {
+            log::warn!("{warning}");
+        }
+    }
+
+    let mut install = InstallInstructions::collect(selected_names.iter().copied().zip(&libraries));
+    if bundled {
+        install.notes.push(
+            "Run `npm install` and `npm run build` to build dist/ before starting the resource"
+                .to_owned(),
+        );
+    }
+
+    let client_example = render_examples(&libraries, &ScriptRuntime::Client, &project_name)?;
+    let mut client_example = with_module_loader(&args.client_modules, client_example);
+    let server_example = render_examples(&libraries, &ScriptRuntime::Server, &project_name)?;
+    let mut server_example = with_module_loader(&args.modules, server_example);
+
+    let mut manifest = ScriptManifest::new(&author_name, use_data_files, libraries);
+    manifest
+        .name(&project_name)
+        .scope(args.scope)
+        .section_style(args.section_style)
+        .extras(&extras)
+        .client_init(args.client_init)
+        .bundled(bundled);
+    if let Some(server_version) = args.server_version {
+        manifest.dependency(format!("/server:{server_version}"));
+    }
+
+    if args.onesync {
+        manifest.dependency("/onesync");
+    }
+
+    if let Some(game_build) = args.game_build {
+        manifest.dependency(format!("/gameBuild:{game_build}"));
+    }
+
+    for (runtime, names) in [
+        (ScriptRuntime::Server, &args.modules),
+        (ScriptRuntime::Client, &args.client_modules),
+    ] {
+        for name in names {
+            manifest.module(runtime.clone(), module_path(&runtime, name));
+            if args.priority.contains(name) {
+                manifest.priority_script(module_path(&runtime, name));
+            }
+        }
+    }
+
+    if use_locales {
+        if args.scope.includes(&ScriptRuntime::Client) {
+            client_example = setup_locales(&mut manifest, client_example);
+        } else {
+            server_example = setup_locales(&mut manifest, server_example);
+        }
+    }
+
+    let stubs = args.export_stubs;
+    client_example = setup_exports(
+        &mut manifest,
+        ScriptRuntime::Client,
+        &args.exports,
+        stubs,
+        client_example,
+    );
+    server_example = setup_exports(
+        &mut manifest,
+        ScriptRuntime::Server,
+        &args.server_exports,
+        stubs,
+        server_example,
+    );
+
+    let manifest_str = manifest.build()?;
+    let mut files = vec![];
+
+    let config = Config::load()?;
+    let project_path = project_dir(
+        config.resources_root().as_deref(),
+        args.category.as_deref(),
+        &project_name,
+    );
+    let path = |relative: &str| project_path.join(relative).to_string_lossy().to_string();
+
+    let created = CreatedDir::new(&project_path);
+    let staged = StagedWrite::new(&project_path)?;
+    let base_path = staged.dir().to_path_buf();
+    if use_data_files {
+        create_dir_all(base_path.join("data"))?;
+    }
+
+    create_dir_all(base_path.join("src/shared"))?;
+    if bundled {
+        let bundle = write_bundle(&base_path, &project_name, args.scope, args.language)?;
+        files.extend(bundle.iter().map(|file| path(file)));
+    } else if args.scope.includes(&ScriptRuntime::Client) {
+        create_dir_all(base_path.join("src/client"))?;
+        let mut main = File::create(base_path.join("src/client/main.lua"))?;
+        main.write_all(client_example.as_bytes())?;
+        files.push(path("src/client/main.lua"));
+
+        let modules = write_modules(&base_path, &ScriptRuntime::Client, &args.client_modules)?;
+        files.extend(modules.iter().map(|module| path(module)));
+    }
+
+    if !bundled && args.scope.includes(&ScriptRuntime::Server) {
+        create_dir_all(base_path.join("src/server"))?;
+        let mut main = File::create(base_path.join("src/server/main.lua"))?;
+        main.write_all(server_example.as_bytes())?;
+        files.push(path("src/server/main.lua"));
+
+        let modules = write_modules(&base_path, &ScriptRuntime::Server, &args.modules)?;
+        files.extend(modules.iter().map(|module| path(module)));
+    }
+
+    if use_locales {
+        files.push(path(&write_locales(&base_path, &project_name)?));
+    }
+
+    atomic_write(base_path.join("fxmanifest.lua"), &manifest_str)?;
+    files.push(path("fxmanifest.lua"));
+
+    if use_version_file {
+        File::create(base_path.join(VERSION_FILE))?
+            .write_all(manifest.version_file().as_bytes())?;
+        files.push(path(VERSION_FILE));
+    }
+
+    if args.changelog {
+        atomic_write(
+            base_path.join(CHANGELOG_FILE),
+            changelog(&manifest.version, &today()),
+        )?;
+        files.push(path(CHANGELOG_FILE));
+    }
+
+    let project = ProjectConfig {
+        name: project_name.clone(),
+        author: author_name.clone(),
+        libraries: selected_names.iter().map(|&name| name.to_owned()).collect(),
+        required_convars: install.required_convars.clone(),
+        language: args.language,
+        create: used.clone(),
+        ..Default::default()
+    };
+    project.save(&base_path)?;
+    files.push(path(PROJECT_FILE));
+
+    if let Some(source) = &args.icon {
+        write_icon(&base_path, source.as_deref(), &project_name)?;
+        files.push(path(ICON_FILE));
+    }
+
+    if !install.is_empty() {
+        let mut install_file = File::create(base_path.join("INSTALL.md"))?;
+        install_file.write_all(install.build_markdown(&project_name).as_bytes())?;
+        files.push(path("INSTALL.md"));
+
+        for convar in &install.required_convars {
+            log::info!("Required convar: {convar}");
+        }
+
+        for notes in &install.notes {
+            log::info!("Note: {notes}");
+        }
+    }
+
+    check_written_scripts(&base_path, &manifest_str, bundled)?;
+
+    let protected = staged.finish(&args.allow_overwrite, cancel)?;
+    files.retain(|file| !protected.iter().any(|relative| *file == path(relative)));
+
+    if args.check {
+        warn_syntax_errors(&project_path)?;
+    }
+
+    created.keep();
+    log::info!(
+        "{}",
+        tf(
+            "create.created",
+            &[
+                ("name", &project_name),
+                ("path", &project_path.display().to_string())
+            ]
+        )
+    );
+
+    Ok(CreateSummary {
+        name: project_name,
+        author: author_name,
+        files,
+        install,
+        answers: used,
+    })
+}
+
+/// Everything a manifest is generated from, for building one without prompts.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestSpec {
+    pub name: Option<String>,
+    pub author: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_version")]
+    pub version: String,
+    #[serde(default = "default_games")]
+    pub games: Vec<Game>,
+    /// Entries of `dependency` lines, e.g. `oxmysql` or `/server:7290`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Resources this one replaces, see `provides`
+    #[serde(default)]
+    pub provides: Vec<String>,
+    /// Names from the library registry, e.g. `ox_lib`
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    #[serde(default)]
+    pub scope: Scope,
+    #[serde(default)]
+    pub section_style: SectionStyle,
+    /// See `create --client-init`
+    #[serde(default)]
+    pub client_init: bool,
+    #[serde(default)]
+    pub scripts: SpecScripts,
+    /// Libraries or script paths listed at the top of their section
+    #[serde(default)]
+    pub priority: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub data_files: Vec<SpecDataFile>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecScripts {
+    #[serde(default)]
+    pub server: Vec<String>,
+    #[serde(default)]
+    pub client: Vec<String>,
+    #[serde(default)]
+    pub shared: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpecDataFile {
+    pub kind: String,
+    pub path: String,
+}
+
+fn default_version() -> String {
+    INITIAL_VERSION.to_owned()
+}
+
+fn default_games() -> Vec<Game> {
+    vec![Game::Gta5]
+}
+
+impl ManifestSpec {
+    pub fn build(&self) -> CfxResult<String> {
+        let libraries = self
+            .libraries
+            .iter()
+            .map(|name| {
+                let library = LIBRARIES
+                    .get(name.as_str())
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown library `{name}`"))?;
+                if let Some(warning) = library.unsupported_games(name, &self.games) {
+                    log::warn!("{warning}");
+                }
+
+                Ok(library.with_priority(self.priority.contains(name)))
+            })
+            .collect::<Result<Vec<Library>, String>>()?;
+
+        let mut manifest = ScriptManifest::new(&self.author, false, libraries);
+        manifest
+            .version(&self.version)
+            .games(&self.games)
+            .scope(self.scope)
+            .section_style(self.section_style)
+            .client_init(self.client_init)
+            .entry_scripts(false);
+        if let Some(name) = &self.name {
+            manifest.name(name);
+        }
+
+        if let Some(description) = &self.description {
+            manifest.description(description);
+        }
+
+        for dependency in &self.dependencies {
+            manifest.dependency(dependency);
+        }
+
+        for resource in &self.provides {
+            manifest.provides(resource);
+        }
+
+        for (runtime, scripts) in [
+            (ScriptRuntime::Server, &self.scripts.server),
+            (ScriptRuntime::Client, &self.scripts.client),
+            (ScriptRuntime::Shared, &self.scripts.shared),
+        ] {
+            for script in scripts {
+                manifest.script(runtime.clone(), script);
+            }
+        }
+
+        let scripts = [
+            &self.scripts.server,
+            &self.scripts.client,
+            &self.scripts.shared,
+        ];
+        for entry in &self.priority {
+            if scripts.iter().any(|scripts| scripts.contains(entry)) {
+                manifest.priority_script(entry);
+            } else if !self.libraries.contains(entry) {
+                return Err(
+                    format!("Priority entry `{entry}` is not a listed library or script").into(),
+                );
+            }
+        }
+
+        for file in &self.files {
+            manifest.file(file);
+        }
+
+        for data_file in &self.data_files {
+            manifest.data_file(&data_file.kind, &data_file.path);
+        }
+
+        manifest.build()
+    }
+}
+
+/// Creates an asset add-on through `write`, which returns the files it wrote
+/// relative to the project directory, and records `notes` in INSTALL.md.
+fn create_addon(
+    args: &CreateArgs,
+    cancel: &CancelToken,
+    project_name: String,
+    author_name: String,
+    answers: CreateAnswers,
+    notes: Vec<String>,
+    write: impl FnOnce(&Path) -> CfxResult<Vec<String>>,
+) -> CfxResult<CreateSummary> {
+    let config = Config::load()?;
+    let project_path = project_dir(
+        config.resources_root().as_deref(),
+        args.category.as_deref(),
+        &project_name,
+    );
+    let path = |relative: &str| project_path.join(relative).to_string_lossy().to_string();
+
+    let created = CreatedDir::new(&project_path);
+    let staged = StagedWrite::new(&project_path)?;
+    let base_path = staged.dir().to_path_buf();
+    let files = write(&base_path)?;
+    let project = ProjectConfig {
+        name: project_name.clone(),
+        author: author_name.clone(),
+        create: answers.clone(),
+        ..Default::default()
+    };
+    project.save(&base_path)?;
+
+    let install = InstallInstructions {
+        notes,
+        ..Default::default()
+    };
+    let mut install_file = File::create(base_path.join("INSTALL.md"))?;
+    install_file.write_all(install.build_markdown(&project_name).as_bytes())?;
+
+    for notes in &install.notes {
+        log::info!("Note: {notes}");
+    }
+
+    let mut files = files.iter().map(|file| path(file)).collect::<Vec<String>>();
+    for file in [PROJECT_FILE, "INSTALL.md"] {
+        files.push(path(file));
+    }
+
+    if args.changelog {
+        atomic_write(
+            base_path.join(CHANGELOG_FILE),
+            changelog(INITIAL_VERSION, &today()),
+        )?;
+        files.push(path(CHANGELOG_FILE));
+    }
+
+    if let Some(source) = &args.icon {
+        write_icon(&base_path, source.as_deref(), &project_name)?;
+        files.push(path(ICON_FILE));
+    }
+
+    let protected = staged.finish(&args.allow_overwrite, cancel)?;
+    files.retain(|file| !protected.iter().any(|relative| *file == path(relative)));
+
+    if args.check {
+        warn_syntax_errors(&project_path)?;
+    }
+
+    created.keep();
+    log::info!(
+        "{}",
+        tf(
+            "create.created",
+            &[
+                ("name", &project_name),
+                ("path", &project_path.display().to_string())
+            ]
+        )
+    );
+
+    Ok(CreateSummary {
+        name: project_name,
+        author: author_name,
+        files,
+        install,
+        answers,
+    })
+}
+
+/// Renders the registered `template` into `base_path`, returning the written
+/// files relative to it.
+fn write_template(
+    base_path: &Path,
+    template: &str,
+    variables: &HashMap<String, String>,
+) -> CfxResult<Vec<String>> {
+    let config = Config::load()?;
+    let entry = config
+        .templates
+        .get(template)
+        .ok_or_else(|| format!("Template `{template}` is not registered"))?;
+
+    let written = render(&resolve(template, entry)?, base_path, variables)
+        .map_err(|err| format!("Template `{template}` failed to render: {err}"))?;
+    let mut files = written
+        .iter()
+        .map(|path| {
+            let relative = path.strip_prefix(base_path).unwrap_or(path);
+            relative.to_string_lossy().replace('\\', "/")
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Writes the manifest, the meta files and an empty `stream/` folder of a
+/// vehicle add-on, returning the written files relative to `base_path`.
+fn write_vehicle(
+    base_path: &Path,
+    name: &str,
+    author: &str,
+    model: &str,
+    extras: &ManifestExtras,
+) -> CfxResult<Vec<String>> {
+    let variables = HashMap::from([("model".to_owned(), model.to_owned())]);
+    let mut manifest = ScriptManifest::new(author, false, vec![]);
+    manifest
+        .name(name)
+        .kind(ResourceKind::Vehicle)
+        .extras(extras);
+
+    create_dir_all(base_path.join("stream"))?;
+    create_dir_all(base_path.join("data"))?;
+
+    let mut files = vec![];
+    for (kind, path, content) in VEHICLE_DATA_FILES {
+        let content = substitute(content, &variables)
+            .map_err(|var| format!("Vehicle meta uses unknown variable `{var}`"))?;
+        File::create(base_path.join(path))?.write_all(content.as_bytes())?;
+
+        manifest.file(path).data_file(kind, path);
+        files.push(path.to_owned());
+    }
+
+    atomic_write(base_path.join("fxmanifest.lua"), manifest.build()?)?;
+    files.push("fxmanifest.lua".to_owned());
+
+    Ok(files)
+}
+
+fn ped_stream_notes(addon: PedAddon, model: &str, collection: &str) -> String {
+    match addon {
+        PedAddon::FullPed => {
+            format!("Drop {model}.ydd, {model}.yft, {model}.ymt and {model}.ytd into stream/")
+        }
+        PedAddon::Components => format!(
+            "Drop {model}_{collection}.ymt, {model}_{collection}.ytyp and the component \
+             .ydd/.ytd files into stream/{model}_{collection}/"
+        ),
+    }
+}
+
+/// Writes the manifest, the meta file and the `stream/` layout of a ped
+/// add-on, returning the written files relative to `base_path`. Component
+/// packs are named `<model>_<collection>` like the game's own DLC packs.
+fn write_ped(
+    base_path: &Path,
+    collection: &str,
+    author: &str,
+    model: &str,
+    addon: PedAddon,
+    extras: &ManifestExtras,
+) -> CfxResult<Vec<String>> {
+    let mut manifest = ScriptManifest::new(author, false, vec![]);
+    manifest
+        .name(collection)
+        .kind(ResourceKind::Ped)
+        .extras(extras);
+    create_dir_all(base_path.join("data"))?;
+
+    let (meta_path, content) = match addon {
+        PedAddon::FullPed => {
+            create_dir_all(base_path.join("stream"))?;
+            let meta = PedsMeta {
+                init_datas: PedInitDatas {
+                    items: vec![PedInitData::civilian(model)],
+                },
+            };
+
+            let meta_path = "data/peds.meta".to_owned();
+            manifest
+                .file(&meta_path)
+                .data_file("PED_METADATA_FILE", &meta_path);
+            (meta_path, to_xml(&meta)?)
+        }
+        PedAddon::Components => {
+            let meta = ShopPedApparel::new(model, collection);
+            create_dir_all(base_path.join("stream").join(&meta.full_dlc_name))?;
+
+            let meta_path = format!("data/{}.meta", meta.full_dlc_name);
+            manifest
+                .file(&meta_path)
+                .data_file("SHOP_PED_APPAREL_META_FILE", &meta_path)
+                .data_file(
+                    "DLC_ITYP_REQUEST",
+                    format!("stream/{}.ytyp", meta.full_dlc_name),
+                );
+            (meta_path, to_xml(&meta)?)
+        }
+    };
+
+    File::create(base_path.join(&meta_path))?.write_all(content.as_bytes())?;
+    atomic_write(base_path.join("fxmanifest.lua"), manifest.build()?)?;
+
+    Ok(vec![meta_path, "fxmanifest.lua".to_owned()])
+}
+
+/// Audio files of a sound pack in load order: the `data_file` type, the path
+/// the manifest names and the file it loads. `{{name}}` is the wavepack name.
+const AUDIO_DATA_FILES: [(&str, &str, &str); 3] = [
+    (
+        "AUDIO_GAMEDATA",
+        "audioconfig/{{name}}_game.dat",
+        "audioconfig/{{name}}_game.dat151.rel",
+    ),
+    (
+        "AUDIO_SOUNDDATA",
+        "audioconfig/{{name}}_sounds.dat",
+        "audioconfig/{{name}}_sounds.dat54.rel",
+    ),
+    (
+        "AUDIO_WAVEPACK",
+        "sfx/dlc_{{name}}",
+        "sfx/dlc_{{name}}/{{name}}.awc",
+    ),
+];
+
+fn audio_notes(wavepack: &str) -> Vec<String> {
+    let variables = HashMap::from([("name".to_owned(), wavepack.to_owned())]);
+    AUDIO_DATA_FILES
+        .iter()
+        .filter_map(|(kind, _, file)| {
+            let file = substitute(file, &variables).ok()?;
+            Some(format!("Place the {kind} file at {file}"))
+        })
+        .collect()
+}
+
+/// Writes the manifest and the `audioconfig/` and wavepack folders of a sound
+/// pack, returning the written files relative to `base_path`.
+fn write_audio(
+    base_path: &Path,
+    name: &str,
+    author: &str,
+    wavepack: &str,
+    extras: &ManifestExtras,
+) -> CfxResult<Vec<String>> {
+    let variables = HashMap::from([("name".to_owned(), wavepack.to_owned())]);
+    let mut manifest = ScriptManifest::new(author, false, vec![]);
+    manifest.name(name).kind(ResourceKind::Audio).extras(extras);
+
+    for (kind, path, file) in AUDIO_DATA_FILES {
+        let path = substitute(path, &variables)
+            .map_err(|var| format!("Audio path uses unknown variable `{var}`"))?;
+        let file = substitute(file, &variables)
+            .map_err(|var| format!("Audio path uses unknown variable `{var}`"))?;
+
+        if let Some(parent) = Path::new(&file).parent() {
+            create_dir_all(base_path.join(parent))?;
+        }
+
+        manifest.file(file).data_file(kind, path);
+    }
+
+    atomic_write(base_path.join("fxmanifest.lua"), manifest.build()?)?;
+    Ok(vec!["fxmanifest.lua".to_owned()])
+}
+
+/// Writes the manifest and the NUI page of a loading screen, returning the
+/// written files relative to `base_path`.
+fn write_loadscreen(
+    base_path: &Path,
+    name: &str,
+    author: &str,
+    manual_shutdown: bool,
+    extras: &ManifestExtras,
+) -> CfxResult<Vec<String>> {
+    let variables = HashMap::from([("name".to_owned(), name.to_owned())]);
+    let mut manifest = ScriptManifest::new(author, false, vec![]);
+    manifest
+        .name(name)
+        .kind(ResourceKind::Loadscreen)
+        .manual_shutdown(manual_shutdown)
+        .extras(extras);
+
+    create_dir_all(base_path.join("html"))?;
+    let mut files = vec![];
+    for (path, content) in LOADSCREEN_FILES {
+        let content = substitute(content, &variables)
+            .map_err(|var| format!("Loading screen uses unknown variable `{var}`"))?;
+        File::create(base_path.join(path))?.write_all(content.as_bytes())?;
+
+        manifest.file(path);
+        files.push(path.to_owned());
+    }
+
+    atomic_write(base_path.join("fxmanifest.lua"), manifest.build()?)?;
+    files.push("fxmanifest.lua".to_owned());
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::is_cancellation;
+    use crate::wizard::{AssumeYes, Scripted, ScriptedPrompter};
+
+    #[test]
+    fn create_answers_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let answers_path = dir.path().join("answers.toml");
+        std::fs::write(
+            &answers_path,
+            "name = \"bank\"\nauthor = \"file\"\nlibraries = [\"ox_lib\"]\n",
+        )
+        .unwrap();
+        let project_path = dir.path().join(PROJECT_FILE);
+        std::fs::write(
+            &project_path,
+            "name = \"bank\"\nauthor = \"cfx\"\n\n[create]\nmodel = \"adder\"\nped_addon = \"full-ped\"\n",
+        )
+        .unwrap();
+
+        let file = CreateAnswers::load(&answers_path).unwrap();
+        let project = CreateAnswers::load(&project_path).unwrap();
+        assert_eq!(project.ped_addon, Some(PedAddon::FullPed));
+        assert_eq!(project.project_name, None);
+
+        let flags = CreateAnswers {
+            author: Some("flag".to_owned()),
+            ..Default::default()
+        };
+        let config = CreateAnswers {
+            project_name: Some("config".to_owned()),
+            data_files: Some(true),
+            ..Default::default()
+        };
+        let answers = flags.or(file).or(config);
+        assert_eq!(answers.project_name.as_deref(), Some("bank"));
+        assert_eq!(answers.author.as_deref(), Some("flag"));
+        assert_eq!(answers.data_files, Some(true));
+
+        std::fs::write(&answers_path, "nmae = \"typo\"\n").unwrap();
+        assert!(CreateAnswers::load(&answers_path).is_err());
+
+        // Only the missing answers are prompted for
+        let mut wizard = CreateWizard {
+            kind: ResourceKind::Script,
+            bundled: false,
+            template: false,
+            library_names: vec!["ox_lib".to_owned()],
+            answers,
+        };
+        wizard.check_answers().unwrap();
+        let mut prompter = ScriptedPrompter::new([
+            Scripted::Confirm(false),
+            Scripted::Confirm(true),
+            Scripted::Select(0),
+        ]);
+        run_wizard(&CREATE_STEPS, &mut prompter, &mut wizard).unwrap();
+        assert_eq!(
+            prompter.asked,
+            [
+                "Do you want to track the version in a VERSION file?",
+                "Do you want to set up ox_lib locales?",
+                "Continue with these answers?",
+            ]
+        );
+        assert_eq!(
+            toml::to_string(&wizard.used_answers()).unwrap(),
+            "name = \"bank\"\nauthor = \"flag\"\ndata_files = true\nversion_file = false\nlibraries = [\"ox_lib\"]\nlocales = true\n"
+        );
+
+        // --yes accepts every confirmation and the answers without asking
+        let mut assumed = CreateWizard {
+            answers: CreateAnswers {
+                version_file: None,
+                locales: None,
+                ..wizard.answers.clone()
+            },
+            library_names: wizard.library_names.clone(),
+            ..wizard
+        };
+        let mut prompter = AssumeYes(ScriptedPrompter::new([]));
+        run_wizard(&CREATE_STEPS, &mut prompter, &mut assumed).unwrap();
+        assert!(prompter.0.asked.is_empty());
+        assert_eq!(
+            (assumed.answers.version_file, assumed.answers.locales),
+            (Some(true), Some(true))
+        );
+
+        // A fully answered wizard prompts for nothing
+        let mut prompter = ScriptedPrompter::new([]);
+        run_wizard(&CREATE_STEPS, &mut prompter, &mut wizard).unwrap();
+        assert!(prompter.asked.is_empty());
+
+        wizard.library_names.clear();
+        assert!(wizard.check_answers().is_err());
+    }
+
+    #[test]
+    fn create_wizard_test() {
+        let mut wizard = CreateWizard {
+            kind: ResourceKind::Script,
+            bundled: false,
+            template: false,
+            library_names: vec!["es_extended".to_owned(), "ox_lib".to_owned()],
+            answers: CreateAnswers::default(),
+        };
+        let mut prompter = ScriptedPrompter::new([
+            Scripted::Text("bank".into()),
+            Scripted::Text("someone".into()),
+            Scripted::Confirm(true),
+            Scripted::Back,
+            Scripted::Confirm(false),
+            Scripted::Confirm(true),
+            Scripted::MultiSelect(vec![1]),
+            Scripted::Confirm(true),
+            // Change the libraries, dropping ox_lib also drops the locales step
+            Scripted::Select(5),
+            Scripted::MultiSelect(vec![0]),
+            Scripted::Select(0),
+        ]);
+        run_wizard(&CREATE_STEPS, &mut prompter, &mut wizard).unwrap();
+
+        let answers = wizard.answers;
+        assert_eq!(answers.project_name.as_deref(), Some("bank"));
+        assert_eq!(answers.data_files, Some(false));
+        assert_eq!(answers.version_file, Some(true));
+        assert_eq!(answers.libraries, Some(vec!["es_extended".to_owned()]));
+        assert_eq!(answers.model, None);
+        assert!(!CreateStep::Locales.applies(&CreateWizard { answers, ..wizard }));
+    }
+
+    #[test]
+    fn create_wizard_template_test() {
+        let mut wizard = CreateWizard {
+            kind: ResourceKind::Script,
+            bundled: false,
+            template: true,
+            library_names: vec!["ox_lib".to_owned()],
+            answers: CreateAnswers {
+                data_files: Some(true),
+                ..Default::default()
+            },
+        };
+        let mut prompter = ScriptedPrompter::new([
+            Scripted::Text("bank".into()),
+            Scripted::Text("someone".into()),
+            Scripted::Select(0),
+        ]);
+        run_wizard(&CREATE_STEPS, &mut prompter, &mut wizard).unwrap();
+
+        // The template provides the scripts, so none of their questions apply
+        assert_eq!(prompter.asked.len(), 3);
+        assert_eq!(
+            toml::to_string(&wizard.used_answers()).unwrap(),
+            "name = \"bank\"\nauthor = \"someone\"\n"
+        );
+    }
+
+    #[test]
+    fn script_section_multiple_test() {
+        let mut builder = ScriptSectionBuilder::new("server");
+        builder.append("@es_extended/imports.lua");
+        builder.append("src/server/main.lua");
+
+        let result = builder.build().unwrap().unwrap();
+        let expected = r#"server_scripts {
+    "@es_extended/imports.lua",
+    "src/server/main.lua"
+}"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn script_section_single_test() {
+        let mut builder = ScriptSectionBuilder::new("server");
+        builder.append("src/server/main.lua");
+
+        let result = builder.build().unwrap().unwrap();
+        let expected = r#"server_scripts {
+    "src/server/main.lua"
+}"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn manifest_server_scope_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        manifest.scope(Scope::Server);
+
+        let result = manifest.build().unwrap();
+        assert!(result.contains("server_only \"yes\""));
+        assert!(!result.contains("client_scripts"));
+    }
+
+    #[test]
+    fn manifest_other_scopes_test() {
+        for scope in [Scope::Both, Scope::Client] {
+            let mut manifest = ScriptManifest::new("author", false, vec![]);
+            manifest.scope(scope);
+
+            let result = manifest.build().unwrap();
+            assert!(!result.contains("server_only"));
+        }
+    }
+
+    #[test]
+    fn script_section_empty_test() {
+        let builder = ScriptSectionBuilder::new("shared");
+        assert_eq!(builder.build().unwrap(), None);
+    }
+
+    #[test]
+    fn script_section_inline_test() {
+        let mut builder = ScriptSectionBuilder::new("client");
+        builder.style(SectionStyle::Inline);
+        builder.append("src/client/main.lua");
+
+        let result = builder.build().unwrap().unwrap();
+        assert_eq!(result, r#"client_script "src/client/main.lua""#);
+    }
+
+    #[test]
+    fn manifest_empty_sections_test() {
+        let manifest = ScriptManifest::new("author", false, vec![]);
+
+        let result = manifest.build().unwrap();
+        let expected = r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+server_scripts {
+    "src/server/main.lua"
+}
+
+client_scripts {
+    "src/client/main.lua"
+}
+"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn manifest_inline_sections_test() {
+        let mut manifest = ScriptManifest::new("author", true, vec![]);
+        manifest.section_style(SectionStyle::Inline);
+
+        let result = manifest.build().unwrap();
+        let expected = r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+server_script "src/server/main.lua"
+
+client_script "src/client/main.lua"
+
+data_files {
+    "data/*.lua"
+}
+"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn manifest_many_entries_test() {
+        let libraries = ["es_extended", "ox_lib", "oxmysql"]
+            .iter()
+            .map(|&name| LIBRARIES.get(name).unwrap().clone())
+            .collect();
+        let mut manifest = ScriptManifest::new("author", false, libraries);
+        manifest.section_style(SectionStyle::Inline);
+
+        let result = manifest.build().unwrap();
+        let expected = r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+server_scripts {
+    "@oxmysql/lib/MySQL.lua",
+    "src/server/main.lua"
+}
+
+client_script "src/client/main.lua"
+
+shared_scripts {
+    "@ox_lib/init.lua",
+    "@es_extended/imports.lua"
+}
+"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn modules_test() {
+        let modules = vec!["inventory".to_owned(), "shops".to_owned()];
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        for name in &modules {
+            manifest.module(
+                ScriptRuntime::Server,
+                module_path(&ScriptRuntime::Server, name),
+            );
+        }
+
+        assert!(manifest.build().unwrap().contains(
+            r#"server_scripts {
+    "src/server/modules/inventory.lua",
+    "src/server/modules/shops.lua",
+    "src/server/main.lua"
+}"#
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let files = write_modules(dir.path(), &ScriptRuntime::Server, &modules).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                "src/server/modules/inventory.lua",
+                "src/server/modules/shops.lua"
+            ]
+        );
+        let stub = std::fs::read_to_string(dir.path().join(&files[1])).unwrap();
+        assert!(stub.ends_with("Modules['shops'] = module\n"));
+
+        let main = with_module_loader(&modules, String::new());
+        assert!(main.starts_with("for _, name in ipairs({ 'inventory', 'shops' }) do\n"));
+        assert!(parse_module_name("bad name").is_err());
+    }
+
+    #[test]
+    fn script_section_priority_test() {
+        let mut builder = ScriptSectionBuilder::new("client");
+        builder.append("@ox_lib/init.lua");
+        builder.append("src/client/main.lua");
+        builder.append_priority("src/client/polyfill.lua");
+        builder.append_priority("src/client/compat.lua");
+
+        assert_eq!(
+            builder.scripts,
+            vec![
+                "src/client/polyfill.lua",
+                "src/client/compat.lua",
+                "@ox_lib/init.lua",
+                "src/client/main.lua"
+            ]
+        );
+    }
+
+    #[test]
+    fn manifest_priority_test() {
+        let libraries = vec![
+            LIBRARIES["ox_lib"].clone(),
+            LIBRARIES["es_extended"].clone().with_priority(true),
+        ];
+        let mut manifest = ScriptManifest::new("author", false, libraries);
+        manifest
+            .module(ScriptRuntime::Server, "src/server/modules/db.lua")
+            .module(ScriptRuntime::Server, "src/server/modules/polyfill.lua")
+            .priority_script("src/server/modules/polyfill.lua");
+
+        let result = manifest.build().unwrap();
+        assert!(result.contains(
+            r#"server_scripts {
+    "src/server/modules/polyfill.lua",
+    "src/server/modules/db.lua",
+    "src/server/main.lua"
+}"#
+        ));
+        assert!(result.contains(
+            r#"shared_scripts {
+    "@es_extended/imports.lua",
+    "@ox_lib/init.lua"
+}"#
+        ));
+    }
+
+    #[test]
+    fn locales_test() {
+        assert!(!supports_locales(&["es_extended", "oxmysql"]));
+        let manifest = ScriptManifest::new("author", false, vec![LIBRARIES["es_extended"].clone()]);
+        assert!(!manifest.build().unwrap().contains(LOCALES_PATTERN));
+
+        assert!(supports_locales(&["es_extended", "ox_lib"]));
+        let mut manifest = ScriptManifest::new("author", false, vec![LIBRARIES["ox_lib"].clone()]);
+        let example = setup_locales(&mut manifest, "print('hi')\n".to_owned());
+        assert_eq!(example, "lib.locale()\n\nprint('hi')\n");
+        assert!(manifest.build().unwrap().contains(
+            r#"files {
+    "locales/*.json"
+}"#
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            write_locales(dir.path(), "bank").unwrap(),
+            "locales/en.json"
+        );
+        let locale = std::fs::read_to_string(dir.path().join("locales/en.json")).unwrap();
+        assert_eq!(locale, "{\n  \"greeting\": \"Hello from bank!\"\n}\n");
+    }
+
+    #[test]
+    fn exports_test() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| name.to_owned())
+                .collect::<Vec<_>>()
+        };
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        let client = setup_exports(
+            &mut manifest,
+            ScriptRuntime::Client,
+            &names(&["getFoo", "getBar"]),
+            true,
+            "print('hi')\n".to_owned(),
+        );
+        let server = setup_exports(
+            &mut manifest,
+            ScriptRuntime::Server,
+            &names(&["setBar"]),
+            true,
+            String::new(),
+        );
+
+        assert_eq!(
+            client,
+            "print('hi')\n\nexports('getFoo', function()\nend)\n\nexports('getBar', function()\nend)\n"
+        );
+        assert_eq!(server, "exports('setBar', function()\nend)\n");
+        assert!(manifest.build().unwrap().contains(
+            r#"    "src/client/main.lua"
+}
+
+exports {
+    "getFoo",
+    "getBar"
+}
+server_exports {
+    "setBar"
+}
+"#
+        ));
+
+        // Without stubs only the manifest declares them
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        let example = setup_exports(
+            &mut manifest,
+            ScriptRuntime::Server,
+            &names(&["setBar"]),
+            false,
+            String::new(),
+        );
+        assert_eq!(example, "");
+        assert!(manifest.build().unwrap().contains("server_exports {"));
+
+        assert!(parse_export_name("get_Foo2").is_ok());
+        assert!(parse_export_name("2fast").is_err());
+        assert!(parse_export_name("get-foo").is_err());
+    }
+
+    #[test]
+    fn bundled_manifest_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![LIBRARIES["ox_lib"].clone()]);
+        manifest.bundled(true);
+        let built = manifest.build().unwrap();
+
+        assert!(built.contains("server_scripts {\n    \"dist/server.js\"\n}"));
+        assert!(built.contains("client_scripts {\n    \"dist/client.js\"\n}"));
+        assert!(built.contains("\"@ox_lib/init.lua\""));
+        assert!(!built.contains("src/"));
+
+        manifest.scope(Scope::Server);
+        assert!(!manifest.build().unwrap().contains("dist/client.js"));
+    }
+
+    #[test]
+    fn bundle_files_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = write_bundle(dir.path(), "Bank", Scope::Both, Language::Ts).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                "src/client/main.ts",
+                "src/server/main.ts",
+                "build.js",
+                "package.json"
+            ]
+        );
+
+        let build = std::fs::read_to_string(dir.path().join("build.js")).unwrap();
+        assert!(build.contains("entryPoints: ['src/client/main.ts'], outfile: 'dist/client.js'"));
+        assert!(build.contains(
+            "entryPoints: ['src/server/main.ts'], outfile: 'dist/server.js', platform: 'node'"
+        ));
+
+        let package: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("package.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(package["name"], "bank");
+        assert_eq!(package["scripts"]["build"], "node build.js");
+        assert!(package["devDependencies"]["esbuild"].is_string());
+
+        assert_eq!(
+            bundle_entries(Scope::Client, Language::Js),
+            vec![(
+                "client",
+                "src/client/main.js".to_owned(),
+                "dist/client.js".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn generated_lua_syntax_test() {
+        let libraries = LIBRARIES.values().cloned().collect::<Vec<Library>>();
+        for runtime in [ScriptRuntime::Client, ScriptRuntime::Server] {
+            let example = render_examples(&libraries, &runtime, "bank").unwrap();
+            let modules = ["jobs".to_owned(), "shops".to_owned()];
+            let example = with_module_loader(&modules, example);
+            let mut manifest = ScriptManifest::new("author", false, vec![]);
+            let example = setup_locales(&mut manifest, example);
+            assert_eq!(crate::lua::check_syntax(&example), Ok(()), "{example}");
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        write_modules(dir.path(), &ScriptRuntime::Server, &["jobs".to_owned()]).unwrap();
+        let stub = std::fs::read_to_string(dir.path().join("src/server/modules/jobs.lua")).unwrap();
+        assert_eq!(crate::lua::check_syntax(&stub), Ok(()));
+    }
+
+    #[test]
+    fn changelog_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        manifest.version("1.2.0");
+
+        let changelog = changelog(&manifest.version, "2024-03-01");
+        assert!(changelog.starts_with("# Changelog\n"));
+        assert!(changelog.contains("## [Unreleased]\n\n## [1.2.0] - 2024-03-01\n"));
+        assert!(changelog.ends_with("- Initial release\n"));
+
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(19783), (2024, 3, 1));
+        assert_eq!(civil_date(11016), (2000, 2, 29));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+        assert_eq!(today().len(), 10);
+    }
+
+    #[test]
+    fn version_file_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        manifest.version("1.4.2");
+
+        let parsed = crate::manifest::Manifest::parse(&manifest.build().unwrap()).unwrap();
+        assert_eq!(manifest.version_file(), "1.4.2\n");
+        assert_eq!(
+            parsed.value("version"),
+            Some(manifest.version_file().trim_end())
+        );
+    }
+
+    #[test]
+    fn manifest_library_order_test() {
+        let orders = [
+            ["es_extended", "ox_lib", "oxmysql", "ox_target"],
+            ["ox_target", "oxmysql", "es_extended", "ox_lib"],
+            ["oxmysql", "ox_lib", "ox_target", "es_extended"],
+        ];
+
+        let results = orders
+            .iter()
+            .map(|names| {
+                let libraries = names.iter().map(|&name| LIBRARIES[name].clone()).collect();
+                let mut manifest = ScriptManifest::new("author", false, libraries);
+                manifest.client_init(true).build().unwrap()
+            })
+            .collect::<Vec<String>>();
+
+        assert!(results.iter().all(|result| *result == results[0]));
+        assert!(results[0].find("@ox_lib").unwrap() < results[0].find("@es_extended").unwrap());
+    }
+
+    #[test]
+    fn manifest_whitespace_test() {
+        let libraries = vec![LIBRARIES.get("ox_lib").unwrap().clone()];
+        let manifest = ScriptManifest::new("author", true, libraries);
+
+        let result = manifest.build().unwrap();
+        let expected = r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+server_scripts {
+    "src/server/main.lua"
+}
+
+client_scripts {
+    "src/client/main.lua"
+}
+
+shared_scripts {
+    "@ox_lib/init.lua"
+}
+
+data_files {
+    "data/*.lua"
+}
+"#;
+
+        assert_eq!(result, expected);
+        assert!(result.lines().all(|line| line.trim_end() == line));
+        assert!(!result.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn manifest_client_init_test() {
+        let libraries = vec![LIBRARIES["ox_lib"].clone(), LIBRARIES["oxmysql"].clone()];
+        let mut manifest = ScriptManifest::new("author", false, libraries);
+        manifest.section_style(SectionStyle::Inline);
+
+        let default = manifest.build().unwrap();
+        assert!(default.contains("\nclient_script \"src/client/main.lua\"\n"));
+        assert_eq!(default.matches("@ox_lib/init.lua").count(), 1);
+
+        let opt_in = manifest.client_init(true).build().unwrap();
+        assert!(opt_in.contains(
+            "\nclient_scripts {\n    \"@ox_lib/init.lua\",\n    \"src/client/main.lua\"\n}\n"
+        ));
+        assert!(opt_in.contains("\nshared_script \"@ox_lib/init.lua\"\n"));
+        assert!(!opt_in.contains("client_scripts {\n    \"@oxmysql"));
+    }
+
+    #[test]
+    fn manifest_build_constraints_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        manifest
+            .section_style(SectionStyle::Inline)
+            .dependency("/server:7290")
+            .dependency("/gameBuild:2699");
+
+        let result = manifest.build().unwrap();
+        let expected = r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+dependency "/server:7290"
+dependency "/gameBuild:2699"
+
+server_script "src/server/main.lua"
+
+client_script "src/client/main.lua"
+"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn build_number_validation_test() {
+        assert_eq!(parse_server_version("7290"), Ok(7290));
+        assert_eq!(parse_game_build("2699"), Ok(2699));
+        assert!(parse_server_version("12").is_err());
+        assert!(parse_game_build("latest").is_err());
+        assert!(parse_game_build("100000").is_err());
+    }
+
+    #[test]
+    fn install_instructions_test() {
+        let names = ["oxmysql", "ox_lib", "es_extended"];
+        let libraries = names.map(|name| LIBRARIES.get(name).unwrap().clone());
+        let install = InstallInstructions::collect(names.into_iter().zip(&libraries));
+
+        assert_eq!(install.required_convars, vec!["mysql_connection_string"]);
+        assert_eq!(install.notes.len(), 2);
+
+        let markdown = install.build_markdown("example");
+        assert!(markdown.starts_with("# Installing example\n"));
+        assert!(markdown.contains("- `set mysql_connection_string \"...\"`\n"));
+        assert!(markdown.contains("- oxmysql: Ensure oxmysql before"));
+    }
+
+    #[test]
+    fn install_instructions_empty_test() {
+        let library = LIBRARIES.get("ox_lib").unwrap();
+        assert!(InstallInstructions::collect([("ox_lib", library)]).is_empty());
+    }
+
+    #[test]
+    fn manifest_multiple_games_test() {
+        let mut manifest = ScriptManifest::new("author", false, vec![]);
+        manifest
+            .section_style(SectionStyle::Inline)
+            .games(&[Game::Gta5, Game::Rdr3, Game::Gta5]);
+
+        let result = manifest.build().unwrap();
+        let expected = format!(
+            r#"fx_version "cerulean"
+games {{ "gta5", "rdr3" }}
+rdr3_warning "{RDR3_WARNING}"
+lua54 "yes"
+
+author "author"
+version "0.0.0"
+
+server_script "src/server/main.lua"
+
+client_script "src/client/main.lua"
+"#
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn library_games_test() {
+        let both = [Game::Gta5, Game::Rdr3];
+        assert_eq!(LIBRARIES["ox_lib"].unsupported_games("ox_lib", &both), None);
+        assert_eq!(
+            LIBRARIES["es_extended"].unsupported_games("es_extended", &both),
+            Some("es_extended does not support rdr3".to_owned())
+        );
+        assert_eq!(
+            LIBRARIES["qb-target"].unsupported_games("qb-target", &[Game::Gta5]),
+            None
+        );
+    }
+
+    #[test]
+    fn games_argument_test() {
+        assert_eq!(Game::from_str("rdr3", false), Ok(Game::Rdr3));
+        assert!(Game::from_str("gta4", false).is_err());
+    }
+
+    #[test]
+    fn allow_overwrite_test() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("bank");
+        create_dir_all(project.join("src/client")).unwrap();
+        std::fs::write(project.join("fxmanifest.lua"), "-- old").unwrap();
+        std::fs::write(project.join("src/client/main.lua"), "-- mine").unwrap();
+
+        let staged = StagedWrite::new(&project).unwrap();
+        let base_path = staged.dir().to_path_buf();
+        assert_ne!(base_path, project);
+        create_dir_all(base_path.join("src/client")).unwrap();
+        create_dir_all(base_path.join("src/shared")).unwrap();
+        std::fs::write(base_path.join("fxmanifest.lua"), "-- new").unwrap();
+        std::fs::write(base_path.join("src/client/main.lua"), "-- generated").unwrap();
+        std::fs::write(base_path.join("cfx.toml"), "name = \"bank\"").unwrap();
+
+        let allow = [Pattern::new("fxmanifest.lua").unwrap()];
+        let protected = staged.finish(&allow, &CancelToken::new()).unwrap();
+        assert_eq!(protected, ["src/client/main.lua"]);
+
+        let read = |file: &str| std::fs::read_to_string(project.join(file)).unwrap();
+        assert_eq!(read("fxmanifest.lua"), "-- new");
+        assert_eq!(read("src/client/main.lua"), "-- mine");
+        assert_eq!(read("cfx.toml"), "name = \"bank\"");
+        assert!(project.join("src/shared").is_dir());
+        assert!(!base_path.exists());
+
+        // Once cancelled, nothing is merged into the existing directory
+        let staged = StagedWrite::new(&project).unwrap();
+        let base_path = staged.dir().to_path_buf();
+        std::fs::write(base_path.join("fxmanifest.lua"), "-- cancelled").unwrap();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let err = staged.finish(&allow, &cancel).unwrap_err();
+        assert!(is_cancellation(err.as_ref()));
+        assert_eq!(read("fxmanifest.lua"), "-- new");
+        assert!(!base_path.exists());
+
+        // A new project is written in place
+        let staged = StagedWrite::new(&root.path().join("fresh")).unwrap();
+        assert_eq!(staged.dir(), root.path().join("fresh"));
+    }
+
+    #[test]
+    fn created_dir_test() {
+        let root = tempfile::tempdir().unwrap();
+        let base_path = project_dir(Some(root.path()), Some("jobs"), "bank");
+
+        let created = CreatedDir::new(&base_path);
+        create_dir_all(base_path.join("src")).unwrap();
+        drop(created);
+        assert!(!root.path().join("[jobs]").exists());
+
+        let created = CreatedDir::new(&base_path);
+        create_dir_all(&base_path).unwrap();
+        created.keep();
+        assert!(base_path.exists());
+
+        // Existing projects are never removed
+        drop(CreatedDir::new(&base_path));
+        assert!(base_path.exists());
+    }
+
+    #[test]
+    fn project_dir_test() {
+        let root = Path::new("/srv/fivem/resources");
+
+        assert_eq!(
+            project_dir(Some(root), Some("jobs"), "bank"),
+            root.join("[jobs]").join("bank")
+        );
+        assert_eq!(
+            project_dir(Some(root), Some("[jobs]"), "bank"),
+            root.join("[jobs]").join("bank")
+        );
+        assert_eq!(project_dir(Some(root), None, "bank"), root.join("bank"));
+        assert_eq!(project_dir(None, None, "bank"), PathBuf::from("bank"));
+    }
+
+    #[test]
+    fn target_examples_test() {
+        let libraries = [LIBRARIES["ox_lib"].clone(), LIBRARIES["ox_target"].clone()];
+        let client = render_examples(&libraries, &ScriptRuntime::Client, "bank").unwrap();
+        let server = render_examples(&libraries, &ScriptRuntime::Server, "bank").unwrap();
+
+        assert!(client.starts_with("exports.ox_target:addBoxZone({"));
+        assert!(client.contains("TriggerServerEvent('bank:interact')"));
+        assert!(server.starts_with("RegisterNetEvent('bank:interact', function()"));
+
+        let libraries = [
+            LIBRARIES["qb-target"].clone(),
+            LIBRARIES["ox_target"].clone(),
+        ];
+        let client = render_examples(&libraries, &ScriptRuntime::Client, "bank").unwrap();
+        let server = render_examples(&libraries, &ScriptRuntime::Server, "bank").unwrap();
+
+        assert!(client.starts_with("exports['qb-target']:AddBoxZone('bank:interact'"));
+        assert!(client.contains("exports.ox_target:addBoxZone"));
+        assert_eq!(server.matches("RegisterNetEvent").count(), 1);
+    }
+
+    #[test]
+    fn exports_only_library_test() {
+        let libraries = vec![LIBRARIES["qb-target"].clone()];
+        let manifest = ScriptManifest::new("cfx", false, libraries)
+            .build()
+            .unwrap();
+
+        assert!(!manifest.contains("qb-target"));
+    }
+
+    #[test]
+    fn check_written_scripts_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("src/client")).unwrap();
+        std::fs::write(dir.path().join("src/client/main.lua"), "").unwrap();
+
+        let manifest = r#"client_scripts {
+    "@ox_lib/init.lua",
+    "src/client/main.lua"
+}
+
+server_scripts {
+    "dist/server.js",
+    "src/server/main.lua"
+}
+"#;
+        let err = check_written_scripts(dir.path(), manifest, true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The manifest lists `src/server/main.lua` but no such file was created"
+        );
+
+        create_dir_all(dir.path().join("src/server")).unwrap();
+        std::fs::write(dir.path().join("src/server/main.lua"), "").unwrap();
+        check_written_scripts(dir.path(), manifest, true).unwrap();
+        assert!(check_written_scripts(dir.path(), manifest, false).is_err());
+    }
+
+    #[test]
+    fn icon_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("my_bank");
+        let extras = ManifestExtras {
+            icon: true,
+            ..Default::default()
+        };
+        write_audio(&base_path, "my_bank", "cfx", "my_bank", &extras).unwrap();
+        let manifest = std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap();
+        assert!(manifest.contains("files {\n    \"icon.png\","));
+
+        let rules = |base_path: &Path| {
+            crate::commands::validate::validate_resource(base_path)
+                .unwrap()
+                .rules
+                .into_iter()
+                .filter(|report| report.rule == "invalid-icon")
+                .flat_map(|report| report.findings)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            rules(&base_path),
+            ["`icon.png` is listed in files but missing"]
+        );
+
+        write_icon(&base_path, None, "my_bank").unwrap();
+        assert!(rules(&base_path).is_empty());
+
+        let wide = dir.path().join("wide.png");
+        std::fs::write(&wide, crate::icon::encode_png(128, 64, &[0; 128 * 64 * 3])).unwrap();
+        assert!(write_icon(&base_path, Some(&wide), "my_bank").is_err());
+        std::fs::copy(&wide, base_path.join(ICON_FILE)).unwrap();
+        assert_eq!(
+            rules(&base_path),
+            ["`icon.png` is 128x64, icons have to be square"]
+        );
+    }
+
+    #[test]
+    fn vehicle_manifest_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("sultanrs2");
+        let files = write_vehicle(
+            &base_path,
+            "sultanrs2",
+            "cfx",
+            "sultanrs2",
+            &ManifestExtras::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                "data/handling.meta",
+                "data/vehicles.meta",
+                "data/carcols.meta",
+                "data/carvariations.meta",
+                "fxmanifest.lua"
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap(),
+            r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+name "sultanrs2"
+author "cfx"
+version "0.0.0"
+
+files {
+    "data/handling.meta",
+    "data/vehicles.meta",
+    "data/carcols.meta",
+    "data/carvariations.meta"
+}
+
+data_file "HANDLING_FILE" "data/handling.meta"
+data_file "VEHICLE_METADATA_FILE" "data/vehicles.meta"
+data_file "CARCOLS_FILE" "data/carcols.meta"
+data_file "VEHICLE_VARIATION_FILE" "data/carvariations.meta"
+"#
+        );
+
+        let vehicles = std::fs::read_to_string(base_path.join("data/vehicles.meta")).unwrap();
+        assert!(vehicles.contains("<modelName>sultanrs2</modelName>"));
+
+        let summary = crate::commands::validate::validate_resource(&base_path).unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(summary.warnings.len(), 1);
+    }
+
+    #[test]
+    fn manifest_spec_test() {
+        let spec: ManifestSpec = serde_json::from_str(
+            r#"{
+                "name": "bank",
+                "author": "cfx",
+                "version": "1.2.0",
+                "games": ["gta5", "rdr3"],
+                "libraries": ["ox_lib"],
+                "section_style": "inline",
+                "scripts": {
+                    "client": ["client/main.lua"],
+                    "server": ["server/main.lua", "server/db.lua"]
+                },
+                "files": ["html/index.html"],
+                "data_files": [{ "kind": "DLC_ITYP_REQUEST", "path": "stream/bank.ytyp" }]
+            }"#,
+        )
+        .unwrap();
+
+        let expected = format!(
+            r#"fx_version "cerulean"
+games {{ "gta5", "rdr3" }}
+rdr3_warning "{RDR3_WARNING}"
+lua54 "yes"
+
+name "bank"
+author "cfx"
+version "1.2.0"
+
+server_scripts {{
+    "server/main.lua",
+    "server/db.lua"
+}}
+
+client_script "client/main.lua"
+
+shared_script "@ox_lib/init.lua"
+
+files {{
+    "html/index.html"
+}}
+
+data_file "DLC_ITYP_REQUEST" "stream/bank.ytyp"
+"#
+        );
+
+        assert_eq!(spec.build().unwrap(), expected);
+    }
+
+    #[test]
+    fn manifest_flags_test() {
+        let flags = [
+            "use_experimental_fxv2_oal=yes",
+            "clr_disable_task_scheduler=yes",
+        ]
+        .map(|flag| parse_manifest_flag(flag).unwrap());
+        let mut manifest = ScriptManifest::new("cfx", false, vec![]);
+        manifest.entry_scripts(false).flags(&flags);
+
+        assert!(manifest.build().unwrap().starts_with(
+            r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+use_experimental_fxv2_oal "yes"
+clr_disable_task_scheduler "yes"
+
+author "cfx"
+"#
+        ));
+
+        assert!(parse_manifest_flag("use_fxv2_oal").is_err());
+        assert!(parse_manifest_flag("lua54=no").is_err());
+        assert!(parse_manifest_flag("bad key=yes").is_err());
+        assert!(parse_manifest_flag("use_fxv2_oal=\"yes").is_err());
+
+        let custom = [parse_manifest_flag("my_toggle=yes").unwrap()];
+        assert!(check_manifest_flags(&flags, true).is_ok());
+        assert!(check_manifest_flags(&custom, false).is_ok());
+        assert!(check_manifest_flags(&custom, true).is_err());
+    }
+
+    #[test]
+    fn manifest_comments_test() {
+        let mut manifest = ScriptManifest::new("cfx", false, vec![LIBRARIES["ox_lib"].clone()]);
+        manifest.scope(Scope::Server).file("html/index.html");
+        let plain = manifest.build().unwrap();
+        assert!(!plain.contains("--"));
+
+        let commented = manifest.comments(true).build().unwrap();
+        assert!(commented.starts_with(
+            r#"-- What the resource runs on
+fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+server_only "yes"
+
+-- About the resource
+author "cfx"
+"#
+        ));
+        assert!(commented.contains("-- Scripts run by the server\nserver_scripts {"));
+        assert!(commented.contains("-- Files sent to clients, such as NUI pages\nfiles {"));
+        assert!(!commented.contains("-- Scripts run by every client"));
+
+        // The comments are invisible to the manifest parser
+        let directives = |text: &str| {
+            crate::manifest::Manifest::parse(text)
+                .unwrap()
+                .directives
+                .into_iter()
+                .map(|directive| (directive.name, directive.values))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(directives(&commented), directives(&plain));
+
+        // Nor do they change what validate finds in a generated resource
+        let findings = |comments: bool| {
+            let dir = tempfile::tempdir().unwrap();
+            let base_path = dir.path().join("my_bank");
+            let extras = ManifestExtras {
+                comments,
+                ..Default::default()
+            };
+            write_audio(&base_path, "my_bank", "cfx", "my_bank", &extras).unwrap();
+            let manifest = std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap();
+            assert_eq!(manifest.starts_with("-- "), comments);
+            crate::commands::validate::validate_resource(&base_path)
+                .unwrap()
+                .rules
+                .into_iter()
+                .flat_map(|report| report.findings)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(findings(true), findings(false));
+    }
+
+    #[test]
+    fn manifest_canonical_order_test() {
+        // Keys are set in a scrambled order, build emits them canonically
+        let mut manifest = ScriptManifest::new("cfx", true, vec![LIBRARIES["ox_lib"].clone()]);
+        manifest
+            .data_file("DLC_ITYP_REQUEST", "stream/bank.ytyp")
+            .file("html/index.html")
+            .provides("bank_legacy")
+            .dependency("/onesync")
+            .version("2.0.0")
+            .description("Banking for everyone")
+            .games(&[Game::Gta5, Game::Rdr3])
+            .script(ScriptRuntime::Shared, "shared/config.lua")
+            .module(ScriptRuntime::Server, "src/server/db.lua")
+            .name("bank");
+
+        let expected = format!(
+            r#"fx_version "cerulean"
+games {{ "gta5", "rdr3" }}
+rdr3_warning "{RDR3_WARNING}"
+lua54 "yes"
+
+name "bank"
+author "cfx"
+description "Banking for everyone"
+version "2.0.0"
+
+dependency "/onesync"
+provides "bank_legacy"
+
+server_scripts {{
+    "src/server/db.lua",
+    "src/server/main.lua"
+}}
+
+client_scripts {{
+    "src/client/main.lua"
+}}
+
+shared_scripts {{
+    "@ox_lib/init.lua",
+    "shared/config.lua"
+}}
+
+data_files {{
+    "data/*.lua"
+}}
+
+files {{
+    "html/index.html"
+}}
+
+data_file "DLC_ITYP_REQUEST" "stream/bank.ytyp"
+"#
+        );
+
+        assert_eq!(manifest.build().unwrap(), expected);
+    }
+
+    #[test]
+    fn manifest_spec_priority_test() {
+        let spec: ManifestSpec = serde_json::from_str(
+            r#"{
+                "author": "cfx",
+                "scripts": { "client": ["client/main.lua", "client/polyfill.lua"] },
+                "priority": ["client/polyfill.lua"]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(spec.build().unwrap().contains(
+            r#"client_scripts {
+    "client/polyfill.lua",
+    "client/main.lua"
+}"#
+        ));
+
+        let spec: ManifestSpec =
+            serde_json::from_str(r#"{ "author": "cfx", "priority": ["client/nope.lua"] }"#)
+                .unwrap();
+        assert!(spec.build().is_err());
+    }
+
+    #[test]
+    fn manifest_spec_unknown_library_test() {
+        let spec: ManifestSpec =
+            serde_json::from_str(r#"{ "author": "cfx", "libraries": ["nope"] }"#).unwrap();
+
+        assert!(spec.build().is_err());
+    }
+
+    #[test]
+    fn ped_components_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("mypack");
+        let files = write_ped(
+            &base_path,
+            "mypack",
+            "cfx",
+            "mp_m_freemode_01",
+            PedAddon::Components,
+            &ManifestExtras::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec!["data/mp_m_freemode_01_mypack.meta", "fxmanifest.lua"]
+        );
+        assert!(base_path.join("stream/mp_m_freemode_01_mypack").is_dir());
+
+        let manifest = std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap();
+        assert!(manifest.ends_with(
+            r#"files {
+    "data/mp_m_freemode_01_mypack.meta"
+}
+
+data_file "SHOP_PED_APPAREL_META_FILE" "data/mp_m_freemode_01_mypack.meta"
+data_file "DLC_ITYP_REQUEST" "stream/mp_m_freemode_01_mypack.ytyp"
+"#
+        ));
+
+        let summary = crate::commands::validate::validate_resource(&base_path).unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(summary.warnings.len(), 1);
+    }
+
+    #[test]
+    fn full_ped_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("peds");
+        write_ped(
+            &base_path,
+            "peds",
+            "cfx",
+            "a_m_y_custom",
+            PedAddon::FullPed,
+            &ManifestExtras::default(),
+        )
+        .unwrap();
+
+        let meta = std::fs::read_to_string(base_path.join("data/peds.meta")).unwrap();
+        let meta: PedsMeta = crate::meta::from_xml(&meta).unwrap();
+        assert_eq!(meta.init_datas.items[0].name, "a_m_y_custom");
+
+        let manifest = std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap();
+        assert!(manifest.contains("data_file \"PED_METADATA_FILE\" \"data/peds.meta\""));
+
+        let summary = crate::commands::validate::validate_resource(&base_path).unwrap();
+        assert!(summary.errors.is_empty());
+    }
+
+    #[test]
+    fn audio_pack_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("sirens");
+        write_audio(
+            &base_path,
+            "sirens",
+            "cfx",
+            "sirens",
+            &ManifestExtras::default(),
+        )
+        .unwrap();
+
+        assert!(base_path.join("audioconfig").is_dir());
+        assert!(base_path.join("sfx/dlc_sirens").is_dir());
+
+        let manifest = std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap();
+        assert!(manifest.ends_with(
+            r#"files {
+    "audioconfig/sirens_game.dat151.rel",
+    "audioconfig/sirens_sounds.dat54.rel",
+    "sfx/dlc_sirens/sirens.awc"
+}
+
+data_file "AUDIO_GAMEDATA" "audioconfig/sirens_game.dat"
+data_file "AUDIO_SOUNDDATA" "audioconfig/sirens_sounds.dat"
+data_file "AUDIO_WAVEPACK" "sfx/dlc_sirens"
+"#
+        ));
+
+        let validate = crate::commands::validate::validate_resource;
+        let summary = validate(&base_path).unwrap();
+        assert!(summary.errors.is_empty());
+        assert_eq!(summary.warnings.len(), 6);
+
+        // Once present, containers have to parse
+        std::fs::write(base_path.join("sfx/dlc_sirens/sirens.awc"), b"not audio").unwrap();
+        let summary = validate(&base_path).unwrap();
+        assert_eq!(
+            summary.errors,
+            vec![
+                "`sfx/dlc_sirens/sirens.awc` is not a valid AWC container: \
+                 too short for an AWC header (9 bytes)"
+            ]
+        );
+    }
+
+    #[test]
+    fn loadscreen_manifest_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_path = dir.path().join("loading");
+        let files = write_loadscreen(
+            &base_path,
+            "loading",
+            "cfx",
+            true,
+            &ManifestExtras::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                "html/index.html",
+                "html/style.css",
+                "html/script.js",
+                "fxmanifest.lua"
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(base_path.join("fxmanifest.lua")).unwrap(),
+            r#"fx_version "cerulean"
+game "gta5"
+lua54 "yes"
+
+name "loading"
+author "cfx"
+version "0.0.0"
+
+loadscreen "html/index.html"
+loadscreen_manual_shutdown "yes"
+
+files {
+    "html/index.html",
+    "html/style.css",
+    "html/script.js"
+}
+"#
+        );
+
+        let page = std::fs::read_to_string(base_path.join(LOADSCREEN_PAGE)).unwrap();
+        assert!(page.contains("<title>loading</title>"));
+
+        let summary = crate::commands::validate::validate_resource(&base_path).unwrap();
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+
+        // Without manual shutdown the game closes the page once loaded, and a
+        // loading screen never gets script sections whatever its scope
+        let mut manifest = ScriptManifest::new("cfx", false, vec![]);
+        manifest.kind(ResourceKind::Loadscreen).scope(Scope::Client);
+        let manifest = manifest.build().unwrap();
+        assert!(manifest.ends_with("version \"0.0.0\"\n\nloadscreen \"html/index.html\"\n"));
     }
 }
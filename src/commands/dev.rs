@@ -0,0 +1,397 @@
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::joaat::joaat;
+use crate::map::{ymap_page, ytyp_page};
+use crate::nav::{ynd_page, ynv_page};
+use crate::texture::{texture_dictionary_pages, Texture};
+use crate::writer::write_rsc7;
+use crate::CfxResult;
+
+#[derive(Subcommand)]
+pub enum DevCommand {
+    /// Synthesize an RSC7 file from a JSON description of its pages
+    MakeFixture {
+        /// JSON file with `version`, `virtual`, `physical` and `trailing`
+        /// entries, or with an `asset` to lay the pages out for
+        description: PathBuf,
+        /// Where to write the RSC7 file
+        output: PathBuf,
+    },
+}
+
+/// Uncompressed page contents, given either as text or as a byte array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum PageData {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Default for PageData {
+    fn default() -> Self {
+        Self::Bytes(vec![])
+    }
+}
+
+impl PageData {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(text) => text.as_bytes(),
+            Self::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+/// An asset whose pages `make-fixture` lays out, with names given as text and
+/// hashed as the game does.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Asset {
+    /// `.ytd` of single-level DXT1 textures
+    TextureDictionary { textures: Vec<TextureDescription> },
+    /// `.ymap` placing entities, interior instances after the plain ones
+    Ymap {
+        name: String,
+        #[serde(default)]
+        entities: Vec<EntityDescription>,
+        #[serde(default)]
+        mlo_instances: Vec<EntityDescription>,
+    },
+    /// `.ytyp` defining archetypes, interiors after the plain ones
+    Ytyp {
+        name: String,
+        #[serde(default)]
+        archetypes: Vec<String>,
+        #[serde(default)]
+        mlos: Vec<MloDescription>,
+    },
+    /// `.ynd` path nodes
+    Paths {
+        nodes: Vec<NodeDescription>,
+        vehicle_nodes: u32,
+        links: u32,
+    },
+    /// `.ynv` navmesh whose sector tree spans `min` to `max`
+    Navmesh {
+        vertices: u32,
+        polys: u32,
+        min: [f32; 3],
+        max: [f32; 3],
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextureDescription {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    /// DXT1 blocks, all zero when left out
+    #[serde(default)]
+    pub data: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntityDescription {
+    pub archetype: String,
+    #[serde(default)]
+    pub guid: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MloDescription {
+    pub name: String,
+    #[serde(default)]
+    pub rooms: u16,
+    #[serde(default)]
+    pub portals: u16,
+    /// Archetypes of the entities inside
+    #[serde(default)]
+    pub entities: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeDescription {
+    pub area_id: u16,
+    pub link_id: u16,
+    pub links: u8,
+}
+
+impl Asset {
+    /// The resource version files of this kind use.
+    fn version(&self) -> u8 {
+        match self {
+            Asset::TextureDictionary { .. } => 13,
+            Asset::Ymap { .. } | Asset::Ytyp { .. } | Asset::Navmesh { .. } => 2,
+            Asset::Paths { .. } => 1,
+        }
+    }
+
+    /// The virtual and physical pages of the asset.
+    fn pages(&self) -> CfxResult<(Vec<u8>, Vec<u8>)> {
+        let entities = |entities: &[EntityDescription]| {
+            entities
+                .iter()
+                .map(|entity| (joaat(&entity.archetype), entity.guid))
+                .collect::<Vec<_>>()
+        };
+        let hashes = |names: &[String]| names.iter().map(|name| joaat(name)).collect::<Vec<_>>();
+
+        let virtual_page = match self {
+            Asset::TextureDictionary { textures } => {
+                let data = textures
+                    .iter()
+                    .map(|texture| match &texture.data {
+                        Some(data) => data.clone(),
+                        None => vec![0; Texture::data_size(texture.width, texture.height)],
+                    })
+                    .collect::<Vec<_>>();
+                let textures = textures
+                    .iter()
+                    .zip(&data)
+                    .map(|(texture, data)| Texture {
+                        name: &texture.name,
+                        width: texture.width,
+                        height: texture.height,
+                        data,
+                    })
+                    .collect::<Vec<_>>();
+                return texture_dictionary_pages(&textures);
+            }
+            Asset::Ymap {
+                name,
+                entities: plain,
+                mlo_instances,
+            } => {
+                if plain.len() + mlo_instances.len() > u16::MAX as usize {
+                    return Err("A ymap holds at most 65535 entities".into());
+                }
+                ymap_page(joaat(name), &entities(plain), &entities(mlo_instances))
+            }
+            Asset::Ytyp {
+                name,
+                archetypes,
+                mlos,
+            } => {
+                if archetypes.len() + mlos.len() > u16::MAX as usize {
+                    return Err("A ytyp holds at most 65535 archetypes".into());
+                }
+                let inside = mlos
+                    .iter()
+                    .map(|mlo| hashes(&mlo.entities))
+                    .collect::<Vec<_>>();
+                let mlos = mlos
+                    .iter()
+                    .zip(&inside)
+                    .map(|(mlo, inside)| (joaat(&mlo.name), mlo.rooms, mlo.portals, &inside[..]))
+                    .collect::<Vec<_>>();
+                ytyp_page(joaat(name), &hashes(archetypes), &mlos)
+            }
+            Asset::Paths {
+                nodes,
+                vehicle_nodes,
+                links,
+            } => {
+                if *vehicle_nodes as usize > nodes.len() {
+                    return Err(format!(
+                        "{vehicle_nodes} vehicle nodes are more than the {} nodes",
+                        nodes.len()
+                    )
+                    .into());
+                }
+                if let Some(node) = nodes.iter().find(|node| node.links > 31) {
+                    return Err(format!("A node has at most 31 links, not {}", node.links).into());
+                }
+                let nodes = nodes
+                    .iter()
+                    .map(|node| (node.area_id, node.link_id, node.links))
+                    .collect::<Vec<_>>();
+                ynd_page(&nodes, *vehicle_nodes, *links)
+            }
+            Asset::Navmesh {
+                vertices,
+                polys,
+                min,
+                max,
+            } => ynv_page(*vertices, *polys, *min, *max),
+        };
+
+        Ok((virtual_page, vec![]))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FixtureDescription {
+    /// Required for pages given as data, defaults to the usual version of
+    /// the kind of an `asset`
+    #[serde(default)]
+    pub version: Option<u8>,
+    #[serde(default)]
+    pub asset: Option<Asset>,
+    #[serde(default)]
+    pub r#virtual: PageData,
+    #[serde(default)]
+    pub physical: PageData,
+    /// Raw bytes appended after the pages, for files that are out of spec
+    #[serde(default)]
+    pub trailing: PageData,
+}
+
+impl FixtureDescription {
+    pub fn build(&self) -> CfxResult<Vec<u8>> {
+        let (version, virtual_page, physical_page) = match &self.asset {
+            Some(asset) => {
+                if !self.r#virtual.as_bytes().is_empty() || !self.physical.as_bytes().is_empty() {
+                    return Err(
+                        "Give either an `asset` or the `virtual` and `physical` pages".into(),
+                    );
+                }
+                let (virtual_page, physical_page) = asset.pages()?;
+                (
+                    self.version.unwrap_or(asset.version()),
+                    virtual_page,
+                    physical_page,
+                )
+            }
+            None => (
+                self.version
+                    .ok_or("`version` is required without an `asset`")?,
+                self.r#virtual.as_bytes().to_vec(),
+                self.physical.as_bytes().to_vec(),
+            ),
+        };
+
+        let mut data = write_rsc7(version, &virtual_page, &physical_page)?;
+        data.extend_from_slice(self.trailing.as_bytes());
+        Ok(data)
+    }
+}
+
+#[derive(Serialize)]
+pub struct FixtureSummary {
+    pub output: PathBuf,
+    pub size: usize,
+}
+
+pub fn handle_dev_command(command: &DevCommand) -> CfxResult<FixtureSummary> {
+    match command {
+        DevCommand::MakeFixture {
+            description,
+            output,
+        } => {
+            let content = read_to_string(description)?;
+            let fixture: FixtureDescription = serde_json::from_str(&content)
+                .map_err(|err| format!("Invalid fixture description {description:?}: {err}"))?;
+
+            let data = fixture.build()?;
+            write(output, &data)?;
+            log::info!("Wrote {} ({} bytes)", output.display(), data.len());
+
+            Ok(FixtureSummary {
+                output: output.clone(),
+                size: data.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The checked-in fixtures have to stay reproducible from their descriptions.
+    #[test]
+    fn minimal_fixture_test() {
+        let fixtures: [(&str, &[u8]); 7] = [
+            (
+                include_str!("../../fixtures/minimal.json"),
+                include_bytes!("../../fixtures/minimal.rsc7"),
+            ),
+            (
+                include_str!("../../fixtures/deviations/trailing_garbage.json"),
+                include_bytes!("../../fixtures/deviations/trailing_garbage.rsc7"),
+            ),
+            (
+                include_str!("../../fixtures/deviations/checksum_mismatch.json"),
+                include_bytes!("../../fixtures/deviations/checksum_mismatch.rsc7"),
+            ),
+            (
+                include_str!("../../fixtures/nav/paths.json"),
+                include_bytes!("../../fixtures/nav/paths.ynd"),
+            ),
+            (
+                include_str!("../../fixtures/nav/navmesh.json"),
+                include_bytes!("../../fixtures/nav/navmesh.ynv"),
+            ),
+            (
+                include_str!("../../fixtures/textures/two_textures.json"),
+                include_bytes!("../../fixtures/textures/two_textures.ytd"),
+            ),
+            (
+                include_str!("../../fixtures/map/three_entities.json"),
+                include_bytes!("../../fixtures/map/three_entities.ymap"),
+            ),
+        ];
+
+        for (description, data) in fixtures {
+            let fixture: FixtureDescription = serde_json::from_str(description).unwrap();
+            assert_eq!(fixture.build().unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn asset_fixture_test() {
+        let build = |description: serde_json::Value| {
+            serde_json::from_value::<FixtureDescription>(description)
+                .map_err(|err| err.to_string())
+                .and_then(|fixture| fixture.build().map_err(|err| err.to_string()))
+        };
+
+        // The version is split over the high nibbles of the page flags
+        let version = |data: &[u8]| {
+            let flags =
+                |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+            ((flags(8) >> 28) << 4 | flags(12) >> 28) as u8
+        };
+
+        // Typed assets default to the version of their kind
+        let data = build(serde_json::json!({
+            "asset": { "kind": "texture_dictionary", "textures": [{ "name": "logo", "width": 8, "height": 4 }] }
+        }))
+        .unwrap();
+        assert_eq!(version(&data), 13);
+        let data = build(serde_json::json!({
+            "version": 3,
+            "asset": { "kind": "ymap", "name": "map" }
+        }))
+        .unwrap();
+        assert_eq!(version(&data), 3);
+
+        let err = build(serde_json::json!({
+            "asset": { "kind": "ymap", "name": "map" },
+            "virtual": "pages"
+        }))
+        .unwrap_err();
+        assert!(err.contains("either an `asset`"), "{err}");
+        let err = build(serde_json::json!({ "virtual": "pages" })).unwrap_err();
+        assert!(err.contains("`version` is required"), "{err}");
+        let err = build(serde_json::json!({
+            "asset": { "kind": "paths", "nodes": [], "vehicle_nodes": 1, "links": 0 }
+        }))
+        .unwrap_err();
+        assert!(err.contains("more than the 0 nodes"), "{err}");
+        let err = build(serde_json::json!({
+            "asset": { "kind": "ymap", "name": "map", "entites": [] }
+        }))
+        .unwrap_err();
+        assert!(err.contains("unknown field `entites`"), "{err}");
+        assert!(build(serde_json::json!({ "asset": { "kind": "ydr" } })).is_err());
+    }
+}
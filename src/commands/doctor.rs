@@ -0,0 +1,139 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::commands::create::library_count;
+use crate::magic::Format;
+use crate::CfxResult;
+
+/// Optional programs other commands or the usual workflow make use of.
+const TOOLS: [(&str, &str); 3] = [
+    (
+        "git",
+        "Install git to version your resources: https://git-scm.com",
+    ),
+    (
+        "stylua",
+        "Install StyLua to format Lua: https://github.com/JohnnyMorganz/StyLua",
+    ),
+    (
+        "luacheck",
+        "Install luacheck to lint Lua: https://github.com/lunarmodules/luacheck",
+    ),
+];
+
+#[derive(Debug, Serialize)]
+pub struct ToolCheck {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DoctorSummary {
+    pub tools: Vec<ToolCheck>,
+    pub resource_formats: Vec<String>,
+    pub libraries: usize,
+}
+
+pub fn handle_doctor_command() -> CfxResult<DoctorSummary> {
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    let mut tools = TOOLS
+        .iter()
+        .map(|&(name, hint)| check_tool(name, hint, &path_var))
+        .collect::<Vec<ToolCheck>>();
+
+    // $EDITOR may hold arguments, e.g. `code --wait`
+    let editor = env::var("EDITOR")
+        .ok()
+        .and_then(|editor| editor.split_whitespace().next().map(str::to_owned))
+        .unwrap_or_else(|| "code".to_owned());
+    tools.push(check_tool(
+        &editor,
+        "Set $EDITOR or install Visual Studio Code: https://code.visualstudio.com",
+        &path_var,
+    ));
+
+    for tool in &tools {
+        match (&tool.path, &tool.hint) {
+            (Some(path), _) => log::info!("Found {} at {}", tool.name, path.display()),
+            (None, Some(hint)) => log::warn!("{} is missing. {hint}", tool.name),
+            (None, None) => log::warn!("{} is missing", tool.name),
+        }
+    }
+
+    let resource_formats = Format::ALL
+        .into_iter()
+        .filter(|format| matches!(format, Format::Rsc7 | Format::Rsc8))
+        .map(|format| format.to_string())
+        .collect::<Vec<String>>();
+    log::info!("Resource formats: {}", resource_formats.join(", "));
+
+    let libraries = library_count();
+    log::info!("Built-in libraries: {libraries}");
+
+    Ok(DoctorSummary {
+        tools,
+        resource_formats,
+        libraries,
+    })
+}
+
+fn check_tool(name: &str, hint: &str, path_var: &OsStr) -> ToolCheck {
+    let path = find_in_path(name, path_var);
+    ToolCheck {
+        name: name.to_owned(),
+        hint: path.is_none().then(|| hint.to_owned()),
+        path,
+    }
+}
+
+/// Looks `name` up in the directories of `path_var` like `which`, also trying
+/// the `.exe` and `.cmd` extensions on Windows. Paths are returned as is.
+pub(crate) fn find_in_path(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+    if Path::new(name).components().count() > 1 {
+        let path = PathBuf::from(name);
+        return path.is_file().then_some(path);
+    }
+
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd"]
+    } else {
+        &[""]
+    };
+
+    env::split_paths(path_var).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|extension| dir.join(format!("{name}{extension}")))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_in_path_test() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        std::fs::write(second.path().join("stylua"), "").unwrap();
+        std::fs::create_dir(first.path().join("git")).unwrap();
+
+        let path_var = env::join_paths([first.path(), second.path()]).unwrap();
+        assert_eq!(
+            find_in_path("stylua", &path_var),
+            Some(second.path().join("stylua"))
+        );
+        // Directories are not programs
+        assert_eq!(find_in_path("git", &path_var), None);
+
+        let check = check_tool("luacheck", "hint", &path_var);
+        assert_eq!(check.path, None);
+        assert_eq!(check.hint.as_deref(), Some("hint"));
+    }
+}
@@ -0,0 +1,50 @@
+use clap::Args;
+use serde::Serialize;
+
+use crate::errors::{ErrorCode, Explanation, ERROR_CODES};
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct ExplainArgs {
+    /// Error code, such as E001
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct ExplainSummary {
+    pub code: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub explanation: Explanation,
+}
+
+pub fn handle_explain_command(args: &ExplainArgs) -> CfxResult<ExplainSummary> {
+    let error = ErrorCode::parse(&args.code).ok_or_else(|| {
+        let codes = ERROR_CODES.map(|error| error.code());
+        format!(
+            "Unknown error code `{}`, known codes are {}",
+            args.code,
+            codes.join(", ")
+        )
+    })?;
+
+    let summary = ExplainSummary {
+        code: error.code(),
+        name: format!("{error:?}"),
+        explanation: error.explanation(),
+    };
+
+    log::info!(
+        "{} {}: {}",
+        summary.code,
+        summary.name,
+        summary.explanation.summary
+    );
+    log::info!("Common causes:");
+    for cause in summary.explanation.causes {
+        log::info!("  - {cause}");
+    }
+    log::info!("Remedy: {}", summary.explanation.remedy);
+
+    Ok(summary)
+}
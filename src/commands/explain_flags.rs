@@ -0,0 +1,216 @@
+use clap::Args;
+use serde::Serialize;
+
+use crate::commands::unpack::ResourceChunkFlags;
+use crate::writer::page_flags;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct ExplainFlagsArgs {
+    /// Page flags dword, in hex with a `0x` prefix or in decimal
+    #[arg(required_unless_present = "for_size")]
+    pub value: Option<String>,
+
+    /// Print the flags the encoder chooses for a page of this many bytes instead
+    #[arg(long, value_name = "BYTES", conflicts_with = "value")]
+    pub for_size: Option<usize>,
+
+    /// Resource version whose nibbles go in the high bits of the encoded flags
+    #[arg(long, requires = "for_size", default_value_t = 0)]
+    pub version: u8,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Bucket {
+    pub count: u32,
+    pub chunk_size: u64,
+    pub size: u64,
+}
+
+/// What a flags dword decodes to.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DecodedFlags {
+    pub value: u32,
+    /// High nibble, half of the resource version
+    pub type_nibble: u32,
+    pub base_shift: u32,
+    pub base_size: u64,
+    /// Buckets from the largest chunk size down
+    pub buckets: Vec<Bucket>,
+    /// Can exceed the 4 GiB a page holds, which unpack rejects
+    pub total_size: u64,
+}
+
+/// The flags the encoder writes for a page, virtual and physical flags
+/// carrying the high and low nibble of the version.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EncodedFlags {
+    pub size: usize,
+    pub version: u8,
+    pub virtual_flags: u32,
+    pub physical_flags: u32,
+    /// Size of the page the flags describe, `size` rounded up to fit them
+    pub page_size: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ExplainFlagsSummary {
+    Decoded(DecodedFlags),
+    Encoded(EncodedFlags),
+}
+
+pub fn handle_explain_flags_command(args: &ExplainFlagsArgs) -> CfxResult<ExplainFlagsSummary> {
+    if let Some(size) = args.for_size {
+        let encoded = encode_flags(size, args.version)?;
+        log::info!("size            {size} ({size:#x})");
+        log::info!("version         {}", encoded.version);
+        log::info!("virtual flags   {:#010x}", encoded.virtual_flags);
+        log::info!("physical flags  {:#010x}", encoded.physical_flags);
+        log::info!("page size       {0} ({0:#x})", encoded.page_size);
+        return Ok(ExplainFlagsSummary::Encoded(encoded));
+    }
+
+    let value = parse_flags(args.value.as_deref().unwrap_or_default())?;
+    let decoded = decode_flags(value);
+    log::info!("flags       {:#010x}", decoded.value);
+    log::info!("type nibble {}", decoded.type_nibble);
+    log::info!("base shift  {}", decoded.base_shift);
+    log::info!("base size   {0} ({0:#x})", decoded.base_size);
+    log::info!("bucket  count  chunk size  size");
+    for (i, bucket) in decoded.buckets.iter().enumerate() {
+        log::info!(
+            "{i:<7} {:<6} {:<#11x} {:#x}",
+            bucket.count,
+            bucket.chunk_size,
+            bucket.size
+        );
+    }
+    log::info!("total size  {0} ({0:#x})", decoded.total_size);
+
+    Ok(ExplainFlagsSummary::Decoded(decoded))
+}
+
+/// Parses a flags dword written in hex with a `0x` prefix, or in decimal.
+fn parse_flags(value: &str) -> CfxResult<u32> {
+    let value = value.trim();
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse::<u32>(),
+    };
+
+    parsed.map_err(|err| {
+        let hint = match value.chars().all(|c| c.is_ascii_hexdigit()) {
+            true => ", prefix hex values with 0x",
+            false => "",
+        };
+        format!("`{value}` is not a flags dword: {err}{hint}").into()
+    })
+}
+
+fn decode_flags(value: u32) -> DecodedFlags {
+    let flags = ResourceChunkFlags::new(value);
+    let buckets = flags
+        .get_buckets_count()
+        .into_iter()
+        .zip(flags.get_chunk_sizes())
+        .map(|(count, chunk_size)| Bucket {
+            count,
+            chunk_size,
+            // At most 2^32 times a 7-bit count, so this fits easily
+            size: chunk_size * count as u64,
+        })
+        .collect::<Vec<_>>();
+
+    let total_size = buckets.iter().map(|bucket: &Bucket| bucket.size).sum();
+    DecodedFlags {
+        value,
+        type_nibble: flags.type_val(),
+        base_shift: flags.base_shift(),
+        base_size: flags.base_size(),
+        buckets,
+        total_size,
+    }
+}
+
+/// The flags `write_rsc7` writes for a compressed page of `size` bytes.
+fn encode_flags(size: usize, version: u8) -> CfxResult<EncodedFlags> {
+    let flags = page_flags(size)?;
+    let version = version as u32;
+
+    Ok(EncodedFlags {
+        size,
+        version: version as u8,
+        virtual_flags: flags | (version >> 4) << 28,
+        physical_flags: flags | (version & 0xF) << 28,
+        page_size: ResourceChunkFlags::new(flags).get_size()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_flags_test() {
+        assert_eq!(parse_flags("0x20000001").unwrap(), 0x20000001);
+        assert_eq!(parse_flags("0X1f").unwrap(), 0x1F);
+        assert_eq!(parse_flags("512").unwrap(), 512);
+
+        let err = parse_flags("ff").unwrap_err().to_string();
+        assert!(err.contains("prefix hex values with 0x"), "{err}");
+        assert!(parse_flags("0xnope").is_err());
+    }
+
+    #[test]
+    fn decode_flags_test() {
+        // Base shift 1, one chunk in the fifth bucket and type nibble 2
+        let decoded = decode_flags(0x2002_0001);
+        assert_eq!(decoded.type_nibble, 2);
+        assert_eq!(decoded.base_shift, 1);
+        assert_eq!(decoded.base_size, 0x400);
+        assert_eq!(
+            decoded.buckets[4],
+            Bucket {
+                count: 1,
+                chunk_size: 0x4000,
+                size: 0x4000
+            }
+        );
+        assert_eq!(decoded.total_size, 0x4000);
+    }
+
+    #[test]
+    fn encode_flags_round_trip_test() {
+        let encoded = encode_flags(0x1234, 0xA2).unwrap();
+        assert_eq!(encoded.virtual_flags >> 28, 0xA);
+        assert_eq!(encoded.physical_flags >> 28, 0x2);
+
+        let decoded = decode_flags(encoded.virtual_flags);
+        assert_eq!(decoded.total_size, encoded.page_size as u64);
+        assert!(encoded.page_size as usize >= encoded.size);
+        assert_eq!(
+            decode_flags(encoded.physical_flags).total_size,
+            encoded.page_size as u64
+        );
+    }
+
+    #[test]
+    fn flags_edge_values_test() {
+        // Every bucket full at the largest base shift
+        let decoded = decode_flags(0xFFFF_FFFF);
+        assert_eq!(decoded.base_size, 0x200 << 15);
+        assert_eq!(decoded.buckets[0].chunk_size, 1 << 32);
+        assert_eq!(decoded.total_size, 0x16_1F00_0000);
+        assert_eq!(decode_flags(0x0FFF_FFFF).total_size, decoded.total_size);
+        assert!(ResourceChunkFlags::new(0x0FFF_FFFF).get_size().is_err());
+
+        for size in [u32::MAX as usize, usize::MAX] {
+            let err = encode_flags(size, 13).unwrap_err().to_string();
+            assert!(err.contains("do not fit"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+use crate::magic::Format;
+use crate::CfxResult;
+
+#[derive(Serialize)]
+pub struct FormatInfo {
+    pub format: Format,
+    pub name: String,
+    pub description: &'static str,
+    /// Magic bytes in hex
+    pub magic: String,
+}
+
+pub fn handle_formats_command() -> CfxResult<Vec<FormatInfo>> {
+    let formats = Format::ALL
+        .into_iter()
+        .map(|format| FormatInfo {
+            format,
+            name: format.to_string(),
+            description: format.description(),
+            magic: format
+                .magic()
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
+        .collect::<Vec<_>>();
+
+    for info in &formats {
+        log::info!("{:<5} {:<12} {}", info.name, info.magic, info.description);
+    }
+
+    Ok(formats)
+}
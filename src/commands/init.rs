@@ -0,0 +1,260 @@
+use std::fs::{read_to_string, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use inquire::Text;
+use serde::Serialize;
+
+use crate::commands::create::{is_known_library, library_convars};
+use crate::manifest::{Manifest, SCRIPT_DIRECTIVES};
+use crate::project::{Language, ProjectConfig, PROJECT_FILE};
+use crate::wizard::confirm;
+use crate::CfxResult;
+
+const GITIGNORE: &str = "node_modules/\ndist/\n*.zip\n*.zip.sha256\n";
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Existing resource directory containing an fxmanifest.lua
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+/// What can be learned about a resource from its manifest and files.
+#[derive(Debug, PartialEq)]
+pub struct Detected {
+    pub name: String,
+    pub author: Option<String>,
+    pub language: Language,
+    pub libraries: Vec<String>,
+    pub lua54: bool,
+}
+
+#[derive(Serialize)]
+pub struct InitSummary {
+    pub project: ProjectConfig,
+    /// Whether an existing cfx.toml was updated rather than created
+    pub updated: bool,
+    pub files: Vec<String>,
+}
+
+pub fn handle_init_command(args: &InitArgs) -> CfxResult<InitSummary> {
+    let detected = detect(&args.path)?;
+    let existing = args.path.join(PROJECT_FILE).is_file();
+
+    let project = if existing {
+        let update = confirm(
+            &format!("{PROJECT_FILE} already exists, update it with the detected settings?"),
+            true,
+        )?;
+        if !update {
+            return Err(format!("Left the existing {PROJECT_FILE} untouched").into());
+        }
+
+        merge(ProjectConfig::load(&args.path)?, &detected)
+    } else {
+        let author = match &detected.author {
+            Some(author) => author.clone(),
+            None => Text::new("What is the authors name?").prompt()?,
+        };
+
+        ProjectConfig {
+            name: detected.name.clone(),
+            author,
+            required_convars: library_convars(&detected.libraries),
+            libraries: detected.libraries.clone(),
+            language: detected.language,
+            ..Default::default()
+        }
+    };
+
+    project.save(&args.path)?;
+    let mut files = vec![PROJECT_FILE.to_owned()];
+
+    if !args.path.join(".gitignore").exists() && confirm("Add a .gitignore?", true)? {
+        write_new(&args.path.join(".gitignore"), GITIGNORE)?;
+        files.push(".gitignore".to_owned());
+    }
+
+    if detected.language == Language::Lua
+        && !args.path.join(".luarc.json").exists()
+        && confirm("Add a .luarc.json for the Lua language server?", true)?
+    {
+        write_new(&args.path.join(".luarc.json"), &build_luarc(detected.lua54))?;
+        files.push(".luarc.json".to_owned());
+    }
+
+    log::info!(
+        "{} {} ({:?}, libraries: {})",
+        if existing { "Updated" } else { "Initialized" },
+        project.name,
+        project.language,
+        project.libraries.join(", ")
+    );
+
+    Ok(InitSummary {
+        project,
+        updated: existing,
+        files,
+    })
+}
+
+/// Infers the name from the folder, the author from the manifest, the
+/// language from the scripts and the libraries from `@resource/...` imports.
+pub fn detect(resource_dir: &Path) -> CfxResult<Detected> {
+    let manifest_path = resource_dir.join("fxmanifest.lua");
+    if !manifest_path.is_file() {
+        return Err(format!("No fxmanifest.lua found in {resource_dir:?}").into());
+    }
+
+    let manifest = Manifest::parse(&read_to_string(manifest_path)?)?;
+    let name = resource_dir
+        .canonicalize()?
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("Resource directory has no name")?;
+
+    let scripts = manifest.values(&SCRIPT_DIRECTIVES);
+    let mut libraries = vec![];
+    for script in &scripts {
+        let Some(import) = script.strip_prefix('@') else {
+            continue;
+        };
+
+        let resource = import.split('/').next().unwrap_or_default().to_owned();
+        if is_known_library(&resource) && !libraries.contains(&resource) {
+            libraries.push(resource);
+        }
+    }
+
+    let local = scripts
+        .iter()
+        .filter(|script| !script.starts_with('@'))
+        .collect::<Vec<_>>();
+    let js = local
+        .iter()
+        .filter(|script| script.ends_with(".js"))
+        .count();
+    let language = if resource_dir.join("tsconfig.json").is_file() {
+        Language::Ts
+    } else if js * 2 > local.len() {
+        Language::Js
+    } else {
+        Language::Lua
+    };
+
+    Ok(Detected {
+        name,
+        author: manifest.value("author").map(str::to_owned),
+        language,
+        libraries,
+        lua54: manifest.value("lua54") == Some("yes"),
+    })
+}
+
+/// Keeps what the user already set and adds what was detected since.
+fn merge(mut project: ProjectConfig, detected: &Detected) -> ProjectConfig {
+    for library in &detected.libraries {
+        if !project.libraries.contains(library) {
+            project.libraries.push(library.clone());
+        }
+    }
+
+    for convar in library_convars(&project.libraries) {
+        if !project.required_convars.contains(&convar) {
+            project.required_convars.push(convar);
+        }
+    }
+
+    project.language = detected.language;
+    project
+}
+
+fn build_luarc(lua54: bool) -> String {
+    let version = if lua54 { "Lua 5.4" } else { "Lua 5.3" };
+    let luarc = serde_json::json!({
+        "runtime.version": version,
+        "runtime.nonstandardSymbol": ["/**/", "`", "+=", "-=", "*=", "/=", "<<=", ">>=", "&=", "|=", "^="],
+        "workspace.checkThirdParty": false,
+    });
+
+    format!("{:#}\n", luarc)
+}
+
+fn write_new(path: &Path, content: &str) -> CfxResult<()> {
+    let mut file = File::create_new(path)?;
+    file.write_all(content.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn detect_test() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("bank");
+        create_dir_all(&dir).unwrap();
+        write(
+            dir.join("fxmanifest.lua"),
+            r#"fx_version 'cerulean'
+author 'cfx'
+lua54 'yes'
+shared_scripts { '@ox_lib/init.lua', '@es_extended/imports.lua', '@unknown/init.lua' }
+server_scripts { '@oxmysql/lib/MySQL.lua', 'server/*.lua' }
+client_script 'client/main.lua'"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            detect(&dir).unwrap(),
+            Detected {
+                name: "bank".to_owned(),
+                author: Some("cfx".to_owned()),
+                language: Language::Lua,
+                libraries: vec![
+                    "ox_lib".to_owned(),
+                    "es_extended".to_owned(),
+                    "oxmysql".to_owned()
+                ],
+                lua54: true,
+            }
+        );
+
+        write(dir.join("tsconfig.json"), "{}").unwrap();
+        assert_eq!(detect(&dir).unwrap().language, Language::Ts);
+    }
+
+    #[test]
+    fn merge_test() {
+        let project = ProjectConfig {
+            name: "bank".to_owned(),
+            author: "someone".to_owned(),
+            libraries: vec!["ox_lib".to_owned()],
+            required_convars: vec!["bank:currency".to_owned()],
+            language: Language::Lua,
+            ..Default::default()
+        };
+        let detected = Detected {
+            name: "renamed".to_owned(),
+            author: Some("cfx".to_owned()),
+            language: Language::Js,
+            libraries: vec!["oxmysql".to_owned(), "ox_lib".to_owned()],
+            lua54: false,
+        };
+
+        let merged = merge(project, &detected);
+        assert_eq!(merged.name, "bank");
+        assert_eq!(merged.author, "someone");
+        assert_eq!(merged.libraries, vec!["ox_lib", "oxmysql"]);
+        assert_eq!(
+            merged.required_convars,
+            vec!["bank:currency", "mysql_connection_string"]
+        );
+        assert_eq!(merged.language, Language::Js);
+    }
+}
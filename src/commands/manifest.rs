@@ -0,0 +1,41 @@
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::atomic::atomic_write;
+use crate::commands::create::ManifestSpec;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct ManifestArgs {
+    /// JSON file describing the project
+    #[arg(long)]
+    pub spec: PathBuf,
+
+    /// Where to write the manifest
+    #[arg(long, default_value = "fxmanifest.lua")]
+    pub output: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct ManifestSummary {
+    pub output: PathBuf,
+    pub manifest: String,
+}
+
+pub fn handle_manifest_command(args: &ManifestArgs) -> CfxResult<ManifestSummary> {
+    let content = read_to_string(&args.spec)?;
+    let spec: ManifestSpec = serde_json::from_str(&content)
+        .map_err(|err| format!("Invalid manifest spec {:?}: {err}", args.spec))?;
+
+    let manifest = spec.build()?;
+    atomic_write(&args.output, &manifest)?;
+    log::info!("Wrote {}", args.output.display());
+
+    Ok(ManifestSummary {
+        output: args.output.clone(),
+        manifest,
+    })
+}
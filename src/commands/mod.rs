@@ -1,2 +1,24 @@
+pub mod add;
+pub mod audit_map;
+pub mod bump;
+pub mod cache;
+pub mod check;
+pub mod check_events;
+pub mod check_exports;
+pub mod compare_manifests;
 pub mod create;
+pub mod dev;
+pub mod doctor;
+pub mod explain;
+pub mod explain_flags;
+pub mod formats;
+pub mod init;
+pub mod manifest;
+pub mod pack_resource;
+pub mod repack;
+pub mod strings;
+pub mod template;
 pub mod unpack;
+pub mod upgrade_manifest;
+pub mod validate;
+pub mod watch;
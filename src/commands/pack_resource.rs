@@ -0,0 +1,217 @@
+use std::fs::{canonicalize, read_to_string, File};
+use std::io::{copy, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Args;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::commands::validate::validate_resource;
+use crate::manifest::{referenced_files, Manifest};
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct PackResourceArgs {
+    /// Resource directory to package
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Run `npm run build` first when the resource has a package.json
+    #[arg(long)]
+    pub build: bool,
+
+    /// Write a SHA-256 sidecar file next to the archive
+    #[arg(long)]
+    pub checksum: bool,
+
+    /// Directory, or `.zip` path, to write the archive to
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct PackResourceSummary {
+    pub archive: PathBuf,
+    pub files: Vec<String>,
+    pub sha256: Option<String>,
+}
+
+pub fn handle_pack_resource_command(args: &PackResourceArgs) -> CfxResult<PackResourceSummary> {
+    let resource_dir = canonicalize(&args.path)?;
+    if args.build && resource_dir.join("package.json").is_file() {
+        log::info!("Building resource");
+        let status = Command::new("npm")
+            .args(["run", "build"])
+            .current_dir(&resource_dir)
+            .status()
+            .map_err(|err| format!("Could not run npm: {err}"))?;
+
+        if !status.success() {
+            return Err(format!("Build failed ({status})").into());
+        }
+    }
+
+    let validation = validate_resource(&resource_dir)?;
+    for warning in &validation.warnings {
+        log::warn!("{warning}");
+    }
+
+    if !validation.errors.is_empty() {
+        for error in &validation.errors {
+            log::error!("{error}");
+        }
+
+        return Err("Resource does not validate, not packing it".into());
+    }
+
+    let manifest = Manifest::parse(&read_to_string(resource_dir.join("fxmanifest.lua"))?)?;
+    let name = resource_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("Resource directory has no name")?;
+    let version = manifest.value("version").unwrap_or("0.0.0");
+
+    let archive = match &args.output {
+        Some(output) if output.extension().is_some_and(|ext| ext == "zip") => output.clone(),
+        Some(output) => output.join(format!("{name}-{version}.zip")),
+        None => PathBuf::from(format!("{name}-{version}.zip")),
+    };
+
+    let files = referenced_files(&resource_dir, &manifest)?;
+    let entries = write_archive(&archive, &resource_dir, &name, &files)?;
+    log::info!("Packed {} files into {}", entries.len(), archive.display());
+
+    let sha256 = if args.checksum {
+        let digest = sha256_file(&archive)?;
+        let file_name = archive.file_name().unwrap_or_default().to_string_lossy();
+        let mut sidecar = File::create(format!("{}.sha256", archive.display()))?;
+        sidecar.write_all(format!("{digest}  {file_name}\n").as_bytes())?;
+
+        Some(digest)
+    } else {
+        None
+    };
+
+    Ok(PackResourceSummary {
+        archive,
+        files: entries,
+        sha256,
+    })
+}
+
+/// Zips `files` below a top-level `name/` folder, returning the entry names.
+fn write_archive(
+    archive: &Path,
+    resource_dir: &Path,
+    name: &str,
+    files: &[PathBuf],
+) -> CfxResult<Vec<String>> {
+    let mut writer = ZipWriter::new(File::create(archive)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut entries = vec![];
+    for file in files {
+        let relative = file.strip_prefix(resource_dir)?;
+        let parts = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>();
+        let entry = format!("{name}/{}", parts.join("/"));
+
+        writer.start_file(entry.as_str(), options)?;
+        copy(&mut File::open(file)?, &mut writer)?;
+        entries.push(entry);
+    }
+
+    writer.finish()?;
+    Ok(entries)
+}
+
+fn sha256_file(path: &Path) -> CfxResult<String> {
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?;
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn pack_resource_test() {
+        let root = tempfile::tempdir().unwrap();
+        let resource = root.path().join("bank");
+        create_dir_all(resource.join("client")).unwrap();
+        create_dir_all(resource.join(".git")).unwrap();
+        write(
+            resource.join("fxmanifest.lua"),
+            "fx_version 'cerulean'\nversion '1.2.0'\nclient_script 'client/main.lua'",
+        )
+        .unwrap();
+        write(resource.join("client/main.lua"), "print('hi')").unwrap();
+        write(resource.join("client/notes.txt"), "").unwrap();
+        write(resource.join(".git/HEAD"), "").unwrap();
+
+        let args = PackResourceArgs {
+            path: resource,
+            build: false,
+            checksum: true,
+            output: Some(root.path().to_path_buf()),
+        };
+        let summary = handle_pack_resource_command(&args).unwrap();
+
+        assert_eq!(summary.archive, root.path().join("bank-1.2.0.zip"));
+        assert_eq!(
+            summary.files,
+            vec!["bank/client/main.lua", "bank/fxmanifest.lua"]
+        );
+
+        let sidecar = read_to_string(root.path().join("bank-1.2.0.zip.sha256")).unwrap();
+        assert_eq!(
+            sidecar,
+            format!("{}  bank-1.2.0.zip\n", summary.sha256.unwrap())
+        );
+
+        let zip = zip::ZipArchive::new(File::open(&summary.archive).unwrap()).unwrap();
+        assert_eq!(zip.len(), 2);
+    }
+
+    #[test]
+    fn pack_invalid_resource_test() {
+        let resource = tempfile::tempdir().unwrap();
+        write(
+            resource.path().join("fxmanifest.lua"),
+            "client_script 'client/main.lua'",
+        )
+        .unwrap();
+
+        let args = PackResourceArgs {
+            path: resource.path().to_path_buf(),
+            build: false,
+            checksum: false,
+            output: Some(resource.path().join("out.zip")),
+        };
+
+        assert!(handle_pack_resource_command(&args).is_err());
+        assert!(!resource.path().join("out.zip").exists());
+    }
+}
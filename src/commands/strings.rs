@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::archive::{FArchive, FArchiveExt, FMemoryArchive};
+use crate::commands::unpack::{
+    inflate_page, read_input, ArchiveHeader, ResourceChunkFlags, HEADER_SIZE,
+};
+use crate::magic::RSC7_MAGIC;
+use crate::output::is_machine;
+use crate::progress::NoProgress;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct StringsArgs {
+    /// RSC7 file to search
+    pub input: PathBuf,
+
+    /// Shortest run of printable characters to report
+    #[arg(long, short = 'n', default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub min_length: u32,
+}
+
+/// A run of printable ASCII in a decompressed page.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FoundString {
+    /// `virtual` or `physical`
+    pub segment: &'static str,
+    /// Offset within the decompressed page
+    pub offset: usize,
+    pub text: String,
+}
+
+#[derive(Serialize)]
+pub struct StringsSummary {
+    pub strings: Vec<FoundString>,
+}
+
+/// Decompresses both pages of an RSC7 file and prints every run of at least
+/// `--min-length` printable characters with its page and offset, like
+/// `strings -t x`.
+pub fn handle_strings_command(args: &StringsArgs) -> CfxResult<StringsSummary> {
+    let buffer = read_input(&args.input)?;
+    let mut archive = FMemoryArchive::new(&buffer);
+    if archive.read_uint()? != RSC7_MAGIC {
+        return Err(format!("{} is not an RSC7 file", args.input.display()).into());
+    }
+
+    let header = ArchiveHeader::from(&mut archive)?;
+    let virtual_size = ResourceChunkFlags::new(header.virtual_page_flags).get_size()? as usize;
+    let physical_size = ResourceChunkFlags::new(header.physical_page_flags).get_size()? as usize;
+    if (virtual_size + physical_size) as u64 > archive.remaining() {
+        return Err(format!("{} is truncated", args.input.display()).into());
+    }
+
+    let virtual_end = HEADER_SIZE + virtual_size;
+    let pages = [
+        ("virtual", &buffer[HEADER_SIZE..virtual_end]),
+        (
+            "physical",
+            &buffer[virtual_end..virtual_end + physical_size],
+        ),
+    ];
+
+    let mut strings = vec![];
+    for (segment, page) in pages {
+        let data = inflate_page(page, &mut NoProgress)?;
+        for (offset, text) in find_strings(&data, args.min_length as usize) {
+            if !is_machine() {
+                println!("{segment:<8} {offset:>#10x} {text}");
+            }
+            strings.push(FoundString {
+                segment,
+                offset,
+                text,
+            });
+        }
+    }
+
+    Ok(StringsSummary { strings })
+}
+
+/// Offsets and contents of the runs of printable ASCII, spaces and tabs
+/// included, that are at least `min_length` bytes long.
+fn find_strings(data: &[u8], min_length: usize) -> Vec<(usize, String)> {
+    let mut result = vec![];
+    let mut start = 0;
+    for (index, &byte) in data.iter().chain([&0]).enumerate() {
+        if byte == b'\t' || (0x20..0x7f).contains(&byte) {
+            continue;
+        }
+
+        if index - start >= min_length {
+            result.push((
+                start,
+                String::from_utf8_lossy(&data[start..index]).to_string(),
+            ));
+        }
+
+        start = index + 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::write_rsc7;
+    use std::fs::write;
+
+    #[test]
+    fn find_strings_test() {
+        let mut data = vec![0u8; 4];
+        data.extend(b"prop_bank_01\0\x01abc\xffshader:emissive");
+        data.push(0);
+        data.extend(b"tail");
+
+        assert_eq!(
+            find_strings(&data, 4),
+            vec![
+                (4, "prop_bank_01".to_owned()),
+                (22, "shader:emissive".to_owned()),
+                (38, "tail".to_owned()),
+            ]
+        );
+        assert_eq!(find_strings(&data, 3)[1], (18, "abc".to_owned()));
+    }
+
+    #[test]
+    fn strings_command_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("sample.ydr");
+        write(
+            &input,
+            write_rsc7(165, b"\0\0name:bank_vault\0", b"\x10\x01texture_diffuse").unwrap(),
+        )
+        .unwrap();
+
+        let args = StringsArgs {
+            input,
+            min_length: 6,
+        };
+        let summary = handle_strings_command(&args).unwrap();
+        assert_eq!(
+            summary.strings,
+            vec![
+                FoundString {
+                    segment: "virtual",
+                    offset: 2,
+                    text: "name:bank_vault".to_owned()
+                },
+                FoundString {
+                    segment: "physical",
+                    offset: 2,
+                    text: "texture_diffuse".to_owned()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs::{canonicalize, remove_dir_all};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::config::{Config, TemplateEntry};
+use crate::output::is_machine;
+use crate::template::{
+    fetch, is_remote, parse_template_name, parse_variable, resolve, template_cache_dir, validate,
+};
+use crate::CfxResult;
+
+#[derive(Subcommand)]
+pub enum TemplateCommand {
+    /// List registered templates
+    List,
+    /// Register a template from a git URL or a local directory
+    Add {
+        #[arg(value_parser = parse_template_name)]
+        name: String,
+        source: String,
+        /// Value to check the template with for a `{{KEY}}` placeholder other
+        /// than project_name and author. Can be repeated
+        #[arg(long = "var", alias = "template-var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+    },
+    /// Re-fetch a remote template into the cache
+    Update {
+        name: String,
+        /// Same as for `add`
+        #[arg(long = "var", alias = "template-var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+    },
+    /// Unregister a template and delete its cached copy
+    Remove { name: String },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub source: String,
+    /// RFC 3339 time of the last fetch or check
+    pub last_updated: String,
+}
+
+/// What a template command did, tagged by `action`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum TemplateSummary {
+    List { templates: Vec<TemplateInfo> },
+    Add { name: String, source: String },
+    Update { name: String, source: String },
+    Remove { name: String },
+}
+
+pub fn handle_template_command(command: &TemplateCommand) -> CfxResult<TemplateSummary> {
+    match command {
+        TemplateCommand::List => list_templates(&Config::load()?),
+        TemplateCommand::Add { name, source, vars } => Config::update(|config| {
+            if config.templates.contains_key(name) {
+                return Err(format!("Template `{name}` is already registered").into());
+            }
+
+            let source = if is_remote(source) {
+                source.clone()
+            } else {
+                canonicalize(source)?.to_string_lossy().to_string()
+            };
+
+            let entry = TemplateEntry {
+                source,
+                last_updated: now(),
+            };
+            check_template(name, &entry, vars)?;
+
+            log::info!("Registered template `{name}` from {}", entry.source);
+            let source = entry.source.clone();
+            config.templates.insert(name.clone(), entry);
+            Ok(TemplateSummary::Add {
+                name: name.clone(),
+                source,
+            })
+        }),
+        TemplateCommand::Update { name, vars } => Config::update(|config| {
+            let entry = config
+                .templates
+                .get_mut(name)
+                .ok_or_else(|| format!("Template `{name}` is not registered"))?;
+
+            if is_remote(&entry.source) {
+                fetch(name, &entry.source)?;
+            } else {
+                log::info!("Template `{name}` is a local directory, nothing to fetch");
+            }
+
+            check_template(name, entry, vars)?;
+            entry.last_updated = now();
+            Ok(TemplateSummary::Update {
+                name: name.clone(),
+                source: entry.source.clone(),
+            })
+        }),
+        TemplateCommand::Remove { name } => Config::update(|config| {
+            if config.templates.remove(name).is_none() {
+                return Err(format!("Template `{name}` is not registered").into());
+            }
+
+            // Names registered before they were checked may point anywhere,
+            // so only a folder inside the template cache is deleted
+            match template_cache_dir(name) {
+                Ok(cached) if cached.exists() => remove_dir_all(cached)?,
+                Ok(_) => {}
+                Err(err) => log::warn!("Left the files of `{name}` alone: {err}"),
+            }
+
+            log::info!("Removed template `{name}`");
+            Ok(TemplateSummary::Remove { name: name.clone() })
+        }),
+    }
+}
+
+pub fn list_templates(config: &Config) -> CfxResult<TemplateSummary> {
+    if config.templates.is_empty() {
+        log::info!("No templates registered");
+    }
+
+    let templates = config
+        .templates
+        .iter()
+        .map(|(name, entry)| {
+            let updated = UNIX_EPOCH + Duration::from_secs(entry.last_updated);
+            TemplateInfo {
+                name: name.clone(),
+                source: entry.source.clone(),
+                last_updated: humantime::format_rfc3339_seconds(updated).to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if !is_machine() {
+        for template in &templates {
+            println!(
+                "{}\t{}\t{}",
+                template.name, template.source, template.last_updated
+            );
+        }
+    }
+
+    Ok(TemplateSummary::List { templates })
+}
+
+/// Renders the template with dummy answers, dropping a freshly fetched
+/// cache entry again when the template turns out to be broken.
+fn check_template(name: &str, entry: &TemplateEntry, vars: &[(String, String)]) -> CfxResult<()> {
+    let dir = resolve(name, entry)?;
+    if let Err(err) = validate(&dir, vars) {
+        if is_remote(&entry.source) {
+            remove_dir_all(&dir)?;
+        }
+
+        return Err(format!("Template `{name}` failed to render: {err}").into());
+    }
+
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -1,21 +1,49 @@
-use deflate::deflate_bytes;
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use std::collections::HashSet;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{self, copy, sink, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::sync_channel;
+use std::thread;
+use std::time::Instant;
 
-use crate::archive::{FArchive, FArchiveExt, FMemoryArchive, FResourceArchive};
+use clap::{Args, ValueEnum};
+use flate2::read::{DeflateDecoder, GzDecoder};
+use serde::Serialize;
+
+use crate::archive::{
+    FArchive, FArchiveExt, FMemoryArchive, FResourceArchive, FStreamArchive, VIRTUAL_BASE,
+};
+use crate::atomic::{atomic_write, atomic_write_with};
+use crate::batch::BatchStats;
+use crate::cancel::{CancelReader, CancelToken, Cancelled};
+use crate::errors::{CodedError, ErrorCode};
+use crate::magic::{detect_format, Format, RSC7_MAGIC, ZSTD_MAGIC};
+use crate::naming::{
+    common_base, output_path, render_name, NameInput, NameLayout, DEFAULT_NAME_TEMPLATE,
+};
+use crate::nav::{NavKind, NavSummary};
+use crate::output::{is_machine, warning_count, warnings_since, ReportItem, RunReport};
+use crate::parse::{ParseContext, ParseOptions, ParseWarning};
+use crate::passthrough::{pretty_xml, sniff_text, TextKind};
+use crate::progress::{ProgressReader, ProgressSink, SharedProgress};
 use crate::CfxResult;
 
-const MAGIC: u32 = 0x37435352;
+/// Magic plus the four header dwords read by [`ArchiveHeader::from`].
+pub const HEADER_SIZE: usize = 20;
+
+/// How far into a file `--scan-magic` looks for the magic.
+const MAGIC_SCAN_LIMIT: usize = 512;
 
-const BUCKETS_CAPACITY: [u32; 9] = [0x1, 0x3, 0xF, 0x3F, 0x7F, 0x1, 0x1, 0x1, 0x1];
-const BUCKETS_SHIFTS: [usize; 9] = [4, 5, 7, 11, 17, 24, 25, 26, 27];
+pub const BUCKETS_CAPACITY: [u32; 9] = [0x1, 0x3, 0xF, 0x3F, 0x7F, 0x1, 0x1, 0x1, 0x1];
+pub const BUCKETS_SHIFTS: [usize; 9] = [4, 5, 7, 11, 17, 24, 25, 26, 27];
 
-struct ResourceChunkFlags {
+#[allow(dead_code)]
+pub struct ResourceChunkFlags {
     value: u32,
     type_val: u32,
     base_shift: u32,
-    base_size: u32,
+    base_size: u64,
 }
 
 impl ResourceChunkFlags {
@@ -25,12 +53,22 @@ impl ResourceChunkFlags {
             value,
             type_val: (value >> 28) & 0xF,
             base_shift,
-            base_size: (0x200u32 << base_shift as i32),
+            base_size: 0x200u64 << base_shift,
         }
     }
 
-    fn get_chunk_sizes(&self) -> Vec<u32> {
-        let result: Vec<u32> = vec![
+    pub fn base_shift(&self) -> u32 {
+        self.base_shift
+    }
+
+    pub fn base_size(&self) -> u64 {
+        self.base_size
+    }
+
+    /// Chunk size of each bucket, largest first. The largest base size
+    /// shifted up by 8 is 2^32, so these are u64.
+    pub fn get_chunk_sizes(&self) -> Vec<u64> {
+        let result: Vec<u64> = vec![
             self.base_size << 8,
             self.base_size << 7,
             self.base_size << 6,
@@ -39,13 +77,13 @@ impl ResourceChunkFlags {
             self.base_size << 3,
             self.base_size << 2,
             self.base_size << 1,
-            self.base_size << 0,
+            self.base_size,
         ];
 
         result
     }
 
-    fn get_buckets_count(&self) -> Vec<u32> {
+    pub fn get_buckets_count(&self) -> Vec<u32> {
         let result: Vec<u32> = vec![
             (self.value >> BUCKETS_SHIFTS[0]) & BUCKETS_CAPACITY[0],
             (self.value >> BUCKETS_SHIFTS[1]) & BUCKETS_CAPACITY[1],
@@ -61,40 +99,90 @@ impl ResourceChunkFlags {
         result
     }
 
-    fn get_buckets_sizes(&self) -> Vec<u32> {
-        let chunk_sizes = self.get_chunk_sizes();
-        let buckets_count = self.get_buckets_count();
-        let result: Vec<u32> = vec![
-            chunk_sizes[0] * buckets_count[0],
-            chunk_sizes[1] * buckets_count[1],
-            chunk_sizes[2] * buckets_count[2],
-            chunk_sizes[3] * buckets_count[3],
-            chunk_sizes[4] * buckets_count[4],
-            chunk_sizes[5] * buckets_count[5],
-            chunk_sizes[6] * buckets_count[6],
-            chunk_sizes[7] * buckets_count[7],
-            chunk_sizes[8] * buckets_count[8],
-        ];
+    /// Bytes each bucket holds, `None` for a bucket too big to count.
+    pub fn get_buckets_sizes(&self) -> Vec<Option<u64>> {
+        self.get_chunk_sizes()
+            .into_iter()
+            .zip(self.get_buckets_count())
+            .map(|(chunk_size, count)| chunk_size.checked_mul(count as u64))
+            .collect()
+    }
 
-        result
+    /// The high nibble, half of the resource type version; see [`ResourceType`].
+    pub fn type_val(&self) -> u32 {
+        self.type_val
     }
 
-    fn get_size(&self) -> u32 {
-        let buckets_sizes = self.get_buckets_sizes();
-        return buckets_sizes[0]
-            + buckets_sizes[1]
-            + buckets_sizes[2]
-            + buckets_sizes[3]
-            + buckets_sizes[4]
-            + buckets_sizes[5]
-            + buckets_sizes[6]
-            + buckets_sizes[7]
-            + buckets_sizes[8];
+    /// Bytes the flags describe, `None` when they cannot be counted.
+    pub fn total_size(&self) -> Option<u64> {
+        self.get_buckets_sizes()
+            .into_iter()
+            .try_fold(0u64, |total, size| total.checked_add(size?))
+    }
+
+    /// Size of the page the flags describe. Flags read from a file can
+    /// describe more than a page holds, which is an error rather than a
+    /// wrapped size.
+    pub fn get_size(&self) -> CfxResult<u32> {
+        self.total_size()
+            .and_then(|size| u32::try_from(size).ok())
+            .ok_or_else(|| {
+                format!(
+                    "Page flags {:#010x} describe a page larger than 4 GiB",
+                    self.value
+                )
+                .into()
+            })
     }
 }
 
-#[derive(Debug)]
-struct ArchiveHeader {
+/// Resource type stored in the high nibbles of the page flags. GTA V splits the
+/// type version across them, virtual nibble first; RDR3 resources are RSC8 and
+/// never reach this path.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceType {
+    /// `ymap`, `ytyp` and `ynv` files all use version 2
+    Metadata,
+    /// `ynd`
+    PathNodes,
+    /// `ytd`
+    TextureDictionary,
+    /// `ybn`
+    Bounds,
+    /// `ycd`
+    ClipDictionary,
+    /// `ypt`
+    ParticleEffects,
+    /// `yft`
+    Fragment,
+    /// `ydr` and `ydd`
+    Drawable,
+    Unknown(u32),
+}
+
+impl ResourceType {
+    pub fn from_flags(
+        virtual_flags: &ResourceChunkFlags,
+        physical_flags: &ResourceChunkFlags,
+    ) -> Self {
+        match (virtual_flags.type_val() << 4) | physical_flags.type_val() {
+            1 => Self::PathNodes,
+            2 => Self::Metadata,
+            13 => Self::TextureDictionary,
+            43 => Self::Bounds,
+            46 => Self::ClipDictionary,
+            68 => Self::ParticleEffects,
+            162 => Self::Fragment,
+            165 => Self::Drawable,
+            version => Self::Unknown(version),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize)]
+pub struct ArchiveHeader {
     pub flags: u32,
     pub virtual_page_flags: u32,
     pub physical_page_flags: u32,
@@ -107,49 +195,1305 @@ impl ArchiveHeader {
         Data: AsRef<[u8]>,
     {
         Ok(ArchiveHeader {
-            flags: archive.read_uint()?,
-            virtual_page_flags: archive.read_uint()?,
-            physical_page_flags: archive.read_uint()?,
-            version: archive.read_int()? & 0xFF,
+            flags: trace_read(archive, "flags", |archive| archive.read_uint())?,
+            virtual_page_flags: trace_read(archive, "virtual_page_flags", |archive| {
+                archive.read_uint()
+            })?,
+            physical_page_flags: trace_read(archive, "physical_page_flags", |archive| {
+                archive.read_uint()
+            })?,
+            version: trace_read(archive, "version", |archive| archive.read_int())? & 0xFF,
+        })
+    }
+}
+
+/// Runs `read` and logs its outcome with the cursor offset before and after
+/// at debug level, so a `--verbose-header` run shows how far parsing got.
+fn trace_read<Data, T>(
+    archive: &mut FMemoryArchive<Data>,
+    field: &str,
+    read: impl FnOnce(&mut FMemoryArchive<Data>) -> CfxResult<T>,
+) -> CfxResult<T>
+where
+    Data: AsRef<[u8]>,
+    T: std::fmt::Debug,
+{
+    let start = archive.position();
+    match read(archive) {
+        Ok(value) => {
+            log::debug!(
+                "{field}: {value:?} (offset {start:#x} -> {:#x})",
+                archive.position()
+            );
+            Ok(value)
+        }
+        Err(err) => {
+            log::debug!("{field}: failed at offset {start:#x}: {err}");
+            Err(err)
+        }
+    }
+}
+
+/// Advances past a `len` byte page and returns its range in the archive data,
+/// so pages can be borrowed from the input instead of copied out of it.
+fn page_range<Data>(archive: &mut FMemoryArchive<Data>, len: usize) -> CfxResult<Range<usize>>
+where
+    Data: AsRef<[u8]>,
+{
+    let start = archive.position() as usize;
+    let end = start + len;
+    if len as u64 > archive.remaining() {
+        return Err(format!(
+            "page of {len} bytes at {start:#x} runs past the end of the file ({:#x})",
+            archive.len()
+        )
+        .into());
+    }
+
+    archive.set_position(end as u64)?;
+    Ok(start..end)
+}
+
+/// Inflates a page in memory. The zero padding after the deflate stream is
+/// not part of it and ignored.
+pub(crate) fn inflate_page(page: &[u8], progress: &mut dyn ProgressSink) -> CfxResult<Vec<u8>> {
+    let mut result = vec![];
+    page_decoder(page, ProgressReader::new(page, progress))?
+        .read_to_end(&mut result)
+        .map_err(|err| format!("Invalid page data: {err}"))?;
+
+    Ok(result)
+}
+
+/// Inflates `pages` in memory, each on its own thread unless `jobs` is 1.
+fn inflate_pages(
+    pages: &[(&str, &[u8])],
+    jobs: u32,
+    progress: &mut dyn ProgressSink,
+) -> CfxResult<Vec<Vec<u8>>> {
+    map_pages(pages, jobs, progress, |_, page, progress| {
+        inflate_page(page, progress)
+    })
+}
+
+/// Inflates `pages` without keeping them and returns their sizes, logging
+/// the header at the start of the virtual page as it streams past.
+fn scan_pages(
+    pages: &[(&str, &[u8])],
+    jobs: u32,
+    progress: &mut dyn ProgressSink,
+) -> CfxResult<Vec<u64>> {
+    map_pages(pages, jobs, progress, |kind, page, progress| {
+        let decoder = page_decoder(page, ProgressReader::new(page, progress))?;
+        let mut archive = FStreamArchive::new(decoder, VIRTUAL_BASE);
+        let scan = || -> CfxResult<u64> {
+            if kind == "virtual" {
+                let mut pointers = [0u8; 16];
+                archive.read_bytes_exact(&mut pointers)?;
+                let (vft, pages_info_pointer) = pointers.split_at(8);
+                log::info!("VFT: {}", u64::from_le_bytes(vft.try_into()?));
+                log::info!(
+                    "Pages info pointer: {}",
+                    u64::from_le_bytes(pages_info_pointer.try_into()?)
+                );
+            }
+
+            Ok(archive.finish()? - VIRTUAL_BASE)
+        };
+
+        scan().map_err(|err| format!("Invalid page data: {err}").into())
+    })
+}
+
+/// Runs `read` over `pages`, each on its own thread unless `jobs` is 1.
+fn map_pages<T: Send>(
+    pages: &[(&str, &[u8])],
+    jobs: u32,
+    progress: &mut dyn ProgressSink,
+    read: impl Fn(&str, &[u8], &mut dyn ProgressSink) -> CfxResult<T> + Sync,
+) -> CfxResult<Vec<T>> {
+    if jobs == 1 || pages.len() < 2 {
+        return pages
+            .iter()
+            .map(|&(kind, page)| read(kind, page, progress))
+            .collect();
+    }
+
+    let read = &read;
+    let shared = SharedProgress::new(progress);
+    thread::scope(|scope| {
+        let threads = pages
+            .iter()
+            .map(|&(kind, page)| {
+                let mut progress = shared.handle();
+                scope.spawn(move || read(kind, page, &mut progress).map_err(|err| err.to_string()))
+            })
+            .collect::<Vec<_>>();
+
+        threads
+            .into_iter()
+            .map(|thread| match thread.join() {
+                Ok(result) => result.map_err(|err| err.into()),
+                Err(_) => Err("A decompression thread panicked".into()),
+            })
+            .collect()
+    })
+}
+
+/// Decompresses `reader`, which reads `page`. Pages are raw deflate. A page
+/// starting with the zstd magic fails with [`ErrorCode::UnsupportedCompression`]
+/// until a zstd decoder is among the dependencies.
+fn page_decoder<R: Read>(page: &[u8], reader: R) -> CfxResult<DeflateDecoder<R>> {
+    if page.starts_with(&ZSTD_MAGIC) {
+        return Err(CodedError::new(
+            ErrorCode::UnsupportedCompression,
+            "The page is zstd-compressed, which is not supported yet",
+        )
+        .into());
+    }
+
+    Ok(DeflateDecoder::new(reader))
+}
+
+/// Size of a page once inflated, decompressing it without keeping the data.
+fn inflated_size(page: &[u8], progress: &mut dyn ProgressSink) -> CfxResult<u64> {
+    copy(
+        &mut page_decoder(page, ProgressReader::new(page, progress))?,
+        &mut sink(),
+    )
+    .map_err(|err| format!("Invalid page data: {err}").into())
+}
+
+/// Inflates a page straight into `path`, so the decompressed data never has
+/// to be held in memory. Unless `jobs` is 1, inflating and writing happen on
+/// separate threads. Returns the number of bytes written, or `None` when
+/// cancelled, in which case nothing is left at `path`.
+fn stream_page(
+    page: &[u8],
+    path: &Path,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+    jobs: u32,
+) -> CfxResult<Option<u64>> {
+    let mut decoder = page_decoder(
+        page,
+        ProgressReader::new(CancelReader::new(page, cancel), progress),
+    )?;
+    let result = atomic_write_with(path, |file| {
+        let mut writer = BufWriter::new(file);
+        let written = match jobs {
+            1 => copy(&mut decoder, &mut writer)?,
+            _ => pipelined_copy(&mut decoder, &mut writer)?,
+        };
+        writer.flush()?;
+        Ok(written)
+    });
+
+    match result {
+        Ok(written) => Ok(Some(written)),
+        Err(_) if cancel.is_cancelled() => Ok(None),
+        Err(err) => Err(format!("Invalid page data: {err}").into()),
+    }
+}
+
+/// [`copy`] reading on another thread, which hands over up to
+/// [`PIPELINE_DEPTH`] chunks so reading and writing overlap. A failed write
+/// stops the reading thread.
+fn pipelined_copy(reader: &mut (impl Read + Send), writer: &mut impl Write) -> io::Result<u64> {
+    let (sender, receiver) = sync_channel::<Vec<u8>>(PIPELINE_DEPTH);
+    thread::scope(|scope| {
+        let reading = scope.spawn(move || loop {
+            let mut chunk = vec![0u8; PIPELINE_CHUNK_SIZE];
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            chunk.truncate(read);
+            if sender.send(chunk).is_err() {
+                // The writing side failed and reports why
+                return Ok(());
+            }
+        });
+
+        let mut written = 0;
+        let mut wrote = Ok(());
+        for chunk in receiver {
+            if let Err(err) = writer.write_all(&chunk) {
+                wrote = Err(err);
+                break;
+            }
+
+            written += chunk.len() as u64;
+        }
+
+        let read = reading
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("the decompression thread panicked")));
+        wrote.and(read).map(|_| written)
+    })
+}
+
+/// Outcome of checking the CRC32 some RSC7 variants append after the pages.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingChecksum {
+    Absent,
+    Valid,
+    Mismatch { stored: u32, computed: u32 },
+}
+
+impl TrailingChecksum {
+    /// Reads the checksum when exactly four bytes follow `pages_end`; any other
+    /// amount of trailing data is not a checksum.
+    pub fn read(data: &[u8], pages_end: usize) -> Self {
+        if data.len() != pages_end + 4 {
+            return TrailingChecksum::Absent;
+        }
+
+        let mut stored = [0u8; 4];
+        stored.copy_from_slice(&data[pages_end..]);
+        let stored = u32::from_le_bytes(stored);
+        let computed = crc32fast::hash(&data[..pages_end]);
+
+        if stored == computed {
+            TrailingChecksum::Valid
+        } else {
+            TrailingChecksum::Mismatch { stored, computed }
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct UnpackSummary {
+    pub file: String,
+    pub size: usize,
+    pub header: ArchiveHeader,
+    pub virtual_size: u32,
+    pub physical_size: u32,
+    pub resource_type: ResourceType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decompressed_virtual_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decompressed_physical_size: Option<usize>,
+    pub checksum: TrailingChecksum,
+    /// Where `--scan-magic` found the magic, when it was not at the start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magic_offset: Option<usize>,
+    /// Deviations from the format the parse carried on past
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parse_warnings: Vec<ParseWarning>,
+    /// Files the still-compressed pages were written to with `--raw`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub raw_pages: Vec<PathBuf>,
+    /// Files the decompressed pages were streamed to with `--output`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extracted_pages: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub comparisons: Vec<PageComparison>,
+    /// Files `--dry-run` would have written, in place of `raw_pages` or
+    /// `extracted_pages`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub planned_files: Vec<PlannedFile>,
+    /// Bytes `--resume` found already written and kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumed_bytes: Option<u64>,
+    /// Counts read from a `.ynd` or `.ynv` when its pages were inflated in
+    /// memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav: Option<NavSummary>,
+}
+
+/// A file `--dry-run` reports instead of writing.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Args)]
+pub struct UnpackArgs {
+    /// Resource files to unpack
+    #[arg(required = true)]
+    pub names: Vec<String>,
+
+    /// Print a summary line with counts, bytes and elapsed time, which is
+    /// always done for several files
+    #[arg(long)]
+    pub count: bool,
+
+    /// Log every header field and page read with its offsets
+    #[arg(long)]
+    pub verbose_header: bool,
+
+    /// Write the still-compressed pages to `<name>.virtual.raw` and
+    /// `<name>.physical.raw` instead of decompressing them
+    #[arg(long)]
+    pub raw: bool,
+
+    /// Byte-compare the pages against `<name>.virtual` and `<name>.physical`
+    /// (or the `.raw` names with --raw) in this directory, failing on mismatch
+    #[arg(long, conflicts_with = "output")]
+    pub compare: Option<PathBuf>,
+
+    /// Stream the decompressed pages to `<name>.virtual` and `<name>.physical`
+    /// in this directory instead of inspecting them in memory
+    #[arg(long, short, conflicts_with = "raw")]
+    pub output: Option<PathBuf>,
+
+    /// Put every page directly in the output directory, suffixing names that
+    /// collide with `_2`, `_3`, ...
+    #[arg(long, requires = "output", conflicts_with = "preserve_paths")]
+    pub flat: bool,
+
+    /// Mirror the input paths below the directory all inputs share in the
+    /// output directory, the default
+    #[arg(long, requires = "output")]
+    pub preserve_paths: bool,
+
+    /// Name pages after a template such as `{stem}_{index}.{ext}` instead of
+    /// `{name}.{kind}`. Variables: {name} input file name, {stem} file name
+    /// without extension, {ext} its extension, {kind} `virtual` or
+    /// `physical`, {index} position of the input on the command line
+    #[arg(long, requires = "output")]
+    pub name_template: Option<String>,
+
+    /// Fail on data that is out of spec, such as trailing bytes after the
+    /// pages or a checksum mismatch, instead of warning and carrying on
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Print the header layout as a Rust or C struct with the byte offset and
+    /// size of every field, for writing your own parser
+    #[arg(long, value_enum, value_name = "LANGUAGE")]
+    pub emit_struct: Option<StructLanguage>,
+
+    /// Which page to decompress and write; the other one is skipped
+    #[arg(long, value_enum, default_value_t = Segment::Both)]
+    pub segment: Segment,
+
+    /// When the magic is not at the start, look for it in the first
+    /// 512 bytes and skip what precedes it, such as a BOM or junk from a
+    /// broken download
+    #[arg(long)]
+    pub scan_magic: bool,
+
+    /// Write the outcome, duration, warnings and errors of every file to a
+    /// .json file or a Markdown table in a .md file
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// Parse and decompress in memory, then report the files and sizes that
+    /// --raw or --output would write without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Re-indent the plain XML files, such as loose .meta files, that a batch
+    /// copies to the output directory instead of unpacking. Malformed XML is
+    /// copied as it is
+    #[arg(long, requires = "output")]
+    pub pretty_xml: bool,
+
+    /// Stream pages through `<page>.part` files, flushed every MiB, and keep
+    /// the bytes an interrupted run left in them, or in finished pages, that
+    /// still match the decompressed data instead of writing them again
+    #[arg(long, requires = "output")]
+    pub resume: bool,
+
+    /// Threads to decompress with. Two inflate the virtual and physical
+    /// pages side by side and write extracted pages while the rest inflates,
+    /// the most a single file can use. 1 does it all on one thread, for
+    /// debugging
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+}
+
+#[cfg(test)]
+/// Arguments of a plain unpack of `names`, with the defaults clap fills in.
+pub fn args(names: Vec<String>) -> UnpackArgs {
+    UnpackArgs {
+        names,
+        count: false,
+        verbose_header: false,
+        raw: false,
+        compare: None,
+        output: None,
+        flat: false,
+        preserve_paths: false,
+        name_template: None,
+        strict: false,
+        emit_struct: None,
+        segment: Segment::Both,
+        scan_magic: false,
+        report: None,
+        dry_run: false,
+        pretty_xml: false,
+        resume: false,
+        jobs: 2,
+    }
+}
+
+/// The pages of a resource `unpack` processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Segment {
+    /// The virtual (system) page, holding the resource's structure
+    Virtual,
+    /// The physical (graphics) page, holding vertex and texture data
+    Physical,
+    Both,
+}
+
+impl Segment {
+    fn includes(self, kind: &str) -> bool {
+        match self {
+            Segment::Virtual => kind == "virtual",
+            Segment::Physical => kind == "physical",
+            Segment::Both => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StructLanguage {
+    Rust,
+    C,
+}
+
+/// Fields of the RSC7 header in file order, as read by [`ArchiveHeader::from`]
+/// after the magic: name, C type, Rust type and a note. Every field is a
+/// little-endian dword.
+const HEADER_FIELDS: [(&str, &str, &str, &str); 5] = [
+    ("magic", "uint32_t", "u32", "\"RSC7\""),
+    ("flags", "uint32_t", "u32", ""),
+    (
+        "virtual_page_flags",
+        "uint32_t",
+        "u32",
+        "see `cfx explain-flags`",
+    ),
+    (
+        "physical_page_flags",
+        "uint32_t",
+        "u32",
+        "see `cfx explain-flags`",
+    ),
+    (
+        "version",
+        "int32_t",
+        "i32",
+        "the low byte is the resource version",
+    ),
+];
+
+/// Renders [`HEADER_FIELDS`] as a struct definition, commenting each field
+/// with its byte offset and size.
+pub fn header_struct(language: StructLanguage) -> String {
+    let mut lines = vec![];
+    match language {
+        StructLanguage::Rust => {
+            lines.push(format!(
+                "/// RSC7 resource header, {HEADER_SIZE} bytes, little-endian"
+            ));
+            lines.push("#[repr(C)]".to_owned());
+            lines.push("pub struct ArchiveHeader {".to_owned());
+        }
+        StructLanguage::C => {
+            lines.push("#include <stdint.h>".to_owned());
+            lines.push(String::new());
+            lines.push(format!(
+                "/* RSC7 resource header, {HEADER_SIZE} bytes, little-endian */"
+            ));
+            lines.push("typedef struct ArchiveHeader {".to_owned());
+        }
+    }
+
+    for (i, (name, c_type, rust_type, note)) in HEADER_FIELDS.iter().enumerate() {
+        let mut comment = format!("{:#04x}, 4 bytes", i * 4);
+        if !note.is_empty() {
+            comment.push_str(&format!(", {note}"));
+        }
+
+        lines.push(match language {
+            StructLanguage::Rust => format!("    pub {name}: {rust_type}, // {comment}"),
+            StructLanguage::C => format!("    {c_type} {name}; /* {comment} */"),
+        });
+    }
+
+    lines.push(match language {
+        StructLanguage::Rust => "}".to_owned(),
+        StructLanguage::C => "} ArchiveHeader;".to_owned(),
+    });
+
+    lines.join("\n") + "\n"
+}
+
+/// Hands out the paths pages are extracted to, keeping the names unique
+/// across all files of a run.
+pub struct OutputNames {
+    base: PathBuf,
+    layout: NameLayout,
+    template: String,
+    used: HashSet<PathBuf>,
+}
+
+impl OutputNames {
+    /// Fails on a name template with unknown variables, before anything is
+    /// extracted.
+    pub fn new(args: &UnpackArgs) -> CfxResult<Self> {
+        let template = args
+            .name_template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_NAME_TEMPLATE.to_owned());
+        let sample = NameInput {
+            path: Path::new("sample.ytd"),
+            kind: "virtual",
+            index: 0,
+        };
+        render_name(&template, &sample)?;
+
+        let paths = args.names.iter().map(Path::new).collect::<Vec<&Path>>();
+        Ok(Self {
+            base: common_base(&paths),
+            layout: if args.flat {
+                NameLayout::Flat
+            } else {
+                NameLayout::PreservePaths
+            },
+            template,
+            used: HashSet::new(),
         })
     }
+
+    /// Path of an input copied as it is, named like the input.
+    fn passthrough(&mut self, output_dir: &Path, input: &NameInput) -> CfxResult<PathBuf> {
+        let path = output_path(
+            output_dir,
+            &self.base,
+            self.layout,
+            "{name}",
+            input,
+            &self.used,
+        )?;
+        self.used.insert(path.clone());
+        Ok(path)
+    }
+
+    fn next(&mut self, output_dir: &Path, input: &NameInput) -> CfxResult<PathBuf> {
+        let path = output_path(
+            output_dir,
+            &self.base,
+            self.layout,
+            &self.template,
+            input,
+            &self.used,
+        )?;
+        self.used.insert(path.clone());
+        Ok(path)
+    }
+}
+
+/// How a page produced by `unpack` compares to a reference extractor's output.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PageComparison {
+    pub reference: PathBuf,
+    pub expected_len: usize,
+    pub actual_len: usize,
+    /// Offset of the first differing byte, `None` when the pages are identical
+    pub first_mismatch: Option<usize>,
+}
+
+/// How much of a page `--resume` decompresses, checks and writes at a time.
+const RESUME_CHUNK_SIZE: u64 = 1 << 20;
+
+/// Inflated chunks a pipelined extraction hands from the decompressing to
+/// the writing thread, and how many can wait to be written.
+const PIPELINE_CHUNK_SIZE: usize = 1 << 16;
+const PIPELINE_DEPTH: usize = 4;
+
+/// Where `--resume` streams the page bound for `path`.
+fn partial_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{name}.part"))
+}
+
+/// Streams a page to `path` through its [`partial_path`], chunk by chunk, so
+/// an interrupted run leaves what it wrote behind. What an earlier run left
+/// there, or at `path` itself, is compared against the decompressed data and
+/// kept up to the first differing byte; only the rest is written. Returns
+/// the size of the page and the bytes kept, or `None` when cancelled, in
+/// which case the partial file is kept for the next run.
+fn resume_page(
+    page: &[u8],
+    path: &Path,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> CfxResult<Option<(u64, u64)>> {
+    let partial = partial_path(path);
+    if !partial.exists() && path.is_file() {
+        std::fs::rename(path, &partial)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&partial)?;
+    let existing = file.metadata()?.len();
+    let mut decoder = page_decoder(
+        page,
+        ProgressReader::new(CancelReader::new(page, cancel), progress),
+    )?;
+
+    let mut written = 0;
+    let mut kept = None;
+    let mut chunk = vec![];
+    let mut old = vec![];
+    let result = loop {
+        chunk.clear();
+        if let Err(err) = decoder
+            .by_ref()
+            .take(RESUME_CHUNK_SIZE)
+            .read_to_end(&mut chunk)
+        {
+            break Err(err);
+        }
+
+        if chunk.is_empty() {
+            break Ok(());
+        }
+
+        // Still comparing against what an earlier run wrote
+        let mut start = 0;
+        if kept.is_none() {
+            old.resize(chunk.len().min((existing - written) as usize), 0);
+            let same = file
+                .read_exact(&mut old)
+                .map(|_| first_mismatch(&chunk, &old));
+            match same {
+                Ok(None) => {
+                    written += chunk.len() as u64;
+                    continue;
+                }
+                Ok(Some(offset)) => {
+                    start = offset;
+                    kept = Some(written + offset as u64);
+                }
+                Err(err) => break Err(err),
+            }
+        }
+
+        let rest = &chunk[start..];
+        let write = file
+            .seek(SeekFrom::Start(written + start as u64))
+            .and_then(|_| file.write_all(rest));
+        if let Err(err) = write {
+            break Err(err);
+        }
+
+        written += chunk.len() as u64;
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(_) if cancel.is_cancelled() => return Ok(None),
+        Err(err) => return Err(format!("Invalid page data: {err}").into()),
+    }
+
+    // A longer leftover than the page is cut down to it
+    file.set_len(written)?;
+    drop(file);
+    std::fs::rename(&partial, path)?;
+
+    let kept = kept.unwrap_or(written);
+    if kept > 0 {
+        log::info!(
+            "Kept {kept} of {written} bytes already written to {}",
+            path.display()
+        );
+    }
+
+    Ok(Some((written, kept)))
+}
+
+/// Offset of the first byte where `actual` and `expected` differ; a shorter
+/// buffer differs where it ends.
+fn first_mismatch(actual: &[u8], expected: &[u8]) -> Option<usize> {
+    actual
+        .iter()
+        .zip(expected)
+        .position(|(a, b)| a != b)
+        .or_else(|| (actual.len() != expected.len()).then(|| actual.len().min(expected.len())))
+}
+
+fn compare_pages(
+    reference_dir: &Path,
+    file: &Path,
+    pages: &[(&str, &[u8])],
+    suffix: &str,
+) -> CfxResult<Vec<PageComparison>> {
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+    let mut result = vec![];
+
+    for &(kind, actual) in pages {
+        let reference = reference_dir.join(format!("{file_name}.{kind}{suffix}"));
+        let expected = std::fs::read(&reference)
+            .map_err(|err| format!("Could not read reference {}: {err}", reference.display()))?;
+
+        let comparison = PageComparison {
+            expected_len: expected.len(),
+            actual_len: actual.len(),
+            first_mismatch: first_mismatch(actual, &expected),
+            reference,
+        };
+
+        match comparison.first_mismatch {
+            None => log::info!("{} matches", comparison.reference.display()),
+            Some(offset) => log::error!(
+                "{} differs at offset {offset:#x} (expected {} bytes, got {})",
+                comparison.reference.display(),
+                comparison.expected_len,
+                comparison.actual_len
+            ),
+        }
+
+        result.push(comparison);
+    }
+
+    if result
+        .iter()
+        .any(|comparison| comparison.first_mismatch.is_some())
+    {
+        return Err("Pages differ from the reference".into());
+    }
+
+    Ok(result)
+}
+
+/// Outcome of one file of a batch, with its error in place of a summary
+/// when it failed.
+#[derive(Serialize)]
+pub struct BatchEntry {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<UnpackSummary>,
+    /// Set in place of the summary for plain text files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passthrough: Option<Passthrough>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A plain text file of a batch, such as a loose .meta file, which is copied
+/// to the output directory instead of unpacked so that it mirrors the input.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Passthrough {
+    pub kind: TextKind,
+    pub size: u64,
+    /// Where it was copied with --output, or would be with --dry-run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copied_to: Option<PathBuf>,
+    /// Whether --pretty-xml re-indented it
+    pub pretty: bool,
+}
+
+/// What a batch did with one file.
+enum BatchOutcome {
+    Unpacked(Box<UnpackSummary>),
+    Passthrough(Passthrough),
+}
+
+#[derive(Serialize)]
+pub struct BatchUnpackSummary {
+    pub files: Vec<BatchEntry>,
+    pub stats: BatchStats,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum UnpackReport {
+    File(Box<UnpackSummary>),
+    Batch(BatchUnpackSummary),
+}
+
+pub fn handle_unpack_command(
+    args: &UnpackArgs,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> CfxResult<UnpackReport> {
+    if args.verbose_header {
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+
+    // Stdout is the envelope's in machine mode, so the struct goes to stderr
+    if let Some(language) = args.emit_struct {
+        match is_machine() {
+            true => eprint!("{}", header_struct(language)),
+            false => print!("{}", header_struct(language)),
+        }
+    }
+
+    if let [name] = args.names.as_slice() {
+        if !args.count {
+            let (started, warned) = (Instant::now(), warning_count());
+            let result = unpack_file(args, name, progress, cancel);
+            if let Some(path) = &args.report {
+                let errors = result.as_ref().err().map(|err| vec![err.to_string()]);
+                let item = ReportItem::new(
+                    name,
+                    started.elapsed(),
+                    warnings_since(warned),
+                    errors.unwrap_or_default(),
+                );
+                RunReport::new("unpack", vec![item]).write(path)?;
+            }
+
+            return Ok(UnpackReport::File(Box::new(result?)));
+        }
+    }
+
+    Ok(UnpackReport::Batch(unpack_batch(args, progress, cancel)?))
+}
+
+/// Unpacks every file, carrying on past failures, and logs the totals once
+/// all files were processed or the run was cancelled.
+fn unpack_batch(
+    args: &UnpackArgs,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> CfxResult<BatchUnpackSummary> {
+    let mut names = OutputNames::new(args)?;
+    let mut stats = BatchStats::start();
+    let mut files = vec![];
+    let mut items = vec![];
+    let write_report = |items| match &args.report {
+        Some(path) => RunReport::new("unpack", items).write(path),
+        None => Ok(()),
+    };
+
+    for (index, name) in args.names.iter().enumerate() {
+        let (started, warned) = (Instant::now(), warning_count());
+        let outcome = match pass_through(args, name, index, &mut names) {
+            Ok(Some(passthrough)) => Ok(BatchOutcome::Passthrough(passthrough)),
+            Ok(None) => unpack_entry(args, name, index, &mut names, progress, cancel)
+                .map(|summary| BatchOutcome::Unpacked(Box::new(summary))),
+            Err(err) => Err(err),
+        };
+        let result = match outcome {
+            Err(err) if err.is::<Cancelled>() => None,
+            _ if cancel.is_cancelled() => None,
+            result => Some(result),
+        };
+
+        let Some(result) = result else {
+            stats.finish();
+            log::info!("{stats}");
+            write_report(items)?;
+            return Err(Cancelled {
+                done: stats.processed,
+                total: args.names.len(),
+            }
+            .into());
+        };
+
+        let errors = result.as_ref().err().map(|err| vec![err.to_string()]);
+        items.push(ReportItem::new(
+            name,
+            started.elapsed(),
+            warnings_since(warned),
+            errors.unwrap_or_default(),
+        ));
+
+        match result {
+            Ok(BatchOutcome::Unpacked(summary)) => {
+                let bytes = summary.decompressed_virtual_size.unwrap_or_default()
+                    + summary.decompressed_physical_size.unwrap_or_default();
+                stats.record_success(bytes as u64);
+                files.push(BatchEntry {
+                    file: name.clone(),
+                    summary: Some(*summary),
+                    passthrough: None,
+                    error: None,
+                });
+            }
+            Ok(BatchOutcome::Passthrough(passthrough)) => {
+                stats.record_passthrough();
+                files.push(BatchEntry {
+                    file: name.clone(),
+                    summary: None,
+                    passthrough: Some(passthrough),
+                    error: None,
+                });
+            }
+            Err(err) => {
+                log::warn!("{name}: {err}");
+                stats.record_failure();
+                files.push(BatchEntry {
+                    file: name.clone(),
+                    summary: None,
+                    passthrough: None,
+                    error: Some(err.to_string()),
+                });
+            }
+        }
+    }
+
+    stats.finish();
+    log::info!("{stats}");
+    write_report(items)?;
+
+    if args.dry_run {
+        let planned = files
+            .iter()
+            .filter_map(|entry| entry.summary.as_ref())
+            .flat_map(|summary| &summary.planned_files)
+            .map(|file| file.size)
+            .chain(
+                files
+                    .iter()
+                    .filter_map(|entry| entry.passthrough.as_ref())
+                    .filter(|passthrough| passthrough.copied_to.is_some())
+                    .map(|passthrough| passthrough.size),
+            )
+            .collect::<Vec<_>>();
+        log::info!(
+            "Would write {} bytes in {} file(s)",
+            planned.iter().sum::<u64>(),
+            planned.len()
+        );
+    }
+
+    Ok(BatchUnpackSummary { files, stats })
+}
+
+/// Copies a batch input that sniffs as plain text to the output tree, the
+/// way it would be extracted, re-indenting XML with --pretty-xml. Malformed
+/// XML is copied as it is with a warning. `None` for any other input.
+fn pass_through(
+    args: &UnpackArgs,
+    filename: &str,
+    index: usize,
+    names: &mut OutputNames,
+) -> CfxResult<Option<Passthrough>> {
+    let path = Path::new(filename);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    // Resources and other known formats are never text, whatever follows
+    let mut magic = vec![];
+    File::open(path)?.take(4).read_to_end(&mut magic)?;
+    if detect_format(&magic).is_some() {
+        return Ok(None);
+    }
+
+    let bytes = std::fs::read(path)?;
+    let Some(kind) = sniff_text(&bytes) else {
+        return Ok(None);
+    };
+
+    let mut pretty = None;
+    if kind == TextKind::Xml {
+        match pretty_xml(&bytes) {
+            Ok(xml) if args.pretty_xml => pretty = Some(xml.into_bytes()),
+            Ok(_) => {}
+            Err(err) => log::warn!("{filename} is malformed XML, copying it as it is: {err}"),
+        }
+    }
+
+    let contents = pretty.as_deref().unwrap_or(&bytes);
+    let copied_to = match &args.output {
+        Some(output_dir) => {
+            let input = NameInput {
+                path,
+                kind: "",
+                index,
+            };
+            let target = names.passthrough(output_dir, &input)?;
+            if args.dry_run {
+                log::info!("Would copy {filename} to {}", target.display());
+            } else {
+                if let Some(parent) = target.parent() {
+                    create_dir_all(parent)?;
+                }
+
+                atomic_write(&target, contents)?;
+                log::info!("Copied {filename} to {}", target.display());
+            }
+
+            Some(target)
+        }
+        None => {
+            log::info!("{filename} is plain text, passing it through");
+            None
+        }
+    };
+
+    Ok(Some(Passthrough {
+        kind,
+        size: contents.len() as u64,
+        copied_to,
+        pretty: pretty.is_some(),
+    }))
+}
+
+/// Unpacks a resource, reporting the compressed bytes consumed while
+/// inflating the pages to `progress`.
+pub fn unpack_file(
+    args: &UnpackArgs,
+    filename: &str,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> CfxResult<UnpackSummary> {
+    let index = args
+        .names
+        .iter()
+        .position(|name| name == filename)
+        .unwrap_or_default();
+    let mut names = OutputNames::new(args)?;
+    unpack_entry(args, filename, index, &mut names, progress, cancel)
 }
 
-pub fn handle_unpack_command(filename: &str) -> CfxResult<()> {
+/// Unpacks the input at `index` of `args.names`, taking output paths from
+/// `names`.
+fn unpack_entry(
+    args: &UnpackArgs,
+    filename: &str,
+    index: usize,
+    names: &mut OutputNames,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> CfxResult<UnpackSummary> {
     let filepath = Path::new(filename);
     if !filepath.exists() || !filepath.is_file() {
         return Err("File does not exist".into());
     }
 
-    let mut file = File::open(filename)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
+    let mut buffer = read_input(filepath)?;
     log::info!("Loaded file ({} bytes)", buffer.len());
+    let size = buffer.len();
 
-    let mut archive = FMemoryArchive::new(buffer);
-    let magic = archive.read_uint()?;
-    if magic != MAGIC {
-        return Err(format!("Invalid magic: {:#04x} (expected: {:#04x})", magic, MAGIC).into());
+    let mut magic_offset = None;
+    if args.scan_magic {
+        if let Some(offset) = scan_magic(&buffer).filter(|&offset| offset > 0) {
+            log::warn!("Found the RSC7 magic at offset {offset:#x}, skipping the bytes before it");
+            buffer.drain(..offset);
+            magic_offset = Some(offset);
+        }
+    }
+
+    let mut archive = FMemoryArchive::new(&buffer);
+    let magic = trace_read(&mut archive, "magic", |archive| archive.read_uint())?;
+    if magic != RSC7_MAGIC {
+        return Err(match detect_format(&buffer) {
+            Some(format) => CodedError::new(
+                ErrorCode::UnsupportedFormat,
+                format!("{format} files are not supported, expected RSC7"),
+            ),
+            None => CodedError::new(
+                ErrorCode::InvalidMagic,
+                format!("Invalid magic: {magic:#04x} (expected: {RSC7_MAGIC:#04x})"),
+            ),
+        }
+        .into());
     }
 
     let header = ArchiveHeader::from(&mut archive)?;
     log::info!("Header: {:?}", header);
 
     let virtual_flags = ResourceChunkFlags::new(header.virtual_page_flags);
-    let physical_flags = ResourceChunkFlags::new(header.virtual_page_flags);
-    log::info!("Virtual size: {:?}", virtual_flags.get_size());
-    log::info!("Physical size: {:?}", physical_flags.get_size());
+    let physical_flags = ResourceChunkFlags::new(header.physical_page_flags);
+    let virtual_size = virtual_flags.get_size()?;
+    let physical_size = physical_flags.get_size()?;
+    log::info!("Virtual size: {virtual_size:?}");
+    log::info!("Physical size: {physical_size:?}");
+
+    let resource_type = ResourceType::from_flags(&virtual_flags, &physical_flags);
+    log::info!("Resource type: {:?}", resource_type);
+
+    let options = ParseOptions {
+        strict: args.strict,
+        ..Default::default()
+    };
+    let mut context = ParseContext::new(options);
+    let pages_end = HEADER_SIZE + virtual_size as usize + physical_size as usize;
+    let checksum = TrailingChecksum::read(&buffer, pages_end);
+    match &checksum {
+        TrailingChecksum::Absent if buffer.len() > pages_end => context.deviation(
+            pages_end as u64,
+            format!(
+                "{} bytes of trailing data after the pages",
+                buffer.len() - pages_end
+            ),
+        )?,
+        TrailingChecksum::Absent => {}
+        TrailingChecksum::Valid => log::info!("Trailing checksum is valid"),
+        TrailingChecksum::Mismatch { stored, computed } => context.deviation(
+            pages_end as u64,
+            format!("Trailing checksum mismatch: stored {stored:#010x}, computed {computed:#010x}"),
+        )?,
+    }
+
+    let virtual_range = trace_read(&mut archive, "virtual page", |archive| {
+        page_range(archive, virtual_size as usize)
+    })?;
+    let physical_range = trace_read(&mut archive, "physical page", |archive| {
+        page_range(archive, physical_size as usize)
+    })?;
+    let pages = [
+        ("virtual", &buffer[virtual_range]),
+        ("physical", &buffer[physical_range]),
+    ]
+    .into_iter()
+    .filter(|(kind, _)| args.segment.includes(kind))
+    .collect::<Vec<(&str, &[u8])>>();
+
+    let mut summary = UnpackSummary {
+        file: filename.to_owned(),
+        size,
+        virtual_size,
+        physical_size,
+        resource_type,
+        decompressed_virtual_size: None,
+        decompressed_physical_size: None,
+        checksum,
+        magic_offset,
+        parse_warnings: context.warnings,
+        header,
+        raw_pages: vec![],
+        extracted_pages: vec![],
+        comparisons: vec![],
+        planned_files: vec![],
+        resumed_bytes: None,
+        nav: None,
+    };
+
+    if args.raw {
+        if args.dry_run {
+            for &(kind, page) in &pages {
+                let path = raw_page_path(filepath, kind);
+                summary.planned_files.push(planned(path, page.len() as u64));
+            }
+        } else {
+            summary.raw_pages = write_raw_pages(filepath, &pages)?;
+        }
+
+        if let Some(reference_dir) = &args.compare {
+            summary.comparisons = compare_pages(reference_dir, filepath, &pages, ".raw")?;
+        }
+
+        return Ok(summary);
+    }
+
+    progress.start(pages.iter().map(|(_, page)| page.len() as u64).sum());
+    if let Some(output_dir) = &args.output {
+        for (done, &(kind, page)) in pages.iter().enumerate() {
+            let input = NameInput {
+                path: filepath,
+                kind,
+                index,
+            };
+            let path = names.next(output_dir, &input)?;
+            if args.dry_run {
+                let size = inflated_size(page, progress)?;
+                match kind {
+                    "virtual" => summary.decompressed_virtual_size = Some(size as usize),
+                    _ => summary.decompressed_physical_size = Some(size as usize),
+                }
+
+                summary.planned_files.push(planned(path, size));
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+
+            progress.message(&format!("Extracting {kind} page"));
+            let cancelled = Cancelled {
+                done,
+                total: pages.len(),
+            };
+            if cancel.is_cancelled() {
+                return Err(cancelled.into());
+            }
+
+            let written = match args.resume {
+                true => resume_page(page, &path, progress, cancel)?.map(|(written, kept)| {
+                    *summary.resumed_bytes.get_or_insert(0) += kept;
+                    written
+                }),
+                false => stream_page(page, &path, progress, cancel, args.jobs)?,
+            };
+            let Some(written) = written else {
+                return Err(cancelled.into());
+            };
+            log::info!("Wrote {written} byte {kind} page to {}", path.display());
+
+            match kind {
+                "virtual" => summary.decompressed_virtual_size = Some(written as usize),
+                _ => summary.decompressed_physical_size = Some(written as usize),
+            }
+
+            summary.extracted_pages.push(path);
+        }
+
+        progress.finish();
+        return Ok(summary);
+    }
+
+    // Without a comparison or a navigation summary nothing past the header
+    // is read, so the pages stream through instead of being held in memory
+    if args.compare.is_none() && NavKind::from_path(filepath).is_none() {
+        progress.message("Inflating pages");
+        let sizes = scan_pages(&pages, args.jobs, progress)?;
+        progress.finish();
+        for (&(kind, _), size) in pages.iter().zip(sizes) {
+            log::info!("Decompressed {kind} size: {size:?}");
+            match kind {
+                "virtual" => summary.decompressed_virtual_size = Some(size as usize),
+                _ => summary.decompressed_physical_size = Some(size as usize),
+            }
+        }
+
+        return Ok(summary);
+    }
+
+    progress.message("Inflating pages");
+    let mut inflated = vec![];
+    for (&(kind, _), data) in pages
+        .iter()
+        .zip(inflate_pages(&pages, args.jobs, progress)?)
+    {
+        log::info!("Decompressed {kind} size: {:?}", data.len());
+        match kind {
+            "virtual" => summary.decompressed_virtual_size = Some(data.len()),
+            _ => summary.decompressed_physical_size = Some(data.len()),
+        }
+
+        inflated.push((kind, data));
+    }
+    progress.finish();
 
-    let mut virtual_buffer = vec![0u8; virtual_flags.get_size() as usize];
-    let mut physical_buffer = vec![0u8; physical_flags.get_size() as usize];
-    archive.read_bytes(&mut virtual_buffer)?;
-    archive.read_bytes(&mut physical_buffer)?;
+    if let Some(reference_dir) = &args.compare {
+        let pages = inflated
+            .iter()
+            .map(|(kind, data)| (*kind, data.as_slice()))
+            .collect::<Vec<(&str, &[u8])>>();
+        summary.comparisons = compare_pages(reference_dir, filepath, &pages, "")?;
+    }
 
-    let virtual_data = deflate_bytes(&virtual_buffer);
-    let physical_data = deflate_bytes(&physical_buffer);
-    log::info!("Decompressed virtual size: {:?}", virtual_data.len());
-    log::info!("Decompressed physical size: {:?}", physical_data.len());
+    // The structure starts in the virtual page, without it there is nothing
+    // more to read
+    if args.segment == Segment::Physical {
+        return Ok(summary);
+    }
 
+    let mut segments = inflated.into_iter().map(|(_, data)| data);
+    let virtual_data = segments.next().unwrap_or_default();
+    let physical_data = segments.next().unwrap_or_default();
     let mut graphics_archive = FResourceArchive::new(virtual_data, physical_data);
     graphics_archive.set_position(0x50000000)?;
 
@@ -158,5 +1502,1034 @@ pub fn handle_unpack_command(filename: &str) -> CfxResult<()> {
     log::info!("VFT: {}", vft);
     log::info!("Pages info pointer: {}", pages_info_pointer);
 
-    Ok(())
+    if let Some(kind) = NavKind::from_path(filepath) {
+        let mut context = ParseContext::new(options);
+        let nav = kind.summarize(&mut graphics_archive, &mut context)?;
+        log::info!("Navigation: {nav:?}");
+        summary.parse_warnings.extend(context.warnings);
+        summary.nav = Some(nav);
+    }
+
+    Ok(summary)
+}
+
+/// Offset of the RSC7 magic within the first [`MAGIC_SCAN_LIMIT`] bytes.
+fn scan_magic(buffer: &[u8]) -> Option<usize> {
+    let magic = RSC7_MAGIC.to_le_bytes();
+    let end = buffer.len().min(MAGIC_SCAN_LIMIT + magic.len());
+    buffer[..end]
+        .windows(magic.len())
+        .position(|window| window == magic)
+}
+
+/// Reads `path`, transparently removing an outer gzip layer when the file has
+/// a `.gz` extension or starts with the gzip magic.
+pub(crate) fn read_input(path: &Path) -> CfxResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    File::open(path)?.read_to_end(&mut buffer)?;
+
+    let gzipped = path.extension().is_some_and(|ext| ext == "gz")
+        || detect_format(&buffer) == Some(Format::Gzip);
+    if !gzipped {
+        return Ok(buffer);
+    }
+
+    let mut result = Vec::new();
+    GzDecoder::new(buffer.as_slice())
+        .read_to_end(&mut result)
+        .map_err(|err| format!("Invalid gzip file: {err}"))?;
+    log::info!(
+        "Decompressed gzip input ({} -> {} bytes)",
+        buffer.len(),
+        result.len()
+    );
+
+    Ok(result)
+}
+
+fn raw_page_path(file: &Path, kind: &str) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(format!(".{kind}.raw"));
+    PathBuf::from(path)
+}
+
+fn planned(path: PathBuf, size: u64) -> PlannedFile {
+    log::info!("Would write {size} bytes to {}", path.display());
+    PlannedFile { path, size }
+}
+
+/// Writes the page buffers exactly as read from `file`, next to it.
+fn write_raw_pages(file: &Path, pages: &[(&str, &[u8])]) -> CfxResult<Vec<PathBuf>> {
+    let mut result = vec![];
+    for &(kind, page) in pages {
+        let path = raw_page_path(file, kind);
+        atomic_write(&path, page)?;
+        log::info!("Wrote raw {kind} page to {}", path.display());
+        result.push(path);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::writer::{build_page, write_rsc7};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::fs::write;
+
+    #[test]
+    fn archive_header_test() {
+        let data = write_rsc7(13, &[1u8; 64], &[2u8; 32]).unwrap();
+        let mut archive = FMemoryArchive::new(data);
+
+        assert_eq!(archive.read_uint().unwrap(), RSC7_MAGIC);
+        let header = ArchiveHeader::from(&mut archive).unwrap();
+
+        assert_eq!(header.version, 13);
+        assert_eq!(
+            ResourceChunkFlags::new(header.virtual_page_flags)
+                .get_size()
+                .unwrap(),
+            0x200
+        );
+        assert_eq!(
+            ResourceChunkFlags::new(header.physical_page_flags)
+                .get_size()
+                .unwrap(),
+            0x200
+        );
+    }
+
+    #[test]
+    fn resource_type_test() {
+        let resource_type = |virtual_flags, physical_flags| {
+            ResourceType::from_flags(
+                &ResourceChunkFlags::new(virtual_flags),
+                &ResourceChunkFlags::new(physical_flags),
+            )
+        };
+
+        assert_eq!(
+            resource_type(0x0000_0000, 0xD000_0000),
+            ResourceType::TextureDictionary
+        );
+        assert_eq!(
+            resource_type(0xA800_0000, 0x5800_0000),
+            ResourceType::Drawable
+        );
+        assert_eq!(
+            resource_type(0x0000_0000, 0x1000_0000),
+            ResourceType::PathNodes
+        );
+        assert_eq!(
+            resource_type(0xF000_0000, 0x1000_0000),
+            ResourceType::Unknown(0xF1)
+        );
+    }
+
+    #[test]
+    fn chunk_flags_size_test() {
+        let data = write_rsc7(
+            0,
+            &(0..=255).cycle().take(0x20000).collect::<Vec<u8>>(),
+            &[],
+        )
+        .unwrap();
+        let mut archive = FMemoryArchive::new(&data);
+        archive.read_uint().unwrap();
+
+        let header = ArchiveHeader::from(&mut archive).unwrap();
+        let virtual_size = ResourceChunkFlags::new(header.virtual_page_flags)
+            .get_size()
+            .unwrap();
+        let physical_size = ResourceChunkFlags::new(header.physical_page_flags)
+            .get_size()
+            .unwrap();
+
+        assert_eq!(
+            data.len(),
+            HEADER_SIZE + (virtual_size + physical_size) as usize
+        );
+    }
+
+    #[test]
+    fn trailing_checksum_test() {
+        let mut data = write_rsc7(0, b"virtual", b"physical").unwrap();
+        let pages_end = data.len();
+        assert_eq!(
+            TrailingChecksum::read(&data, pages_end),
+            TrailingChecksum::Absent
+        );
+
+        let crc = crc32fast::hash(&data);
+        data.extend_from_slice(&crc.to_le_bytes());
+        assert_eq!(
+            TrailingChecksum::read(&data, pages_end),
+            TrailingChecksum::Valid
+        );
+
+        data[HEADER_SIZE] ^= 0xFF;
+        assert_eq!(
+            TrailingChecksum::read(&data, pages_end),
+            TrailingChecksum::Mismatch {
+                stored: crc,
+                computed: crc32fast::hash(&data[..pages_end])
+            }
+        );
+    }
+
+    #[test]
+    fn raw_pages_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        let data = write_rsc7(13, &[1u8; 0x400], b"physical").unwrap();
+        write(&file, &data).unwrap();
+
+        let name = file.to_string_lossy().to_string();
+        let args = UnpackArgs {
+            raw: true,
+            ..args(vec![name.clone()])
+        };
+        let summary = unpack_file(&args, &name, &mut NoProgress, &CancelToken::new()).unwrap();
+        let virtual_end = HEADER_SIZE + summary.virtual_size as usize;
+
+        assert_eq!(summary.resource_type, ResourceType::TextureDictionary);
+        assert_eq!(summary.decompressed_virtual_size, None);
+        assert_eq!(
+            summary.raw_pages,
+            vec![
+                dir.path().join("sample.ytd.virtual.raw"),
+                dir.path().join("sample.ytd.physical.raw")
+            ]
+        );
+        assert_eq!(
+            std::fs::read(&summary.raw_pages[0]).unwrap(),
+            &data[HEADER_SIZE..virtual_end]
+        );
+        assert_eq!(
+            std::fs::read(&summary.raw_pages[1]).unwrap(),
+            &data[virtual_end..]
+        );
+    }
+
+    #[test]
+    fn output_pages_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        let virtual_data = (0..0x3000u32).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        write(&file, write_rsc7(13, &virtual_data, b"physical").unwrap()).unwrap();
+
+        let name = file.to_string_lossy().to_string();
+        let mut args = UnpackArgs {
+            output: Some(dir.path().join("out")),
+            ..args(vec![name.clone()])
+        };
+        let summary = unpack_file(&args, &name, &mut NoProgress, &CancelToken::new()).unwrap();
+
+        assert_eq!(summary.decompressed_virtual_size, Some(virtual_data.len()));
+        assert_eq!(summary.decompressed_physical_size, Some(8));
+        assert_eq!(
+            std::fs::read(dir.path().join("out/sample.ytd.virtual")).unwrap(),
+            virtual_data
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("out/sample.ytd.physical")).unwrap(),
+            b"physical"
+        );
+
+        // The in-memory path inflates the same data
+        args.output = None;
+        let summary = unpack_file(&args, &name, &mut NoProgress, &CancelToken::new()).unwrap();
+        assert_eq!(summary.decompressed_virtual_size, Some(virtual_data.len()));
+        assert!(summary.extracted_pages.is_empty());
+    }
+
+    #[test]
+    fn batch_stats_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut names = vec![];
+        for (name, data) in [
+            ("a.ytd", write_rsc7(13, &[1u8; 100], b"physical").unwrap()),
+            ("broken.ytd", b"\0not a resource".to_vec()),
+            ("b.ytd", write_rsc7(13, b"virtual", b"").unwrap()),
+            // Flags describing pages past 4 GiB fail the file, not the batch
+            (
+                "crafted.ytd",
+                [RSC7_MAGIC, 0, 0xFFFF_FFFF, 0x0FFF_FFFF, 13]
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect(),
+            ),
+        ] {
+            let file = dir.path().join(name);
+            write(&file, data).unwrap();
+            names.push(file.to_string_lossy().to_string());
+        }
+        names.push(dir.path().join("missing.ytd").to_string_lossy().to_string());
+
+        let args = UnpackArgs {
+            output: Some(dir.path().join("out")),
+            ..args(names)
+        };
+        let UnpackReport::Batch(batch) =
+            handle_unpack_command(&args, &mut NoProgress, &CancelToken::new()).unwrap()
+        else {
+            panic!("expected a batch report");
+        };
+
+        assert_eq!(
+            (
+                batch.stats.processed,
+                batch.stats.succeeded,
+                batch.stats.failed
+            ),
+            (5, 2, 3)
+        );
+        assert_eq!(batch.stats.bytes, 100 + 8 + 7);
+        assert!(batch.files[1].error.is_some());
+        assert!(batch.files[2].summary.is_some());
+        let error = batch.files[3].error.as_deref().unwrap_or_default();
+        assert!(error.contains("larger than 4 GiB"), "{error}");
+    }
+
+    #[test]
+    fn passthrough_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut names = vec![];
+        for (name, data) in [
+            (
+                "vehicles.meta",
+                b"<CVehicleModelInfo__InitDataList><InitDatas><Item><modelName>adder</modelName></Item></InitDatas></CVehicleModelInfo__InitDataList>".to_vec(),
+            ),
+            ("broken.meta", b"<Item><a></b></Item>".to_vec()),
+            ("a.ytd", write_rsc7(13, b"virtual", b"").unwrap()),
+        ] {
+            let file = dir.path().join(name);
+            write(&file, data).unwrap();
+            names.push(file.to_string_lossy().to_string());
+        }
+
+        let output = dir.path().join("out");
+        let args = UnpackArgs {
+            output: Some(output.clone()),
+            pretty_xml: true,
+            ..args(names)
+        };
+        let UnpackReport::Batch(batch) =
+            handle_unpack_command(&args, &mut NoProgress, &CancelToken::new()).unwrap()
+        else {
+            panic!("expected a batch report");
+        };
+
+        assert_eq!(
+            (
+                batch.stats.processed,
+                batch.stats.succeeded,
+                batch.stats.failed,
+                batch.stats.passed_through
+            ),
+            (3, 1, 0, 2)
+        );
+        assert_eq!(
+            batch.files[0].passthrough.as_ref().unwrap().kind,
+            TextKind::Xml
+        );
+        assert!(batch.files[0].passthrough.as_ref().unwrap().pretty);
+        assert!(!batch.files[1].passthrough.as_ref().unwrap().pretty);
+        assert!(batch.files[2].summary.is_some());
+
+        assert_eq!(
+            std::fs::read_to_string(output.join("vehicles.meta")).unwrap(),
+            "<CVehicleModelInfo__InitDataList>\n  <InitDatas>\n    <Item>\n      <modelName>adder</modelName>\n    </Item>\n  </InitDatas>\n</CVehicleModelInfo__InitDataList>\n"
+        );
+        assert_eq!(
+            std::fs::read(output.join("broken.meta")).unwrap(),
+            b"<Item><a></b></Item>"
+        );
+        assert!(batch.stats.to_string().contains("2 passed through"));
+    }
+
+    #[test]
+    fn scan_magic_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("mangled.ytd");
+        let mut buffer = b"\xEF\xBB\xBFjunk".to_vec();
+        buffer.extend(write_rsc7(13, &[0; 16], b"physical").unwrap());
+        write(&file, &buffer).unwrap();
+
+        let mut args = args(vec![file.to_string_lossy().to_string()]);
+        let unpack = |args: &UnpackArgs| {
+            unpack_file(args, &args.names[0], &mut NoProgress, &CancelToken::new())
+        };
+        assert!(unpack(&args).is_err());
+
+        args.scan_magic = true;
+        let summary = unpack(&args).unwrap();
+        assert_eq!(summary.magic_offset, Some(7));
+        assert_eq!(summary.decompressed_physical_size, Some(8));
+
+        assert_eq!(scan_magic(&buffer[7..]), Some(0));
+        let mut far = vec![0; MAGIC_SCAN_LIMIT + 1];
+        far.extend(RSC7_MAGIC.to_le_bytes());
+        assert_eq!(scan_magic(&far), None);
+    }
+
+    #[test]
+    fn segment_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        write(&file, write_rsc7(13, b"virtual", b"physical").unwrap()).unwrap();
+
+        let run = |segment: Segment, output: Option<PathBuf>| {
+            let args = UnpackArgs {
+                output,
+                segment,
+                ..args(vec![file.to_string_lossy().to_string()])
+            };
+            unpack_file(&args, &args.names[0], &mut NoProgress, &CancelToken::new()).unwrap()
+        };
+
+        let out = dir.path().join("out");
+        let summary = run(Segment::Virtual, Some(out.clone()));
+        assert_eq!(
+            summary.extracted_pages,
+            vec![out.join("sample.ytd.virtual")]
+        );
+        assert_eq!(summary.decompressed_virtual_size, Some(7));
+        assert_eq!(summary.decompressed_physical_size, None);
+        assert!(!out.join("sample.ytd.physical").exists());
+
+        let summary = run(Segment::Physical, None);
+        assert_eq!(summary.decompressed_virtual_size, None);
+        assert_eq!(summary.decompressed_physical_size, Some(8));
+    }
+
+    #[test]
+    fn dry_run_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        write(&file, write_rsc7(13, b"virtual", b"physical").unwrap()).unwrap();
+
+        let run = |raw: bool, output: Option<PathBuf>| {
+            let args = UnpackArgs {
+                raw,
+                output,
+                dry_run: true,
+                ..args(vec![file.to_string_lossy().to_string()])
+            };
+            unpack_file(&args, &args.names[0], &mut NoProgress, &CancelToken::new()).unwrap()
+        };
+
+        let out = dir.path().join("out");
+        let summary = run(false, Some(out.clone()));
+        assert_eq!(
+            summary.planned_files,
+            vec![
+                PlannedFile {
+                    path: out.join("sample.ytd.virtual"),
+                    size: 7
+                },
+                PlannedFile {
+                    path: out.join("sample.ytd.physical"),
+                    size: 8
+                },
+            ]
+        );
+        assert_eq!(summary.decompressed_physical_size, Some(8));
+        assert!(summary.extracted_pages.is_empty());
+
+        let summary = run(true, None);
+        assert_eq!(summary.planned_files.len(), 2);
+        assert_eq!(
+            summary.planned_files[0].path,
+            raw_page_path(&file, "virtual")
+        );
+        assert!(summary.raw_pages.is_empty());
+
+        let entries = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(entries, 1, "dry run wrote files");
+    }
+
+    #[test]
+    fn output_naming_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut names = vec![];
+        for folder in ["cars", "peds"] {
+            std::fs::create_dir_all(dir.path().join(folder)).unwrap();
+            let file = dir.path().join(folder).join("shared.ytd");
+            write(&file, write_rsc7(13, b"virtual", b"physical").unwrap()).unwrap();
+            names.push(file.to_string_lossy().to_string());
+        }
+
+        let run = |flat: bool, name_template: Option<&str>| {
+            let args = UnpackArgs {
+                output: Some(dir.path().join("out")),
+                flat,
+                name_template: name_template.map(str::to_owned),
+                ..args(names.clone())
+            };
+            let UnpackReport::Batch(batch) =
+                handle_unpack_command(&args, &mut NoProgress, &CancelToken::new())?
+            else {
+                panic!("expected a batch report");
+            };
+
+            let pages = batch
+                .files
+                .into_iter()
+                .flat_map(|entry| entry.summary.unwrap().extracted_pages)
+                .map(|path| path.strip_prefix(dir.path()).unwrap().to_path_buf())
+                .collect::<Vec<PathBuf>>();
+            CfxResult::Ok(pages)
+        };
+
+        let out = |path: &str| Path::new("out").join(path);
+        assert_eq!(
+            run(false, None).unwrap(),
+            [
+                "cars/shared.ytd.virtual",
+                "cars/shared.ytd.physical",
+                "peds/shared.ytd.virtual",
+                "peds/shared.ytd.physical"
+            ]
+            .map(out)
+        );
+        assert_eq!(
+            run(true, Some("{stem}.{kind}")).unwrap(),
+            [
+                "shared.virtual",
+                "shared.physical",
+                "shared_2.virtual",
+                "shared_2.physical"
+            ]
+            .map(out)
+        );
+
+        let err = run(true, Some("{stem}_{hash}")).unwrap_err();
+        assert!(err.to_string().contains("valid ones are {name}"));
+        assert!(!dir.path().join("out/shared_3.virtual").exists());
+    }
+
+    #[test]
+    fn deviation_fixtures_test() {
+        let fixtures = [
+            (
+                "fixtures/deviations/trailing_garbage.rsc7",
+                "7 bytes of trailing data after the pages",
+            ),
+            (
+                "fixtures/deviations/checksum_mismatch.rsc7",
+                "Trailing checksum mismatch",
+            ),
+        ];
+
+        for (fixture, message) in fixtures {
+            let mut args = args(vec![fixture.to_owned()]);
+            let summary =
+                unpack_file(&args, fixture, &mut NoProgress, &CancelToken::new()).unwrap();
+            assert_eq!(summary.parse_warnings.len(), 1);
+            assert!(summary.parse_warnings[0].message.starts_with(message));
+            assert_eq!(summary.decompressed_physical_size, Some(4));
+
+            args.strict = true;
+            let Err(err) = unpack_file(&args, fixture, &mut NoProgress, &CancelToken::new()) else {
+                panic!("{fixture} was accepted by --strict");
+            };
+            assert!(err.to_string().starts_with(message));
+        }
+    }
+
+    /// Cancels its token from another thread on the first progress report.
+    struct CancelOnProgress(CancelToken);
+
+    impl ProgressSink for CancelOnProgress {
+        fn start(&mut self, _total: u64) {}
+
+        fn advance(&mut self, _n: u64) {
+            let token = self.0.clone();
+            std::thread::spawn(move || token.cancel()).join().unwrap();
+        }
+
+        fn message(&mut self, _message: &str) {}
+        fn finish(&mut self) {}
+    }
+
+    #[test]
+    fn nav_summary_test() {
+        let mut args = args(vec!["fixtures/nav/paths.ynd".to_owned()]);
+        let UnpackReport::File(summary) =
+            handle_unpack_command(&args, &mut NoProgress, &CancelToken::new()).unwrap()
+        else {
+            panic!("expected a single file report");
+        };
+        assert_eq!(summary.resource_type, ResourceType::PathNodes);
+        let Some(NavSummary::Ynd(ynd)) = &summary.nav else {
+            panic!("expected a ynd summary");
+        };
+        assert_eq!((ynd.node_count, ynd.link_count), (3, 4));
+        assert_eq!(ynd.area_ids, [42, 43]);
+
+        args.names = vec!["fixtures/nav/navmesh.ynv".to_owned()];
+        let UnpackReport::File(summary) =
+            handle_unpack_command(&args, &mut NoProgress, &CancelToken::new()).unwrap()
+        else {
+            panic!("expected a single file report");
+        };
+        let Some(NavSummary::Ynv(ynv)) = &summary.nav else {
+            panic!("expected a ynv summary");
+        };
+        assert_eq!((ynv.vertex_count, ynv.poly_count), (300, 120));
+        assert!(summary.parse_warnings.is_empty());
+
+        // Only summarized when the pages are inflated in memory
+        let dir = tempfile::tempdir().unwrap();
+        args.output = Some(dir.path().to_path_buf());
+        let UnpackReport::File(summary) =
+            handle_unpack_command(&args, &mut NoProgress, &CancelToken::new()).unwrap()
+        else {
+            panic!("expected a single file report");
+        };
+        assert!(summary.nav.is_none());
+    }
+
+    #[test]
+    fn cancel_extraction_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        let mut seed = 1u32;
+        let virtual_data = (0..0x40000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect::<Vec<u8>>();
+        write(&file, write_rsc7(13, &virtual_data, b"physical").unwrap()).unwrap();
+
+        let name = file.to_string_lossy().to_string();
+        let args = UnpackArgs {
+            output: Some(dir.path().join("out")),
+            ..args(vec![name.clone(), name.clone()])
+        };
+
+        let cancel = CancelToken::new();
+        let Err(err) = unpack_file(&args, &name, &mut CancelOnProgress(cancel.clone()), &cancel)
+        else {
+            panic!("expected the extraction to be cancelled");
+        };
+        assert_eq!(
+            err.downcast_ref::<Cancelled>(),
+            Some(&Cancelled { done: 0, total: 2 })
+        );
+        assert!(!dir.path().join("out/sample.ytd.virtual").exists());
+        assert!(!dir.path().join("out/sample.ytd.physical").exists());
+
+        let Err(err) = handle_unpack_command(&args, &mut NoProgress, &cancel) else {
+            panic!("expected the batch to be cancelled");
+        };
+        assert_eq!(err.to_string(), "cancelled after 0 of 2 entries");
+    }
+
+    #[test]
+    fn resume_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("large.ytd");
+        let mut seed = 7u32;
+        // More than one resume chunk, and compressible enough to stay small
+        let virtual_data = (0..0x180000)
+            .map(|index| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                match index % 4 {
+                    0 => (seed >> 16) as u8,
+                    _ => 0,
+                }
+            })
+            .collect::<Vec<u8>>();
+        write(&file, write_rsc7(13, &virtual_data, b"physical").unwrap()).unwrap();
+
+        let out = dir.path().join("out");
+        let name = file.to_string_lossy().to_string();
+        let args = UnpackArgs {
+            output: Some(out.clone()),
+            resume: true,
+            ..args(vec![name.clone()])
+        };
+        let virtual_page = out.join("large.ytd.virtual");
+        let partial = out.join("large.ytd.virtual.part");
+        let resume = |progress: &mut dyn ProgressSink, cancel: &CancelToken| {
+            unpack_file(&args, &name, progress, cancel)
+        };
+
+        let summary = resume(&mut NoProgress, &CancelToken::new()).unwrap();
+        assert_eq!(summary.resumed_bytes, Some(0));
+        assert_eq!(std::fs::read(&virtual_page).unwrap(), virtual_data);
+        assert!(!partial.exists());
+
+        // A run that died 1.2 MB in, then one that wrote a bad byte before that
+        for (prefix, bad_byte, kept) in [
+            (1_200_000, None, 1_200_000),
+            (1_200_000, Some(500_000), 500_000),
+        ] {
+            let mut leftover = virtual_data[..prefix].to_vec();
+            if let Some(offset) = bad_byte {
+                leftover[offset] ^= 0xFF;
+            }
+
+            std::fs::remove_file(&virtual_page).unwrap();
+            write(&partial, leftover).unwrap();
+            let summary = resume(&mut NoProgress, &CancelToken::new()).unwrap();
+            // The finished physical page is kept whole
+            assert_eq!(summary.resumed_bytes, Some(kept + 8));
+            assert_eq!(std::fs::read(&virtual_page).unwrap(), virtual_data);
+            assert!(!partial.exists());
+        }
+
+        // A cancelled run leaves the partial file for the next one
+        let cancel = CancelToken::new();
+        assert!(resume(&mut CancelOnProgress(cancel.clone()), &cancel).is_err());
+        assert!(partial.exists());
+        resume(&mut NoProgress, &CancelToken::new()).unwrap();
+        assert_eq!(std::fs::read(&virtual_page).unwrap(), virtual_data);
+        assert!(!partial.exists());
+    }
+
+    #[test]
+    fn gzip_input_test() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let data = write_rsc7(13, b"virtual", b"physical").unwrap();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&data).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        // Detected by extension as well as by magic alone
+        for name in ["sample.ytd.gz", "sample.ytd"] {
+            let file = dir.path().join(name);
+            write(&file, &gzipped).unwrap();
+            assert_eq!(read_input(&file).unwrap(), data);
+        }
+
+        let file = dir.path().join("plain.ytd");
+        write(&file, &data).unwrap();
+        assert_eq!(read_input(&file).unwrap(), data);
+    }
+
+    #[test]
+    fn zstd_page_test() {
+        // A zstd frame holding "virtual" as a raw block
+        let mut page = ZSTD_MAGIC.to_vec();
+        page.extend([0x20, 0x07, 0x39, 0x00, 0x00]);
+        page.extend(b"virtual");
+
+        let err = inflate_page(&page, &mut NoProgress).unwrap_err();
+        assert!(err.to_string().contains("zstd"), "{err}");
+        assert_eq!(
+            ErrorCode::of(err.as_ref()),
+            Some(ErrorCode::UnsupportedCompression)
+        );
+        assert!(inflated_size(&page, &mut NoProgress).is_err());
+
+        let deflated = write_rsc7(13, b"virtual", b"").unwrap();
+        let page = &deflated[HEADER_SIZE..];
+        assert_eq!(inflate_page(page, &mut NoProgress).unwrap(), b"virtual");
+    }
+
+    /// Counts the bytes reported, whichever thread reports them.
+    #[derive(Default)]
+    struct CountingProgress(u64);
+
+    impl ProgressSink for CountingProgress {
+        fn start(&mut self, _total: u64) {}
+
+        fn advance(&mut self, n: u64) {
+            self.0 += n;
+        }
+
+        fn message(&mut self, _message: &str) {}
+        fn finish(&mut self) {}
+    }
+
+    /// Times unpacking a large resource with one job against the default two,
+    /// keeping the best of a few runs. Too slow for every test run, so run it
+    /// in release mode with
+    /// `cargo test --release unpack_jobs_bench -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn unpack_jobs_bench() {
+        // Four bits of noise per byte, so deflate has work to do both ways
+        let mut seed = 11u32;
+        let mut data = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    (seed >> 16) as u8 & 0x0F
+                })
+                .collect::<Vec<u8>>()
+        };
+        let (virtual_data, physical_data) = (data(32 << 20), data(24 << 20));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("large.ytd");
+        write(
+            &file,
+            write_rsc7(13, &virtual_data, &physical_data).unwrap(),
+        )
+        .unwrap();
+        let name = file.to_string_lossy().to_string();
+
+        let mut best = vec![];
+        for jobs in [1, 2] {
+            let output = dir.path().join(format!("out{jobs}"));
+            let args = UnpackArgs {
+                output: Some(output.clone()),
+                jobs,
+                ..args(vec![name.clone()])
+            };
+            let runs = (0..5).map(|_| {
+                let start = Instant::now();
+                unpack_file(&args, &name, &mut NoProgress, &CancelToken::new()).unwrap();
+                start.elapsed()
+            });
+            best.push(runs.min().unwrap());
+
+            let page = |kind: &str| std::fs::read(output.join(format!("large.ytd.{kind}")));
+            assert!(page("virtual").unwrap() == virtual_data);
+            assert!(page("physical").unwrap() == physical_data);
+        }
+
+        println!(
+            "--jobs 1: {:?}, --jobs 2: {:?}, speedup {:.2}x",
+            best[0],
+            best[1],
+            best[0].as_secs_f64() / best[1].as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn parallel_inflate_test() {
+        let mut seed = 7u32;
+        let mut noise = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    (seed >> 16) as u8
+                })
+                .collect::<Vec<u8>>()
+        };
+        let (virtual_data, physical_data) = (noise(0x30000), noise(0x21000));
+        let (_, virtual_page) = build_page(&virtual_data, 6).unwrap();
+        let (_, physical_page) = build_page(&physical_data, 6).unwrap();
+        let pages = [
+            ("virtual", virtual_page.as_slice()),
+            ("physical", physical_page.as_slice()),
+        ];
+
+        for jobs in [1, 2] {
+            let mut progress = CountingProgress::default();
+            let inflated = inflate_pages(&pages, jobs, &mut progress).unwrap();
+            assert_eq!(inflated, [virtual_data.clone(), physical_data.clone()]);
+            assert_eq!(
+                progress.0,
+                (virtual_page.len() + physical_page.len()) as u64
+            );
+        }
+
+        let mut broken = physical_page.clone();
+        broken[0] = 0xff;
+        let pages = [("virtual", virtual_page.as_slice()), ("physical", &broken)];
+        let err = inflate_pages(&pages, 2, &mut NoProgress).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid page data"), "{err}");
+
+        let mut written = vec![];
+        let copied = pipelined_copy(&mut virtual_data.as_slice(), &mut written).unwrap();
+        assert_eq!(copied, virtual_data.len() as u64);
+        assert_eq!(written, virtual_data);
+    }
+
+    /// Counts the bytes each thread holds allocated and the most it has held,
+    /// for measuring the peak memory of a call.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+        static PEAK: Cell<isize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATED.try_with(|allocated| {
+                allocated.set(allocated.get() + layout.size() as isize);
+                let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+            });
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            let _ = ALLOCATED
+                .try_with(|allocated| allocated.set(allocated.get() - layout.size() as isize));
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// The most `f` had allocated at once on this thread.
+    fn peak_allocation(f: impl FnOnce()) -> isize {
+        let start = ALLOCATED.with(Cell::get);
+        PEAK.with(|peak| peak.set(start));
+        f();
+        PEAK.with(Cell::get) - start
+    }
+
+    #[test]
+    fn streamed_info_memory_test() {
+        const PAGE_SIZE: usize = 16 << 20;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("large.ytd");
+        let mut virtual_data = (0..PAGE_SIZE).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        virtual_data[..16]
+            .copy_from_slice(&[0x10, 0, 0, 0, 0, 0, 0, 0, 0x20, 0, 0, 0x50, 0, 0, 0, 0]);
+        write(&file, write_rsc7(13, &virtual_data, &[]).unwrap()).unwrap();
+        let (_, page) = build_page(&virtual_data, 6).unwrap();
+        drop(virtual_data);
+
+        let name = file.to_string_lossy().to_string();
+        let args = UnpackArgs {
+            jobs: 1,
+            ..args(vec![name.clone()])
+        };
+
+        let mut summary = None;
+        let streamed = peak_allocation(|| {
+            summary =
+                Some(unpack_file(&args, &name, &mut NoProgress, &CancelToken::new()).unwrap());
+        });
+        let summary = summary.unwrap();
+        assert_eq!(summary.decompressed_virtual_size, Some(PAGE_SIZE));
+        assert!(
+            streamed < (PAGE_SIZE / 16) as isize,
+            "streamed peak {streamed}"
+        );
+
+        // Comparing needs the pages in memory, as every release did
+        let materialized = peak_allocation(|| {
+            inflate_pages(&[("virtual", &page)], 1, &mut NoProgress).unwrap();
+        });
+        assert!(
+            materialized >= PAGE_SIZE as isize,
+            "materialized peak {materialized}"
+        );
+    }
+
+    /// Accepts the given number of bytes, then fails every write.
+    struct FailingWriter(usize);
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0 == 0 {
+                return Err(io::Error::other("disk full"));
+            }
+
+            let written = buf.len().min(self.0);
+            self.0 -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pipelined_copy_error_test() {
+        // A failed write stops the reading thread instead of leaving it
+        // blocked on the full channel
+        let data = vec![0u8; PIPELINE_CHUNK_SIZE * (PIPELINE_DEPTH + 4)];
+        let err = pipelined_copy(&mut data.as_slice(), &mut FailingWriter(100)).unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("bad deflate"))
+            }
+        }
+
+        let err = pipelined_copy(&mut FailingReader, &mut vec![]).unwrap_err();
+        assert_eq!(err.to_string(), "bad deflate");
+    }
+
+    #[test]
+    fn first_mismatch_test() {
+        assert_eq!(first_mismatch(b"abcd", b"abcd"), None);
+        assert_eq!(first_mismatch(b"abXd", b"abcd"), Some(2));
+        assert_eq!(first_mismatch(b"ab", b"abcd"), Some(2));
+        assert_eq!(first_mismatch(b"abcd", b"abc"), Some(3));
+    }
+
+    #[test]
+    fn compare_pages_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sample.ytd");
+        write(dir.path().join("sample.ytd.virtual"), b"virtual").unwrap();
+        write(dir.path().join("sample.ytd.physical"), b"physical").unwrap();
+
+        let comparisons = compare_pages(
+            dir.path(),
+            &file,
+            &[("virtual", b"virtual"), ("physical", b"physical")],
+            "",
+        )
+        .unwrap();
+        assert!(comparisons.iter().all(|c| c.first_mismatch.is_none()));
+
+        assert!(compare_pages(
+            dir.path(),
+            &file,
+            &[("virtual", b"virtual"), ("physical", b"physicaX")],
+            "",
+        )
+        .is_err());
+        assert!(
+            compare_pages(dir.path(), &file, &[("virtual", b""), ("missing", b"")], "").is_err()
+        );
+    }
+
+    #[test]
+    fn header_struct_test() {
+        assert_eq!(
+            header_struct(StructLanguage::Rust),
+            r#"/// RSC7 resource header, 20 bytes, little-endian
+#[repr(C)]
+pub struct ArchiveHeader {
+    pub magic: u32, // 0x00, 4 bytes, "RSC7"
+    pub flags: u32, // 0x04, 4 bytes
+    pub virtual_page_flags: u32, // 0x08, 4 bytes, see `cfx explain-flags`
+    pub physical_page_flags: u32, // 0x0c, 4 bytes, see `cfx explain-flags`
+    pub version: i32, // 0x10, 4 bytes, the low byte is the resource version
+}
+"#
+        );
+        assert_eq!(
+            header_struct(StructLanguage::C),
+            r#"#include <stdint.h>
+
+/* RSC7 resource header, 20 bytes, little-endian */
+typedef struct ArchiveHeader {
+    uint32_t magic; /* 0x00, 4 bytes, "RSC7" */
+    uint32_t flags; /* 0x04, 4 bytes */
+    uint32_t virtual_page_flags; /* 0x08, 4 bytes, see `cfx explain-flags` */
+    uint32_t physical_page_flags; /* 0x0c, 4 bytes, see `cfx explain-flags` */
+    int32_t version; /* 0x10, 4 bytes, the low byte is the resource version */
+} ArchiveHeader;
+"#
+        );
+        assert_eq!(HEADER_FIELDS.len() * 4, HEADER_SIZE);
+    }
 }
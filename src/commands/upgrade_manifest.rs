@@ -0,0 +1,407 @@
+use std::fs::read_to_string;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::atomic::atomic_write;
+use crate::commands::create::{render_script_section, SectionStyle};
+use crate::diff::unified_diff;
+use crate::lua::check_syntax;
+use crate::manifest::{expand_pattern, Directive, Manifest, SCRIPT_DIRECTIVES};
+use crate::CfxResult;
+
+/// Manifest of resources from before `fx_version`, which the server still
+/// loads when there is no fxmanifest.lua.
+const LEGACY_MANIFEST: &str = "__resource.lua";
+
+/// `fx_version` values older than cerulean, which the upgrade rewrites.
+const OLD_FX_VERSIONS: [&str; 2] = ["adamant", "bodacious"];
+
+/// Singular script directives and the tables they become.
+const SINGULAR_SCRIPTS: [(&str, &str); 3] = [
+    ("client_script", "client"),
+    ("server_script", "server"),
+    ("shared_script", "shared"),
+];
+
+#[derive(Args)]
+pub struct UpgradeManifestArgs {
+    /// Resource directory containing the fxmanifest.lua, or an old
+    /// __resource.lua to write one from
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Print the changes as a diff instead of writing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// The manifest after the upgrade, what changed and what has to be migrated
+/// by hand.
+#[derive(Debug, PartialEq)]
+pub struct Upgrade {
+    pub content: String,
+    pub changes: Vec<String>,
+    pub manual: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct UpgradeManifestSummary {
+    pub manifest: PathBuf,
+    pub changes: Vec<String>,
+    pub manual: Vec<String>,
+    pub written: bool,
+}
+
+pub fn handle_upgrade_manifest_command(
+    args: &UpgradeManifestArgs,
+) -> CfxResult<UpgradeManifestSummary> {
+    let manifest_path = args.path.join("fxmanifest.lua");
+    let source_path = match manifest_path.is_file() {
+        true => manifest_path.clone(),
+        false => args.path.join(LEGACY_MANIFEST),
+    };
+    let content = read_to_string(&source_path)
+        .map_err(|err| format!("Could not read {}: {err}", manifest_path.display()))?;
+
+    let mut upgrade = upgrade_manifest(&content, &args.path)?;
+    if source_path != manifest_path {
+        upgrade
+            .changes
+            .insert(0, format!("Moved {LEGACY_MANIFEST} to fxmanifest.lua"));
+        upgrade.manual.push(format!(
+            "Delete {LEGACY_MANIFEST} once the resource starts from fxmanifest.lua"
+        ));
+    }
+
+    for change in &upgrade.changes {
+        log::info!("{change}");
+    }
+
+    for manual in &upgrade.manual {
+        log::warn!("Not migrated: {manual}");
+    }
+
+    let unchanged = upgrade.changes.is_empty();
+    if args.dry_run {
+        let diff = unified_diff(
+            &content,
+            &upgrade.content,
+            &format!("a/{}", source_path.file_name().unwrap().to_string_lossy()),
+            "b/fxmanifest.lua",
+        );
+        if !diff.is_empty() {
+            log::info!("Would change fxmanifest.lua:\n{diff}");
+        }
+    } else if !unchanged {
+        atomic_write(&manifest_path, &upgrade.content)?;
+        log::info!("Upgraded {}", manifest_path.display());
+    }
+
+    if unchanged {
+        log::info!("The manifest is already up to date");
+    }
+
+    Ok(UpgradeManifestSummary {
+        manifest: manifest_path,
+        changes: upgrade.changes,
+        manual: upgrade.manual,
+        written: !args.dry_run && !unchanged,
+    })
+}
+
+/// Upgrades the manifest `content` of the resource in `resource_dir` to
+/// cerulean: rewrites old `fx_version`s and `resource_manifest_version`, adds
+/// `lua54` when every Lua script it lists parses as Lua 5.4 and turns runs
+/// of singular script directives into tables. Only the statements that
+/// change are rewritten, comments and everything else stay as they are.
+pub fn upgrade_manifest(content: &str, resource_dir: &Path) -> CfxResult<Upgrade> {
+    let manifest = Manifest::parse(content)?;
+    let quote = quote_style(content);
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    let mut changes = vec![];
+    let mut manual = vec![];
+
+    let fx_version = manifest.get("fx_version");
+    let legacy = manifest.get("resource_manifest_version");
+    match (fx_version, legacy) {
+        (Some(directive), _) => match directive.values.first().map(String::as_str) {
+            Some("cerulean") => {}
+            Some(old) if OLD_FX_VERSIONS.contains(&old) => {
+                let offset = value_offset(content, directive, old)?;
+                edits.push((offset..offset + old.len(), "cerulean".to_owned()));
+                changes.push(format!("Changed fx_version from {old} to cerulean"));
+            }
+            other => manual.push(format!(
+                "Unknown fx_version {}, left as is",
+                other.unwrap_or_default()
+            )),
+        },
+        (None, Some(directive)) => {
+            edits.push((
+                directive.span.clone(),
+                format!("fx_version {quote}cerulean{quote}"),
+            ));
+            changes.push("Replaced resource_manifest_version with fx_version cerulean".to_owned());
+        }
+        (None, None) => {
+            edits.push((0..0, format!("fx_version {quote}cerulean{quote}\n")));
+            changes.push("Added fx_version cerulean".to_owned());
+        }
+    }
+
+    // Left behind by resources moved from __resource.lua by hand
+    if let (Some(_), Some(directive)) = (fx_version, legacy) {
+        edits.push((line_span(content, &directive.span), String::new()));
+        changes.push("Removed resource_manifest_version, fx_version replaces it".to_owned());
+    }
+
+    if manifest.games().is_empty() {
+        manual.push("The manifest declares no game, add `game 'gta5'` or `game 'rdr3'`".to_owned());
+    }
+
+    if manifest.get("lua54").is_none() {
+        let errors = lua_errors(&manifest, resource_dir)?;
+        if errors.is_empty() {
+            let anchor = ["games", "game", "fx_version", "resource_manifest_version"]
+                .iter()
+                .find_map(|name| manifest.get(name));
+            match anchor {
+                Some(directive) => edits.push((
+                    directive.span.end..directive.span.end,
+                    format!("\nlua54 {quote}yes{quote}"),
+                )),
+                None => edits.push((0..0, format!("lua54 {quote}yes{quote}\n"))),
+            }
+            changes.push("Added lua54, every Lua script parses as Lua 5.4".to_owned());
+        } else {
+            manual.extend(
+                errors
+                    .into_iter()
+                    .map(|error| format!("Left out lua54, {error}")),
+            );
+        }
+    }
+
+    for group in singular_runs(content, &manifest) {
+        let (name, section) = SINGULAR_SCRIPTS
+            .iter()
+            .find(|(name, _)| *name == group[0].name)
+            .expect("grouped by singular name");
+        let scripts = group
+            .iter()
+            .flat_map(|directive| directive.values.iter().cloned())
+            .collect::<Vec<_>>();
+        let rendered = render_script_section(section, &scripts, SectionStyle::Table)?;
+        let span = group[0].span.start..group[group.len() - 1].span.end;
+        edits.push((span, rendered.unwrap_or_default()));
+        changes.push(format!(
+            "Converted {} {name} line(s) to a {section}_scripts table",
+            group.len()
+        ));
+    }
+
+    Ok(Upgrade {
+        content: apply_edits(content, edits),
+        changes,
+        manual,
+    })
+}
+
+/// Runs of singular script directives of the same kind, separated by
+/// nothing but whitespace, so that a comment between them ends a run.
+fn singular_runs<'a>(content: &str, manifest: &'a Manifest) -> Vec<Vec<&'a Directive>> {
+    let mut runs: Vec<Vec<&Directive>> = vec![];
+    for directive in &manifest.directives {
+        if !SINGULAR_SCRIPTS
+            .iter()
+            .any(|(name, _)| *name == directive.name)
+        {
+            continue;
+        }
+
+        match runs.last_mut() {
+            Some(run)
+                if run.last().is_some_and(|last| {
+                    last.name == directive.name
+                        && content[last.span.end..directive.span.start]
+                            .trim()
+                            .is_empty()
+                }) =>
+            {
+                run.push(directive)
+            }
+            _ => runs.push(vec![directive]),
+        }
+    }
+
+    runs
+}
+
+/// Syntax errors of the local Lua scripts the manifest lists, as
+/// `file:line:column: message`.
+fn lua_errors(manifest: &Manifest, resource_dir: &Path) -> CfxResult<Vec<String>> {
+    let mut errors = vec![];
+    let patterns = manifest.values(&SCRIPT_DIRECTIVES);
+    for pattern in patterns.into_iter().filter(|pattern| {
+        !pattern.starts_with('@') && !pattern.contains("://") && pattern.ends_with(".lua")
+    }) {
+        for file in expand_pattern(resource_dir, pattern)? {
+            let source =
+                read_to_string(&file).map_err(|err| format!("Could not read {file:?}: {err}"))?;
+            if let Err(error) = check_syntax(&source) {
+                let relative = file.strip_prefix(resource_dir).unwrap_or(&file);
+                errors.push(format!("{}:{error}", relative.display()));
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Offset of `value` in the statement of `directive`, after its name.
+fn value_offset(content: &str, directive: &Directive, value: &str) -> CfxResult<usize> {
+    let statement = &content[directive.span.clone()];
+    let found = statement[directive.name.len()..]
+        .find(value)
+        .ok_or_else(|| format!("Could not locate the {} value", directive.name))?;
+    Ok(directive.span.start + directive.name.len() + found)
+}
+
+/// `span` widened to its whole line, newline included, when nothing else is
+/// on that line.
+fn line_span(content: &str, span: &Range<usize>) -> Range<usize> {
+    let start = content[..span.start]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let end = content[span.end..]
+        .find('\n')
+        .map_or(content.len(), |newline| span.end + newline + 1);
+    match content[start..span.start].trim().is_empty() && content[span.end..end].trim().is_empty() {
+        true => start..end,
+        false => span.clone(),
+    }
+}
+
+/// The quote the manifest writes its first string with, double by default.
+fn quote_style(content: &str) -> char {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .find_map(|line| line.chars().find(|&c| c == '"' || c == '\''))
+        .unwrap_or('"')
+}
+
+/// Replaces each range of `content` with its text. Ranges do not overlap;
+/// insertions at the same offset keep the order they were added in.
+fn apply_edits(content: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut result = String::new();
+    let mut offset = 0;
+    for (range, text) in edits {
+        result.push_str(&content[offset..range.start]);
+        result.push_str(&text);
+        offset = range.end;
+    }
+
+    result.push_str(&content[offset..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn upgrade_manifest_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+        write(dir.path().join("client/main.lua"), "local x <const> = 1\n").unwrap();
+        write(dir.path().join("server.lua"), "print('hi')\n").unwrap();
+
+        let content = "-- Bank by cfx\n\
+                       fx_version 'bodacious'\n\
+                       game 'gta5'\n\
+                       \n\
+                       client_script 'client/main.lua'\n\
+                       client_script '@ox_lib/init.lua' -- before main\n\
+                       \n\
+                       -- the server side\n\
+                       server_script 'server.lua'\n\
+                       files { 'html/index.html' }\n";
+        let upgrade = upgrade_manifest(content, dir.path()).unwrap();
+        assert_eq!(
+            upgrade.content,
+            "-- Bank by cfx\n\
+             fx_version 'cerulean'\n\
+             game 'gta5'\n\
+             lua54 'yes'\n\
+             \n\
+             client_scripts {\n    \"client/main.lua\",\n    \"@ox_lib/init.lua\"\n} -- before main\n\
+             \n\
+             -- the server side\n\
+             server_scripts {\n    \"server.lua\"\n}\n\
+             files { 'html/index.html' }\n"
+        );
+        assert_eq!(upgrade.changes.len(), 4, "{:?}", upgrade.changes);
+        assert!(upgrade.manual.is_empty(), "{:?}", upgrade.manual);
+
+        // Upgrading again changes nothing
+        let again = upgrade_manifest(&upgrade.content, dir.path()).unwrap();
+        assert_eq!(again.content, upgrade.content);
+        assert!(again.changes.is_empty());
+    }
+
+    #[test]
+    fn upgrade_legacy_manifest_test() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path().join("client.lua"), "local x = \n").unwrap();
+
+        let content = "resource_manifest_version \"44febabe-d386-4d18-afbe-5e627f4af937\"\n\
+                       \n\
+                       client_scripts { \"client.lua\" }\n";
+        let upgrade = upgrade_manifest(content, dir.path()).unwrap();
+        assert_eq!(
+            upgrade.content,
+            "fx_version \"cerulean\"\n\nclient_scripts { \"client.lua\" }\n"
+        );
+        assert_eq!(upgrade.manual.len(), 2, "{:?}", upgrade.manual);
+        assert!(upgrade.manual[0].contains("no game"));
+        assert!(upgrade.manual[1].starts_with("Left out lua54, client.lua:2:"));
+
+        // A leftover next to fx_version is dropped along with its line
+        let content = "fx_version 'cerulean'\nresource_manifest_version '77731fab'\ngame 'gta5'\nlua54 'yes'\n";
+        let upgrade = upgrade_manifest(content, dir.path()).unwrap();
+        assert_eq!(
+            upgrade.content,
+            "fx_version 'cerulean'\ngame 'gta5'\nlua54 'yes'\n"
+        );
+    }
+
+    #[test]
+    fn upgrade_dry_run_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = "fx_version 'adamant'\ngame 'gta5'\n";
+        write(dir.path().join(LEGACY_MANIFEST), manifest).unwrap();
+
+        let mut args = UpgradeManifestArgs {
+            path: dir.path().to_path_buf(),
+            dry_run: true,
+        };
+        let summary = handle_upgrade_manifest_command(&args).unwrap();
+        assert!(!summary.written);
+        assert!(!dir.path().join("fxmanifest.lua").exists());
+
+        args.dry_run = false;
+        let summary = handle_upgrade_manifest_command(&args).unwrap();
+        assert!(summary.written);
+        assert_eq!(
+            read_to_string(dir.path().join("fxmanifest.lua")).unwrap(),
+            "fx_version 'cerulean'\ngame 'gta5'\nlua54 'yes'\n"
+        );
+        assert_eq!(summary.manual.len(), 1);
+    }
+}
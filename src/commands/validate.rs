@@ -0,0 +1,977 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::i18n::{t, tf};
+use crate::lint::{
+    find_rule, run_rules, unknown_rules, LintConfig, LintContext, RuleReport, Severity, RULES,
+};
+use crate::magic::{detect_escrow, EscrowReport};
+use crate::manifest::{expand_pattern, Manifest};
+use crate::meta::{from_xml, CarVariationsMeta, HandlingMeta, VehiclesMeta};
+use crate::output::{FailedWith, ReportItem, RunReport};
+use crate::project::{ProjectConfig, PROJECT_FILE};
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Resource directory, or a server directory containing server.cfg
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// How findings are printed; json prints them grouped by rule on stdout
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+
+    /// Describe a rule and how to fix its findings instead of validating
+    #[arg(long, value_name = "RULE")]
+    pub explain: Option<String>,
+
+    /// Write the outcome, duration, warnings and errors of every resource to
+    /// a .json file or a Markdown table in a .md file
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// FXServer binary directory to read the server build from when
+    /// validating a server directory. Looked for next to server.cfg and in a
+    /// sibling `server` folder when omitted
+    #[arg(long, value_name = "DIR")]
+    pub artifacts: Option<PathBuf>,
+
+    /// Cross-check the vehicles.meta, handling.meta and carvariations.meta
+    /// of a vehicle add-on against each other and the models in stream/
+    #[arg(long)]
+    pub vehicles: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Default, Serialize)]
+pub struct ValidateSummary {
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+    /// Findings grouped by the rule that reported them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleReport>,
+    /// Set for resources protected by Cfx escrow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escrow: Option<EscrowReport>,
+    /// Set by --explain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+pub fn handle_validate_command(args: &ValidateArgs) -> CfxResult<ValidateSummary> {
+    if let Some(name) = &args.explain {
+        let explanation = explain(name)?;
+        println!("{explanation}");
+        return Ok(ValidateSummary {
+            explanation: Some(explanation),
+            ..Default::default()
+        });
+    }
+
+    let server_cfg = args.path.join("server.cfg");
+    let mut items = vec![];
+    let summary = if server_cfg.is_file() {
+        if args.vehicles {
+            return Err("--vehicles checks a single resource, not a server directory".into());
+        }
+
+        validate_server(
+            &args.path,
+            &server_cfg,
+            args.artifacts.as_deref(),
+            &mut items,
+        )?
+    } else {
+        let started = Instant::now();
+        let summary = validate_resource(&args.path).and_then(|mut summary| {
+            if args.vehicles {
+                add_vehicle_findings(&args.path, &mut summary)?;
+            }
+
+            Ok(summary)
+        });
+        let item = match &summary {
+            Ok(summary) => ReportItem::new(
+                args.path.to_string_lossy(),
+                started.elapsed(),
+                summary.warnings.clone(),
+                summary.errors.clone(),
+            ),
+            Err(err) => ReportItem::new(
+                args.path.to_string_lossy(),
+                started.elapsed(),
+                vec![],
+                vec![err.to_string()],
+            ),
+        };
+        items.push(item);
+        summary?
+    };
+
+    if let Some(path) = &args.report {
+        RunReport::new("validate", items).write(path)?;
+    }
+
+    match args.format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        ReportFormat::Text if summary.rules.is_empty() => {
+            for warning in &summary.warnings {
+                log::warn!("{warning}");
+            }
+
+            for error in &summary.errors {
+                log::error!("{error}");
+            }
+        }
+        ReportFormat::Text => {
+            for report in &summary.rules {
+                let level = match report.severity {
+                    Severity::Error => log::Level::Error,
+                    _ => log::Level::Warn,
+                };
+                log::log!(
+                    level,
+                    "{} ({} {})",
+                    report.rule,
+                    report.count,
+                    report.severity
+                );
+                for finding in &report.findings {
+                    log::log!(level, "  {finding}");
+                }
+            }
+        }
+    }
+
+    if !summary.errors.is_empty() {
+        let count = summary.errors.len().to_string();
+        let message = tf("validate.found", &[("count", &count)]);
+        return Err(FailedWith::new(message, &summary).into());
+    }
+
+    Ok(summary)
+}
+
+/// The `--explain` text of a rule: its name, default severity and how to fix
+/// what it reports.
+fn explain(name: &str) -> CfxResult<String> {
+    let Some(rule) = find_rule(name) else {
+        let names: Vec<_> = RULES.iter().map(|rule| rule.name()).collect();
+        return Err(format!("Unknown rule `{name}`, the rules are {}", names.join(", ")).into());
+    };
+
+    Ok(format!(
+        "{} (default: {})\n\n{}",
+        rule.name(),
+        rule.default_severity(),
+        rule.explanation()
+    ))
+}
+
+/// Runs the lint rules against a resource, at the severities the user config
+/// and then the resource's `cfx.toml` override. The rules check among others
+/// that every local path the manifest references matches a file, using the
+/// same expansion `pack-resource` selects files with, and that AWC
+/// containers parse.
+pub fn validate_resource(resource_dir: &Path) -> CfxResult<ValidateSummary> {
+    let user = Config::load()?.lint;
+    let mut unknown = unknown_rules(&user, "the user config");
+
+    let project = match resource_dir.join(PROJECT_FILE).is_file() {
+        true => ProjectConfig::load(resource_dir)?.lint,
+        false => LintConfig::new(),
+    };
+    unknown.extend(unknown_rules(&project, PROJECT_FILE));
+
+    for message in unknown {
+        log::warn!("{message}");
+    }
+
+    validate_resource_with(resource_dir, &[&user, &project])
+}
+
+/// Runs the lint rules with severity `overrides`, later ones taking
+/// precedence.
+pub fn validate_resource_with(
+    resource_dir: &Path,
+    overrides: &[&LintConfig],
+) -> CfxResult<ValidateSummary> {
+    let manifest_path = resource_dir.join("fxmanifest.lua");
+    if !manifest_path.is_file() {
+        return Err(format!("No fxmanifest.lua or server.cfg found in {resource_dir:?}").into());
+    }
+
+    let manifest = Manifest::parse(&read_to_string(manifest_path)?)?;
+    let escrow = detect_escrow(resource_dir, &manifest)?;
+    let context = LintContext::new(resource_dir, &manifest, escrow.as_ref())?;
+
+    let mut summary = ValidateSummary {
+        rules: run_rules(&context, overrides)?,
+        ..Default::default()
+    };
+    for report in &summary.rules {
+        let messages = match report.severity {
+            Severity::Error => &mut summary.errors,
+            _ => &mut summary.warnings,
+        };
+        messages.extend(report.findings.iter().cloned());
+    }
+
+    summary.escrow = escrow;
+    Ok(summary)
+}
+
+/// Runs [`check_vehicles`] and reports what it finds as errors, under the
+/// `vehicles` rule.
+fn add_vehicle_findings(resource_dir: &Path, summary: &mut ValidateSummary) -> CfxResult<()> {
+    let findings = check_vehicles(resource_dir)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    summary.errors.extend(findings.iter().cloned());
+    summary.rules.push(RuleReport {
+        rule: "vehicles",
+        severity: Severity::Error,
+        count: findings.len(),
+        findings,
+    });
+    Ok(())
+}
+
+/// Meta files of the `data_file` entries of `kind`, parsed as `T`.
+fn read_vehicle_metas<T: DeserializeOwned>(
+    resource_dir: &Path,
+    manifest: &Manifest,
+    kind: &str,
+) -> CfxResult<Vec<T>> {
+    let mut result = vec![];
+    for directive in &manifest.directives {
+        let [name, path] = directive.values.as_slice() else {
+            continue;
+        };
+
+        if directive.name != "data_file" || name != kind {
+            continue;
+        }
+
+        for file in expand_pattern(resource_dir, path)? {
+            let relative = file.strip_prefix(resource_dir).unwrap_or(&file).display();
+            let meta = from_xml(&read_to_string(&file)?)
+                .map_err(|err| format!("{relative} is not a valid {kind}: {err}"))?;
+            result.push(meta);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Cross-checks the vehicles of an add-on, the usual cause of invisible or
+/// exploding cars: every model in a VEHICLE_METADATA_FILE needs the handling
+/// entry its `handlingId` names, a VEHICLE_VARIATION_FILE entry, a `.yft`
+/// in stream/ and the `.ytd` its `txdName` names. Names are compared
+/// case-insensitively, like the game hashes them.
+pub fn check_vehicles(resource_dir: &Path) -> CfxResult<Vec<String>> {
+    let manifest = Manifest::parse(&read_to_string(resource_dir.join("fxmanifest.lua"))?)?;
+    let vehicles: Vec<VehiclesMeta> =
+        read_vehicle_metas(resource_dir, &manifest, "VEHICLE_METADATA_FILE")?;
+    if vehicles.is_empty() {
+        return Ok(vec![
+            "No VEHICLE_METADATA_FILE data_file lists a vehicles.meta to check".to_owned(),
+        ]);
+    }
+
+    let handling = read_vehicle_metas::<HandlingMeta>(resource_dir, &manifest, "HANDLING_FILE")?
+        .into_iter()
+        .flat_map(|meta| meta.handling_data.items)
+        .map(|item| item.handling_name.to_lowercase())
+        .collect::<HashSet<_>>();
+    let variations =
+        read_vehicle_metas::<CarVariationsMeta>(resource_dir, &manifest, "VEHICLE_VARIATION_FILE")?
+            .into_iter()
+            .flat_map(|meta| meta.variation_data.items)
+            .map(|item| item.model_name.to_lowercase())
+            .collect::<HashSet<_>>();
+    let streamed = expand_pattern(resource_dir, "stream")?
+        .into_iter()
+        .filter_map(|file| Some(file.file_name()?.to_string_lossy().to_lowercase()))
+        .collect::<HashSet<_>>();
+
+    let mut findings = vec![];
+    for vehicle in vehicles.iter().flat_map(|meta| &meta.init_datas.items) {
+        let model = &vehicle.model_name;
+        if !handling.contains(&vehicle.handling_id.to_lowercase()) {
+            findings.push(tf(
+                "validate.vehicle.handling",
+                &[("model", model), ("handling", &vehicle.handling_id)],
+            ));
+        }
+
+        if !variations.contains(&model.to_lowercase()) {
+            findings.push(tf("validate.vehicle.variation", &[("model", model)]));
+        }
+
+        if !streamed.contains(&format!("{}.yft", model.to_lowercase())) {
+            findings.push(tf("validate.vehicle.yft", &[("model", model)]));
+        }
+
+        let txd = &vehicle.txd_name;
+        if !txd.is_empty() && !streamed.contains(&format!("{}.ytd", txd.to_lowercase())) {
+            findings.push(tf(
+                "validate.vehicle.txd",
+                &[("model", model), ("txd", txd)],
+            ));
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Checks that every resource declaring required convars in its `cfx.toml`
+/// has them set somewhere in the server configuration, and that the server
+/// satisfies the `/server:<build>` and `/onesync` dependencies of every
+/// manifest. Every resource is added to `items` for `--report`.
+fn validate_server(
+    server_dir: &Path,
+    server_cfg: &Path,
+    artifacts: Option<&Path>,
+    items: &mut Vec<ReportItem>,
+) -> CfxResult<ValidateSummary> {
+    let mut convars = HashMap::new();
+    collect_convars(server_dir, server_cfg, &mut convars, &mut HashSet::new())?;
+    let onesync = convars
+        .get("onesync")
+        .is_some_and(|value| ONESYNC_ENABLED.contains(&value.as_str()));
+
+    let build = match artifacts {
+        Some(dir) => Some(
+            server_build(dir)
+                .ok_or_else(|| format!("No FXServer build found in {}", dir.display()))?,
+        ),
+        None => artifact_candidates(server_dir)
+            .iter()
+            .find_map(|dir| server_build(dir)),
+    };
+
+    let mut warnings = vec![];
+    let mut unknown_build = false;
+    for resource_dir in find_projects(&server_dir.join("resources"))? {
+        let started = Instant::now();
+        let name = resource_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let mut messages = vec![];
+
+        if resource_dir.join(PROJECT_FILE).is_file() {
+            let project = ProjectConfig::load(&resource_dir)?;
+            messages.extend(
+                project
+                    .required_convars
+                    .iter()
+                    .filter(|&convar| !convars.contains_key(convar))
+                    .map(|convar| {
+                        tf(
+                            "validate.missing_convar",
+                            &[("name", &project.name), ("convar", convar)],
+                        )
+                    }),
+            );
+        }
+
+        let manifest_path = resource_dir.join("fxmanifest.lua");
+        if manifest_path.is_file() {
+            let manifest = Manifest::parse(&read_to_string(manifest_path)?)?;
+            let constraints = ServerConstraints::from_manifest(&manifest);
+            match (constraints.min_build, build) {
+                (Some(required), Some(build)) if build < required => messages.push(tf(
+                    "validate.server_build",
+                    &[
+                        ("name", &name),
+                        ("required", &required.to_string()),
+                        ("build", &build.to_string()),
+                    ],
+                )),
+                (Some(_), None) => unknown_build = true,
+                _ => {}
+            }
+
+            if constraints.onesync && !onesync {
+                messages.push(tf("validate.onesync", &[("name", &name)]));
+            }
+        }
+
+        warnings.extend(messages.iter().cloned());
+        items.push(ReportItem::new(
+            resource_dir.strip_prefix(server_dir)?.to_string_lossy(),
+            started.elapsed(),
+            messages,
+            vec![],
+        ));
+    }
+
+    if unknown_build {
+        log::warn!("{}", t("validate.unknown_build"));
+    }
+
+    Ok(ValidateSummary {
+        warnings,
+        ..Default::default()
+    })
+}
+
+/// Values of the `onesync` convar that turn it on.
+const ONESYNC_ENABLED: [&str; 4] = ["on", "legacy", "true", "1"];
+
+/// What a manifest requires of the server through its dependencies.
+#[derive(Debug, Default, PartialEq)]
+struct ServerConstraints {
+    /// From `dependency '/server:<build>'`
+    min_build: Option<u32>,
+    /// From `dependency '/onesync'`
+    onesync: bool,
+}
+
+impl ServerConstraints {
+    fn from_manifest(manifest: &Manifest) -> Self {
+        let mut constraints = Self::default();
+        for dependency in manifest.values(&["dependency", "dependencies"]) {
+            if dependency == "/onesync" {
+                constraints.onesync = true;
+            } else if let Some(build) = dependency
+                .strip_prefix("/server:")
+                .and_then(|build| build.parse::<u32>().ok())
+            {
+                constraints.min_build = constraints.min_build.max(Some(build));
+            }
+        }
+
+        constraints
+    }
+}
+
+/// Where the FXServer binaries usually are relative to the server data.
+fn artifact_candidates(server_dir: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![server_dir.to_path_buf(), server_dir.join("artifacts")];
+    if let Some(parent) = server_dir.parent() {
+        candidates.push(parent.join("server"));
+    }
+
+    candidates
+}
+
+/// The FXServer build of an artifact directory, read from its `version.txt`
+/// or from a directory name like `7290-<commit>` as artifacts are published.
+fn server_build(dir: &Path) -> Option<u32> {
+    for file in ["version.txt", "citizen/version.txt"] {
+        if let Some(build) = read_to_string(dir.join(file))
+            .ok()
+            .and_then(|content| parse_server_build(&content))
+        {
+            return Some(build);
+        }
+    }
+
+    let name = dir
+        .canonicalize()
+        .ok()?
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    let (build, commit) = name.split_once('-')?;
+    if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    build.parse().ok()
+}
+
+/// Takes the build from a version such as `v1.0.0.7290`, or a bare number.
+fn parse_server_build(content: &str) -> Option<u32> {
+    let version = content.split_whitespace().find_map(|word| {
+        let parts = word.trim_start_matches('v').split('.').collect::<Vec<_>>();
+        match parts.as_slice() {
+            [.., build] if parts.len() == 4 => build.parse().ok(),
+            _ => None,
+        }
+    });
+
+    version.or_else(|| content.trim().parse().ok())
+}
+
+/// Collects convars and their values from `set`, `setr` and `sets` lines,
+/// following `exec` lines relative to the server directory.
+fn collect_convars(
+    server_dir: &Path,
+    cfg: &Path,
+    convars: &mut HashMap<String, String>,
+    visited: &mut HashSet<PathBuf>,
+) -> CfxResult<()> {
+    if !visited.insert(cfg.to_path_buf()) {
+        return Ok(());
+    }
+
+    let content = read_to_string(cfg).map_err(|err| format!("Could not read {cfg:?}: {err}"))?;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("set" | "setr" | "sets"), Some(name)) => {
+                let value = parts.next().unwrap_or_default().trim_matches('"');
+                convars.insert(name.trim_matches('"').to_owned(), value.to_owned());
+            }
+            (Some("exec"), Some(path)) => {
+                let path = server_dir.join(path.trim_matches('"'));
+                collect_convars(server_dir, &path, convars, visited)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn find_projects(dir: &Path) -> CfxResult<Vec<PathBuf>> {
+    let mut result = vec![];
+    if !dir.is_dir() {
+        return Ok(result);
+    }
+
+    if dir.join(PROJECT_FILE).is_file() || dir.join("fxmanifest.lua").is_file() {
+        result.push(dir.to_path_buf());
+        return Ok(result);
+    }
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            result.extend(find_projects(&path)?);
+        }
+    }
+
+    result.sort();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    fn write_project(server_dir: &Path, relative: &str, convars: &[&str]) {
+        let dir = server_dir.join(relative);
+        create_dir_all(&dir).unwrap();
+
+        let project = ProjectConfig {
+            name: dir.file_name().unwrap().to_string_lossy().to_string(),
+            required_convars: convars.iter().map(|&convar| convar.to_owned()).collect(),
+            ..Default::default()
+        };
+        project.save(&dir).unwrap();
+    }
+
+    #[test]
+    fn server_missing_convar_test() {
+        let server = tempfile::tempdir().unwrap();
+        write(
+            server.path().join("server.cfg"),
+            "exec database.cfg\nsetr inventory:framework \"esx\"\n",
+        )
+        .unwrap();
+        write(
+            server.path().join("database.cfg"),
+            "set mysql_connection_string \"\"\n",
+        )
+        .unwrap();
+
+        write_project(
+            server.path(),
+            "resources/[local]/shop",
+            &["inventory:framework"],
+        );
+        write_project(
+            server.path(),
+            "resources/bank",
+            &["mysql_connection_string", "bank:currency"],
+        );
+
+        let args = ValidateArgs {
+            path: server.path().to_path_buf(),
+            format: ReportFormat::Text,
+            explain: None,
+            report: Some(server.path().join("report.json")),
+            artifacts: None,
+            vehicles: false,
+        };
+        let summary = handle_validate_command(&args).unwrap();
+
+        assert_eq!(
+            summary.warnings,
+            vec!["bank requires convar `bank:currency` but server.cfg does not set it"]
+        );
+
+        let report = std::fs::read_to_string(server.path().join("report.json")).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+        let statuses = report["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                (
+                    item["item"].as_str().unwrap(),
+                    item["status"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("resources/[local]/shop", "ok"),
+                ("resources/bank", "warning")
+            ]
+        );
+    }
+
+    #[test]
+    fn server_constraints_test() {
+        let root = tempfile::tempdir().unwrap();
+        let server = root.path().join("server-data");
+        let artifacts = root.path().join("server");
+        create_dir_all(&artifacts).unwrap();
+        write(
+            artifacts.join("version.txt"),
+            "FXServer-master v1.0.0.7290 linux\n",
+        )
+        .unwrap();
+        create_dir_all(&server).unwrap();
+        write(server.join("server.cfg"), "set onesync legacy\n").unwrap();
+
+        for (name, dependencies) in [
+            ("old", "'/server:5181'"),
+            ("new", "'/server:9000', '/onesync'"),
+        ] {
+            let dir = server.join("resources").join(name);
+            create_dir_all(&dir).unwrap();
+            write(
+                dir.join("fxmanifest.lua"),
+                format!("fx_version 'cerulean'\ndependencies {{ {dependencies} }}\n"),
+            )
+            .unwrap();
+        }
+
+        let summary =
+            validate_server(&server, &server.join("server.cfg"), None, &mut vec![]).unwrap();
+        assert_eq!(
+            summary.warnings,
+            vec!["new requires server build 9000 but the server is build 7290"]
+        );
+
+        write(server.join("server.cfg"), "set onesync off\n").unwrap();
+        let summary =
+            validate_server(&server, &server.join("server.cfg"), None, &mut vec![]).unwrap();
+        assert_eq!(summary.warnings.len(), 2);
+        assert!(summary.warnings[1].starts_with("new requires OneSync"));
+    }
+
+    #[test]
+    fn server_build_test() {
+        assert_eq!(parse_server_build("v1.0.0.7290"), Some(7290));
+        assert_eq!(
+            parse_server_build("FXServer-master SERVER v1.0.0.12092 win32"),
+            Some(12092)
+        );
+        assert_eq!(parse_server_build("6683\n"), Some(6683));
+        assert_eq!(parse_server_build("unknown"), None);
+
+        let root = tempfile::tempdir().unwrap();
+        let artifact = root
+            .path()
+            .join("7290-a654bcc2adfa27c4e020fc915a1a6343c3b4f921");
+        create_dir_all(&artifact).unwrap();
+        assert_eq!(server_build(&artifact), Some(7290));
+        assert_eq!(server_build(root.path()), None);
+    }
+
+    #[test]
+    fn resource_missing_script_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+        write(dir.path().join("client/main.lua"), "").unwrap();
+        write(
+            dir.path().join("fxmanifest.lua"),
+            "fx_version 'cerulean'\nshared_script '@ox_lib/init.lua'\nclient_script 'client/*.lua'\nserver_script 'server/main.lua'",
+        )
+        .unwrap();
+
+        let summary = validate_resource(dir.path()).unwrap();
+        assert_eq!(
+            summary.errors,
+            vec!["`server/main.lua` does not match any file"]
+        );
+    }
+
+    #[test]
+    fn resource_name_divergence_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let resource_dir = dir.path().join("bank");
+        create_dir_all(&resource_dir).unwrap();
+        write(
+            resource_dir.join("fxmanifest.lua"),
+            "fx_version 'cerulean'\nname 'bank'\n",
+        )
+        .unwrap();
+
+        assert!(validate_resource(&resource_dir)
+            .unwrap()
+            .warnings
+            .is_empty());
+
+        write(
+            resource_dir.join("fxmanifest.lua"),
+            "fx_version 'cerulean'\nname 'old-bank'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            validate_resource(&resource_dir).unwrap().warnings,
+            vec!["The manifest is named `old-bank` but the folder is `bank`, the server ensures it as `bank`"]
+        );
+    }
+
+    /// A vehicle add-on with `models` in vehicles.meta as (model name,
+    /// handlingId), handling entries and variations for `adder` only, and
+    /// `streamed` files in stream/.
+    fn write_vehicle_addon(dir: &Path, models: &[(&str, &str)], streamed: &[&str]) {
+        create_dir_all(dir.join("data")).unwrap();
+        create_dir_all(dir.join("stream/cars")).unwrap();
+        write(
+            dir.join("fxmanifest.lua"),
+            "fx_version 'cerulean'\n\
+             files { 'data/*.meta' }\n\
+             data_file 'HANDLING_FILE' 'data/handling.meta'\n\
+             data_file 'VEHICLE_METADATA_FILE' 'data/vehicles.meta'\n\
+             data_file 'VEHICLE_VARIATION_FILE' 'data/carvariations.meta'\n",
+        )
+        .unwrap();
+
+        let items = models
+            .iter()
+            .map(|(model, handling)| {
+                format!(
+                    "<Item><modelName>{model}</modelName><txdName>{model}</txdName>\
+                     <handlingId>{handling}</handlingId><type>VEHICLE_TYPE_CAR</type></Item>"
+                )
+            })
+            .collect::<String>();
+        write(
+            dir.join("data/vehicles.meta"),
+            format!(
+                "<CVehicleModelInfo__InitDataList><residentTxd>vehshare</residentTxd>\
+                 <InitDatas>{items}</InitDatas></CVehicleModelInfo__InitDataList>"
+            ),
+        )
+        .unwrap();
+        write(
+            dir.join("data/handling.meta"),
+            "<CHandlingDataMgr><HandlingData><Item type=\"CHandlingData\">\
+             <handlingName>ADDER</handlingName><fMass value=\"1800.0\" /></Item>\
+             </HandlingData></CHandlingDataMgr>",
+        )
+        .unwrap();
+        write(
+            dir.join("data/carvariations.meta"),
+            "<CVehicleModelInfoVariation><variationData><Item><modelName>adder</modelName>\
+             <kits><Item>0_default_modkit</Item></kits></Item></variationData>\
+             </CVehicleModelInfoVariation>",
+        )
+        .unwrap();
+
+        for file in streamed {
+            write(dir.join("stream/cars").join(file), "").unwrap();
+        }
+    }
+
+    #[test]
+    fn check_vehicles_test() {
+        let dir = tempfile::tempdir().unwrap();
+        write_vehicle_addon(
+            dir.path(),
+            &[("Adder", "adder")],
+            &["adder.yft", "ADDER.ytd"],
+        );
+        assert!(check_vehicles(dir.path()).unwrap().is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        write_vehicle_addon(
+            dir.path(),
+            &[("adder", "adder"), ("zentorno", "ZENTORNO")],
+            &["adder.yft", "zentorno.ytd"],
+        );
+        assert_eq!(
+            check_vehicles(dir.path()).unwrap(),
+            [
+                "`adder` uses txdName `adder` but stream/ has no adder.ytd",
+                "`zentorno` uses handlingId `ZENTORNO`, which no HANDLING_FILE defines",
+                "`zentorno` has no entry in any VEHICLE_VARIATION_FILE",
+                "`zentorno` has no zentorno.yft in stream/",
+            ]
+        );
+
+        let args = ValidateArgs {
+            path: dir.path().to_path_buf(),
+            format: ReportFormat::Text,
+            explain: None,
+            report: None,
+            artifacts: None,
+            vehicles: true,
+        };
+        let Err(err) = handle_validate_command(&args) else {
+            panic!("expected the vehicle findings to fail validation");
+        };
+        assert_eq!(err.to_string(), "Validation found 4 error(s)");
+
+        write(
+            dir.path().join("data/handling.meta"),
+            "<CHandlingDataMgr><Item>",
+        )
+        .unwrap();
+        let err = check_vehicles(dir.path()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("data/handling.meta is not a valid HANDLING_FILE"));
+
+        write(dir.path().join("fxmanifest.lua"), "fx_version 'cerulean'\n").unwrap();
+        assert_eq!(
+            check_vehicles(dir.path()).unwrap(),
+            ["No VEHICLE_METADATA_FILE data_file lists a vehicles.meta to check"]
+        );
+    }
+
+    #[test]
+    fn missing_manifest_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = ValidateArgs {
+            path: dir.path().to_path_buf(),
+            format: ReportFormat::Text,
+            explain: None,
+            report: None,
+            artifacts: None,
+            vehicles: false,
+        };
+
+        assert!(handle_validate_command(&args).is_err());
+    }
+
+    fn lint(manifest: &str, overrides: &[&LintConfig]) -> Vec<(&'static str, Severity, usize)> {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+        write(dir.path().join("client/main.lua"), "").unwrap();
+        write(dir.path().join("fxmanifest.lua"), manifest).unwrap();
+
+        validate_resource_with(dir.path(), overrides)
+            .unwrap()
+            .rules
+            .into_iter()
+            .map(|report| (report.rule, report.severity, report.count))
+            .collect()
+    }
+
+    #[test]
+    fn lint_rules_test() {
+        assert_eq!(
+            lint("client_script 'client/main.lua'", &[]),
+            vec![("missing-fx-version", Severity::Error, 1)]
+        );
+        assert_eq!(
+            lint(
+                "fx_version 'cerulean'\nclient_scripts { 'client/main.lua', 'client/main.lua', '/srv/client/main.lua' }\nserver_script 'C:/server.lua'",
+                &[]
+            ),
+            vec![
+                ("script-not-found", Severity::Error, 2),
+                ("absolute-script-path", Severity::Error, 2),
+                ("duplicate-script", Severity::Warning, 1),
+            ]
+        );
+        assert_eq!(
+            lint(
+                "fx_version 'cerulean'\nui_page 'client/main.lua'\nfiles { 'client/*.lua' }\ndata_file 'HANDLING_FILE' 'client/main.lua'",
+                &[]
+            ),
+            vec![]
+        );
+        assert_eq!(
+            lint("fx_version 'cerulean'\ngames { 'gta5', 'rdr3' }", &[]),
+            vec![("rdr3-warning-missing", Severity::Warning, 1)]
+        );
+        assert_eq!(
+            lint(
+                "fx_version 'cerulean'\ngame 'rdr3'\nrdr3_warning 'I acknowledge'",
+                &[]
+            ),
+            vec![]
+        );
+        assert_eq!(
+            lint(
+                "fx_version 'cerulean'\nui_page 'client/main.lua'\ndata_file 'HANDLNG_FILE' 'client/main.lua'",
+                &[]
+            ),
+            vec![
+                ("ui-page-not-in-files", Severity::Error, 1),
+                ("unknown-data-file-type", Severity::Warning, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_overrides_test() {
+        let manifest = "client_script 'client/main.lua'\nclient_script 'client/main.lua'";
+        let user = LintConfig::from([
+            ("missing-fx-version".to_owned(), Severity::Warning),
+            ("duplicate-script".to_owned(), Severity::Error),
+        ]);
+        let project = LintConfig::from([("missing-fx-version".to_owned(), Severity::Off)]);
+
+        assert_eq!(
+            lint(manifest, &[&user]),
+            vec![
+                ("missing-fx-version", Severity::Warning, 1),
+                ("duplicate-script", Severity::Error, 1),
+            ]
+        );
+        assert_eq!(
+            lint(manifest, &[&user, &project]),
+            vec![("duplicate-script", Severity::Error, 1)]
+        );
+
+        let unknown = LintConfig::from([("no-such-rule".to_owned(), Severity::Error)]);
+        assert_eq!(
+            unknown_rules(&unknown, PROJECT_FILE),
+            vec!["Unknown lint rule `no-such-rule` in cfx.toml"]
+        );
+    }
+
+    #[test]
+    fn explain_test() {
+        let explanation = explain("lua54-missing-for-escrow").unwrap();
+        assert!(explanation.starts_with("lua54-missing-for-escrow (default: error)"));
+        assert!(explanation.contains("lua54 'yes'"));
+
+        assert!(explain("no-such-rule")
+            .unwrap_err()
+            .to_string()
+            .contains("missing-fx-version"));
+    }
+}
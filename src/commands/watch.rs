@@ -0,0 +1,303 @@
+use std::collections::BTreeSet;
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Args;
+use serde::Serialize;
+
+use crate::cancel::CancelToken;
+use crate::commands::check::{check_resource, Diagnostic};
+use crate::commands::check_events::check_events;
+use crate::commands::check_exports::{check_exports, ExportIssueKind};
+use crate::commands::validate::validate_resource;
+use crate::config::Config;
+use crate::lua::check_syntax;
+use crate::scan::read_script;
+use crate::watch::Watcher;
+use crate::CfxResult;
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Resource directory
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// On every change, validate the manifest, check the syntax of the
+    /// changed scripts and report export and event issues in them
+    #[arg(long)]
+    pub lint: bool,
+
+    /// Directory of the resources exports and events are cross-referenced
+    /// against, by default the one containing the resource
+    #[arg(long, value_name = "DIR")]
+    pub resources_dir: Option<PathBuf>,
+
+    /// Clear the screen before each run
+    #[arg(long)]
+    pub clear: bool,
+
+    /// Milliseconds files have to stay unchanged before a run, so that a save
+    /// touching several files runs once
+    #[arg(long, value_name = "MS", default_value_t = 300)]
+    pub debounce: u64,
+}
+
+/// Findings of one run over the files changed since the previous one.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct LintRun {
+    pub changed: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WatchSummary {
+    pub runs: usize,
+    pub failed_runs: usize,
+    /// The run before Ctrl-C stopped the watch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<LintRun>,
+}
+
+/// Lints the resource once, then again whenever its files change, until
+/// `cancel` is cancelled. Deploying on change is not available yet, so
+/// `--lint` is required.
+pub fn handle_watch_command(args: &WatchArgs, cancel: &CancelToken) -> CfxResult<WatchSummary> {
+    if !args.lint {
+        return Err(
+            "Only `watch --lint` is available, deploying on change is not supported".into(),
+        );
+    }
+
+    let resource_dir = args
+        .path
+        .canonicalize()
+        .map_err(|err| format!("Could not open {}: {err}", args.path.display()))?;
+    let resources_dir = match &args.resources_dir {
+        Some(dir) => dir.canonicalize()?,
+        None => resource_dir.parent().unwrap_or(&resource_dir).to_path_buf(),
+    };
+
+    let mut watcher = Watcher::new(&resource_dir, Duration::from_millis(args.debounce))?;
+    let mut changed = watcher.files();
+    let mut summary = WatchSummary::default();
+    log::info!("Watching {}, Ctrl-C to stop", resource_dir.display());
+    loop {
+        if args.clear {
+            print!("\x1B[2J\x1B[H");
+            std::io::stdout().flush()?;
+        }
+
+        let run = lint_changes(&resource_dir, &resources_dir, &changed);
+        log_run(&run);
+        summary.runs += 1;
+        if !run.errors.is_empty() {
+            summary.failed_runs += 1;
+        }
+        summary.last = Some(run);
+
+        match watcher.wait(cancel)? {
+            Some(files) => changed = files,
+            None => return Ok(summary),
+        }
+    }
+}
+
+fn log_run(run: &LintRun) {
+    let warnings = match run.warnings.len() {
+        0 => String::new(),
+        count => format!(", {count} warning(s)"),
+    };
+    match run.errors.len() {
+        0 => log::info!("Passed: {} file(s) changed{warnings}", run.changed),
+        count => log::error!(
+            "Failed: {} file(s) changed, {count} error(s){warnings}",
+            run.changed
+        ),
+    }
+
+    for error in &run.errors {
+        log::error!("    {error}");
+    }
+
+    for warning in &run.warnings {
+        log::warn!("    {warning}");
+    }
+}
+
+/// Validates the manifest of `resource_dir`, checks the syntax of the
+/// `changed` scripts and reports the export calls and events in them that
+/// do not match the resources of `resources_dir`. Failures of a check are
+/// errors of the run, so that a half-written manifest or a file deleted
+/// mid-save does not end the watch.
+pub fn lint_changes(resource_dir: &Path, resources_dir: &Path, changed: &[PathBuf]) -> LintRun {
+    let mut run = LintRun {
+        changed: changed.len(),
+        ..Default::default()
+    };
+
+    match validate_resource(resource_dir) {
+        Ok(summary) => {
+            run.errors.extend(summary.errors);
+            run.warnings.extend(summary.warnings);
+        }
+        Err(err) => run.errors.push(err.to_string()),
+    }
+
+    let is_script = |file: &&PathBuf, extensions: &[&str]| {
+        file.is_file()
+            && file
+                .extension()
+                .is_some_and(|ext| extensions.iter().any(|known| ext == *known))
+    };
+    for file in changed.iter().filter(|file| is_script(file, &["lua"])) {
+        let source = match read_script(file) {
+            Ok(source) => source,
+            Err(err) => {
+                run.errors.push(err.to_string());
+                continue;
+            }
+        };
+        if let Err(error) = check_syntax(&source) {
+            let relative = file.strip_prefix(resource_dir).unwrap_or(file);
+            let diagnostic = Diagnostic {
+                file: relative.to_string_lossy().replace('\\', "/"),
+                line: Some(error.line),
+                column: Some(error.column),
+                message: error.message,
+            };
+            run.errors.push(diagnostic.to_string());
+        }
+    }
+
+    // The bundle and type checks cover the whole project, so they only run
+    // again once one of its sources changed
+    if changed.iter().any(|file| is_script(&file, &["js", "ts"])) {
+        match check_resource(resource_dir) {
+            Ok(summary) => {
+                let bundle_errors = summary
+                    .errors
+                    .iter()
+                    .filter(|error| !error.file.ends_with(".lua"));
+                run.errors.extend(bundle_errors.map(ToString::to_string));
+                run.warnings.extend(summary.warnings);
+            }
+            Err(err) => run.errors.push(err.to_string()),
+        }
+    }
+
+    let changed = changed.iter().collect::<BTreeSet<_>>();
+    let ignored = match Config::load() {
+        Ok(config) => config.ignored_exports,
+        Err(err) => {
+            run.errors.push(err.to_string());
+            vec![]
+        }
+    };
+    let issues = match check_exports(resources_dir, &ignored) {
+        Ok(exports) => exports.issues,
+        Err(err) => {
+            run.errors.push(err.to_string());
+            vec![]
+        }
+    };
+    for issue in issues {
+        if changed.contains(&issue.location.file) {
+            let reason = match issue.kind {
+                ExportIssueKind::UnknownResource => "unknown resource",
+                ExportIssueKind::UnknownExport => "unknown export",
+            };
+            run.warnings.push(format!(
+                "{}: exports['{}']:{} ({reason})",
+                issue.location, issue.resource, issue.export
+            ));
+        }
+    }
+
+    let reports = match check_events(resources_dir) {
+        Ok(events) => events.reports,
+        Err(err) => {
+            run.errors.push(err.to_string());
+            vec![]
+        }
+    };
+    for report in reports {
+        let usages = report.registrations.iter().chain(&report.triggers);
+        for usage in usages.filter(|usage| changed.contains(&usage.location.file)) {
+            let issues = report
+                .issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            run.warnings.push(format!(
+                "{}: {} `{}` ({})",
+                usage.location,
+                usage.function,
+                report.name,
+                issues.join(", ")
+            ));
+        }
+    }
+
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn lint_changes_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let resources_dir = dir.path().canonicalize().unwrap();
+        let resource_dir = resources_dir.join("shop");
+        create_dir_all(&resource_dir).unwrap();
+        write(
+            resource_dir.join("fxmanifest.lua"),
+            "fx_version \"cerulean\"\ngame \"gta5\"\n\nclient_script \"client.lua\"\nserver_script \"server.lua\"\n",
+        )
+        .unwrap();
+        let (client, server) = (
+            resource_dir.join("client.lua"),
+            resource_dir.join("server.lua"),
+        );
+        write(&client, "exports['bank']:deposit(1)\nlocal x = \n").unwrap();
+        write(&server, "RegisterNetEvent('shop:buy', function() end)\n").unwrap();
+
+        let run = lint_changes(&resource_dir, &resources_dir, std::slice::from_ref(&client));
+        assert_eq!(run.changed, 1);
+        assert_eq!(run.errors.len(), 1, "{:?}", run.errors);
+        assert!(
+            run.errors[0].starts_with("client.lua:3:"),
+            "{:?}",
+            run.errors
+        );
+        assert!(run.warnings.contains(&format!(
+            "{}:1: exports['bank']:deposit (unknown resource)",
+            client.display()
+        )));
+
+        // Only the changed files are checked
+        let run = lint_changes(&resource_dir, &resources_dir, std::slice::from_ref(&server));
+        assert!(run.errors.is_empty(), "{:?}", run.errors);
+        assert!(run.warnings.iter().all(|warning| !warning.contains("bank")));
+
+        write(resource_dir.join("fxmanifest.lua"), "fx_version 'cerulean").unwrap();
+        let run = lint_changes(&resource_dir, &resources_dir, &[]);
+        assert_eq!(run.errors.len(), 1);
+
+        // A scan that fails elsewhere in the server is reported, not returned
+        let broken = resources_dir.join("broken");
+        create_dir_all(&broken).unwrap();
+        write(broken.join("fxmanifest.lua"), b"author 'Jos\xe9'\n").unwrap();
+        let run = lint_changes(&resource_dir, &resources_dir, &[server]);
+        assert!(
+            run.errors.iter().any(|error| error.contains("UTF-8")),
+            "{:?}",
+            run.errors
+        );
+    }
+}
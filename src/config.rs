@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_to_string};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::atomic::atomic_write;
+use crate::commands::create::CreateAnswers;
+use crate::lint::LintConfig;
+use crate::lock::{lock_path, FileLock};
+use crate::CfxResult;
+
+const CONFIG_DIR_ENV: &str = "CFX_CONFIG_DIR";
+const CACHE_DIR_ENV: &str = "CFX_CACHE_DIR";
+const RESOURCES_ROOT_ENV: &str = "CFX_RESOURCES_ROOT";
+
+/// User-level settings persisted as `config.toml` in the platform config directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub templates: BTreeMap<String, TemplateEntry>,
+    /// `resource` or `resource:export` entries that `check-exports` never reports
+    #[serde(default)]
+    pub ignored_exports: Vec<String>,
+    /// Server `resources` directory that `create` places new projects in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources_root: Option<PathBuf>,
+    /// Severity overrides for `validate` rules, see `cfx validate --explain`
+    #[serde(default, skip_serializing_if = "LintConfig::is_empty")]
+    pub lint: LintConfig,
+    /// Size in MiB the download cache is trimmed to, least recently used first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_mb: Option<u64>,
+    /// Never touch the network, like passing `--offline` to every command
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    /// Language of the prompts and messages, below `--lang` and above `LANG`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Default answers for the `create` prompts, below `--answers` and flags
+    #[serde(default, skip_serializing_if = "CreateAnswers::is_empty")]
+    pub create: CreateAnswers,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateEntry {
+    /// Git URL or local directory the template is rendered from
+    pub source: String,
+    /// Unix timestamp of the last successful add or update
+    pub last_updated: u64,
+}
+
+impl Config {
+    pub fn path() -> CfxResult<PathBuf> {
+        Ok(config_dir()?.join("config.toml"))
+    }
+
+    pub fn load() -> CfxResult<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> CfxResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = read_to_string(path)?;
+        toml::from_str(&content).map_err(|err| format!("Invalid config {path:?}: {err}").into())
+    }
+
+    /// The configured resources root, overridable through `CFX_RESOURCES_ROOT`.
+    pub fn resources_root(&self) -> Option<PathBuf> {
+        std::env::var_os(RESOURCES_ROOT_ENV)
+            .map(PathBuf::from)
+            .or_else(|| self.resources_root.clone())
+    }
+
+    pub fn save_to(&self, path: &Path) -> CfxResult<()> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        atomic_write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Loads, changes and saves the config while holding its lock, so that
+    /// concurrent cfx processes do not drop each other's changes. Nothing is
+    /// saved when `change` fails.
+    pub fn update<T>(change: impl FnOnce(&mut Self) -> CfxResult<T>) -> CfxResult<T> {
+        Self::update_at(&Self::path()?, change)
+    }
+
+    pub fn update_at<T>(
+        path: &Path,
+        change: impl FnOnce(&mut Self) -> CfxResult<T>,
+    ) -> CfxResult<T> {
+        let _lock = FileLock::acquire(&lock_path(path))?;
+        let mut config = Self::load_from(path)?;
+        let result = change(&mut config)?;
+        config.save_to(path)?;
+
+        Ok(result)
+    }
+}
+
+/// Directory holding the user config, overridable through `CFX_CONFIG_DIR`.
+pub fn config_dir() -> CfxResult<PathBuf> {
+    resolve_dir(CONFIG_DIR_ENV, dirs::config_dir())
+}
+
+/// Directory holding disposable caches, overridable through `CFX_CACHE_DIR`.
+/// Everything below it can be regenerated, so it is always safe to delete.
+pub fn cache_dir() -> CfxResult<PathBuf> {
+    resolve_dir(CACHE_DIR_ENV, dirs::cache_dir())
+}
+
+fn resolve_dir(env: &str, platform_dir: Option<PathBuf>) -> CfxResult<PathBuf> {
+    if let Some(dir) = std::env::var_os(env) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    platform_dir
+        .map(|dir| dir.join("cfx"))
+        .ok_or_else(|| format!("Could not determine a directory, set {env}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trip_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/config.toml");
+
+        let mut config = Config::default();
+        config.templates.insert(
+            "starter".to_owned(),
+            TemplateEntry {
+                source: "https://example.com/starter.git".to_owned(),
+                last_updated: 42,
+            },
+        );
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.templates, config.templates);
+    }
+
+    #[test]
+    fn concurrent_update_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let writers = (0..2)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for i in 0..20 {
+                        Config::update_at(&path, |config| {
+                            let entry = TemplateEntry {
+                                source: format!("/templates/{writer}-{i}"),
+                                last_updated: i,
+                            };
+                            config.templates.insert(format!("{writer}-{i}"), entry);
+                            Ok(())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(Config::load_from(&path).unwrap().templates.len(), 40);
+    }
+
+    #[test]
+    fn config_missing_file_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&dir.path().join("config.toml")).unwrap();
+
+        assert!(config.templates.is_empty());
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::manifest::Manifest;
+
+/// Lines of context kept around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Renders a unified diff between `old` and `new`, or an empty string when
+/// they are equal. Meant for the small text files manifest edits touch.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = old.lines().collect::<Vec<&str>>();
+    let new_lines = new.lines().collect::<Vec<&str>>();
+    let edits = diff_lines(&old_lines, &new_lines);
+    if edits.iter().all(|(edit, _)| *edit == Edit::Keep) {
+        return String::new();
+    }
+
+    let mut result = format!("--- {old_name}\n+++ {new_name}\n");
+    let mut i = 0;
+    while i < edits.len() {
+        if edits[i].0 == Edit::Keep {
+            i += 1;
+            continue;
+        }
+
+        // Extend the hunk while changes are close enough to share context
+        let start = i.saturating_sub(CONTEXT);
+        let mut end = i;
+        let mut keeps = 0;
+        while end < edits.len() && keeps <= CONTEXT * 2 {
+            keeps = if edits[end].0 == Edit::Keep {
+                keeps + 1
+            } else {
+                0
+            };
+            end += 1;
+        }
+        end -= keeps.saturating_sub(CONTEXT);
+
+        let (old_start, new_start) = line_numbers(&edits[..start]);
+        let (old_len, new_len) = line_numbers(&edits[start..end]);
+        result.push_str(&format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            old_start + 1,
+            new_start + 1
+        ));
+
+        for (edit, line) in &edits[start..end] {
+            let prefix = match edit {
+                Edit::Keep => ' ',
+                Edit::Remove => '-',
+                Edit::Add => '+',
+            };
+            result.push(prefix);
+            result.push_str(line);
+            result.push('\n');
+        }
+
+        i = end;
+    }
+
+    result
+}
+
+/// Counts the old and new lines a run of edits spans.
+fn line_numbers(edits: &[(Edit, &str)]) -> (usize, usize) {
+    edits
+        .iter()
+        .fold((0, 0), |(old, new), (edit, _)| match edit {
+            Edit::Keep => (old + 1, new + 1),
+            Edit::Remove => (old + 1, new),
+            Edit::Add => (old, new + 1),
+        })
+}
+
+/// Manifest directives compared as lists of entries, with their singular and
+/// plural forms merged.
+const LIST_SECTIONS: [(&str, &[&str]); 5] = [
+    ("client_scripts", &["client_script", "client_scripts"]),
+    ("server_scripts", &["server_script", "server_scripts"]),
+    ("shared_scripts", &["shared_script", "shared_scripts"]),
+    ("files", &["file", "files"]),
+    ("dependencies", &["dependency", "dependencies"]),
+];
+
+/// Entries added to and removed from a list section of a manifest, and with
+/// `ordered` those kept but moved.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ListDiff {
+    pub section: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub moved: Vec<String>,
+}
+
+/// A directive whose values differ, with no values on the side that lacks it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ValueChange {
+    pub directive: String,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+/// Semantic differences between two manifests, blind to formatting, quote
+/// style and whether the singular or plural form of a directive is used.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ManifestDiff {
+    pub lists: Vec<ListDiff>,
+    pub directives: Vec<ValueChange>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty() && self.directives.is_empty()
+    }
+}
+
+impl fmt::Display for ManifestDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for list in &self.lists {
+            writeln!(f, "{}:", list.section)?;
+            for entry in &list.added {
+                writeln!(f, "  + {entry}")?;
+            }
+
+            for entry in &list.removed {
+                writeln!(f, "  - {entry}")?;
+            }
+
+            for entry in &list.moved {
+                writeln!(f, "  ~ {entry} (moved)")?;
+            }
+        }
+
+        for change in &self.directives {
+            writeln!(
+                f,
+                "{}: {} -> {}",
+                change.directive,
+                quoted(&change.old),
+                quoted(&change.new)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn quoted(values: &[String]) -> String {
+    if values.is_empty() {
+        return "(none)".to_owned();
+    }
+
+    values
+        .iter()
+        .map(|value| format!("{value:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Compares the scripts, files, dependencies and `data_file` entries of two
+/// manifests as sets, or with `ordered` also reports entries that moved, and
+/// every other directive by its values.
+pub fn diff_manifests(old: &Manifest, new: &Manifest, ordered: bool) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+    let old_lists = list_sections(old);
+    let new_lists = list_sections(new);
+    for ((section, old_entries), (_, new_entries)) in old_lists.iter().zip(&new_lists) {
+        let list = diff_list(section, old_entries, new_entries, ordered);
+        if !(list.added.is_empty() && list.removed.is_empty() && list.moved.is_empty()) {
+            diff.lists.push(list);
+        }
+    }
+
+    let old_values = other_directives(old);
+    let new_values = other_directives(new);
+    let mut names = old_values
+        .keys()
+        .chain(new_values.keys())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let old = old_values.get(name).cloned().unwrap_or_default();
+        let new = new_values.get(name).cloned().unwrap_or_default();
+        if old != new {
+            diff.directives.push(ValueChange {
+                directive: name.to_string(),
+                old,
+                new,
+            });
+        }
+    }
+
+    diff
+}
+
+/// The entries of every list section and of `data_file`, as `TYPE path`,
+/// without duplicates.
+fn list_sections(manifest: &Manifest) -> Vec<(&'static str, Vec<String>)> {
+    let mut sections = LIST_SECTIONS
+        .iter()
+        .map(|&(section, names)| {
+            let entries = manifest.values(names).into_iter().map(str::to_owned);
+            (section, entries.collect::<Vec<_>>())
+        })
+        .collect::<Vec<_>>();
+
+    let data_files = manifest
+        .directives
+        .iter()
+        .filter(|directive| directive.name == "data_file")
+        .map(|directive| directive.values.join(" "))
+        .collect();
+    sections.push(("data_files", data_files));
+
+    for (_, entries) in &mut sections {
+        let mut seen = HashSet::new();
+        entries.retain(|entry| seen.insert(entry.clone()));
+    }
+
+    sections
+}
+
+/// Values of the directives that are not list sections, by name.
+fn other_directives(manifest: &Manifest) -> BTreeMap<&str, Vec<String>> {
+    let mut result = BTreeMap::<&str, Vec<String>>::new();
+    for directive in &manifest.directives {
+        let listed = LIST_SECTIONS
+            .iter()
+            .any(|(_, names)| names.contains(&directive.name.as_str()));
+        if !listed && directive.name != "data_file" {
+            result
+                .entry(&directive.name)
+                .or_default()
+                .extend(directive.values.iter().cloned());
+        }
+    }
+
+    result
+}
+
+fn diff_list(section: &str, old: &[String], new: &[String], ordered: bool) -> ListDiff {
+    let added = new
+        .iter()
+        .filter(|entry| !old.contains(entry))
+        .cloned()
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|entry| !new.contains(entry))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Entries on both sides that the longest common subsequence leaves out
+    // changed their position relative to the others
+    let mut moved = vec![];
+    if ordered {
+        let old_common = old
+            .iter()
+            .filter(|entry| new.contains(entry))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let new_common = new
+            .iter()
+            .filter(|entry| old.contains(entry))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        moved = diff_lines(&old_common, &new_common)
+            .into_iter()
+            .filter(|(edit, _)| *edit == Edit::Add)
+            .map(|(_, entry)| entry.to_owned())
+            .collect();
+    }
+
+    ListDiff {
+        section: section.to_owned(),
+        added,
+        removed,
+        moved,
+    }
+}
+
+/// Longest-common-subsequence line diff.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Edit, &'a str)> {
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut result = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            result.push((Edit::Keep, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            result.push((Edit::Remove, old[i]));
+            i += 1;
+        } else {
+            result.push((Edit::Add, new[j]));
+            j += 1;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_test() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+
+        assert_eq!(
+            unified_diff(old, new, "a/fxmanifest.lua", "b/fxmanifest.lua"),
+            "--- a/fxmanifest.lua
++++ b/fxmanifest.lua
+@@ -1,7 +1,7 @@
+ a
+ b
+ c
+-d
++D
+ e
+ f
+ g
+@@ -11,3 +11,4 @@
+ k
+ l
+ m
++n
+"
+        );
+    }
+
+    #[test]
+    fn unified_diff_equal_test() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+
+    const OLD_MANIFEST: &str = r#"
+fx_version 'cerulean'
+game 'gta5'
+version '1.0.0'
+
+client_scripts { 'client/a.lua', 'client/b.lua', 'client/c.lua' }
+server_script 'server/main.lua'
+files { 'html/index.html' }
+data_file 'DLC_ITYP_REQUEST' 'stream/props.ytyp'
+"#;
+
+    #[test]
+    fn equivalent_manifests_test() {
+        let new = r#"fx_version "cerulean"
+game "gta5"
+version "1.0.0"
+client_script "client/a.lua"
+client_script "client/b.lua"
+client_script "client/c.lua"
+server_scripts {
+    "server/main.lua",
+}
+file "html/index.html"
+data_file "DLC_ITYP_REQUEST" "stream/props.ytyp"
+"#;
+
+        let old = Manifest::parse(OLD_MANIFEST).unwrap();
+        let new = Manifest::parse(new).unwrap();
+        assert!(diff_manifests(&old, &new, true).is_empty());
+    }
+
+    #[test]
+    fn diff_manifests_test() {
+        let new = r#"
+fx_version 'cerulean'
+game 'gta5'
+version '1.1.0'
+lua54 'yes'
+
+client_scripts { 'client/c.lua', 'client/a.lua', 'client/b.lua', 'client/d.lua' }
+files { 'html/index.html' }
+data_file 'DLC_ITYP_REQUEST' 'stream/props.ytyp'
+data_file 'AUDIO_WAVEPACK' 'audio/wavepack'
+"#;
+
+        let old = Manifest::parse(OLD_MANIFEST).unwrap();
+        let new = Manifest::parse(new).unwrap();
+
+        let diff = diff_manifests(&old, &new, false);
+        assert_eq!(
+            diff.to_string(),
+            r#"client_scripts:
+  + client/d.lua
+server_scripts:
+  - server/main.lua
+data_files:
+  + AUDIO_WAVEPACK audio/wavepack
+lua54: (none) -> "yes"
+version: "1.0.0" -> "1.1.0"
+"#
+        );
+
+        let diff = diff_manifests(&old, &new, true);
+        assert_eq!(diff.lists[0].moved, ["client/c.lua"]);
+    }
+}
@@ -0,0 +1,213 @@
+use std::error::Error;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::cancel::{Cancelled, Interrupted};
+use crate::network::OfflineError;
+
+/// Failures with a stable code, shown next to their message and in the JSON
+/// envelope so that scripts can match on them, and explained by `cfx explain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidMagic,
+    UnsupportedFormat,
+    Offline,
+    Cancelled,
+    Interrupted,
+    UnsupportedCompression,
+}
+
+/// Every code, in the order they were assigned. Codes are never reused, new
+/// ones go at the end.
+pub const ERROR_CODES: [ErrorCode; 6] = [
+    ErrorCode::InvalidMagic,
+    ErrorCode::UnsupportedFormat,
+    ErrorCode::Offline,
+    ErrorCode::Cancelled,
+    ErrorCode::Interrupted,
+    ErrorCode::UnsupportedCompression,
+];
+
+/// What `cfx explain` prints for a code.
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub summary: &'static str,
+    pub causes: &'static [&'static str],
+    pub remedy: &'static str,
+}
+
+impl ErrorCode {
+    fn index(self) -> usize {
+        match self {
+            ErrorCode::InvalidMagic => 0,
+            ErrorCode::UnsupportedFormat => 1,
+            ErrorCode::Offline => 2,
+            ErrorCode::Cancelled => 3,
+            ErrorCode::Interrupted => 4,
+            ErrorCode::UnsupportedCompression => 5,
+        }
+    }
+
+    /// The code as shown, such as `E001`.
+    pub fn code(self) -> String {
+        format!("E{:03}", self.index() + 1)
+    }
+
+    /// The code `code` stands for, in any case.
+    pub fn parse(code: &str) -> Option<Self> {
+        ERROR_CODES
+            .into_iter()
+            .find(|error| error.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// The code of `err`, when it is one of the errors that has one.
+    pub fn of(err: &(dyn Error + 'static)) -> Option<Self> {
+        if let Some(coded) = err.downcast_ref::<CodedError>() {
+            return Some(coded.code);
+        }
+
+        if err.is::<OfflineError>() {
+            Some(ErrorCode::Offline)
+        } else if err.is::<Cancelled>() {
+            Some(ErrorCode::Cancelled)
+        } else if err.is::<Interrupted>() {
+            Some(ErrorCode::Interrupted)
+        } else {
+            None
+        }
+    }
+
+    pub fn explanation(self) -> Explanation {
+        match self {
+            ErrorCode::InvalidMagic => Explanation {
+                summary: "The file does not start with the RSC7 magic, so it is not a resource \
+                          the unpacker can read.",
+                causes: &[
+                    "The file is not a resource, or is a format `cfx formats` does not list",
+                    "The resource sits inside a bigger file, such as a memory dump or an RPF \
+                     that was carved out with extra bytes before it",
+                    "The file was truncated or overwritten",
+                ],
+                remedy: "Check the file with `cfx formats` in mind. When the resource starts \
+                         further in, pass --scan-magic to skip the bytes before the magic.",
+            },
+            ErrorCode::UnsupportedFormat => Explanation {
+                summary: "The file is a known format, but not the RSC7 resources the unpacker \
+                          reads.",
+                causes: &[
+                    "An RPF archive or another container was passed instead of the resource \
+                     inside it",
+                ],
+                remedy: "Extract the resource from its container first, then unpack it.",
+            },
+            ErrorCode::Offline => Explanation {
+                summary: "Offline mode is on and the command needed the network for something \
+                          that is not cached.",
+                causes: &[
+                    "--offline was passed, or `offline = true` is set in the user config",
+                    "A remote template was used before it was ever downloaded",
+                ],
+                remedy: "Run the command once without --offline to fill the cache, or turn \
+                         off the `offline` config key.",
+            },
+            ErrorCode::Cancelled => Explanation {
+                summary: "The command was stopped with Ctrl-C while it worked. Finished entries \
+                          are kept and the one in progress is left out rather than torn.",
+                causes: &["Ctrl-C was pressed, or the process was sent SIGINT"],
+                remedy: "Run the command again. `unpack --resume` picks up where extracting \
+                         pages stopped.",
+            },
+            ErrorCode::Interrupted => Explanation {
+                summary: "An interactive command was left at one of its prompts, before \
+                          anything was written.",
+                causes: &[
+                    "Ctrl-C was pressed at a prompt",
+                    "Esc was pressed at the first question",
+                ],
+                remedy: "Run the command again, passing the answers as flags or with \
+                         --answers to skip the prompts.",
+            },
+            ErrorCode::UnsupportedCompression => Explanation {
+                summary: "A page of the resource is compressed with zstd, which the unpacker \
+                          does not decode yet. Only raw deflate pages are supported.",
+                causes: &[
+                    "The resource comes from a newer build that compresses its pages \
+                           with zstd",
+                ],
+                remedy: "Decompress the page with the `zstd` tool, or re-export the resource \
+                         with deflate pages.",
+            },
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// An error message with its code, for failures that are otherwise plain
+/// strings.
+#[derive(Debug, PartialEq)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CodedError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_test() {
+        for (index, error) in ERROR_CODES.into_iter().enumerate() {
+            assert_eq!(error.index(), index, "{error:?} is out of order");
+            assert_eq!(ErrorCode::parse(&error.code()), Some(error));
+
+            let explanation = error.explanation();
+            assert!(!explanation.summary.is_empty(), "{error:?}");
+            assert!(!explanation.causes.is_empty(), "{error:?}");
+            assert!(!explanation.remedy.is_empty(), "{error:?}");
+        }
+
+        assert_eq!(ErrorCode::InvalidMagic.code(), "E001");
+        assert_eq!(ErrorCode::parse("e005"), Some(ErrorCode::Interrupted));
+        assert_eq!(ErrorCode::UnsupportedCompression.code(), "E006");
+        assert_eq!(ErrorCode::parse("E999"), None);
+    }
+
+    #[test]
+    fn error_code_of_test() {
+        let coded: Box<dyn Error> = CodedError::new(ErrorCode::InvalidMagic, "bad").into();
+        assert_eq!(coded.to_string(), "bad");
+        assert_eq!(ErrorCode::of(coded.as_ref()), Some(ErrorCode::InvalidMagic));
+
+        let interrupted: Box<dyn Error> = Interrupted.into();
+        assert_eq!(
+            ErrorCode::of(interrupted.as_ref()),
+            Some(ErrorCode::Interrupted)
+        );
+
+        let plain: Box<dyn Error> = "broken".into();
+        assert_eq!(ErrorCode::of(plain.as_ref()), None);
+    }
+}
@@ -0,0 +1,346 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Languages the prompts and messages are translated to. English has every
+/// key, the others fall back to it for keys they miss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+}
+
+static LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// The language of a code such as `es` or `es-MX`, or of a `LANG` value
+    /// such as `es_ES.UTF-8`.
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code
+            .split(['_', '-', '.', '@'])
+            .next()?
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "en" | "c" | "posix" => Some(Language::English),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Spanish => &SPANISH,
+        }
+    }
+}
+
+/// Parses `--lang`, listing the supported codes on failure.
+pub fn parse_language(value: &str) -> Result<Language, String> {
+    Language::parse(value).ok_or_else(|| format!("unsupported language `{value}`, use en or es"))
+}
+
+/// The language from `--lang`, else the `lang` config key, else the `LANG`
+/// environment variable, else English. An unsupported config value is
+/// reported, an unsupported `LANG` is not since it covers the whole system.
+pub fn select_language(
+    flag: Option<Language>,
+    config: Option<&str>,
+    env: Option<&str>,
+) -> Language {
+    if let Some(language) = flag {
+        return language;
+    }
+
+    if let Some(code) = config {
+        match Language::parse(code) {
+            Some(language) => return language,
+            None => log::warn!("Unsupported `lang` {code:?} in the config, ignoring it"),
+        }
+    }
+
+    env.and_then(Language::parse).unwrap_or(Language::English)
+}
+
+pub fn set_language(language: Language) {
+    let index = Language::ALL.iter().position(|&known| known == language);
+    LANGUAGE.store(index.unwrap_or_default(), Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed)]
+}
+
+/// The message `key` in the current language, falling back to English and
+/// then to the key itself, so a missing translation never fails.
+pub fn t(key: &'static str) -> &'static str {
+    let lookup = |catalog: &'static [(&'static str, &'static str)]| {
+        catalog
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map(|(_, text)| *text)
+    };
+
+    lookup(language().catalog())
+        .or_else(|| lookup(&ENGLISH))
+        .unwrap_or(key)
+}
+
+/// [`t`] with each `{name}` placeholder replaced by its value in `args`.
+pub fn tf(key: &'static str, args: &[(&str, &str)]) -> String {
+    let mut text = t(key).to_owned();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+
+    text
+}
+
+const ENGLISH: [(&str, &str); 64] = [
+    ("wizard.help", "Esc to go back"),
+    ("wizard.invalid_input", "Invalid input"),
+    ("wizard.review", "Continue with these answers?"),
+    ("wizard.continue", "Looks good, continue"),
+    ("wizard.change", "Change {step}: {answer}"),
+    ("wizard.yes", "yes"),
+    ("wizard.no", "no"),
+    ("wizard.none", "none"),
+    ("create.project_name", "What is your project name?"),
+    ("create.author", "What is the authors name?"),
+    (
+        "create.vehicle_model",
+        "What is the vehicle model (spawn) name?",
+    ),
+    ("create.ped_addon", "What kind of ped add-on is it?"),
+    ("create.ped_model", "What is the ped model name?"),
+    ("create.wavepack", "What is the wavepack name?"),
+    ("create.data_files", "Do you want to use data files?"),
+    (
+        "create.version_file",
+        "Do you want to track the version in a VERSION file?",
+    ),
+    (
+        "create.libraries",
+        "What libraries/frameworks do you want to use?",
+    ),
+    ("create.locales", "Do you want to set up ox_lib locales?"),
+    ("create.step.project_name", "project name"),
+    ("create.step.author", "author"),
+    ("create.step.vehicle_model", "vehicle model"),
+    ("create.step.ped_addon", "ped add-on"),
+    ("create.step.ped_model", "ped model"),
+    ("create.step.wavepack", "wavepack"),
+    ("create.step.data_files", "data files"),
+    ("create.step.version_file", "VERSION file"),
+    ("create.step.libraries", "libraries"),
+    ("create.step.locales", "locales"),
+    ("create.ped_addon.full_ped", "Full ped"),
+    ("create.ped_addon.components", "Component add-ons"),
+    ("create.empty_answer", "The `{key}` answer is empty"),
+    ("create.created", "Created {name} in {path}"),    ("main.completed", "Command completed successfully"),
+    ("main.failed", "Command failed: {error}"),
+    ("main.failed_code", "Command failed: [{code}] {error} (see `cfx explain {code}`)"),
+    ("main.press_enter", "Press enter to exit..."),
+    ("check.found", "Check found {count} error(s)"),
+    ("check.checked", "Checked {count} Lua file(s)"),
+    ("validate.found", "Validation found {count} error(s)"),
+    ("validate.unknown_build", "Could not detect the server build to check `/server:` dependencies against, pass --artifacts"),
+    ("validate.missing_convar", "{name} requires convar `{convar}` but server.cfg does not set it"),
+    ("validate.server_build", "{name} requires server build {required} but the server is build {build}"),
+    ("validate.onesync", "{name} requires OneSync but server.cfg does not enable it with `set onesync on`"),
+    ("validate.vehicle.handling", "`{model}` uses handlingId `{handling}`, which no HANDLING_FILE defines"),
+    ("validate.vehicle.variation", "`{model}` has no entry in any VEHICLE_VARIATION_FILE"),
+    ("validate.vehicle.yft", "`{model}` has no {model}.yft in stream/"),
+    ("validate.vehicle.txd", "`{model}` uses txdName `{txd}` but stream/ has no {txd}.ytd"),
+    ("lint.unknown_rule", "Unknown lint rule `{rule}` in {source}"),
+    ("lint.fx_version", "The manifest does not declare an fx_version"),
+    ("lint.not_found", "`{pattern}` does not match any file"),
+    ("lint.not_found_yet", "`{pattern}` does not match any file yet"),
+    ("lint.absolute_script", "`{script}` is an absolute path"),
+    ("lint.duplicate_script", "`{script}` is listed more than once in {runtime} scripts"),
+    ("lint.ui_page", "ui_page `{page}` is not listed in files"),
+    ("lint.data_file_type", "Unknown data_file type `{kind}`"),
+    ("lint.invalid_awc", "`{file}` is not a valid AWC container: {error}"),
+    ("lint.escrowed", "Escrowed: none of the {count} script(s) can be reviewed"),
+    ("lint.partially_escrowed", "Partially escrowed: {protected} protected, {plain} plain script(s) can be reviewed"),
+    ("lint.lua54_escrow", "Escrowed scripts need `lua54 'yes'`"),
+    ("lint.rdr3_warning", "The resource targets rdr3 but has no rdr3_warning"),
+    ("lint.name_mismatch", "The manifest is named `{name}` but the folder is `{folder}`, the server ensures it as `{folder}`"),
+    ("lint.empty_stream", "stream/ is empty, the resource does not stream any assets yet"),
+    ("lint.icon_missing", "`{file}` is listed in files but missing"),
+    ("lint.invalid_icon", "`{file}` {problem}"),
+];
+
+/// Spanish. The step titles of the add-on kinds fall back to English, and so
+/// does the invalid icon finding, whose problem text is English.
+const SPANISH: [(&str, &str); 57] = [
+    ("wizard.help", "Esc para volver atrás"),
+    ("wizard.invalid_input", "Entrada no válida"),
+    ("wizard.review", "¿Continuar con estas respuestas?"),
+    ("wizard.continue", "Todo bien, continuar"),
+    ("wizard.change", "Cambiar {step}: {answer}"),
+    ("wizard.yes", "sí"),
+    ("wizard.no", "no"),
+    ("wizard.none", "ninguna"),
+    ("create.project_name", "¿Cuál es el nombre del proyecto?"),
+    ("create.author", "¿Cuál es el nombre del autor?"),
+    (
+        "create.vehicle_model",
+        "¿Cuál es el nombre del modelo (spawn) del vehículo?",
+    ),
+    ("create.ped_addon", "¿Qué tipo de add-on de ped es?"),
+    ("create.ped_model", "¿Cuál es el nombre del modelo del ped?"),
+    ("create.wavepack", "¿Cuál es el nombre del wavepack?"),
+    ("create.data_files", "¿Quieres usar archivos de datos?"),
+    (
+        "create.version_file",
+        "¿Quieres llevar la versión en un archivo VERSION?",
+    ),
+    (
+        "create.libraries",
+        "¿Qué librerías/frameworks quieres usar?",
+    ),
+    (
+        "create.locales",
+        "¿Quieres configurar las traducciones de ox_lib?",
+    ),
+    ("create.step.project_name", "nombre del proyecto"),
+    ("create.step.author", "autor"),
+    ("create.step.libraries", "librerías"),
+    ("create.step.locales", "traducciones"),
+    ("create.ped_addon.full_ped", "Ped completo"),
+    ("create.ped_addon.components", "Add-ons de componentes"),
+    ("create.empty_answer", "La respuesta `{key}` está vacía"),
+    ("create.created", "{name} creado en {path}"),    ("main.completed", "Comando completado correctamente"),
+    ("main.failed", "El comando falló: {error}"),
+    ("main.failed_code", "El comando falló: [{code}] {error} (consulta `cfx explain {code}`)"),
+    ("main.press_enter", "Pulsa enter para salir..."),
+    ("check.found", "La comprobación encontró {count} error(es)"),
+    ("check.checked", "{count} archivo(s) Lua comprobados"),
+    ("validate.found", "La validación encontró {count} error(es)"),
+    ("validate.unknown_build", "No se pudo detectar la build del servidor para comprobar las dependencias `/server:`, pasa --artifacts"),
+    ("validate.missing_convar", "{name} requiere la convar `{convar}` pero server.cfg no la define"),
+    ("validate.server_build", "{name} requiere la build {required} del servidor pero el servidor es la build {build}"),
+    ("validate.onesync", "{name} requiere OneSync pero server.cfg no lo activa con `set onesync on`"),
+    ("validate.vehicle.handling", "`{model}` usa el handlingId `{handling}`, que ningún HANDLING_FILE define"),
+    ("validate.vehicle.variation", "`{model}` no tiene entrada en ningún VEHICLE_VARIATION_FILE"),
+    ("validate.vehicle.yft", "`{model}` no tiene {model}.yft en stream/"),
+    ("validate.vehicle.txd", "`{model}` usa el txdName `{txd}` pero stream/ no tiene {txd}.ytd"),
+    ("lint.unknown_rule", "Regla de lint desconocida `{rule}` en {source}"),
+    ("lint.fx_version", "El manifiesto no declara un fx_version"),
+    ("lint.not_found", "`{pattern}` no coincide con ningún archivo"),
+    ("lint.not_found_yet", "`{pattern}` aún no coincide con ningún archivo"),
+    ("lint.absolute_script", "`{script}` es una ruta absoluta"),
+    ("lint.duplicate_script", "`{script}` aparece más de una vez en los scripts de {runtime}"),
+    ("lint.ui_page", "ui_page `{page}` no aparece en files"),
+    ("lint.data_file_type", "Tipo de data_file desconocido `{kind}`"),
+    ("lint.invalid_awc", "`{file}` no es un contenedor AWC válido: {error}"),
+    ("lint.escrowed", "Con escrow: ninguno de los {count} script(s) se puede revisar"),
+    ("lint.partially_escrowed", "Con escrow parcial: {protected} protegidos, {plain} script(s) sin proteger se pueden revisar"),
+    ("lint.lua54_escrow", "Los scripts con escrow necesitan `lua54 'yes'`"),
+    ("lint.rdr3_warning", "El recurso apunta a rdr3 pero no tiene rdr3_warning"),
+    ("lint.name_mismatch", "El manifiesto se llama `{name}` pero la carpeta es `{folder}`, el servidor lo inicia como `{folder}`"),
+    ("lint.empty_stream", "stream/ está vacía, el recurso aún no transmite ningún asset"),
+    ("lint.icon_missing", "`{file}` aparece en files pero falta"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::path::Path;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names = Regex::new(r"\{(\w+)\}")
+            .unwrap()
+            .captures_iter(text)
+            .map(|captures| captures.get(1).unwrap().as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn source_keys(dir: &Path, regex: &Regex, keys: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                source_keys(&path, regex, keys);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                keys.extend(
+                    regex
+                        .captures_iter(&source)
+                        .map(|captures| captures[1].to_owned()),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn catalog_keys_test() {
+        let regex = Regex::new(r#"\btf?\(\s*"([\w.]+)""#).unwrap();
+        let mut keys = vec![];
+        source_keys(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &regex,
+            &mut keys,
+        );
+        assert!(keys.len() > 10, "only found {keys:?}");
+
+        for key in &keys {
+            assert!(
+                ENGLISH.iter().any(|(known, _)| known == key),
+                "`{key}` is used but missing from the English catalog"
+            );
+        }
+
+        for (key, text) in SPANISH {
+            let english = ENGLISH.iter().find(|(known, _)| *known == key);
+            let english = english.unwrap_or_else(|| panic!("`{key}` is not an English key"));
+            assert_eq!(placeholders(text), placeholders(english.1), "{key}");
+        }
+    }
+
+    #[test]
+    fn language_test() {
+        assert_eq!(Language::parse("es_ES.UTF-8"), Some(Language::Spanish));
+        assert_eq!(Language::parse("es-MX"), Some(Language::Spanish));
+        assert_eq!(Language::parse("C"), Some(Language::English));
+        assert_eq!(Language::parse("de_DE.UTF-8"), None);
+        assert!(parse_language("xx").is_err());
+
+        let select = select_language;
+        assert_eq!(
+            select(Some(Language::English), Some("es"), None),
+            Language::English
+        );
+        assert_eq!(select(None, Some("es"), Some("en_US")), Language::Spanish);
+        assert_eq!(select(None, Some("xx"), Some("es_AR")), Language::Spanish);
+        assert_eq!(select(None, None, Some("de_DE")), Language::English);
+    }
+
+    #[test]
+    fn translate_test() {
+        let catalog = |language: Language, key: &'static str| {
+            language
+                .catalog()
+                .iter()
+                .find(|(known, _)| *known == key)
+                .map(|(_, text)| *text)
+        };
+        assert_eq!(
+            catalog(Language::Spanish, "wizard.review"),
+            Some("¿Continuar con estas respuestas?")
+        );
+        assert_eq!(catalog(Language::Spanish, "create.step.wavepack"), None);
+
+        // The language is process-wide, so only English is selected here
+        assert_eq!(t("create.step.wavepack"), "wavepack");
+        assert_eq!(
+            tf("wizard.change", &[("step", "author"), ("answer", "cfx")]),
+            "Change author: cfx"
+        );
+        let missing = "no.such.key";
+        assert_eq!(t(missing), missing);
+    }
+}
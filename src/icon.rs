@@ -0,0 +1,235 @@
+use std::io::Write;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+/// Resource icon shown by marketplace listings and txAdmin, next to the manifest.
+pub const ICON_FILE: &str = "icon.png";
+
+/// Widths, in pixels, accepted for icons.
+pub const ICON_SIZES: std::ops::RangeInclusive<u32> = 64..=1024;
+
+/// Width and height of generated placeholder icons.
+const PLACEHOLDER_SIZE: u32 = 256;
+/// Pixels per font dot in placeholder icons.
+const SCALE: u32 = 16;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// 5x7 glyphs of `A`-`Z` then `0`-`9`, one row per byte, the leftmost dot in
+/// bit 4.
+const FONT: [[u8; 7]; 36] = [
+    [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+    [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+    [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+    [0x1e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1e],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+    [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+    [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+    [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+    [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+    [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+    [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+    [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+];
+
+fn glyph(c: char) -> Option<&'static [u8; 7]> {
+    match c {
+        'A'..='Z' => Some(&FONT[c as usize - 'A' as usize]),
+        '0'..='9' => Some(&FONT[26 + c as usize - '0' as usize]),
+        _ => None,
+    }
+}
+
+/// Up to two uppercase initials of the words in `name`, such as `MB` for
+/// `my_bank`.
+pub fn initials(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|word| word.chars().next())
+        .map(|c| c.to_ascii_uppercase())
+        .take(2)
+        .collect()
+}
+
+/// A square PNG with the [`initials`] of `name` in white on a colour picked
+/// from the name, for resources that have no icon of their own.
+pub fn placeholder_png(name: &str) -> Vec<u8> {
+    let hash = crc32fast::hash(name.as_bytes()).to_le_bytes();
+    // Dark enough for white text to stand out
+    let background = [hash[0] % 128 + 32, hash[1] % 128 + 32, hash[2] % 128 + 32];
+
+    let size = PLACEHOLDER_SIZE as usize;
+    let mut pixels = background.repeat(size * size);
+
+    let glyphs = initials(name).chars().filter_map(glyph).collect::<Vec<_>>();
+    let glyph_width = 5 * SCALE as usize;
+    let width = glyphs.len() * glyph_width + glyphs.len().saturating_sub(1) * SCALE as usize;
+    let left = (size - width) / 2;
+    let top = (size - 7 * SCALE as usize) / 2;
+
+    for (index, rows) in glyphs.iter().enumerate() {
+        let glyph_left = left + index * (glyph_width + SCALE as usize);
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0x10 >> column) == 0 {
+                    continue;
+                }
+
+                for y in 0..SCALE as usize {
+                    let y = top + row * SCALE as usize + y;
+                    let x = glyph_left + column * SCALE as usize;
+                    let start = (y * size + x) * 3;
+                    pixels[start..start + SCALE as usize * 3].fill(0xff);
+                }
+            }
+        }
+    }
+
+    encode_png(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, &pixels)
+}
+
+/// Encodes 8-bit RGB `pixels`, row by row, as a PNG.
+pub fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 3;
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    for row in pixels.chunks(stride) {
+        // Filter type 0, the row is stored as is
+        encoder.write_all(&[0]).unwrap();
+        encoder.write_all(row).unwrap();
+    }
+
+    let mut header = vec![];
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filtering and no interlace
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &encoder.finish().unwrap());
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32fast::hash(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Width and height from the header of a PNG, `None` when `bytes` is not one.
+pub fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 24 || bytes[..8] != PNG_SIGNATURE || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+
+    let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+    Some((width, height))
+}
+
+/// Why `bytes` does not make a good icon: not a PNG, not square, or not
+/// within [`ICON_SIZES`].
+pub fn check_icon(bytes: &[u8]) -> Result<(), String> {
+    let Some((width, height)) = png_size(bytes) else {
+        return Err("is not a PNG".to_owned());
+    };
+
+    if width != height {
+        return Err(format!("is {width}x{height}, icons have to be square"));
+    }
+
+    if !ICON_SIZES.contains(&width) {
+        return Err(format!(
+            "is {width}x{height}, icons have to be {}x{0} to {}x{1}",
+            ICON_SIZES.start(),
+            ICON_SIZES.end()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn initials_test() {
+        assert_eq!(initials("my_bank"), "MB");
+        assert_eq!(initials("bank"), "B");
+        assert_eq!(initials("qb-vehicle-shop"), "QV");
+        assert_eq!(initials("__"), "");
+    }
+
+    #[test]
+    fn placeholder_png_test() {
+        let png = placeholder_png("my_bank");
+        assert_eq!(png_size(&png), Some((256, 256)));
+        assert!(check_icon(&png).is_ok());
+
+        // IDAT follows the signature and the 25 bytes of the IHDR chunk
+        let length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = vec![];
+        ZlibDecoder::new(&png[41..41 + length])
+            .read_to_end(&mut raw)
+            .unwrap();
+        assert_eq!(raw.len(), 256 * (1 + 256 * 3));
+
+        let pixel = |x: usize, y: usize| {
+            let start = y * (1 + 256 * 3) + 1 + x * 3;
+            &raw[start..start + 3]
+        };
+        // Top left dot of the M, the background in the corner
+        assert_eq!(pixel(40, 72), [0xff; 3]);
+        assert_ne!(pixel(0, 0), [0xff; 3]);
+
+        let crc = crc32fast::hash(&png[12..29]).to_be_bytes();
+        assert_eq!(png[29..33], crc);
+    }
+
+    #[test]
+    fn check_icon_test() {
+        assert_eq!(check_icon(b"GIF89a").unwrap_err(), "is not a PNG");
+        let wide = encode_png(128, 64, &[0; 128 * 64 * 3]);
+        assert_eq!(
+            check_icon(&wide).unwrap_err(),
+            "is 128x64, icons have to be square"
+        );
+        let tiny = encode_png(16, 16, &[0; 16 * 16 * 3]);
+        assert_eq!(
+            check_icon(&tiny).unwrap_err(),
+            "is 16x16, icons have to be 64x64 to 1024x1024"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::CfxResult;
+
+/// Jenkins one-at-a-time hash of `bytes` as the game computes it, without
+/// changing case. Structure names in metadata are hashed this way.
+pub const fn joaat_bytes(bytes: &[u8]) -> u32 {
+    let mut hash = 0u32;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash = hash.wrapping_add(bytes[index] as u32);
+        hash = hash.wrapping_add(hash << 10);
+        hash ^= hash >> 6;
+        index += 1;
+    }
+
+    hash = hash.wrapping_add(hash << 3);
+    hash ^= hash >> 11;
+    hash.wrapping_add(hash << 15)
+}
+
+/// Hash of an asset name such as a model or archetype, which the game
+/// lowercases first.
+pub fn joaat(name: &str) -> u32 {
+    joaat_bytes(name.to_ascii_lowercase().as_bytes())
+}
+
+/// Names by hash, for printing the names behind the hashes resources store.
+#[derive(Debug, Default)]
+pub struct HashDictionary {
+    names: HashMap<u32, String>,
+}
+
+impl HashDictionary {
+    pub fn insert(&mut self, name: &str) -> u32 {
+        let hash = joaat(name);
+        self.names.entry(hash).or_insert_with(|| name.to_owned());
+        hash
+    }
+
+    /// Adds the names in a text file, one per line. Blank lines and lines
+    /// starting with `#` are skipped, and a `0x` hex hash stands for a name
+    /// that is not known. Returns the hashes the file lists.
+    pub fn load(&mut self, path: &Path) -> CfxResult<Vec<u32>> {
+        let content = read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+
+        let mut hashes = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let hash = match line.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| {
+                    format!("{}:{}: invalid hash `{line}`", path.display(), index + 1)
+                })?,
+                None => self.insert(line),
+            };
+            hashes.push(hash);
+        }
+
+        Ok(hashes)
+    }
+
+    /// The name behind `hash`, or the hash in hex when it is not known.
+    pub fn name(&self, hash: u32) -> String {
+        match self.names.get(&hash) {
+            Some(name) => name.clone(),
+            None => format!("{hash:#010x}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joaat_test() {
+        assert_eq!(joaat("adder"), 0xB779A091);
+        assert_eq!(joaat("ADDER"), joaat("adder"));
+        assert_eq!(joaat_bytes(b"CMapData"), 3545841574);
+        assert_ne!(joaat_bytes(b"CMapData"), joaat("CMapData"));
+    }
+
+    #[test]
+    fn hash_dictionary_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archetypes.txt");
+        std::fs::write(&path, "# base game props\nprop_bench_01a\n\n0x0000BEEF\n").unwrap();
+
+        let mut dictionary = HashDictionary::default();
+        let hashes = dictionary.load(&path).unwrap();
+        assert_eq!(hashes, [joaat("prop_bench_01a"), 0xBEEF]);
+        assert_eq!(dictionary.name(joaat("PROP_BENCH_01A")), "prop_bench_01a");
+        assert_eq!(dictionary.name(0xBEEF), "0x0000beef");
+
+        std::fs::write(&path, "0xnope\n").unwrap();
+        let err = dictionary.load(&path).unwrap_err();
+        assert!(err.to_string().ends_with(":1: invalid hash `0xnope`"));
+    }
+}
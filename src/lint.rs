@@ -0,0 +1,702 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{t, tf};
+use crate::icon::{check_icon, ICON_FILE};
+use crate::magic::{EscrowReport, EscrowStatus};
+use crate::manifest::{expand_pattern, Manifest, SCRIPT_DIRECTIVES};
+use crate::CfxResult;
+
+/// How a rule's findings are reported; `off` disables the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    #[serde(alias = "warn")]
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Off => "off",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Severity overrides by rule name, the `[lint]` table of `cfx.toml` and the
+/// user config.
+pub type LintConfig = BTreeMap<String, Severity>;
+
+/// What rules check a resource against, gathered once for all of them.
+pub struct LintContext<'a> {
+    pub resource_dir: &'a Path,
+    pub manifest: &'a Manifest,
+    pub escrow: Option<&'a EscrowReport>,
+    /// Referenced local patterns that match no file
+    pub missing: Vec<String>,
+    /// AWC containers the referenced patterns match
+    pub containers: BTreeSet<PathBuf>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(
+        resource_dir: &'a Path,
+        manifest: &'a Manifest,
+        escrow: Option<&'a EscrowReport>,
+    ) -> CfxResult<Self> {
+        let mut missing = vec![];
+        let mut containers = BTreeSet::new();
+        for pattern in manifest.referenced_patterns() {
+            let files = expand_pattern(resource_dir, &pattern)?;
+            if files.is_empty() {
+                missing.push(pattern);
+            }
+
+            containers.extend(
+                files
+                    .into_iter()
+                    .filter(|file| file.extension().is_some_and(|ext| ext == "awc")),
+            );
+        }
+
+        Ok(Self {
+            resource_dir,
+            manifest,
+            escrow,
+            missing,
+            containers,
+        })
+    }
+
+    /// Local script paths, leaving out imports from other resources and URLs.
+    fn local_scripts(&self) -> Vec<&'a str> {
+        self.manifest
+            .values(&SCRIPT_DIRECTIVES)
+            .into_iter()
+            .filter(|script| is_local(script))
+            .collect()
+    }
+}
+
+fn is_local(path: &str) -> bool {
+    !path.starts_with('@') && !path.contains("://")
+}
+
+/// A named check of a resource. New rules implement this and are added to
+/// [`RULES`].
+pub trait Rule: Sync {
+    /// Kebab-case name used in `[lint]` tables and with `--explain`.
+    fn name(&self) -> &'static str;
+    fn default_severity(&self) -> Severity;
+    /// What the rule checks and how to fix its findings, for `--explain`.
+    fn explanation(&self) -> &'static str;
+    /// One message per finding.
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>>;
+}
+
+/// Every rule `validate` runs, in reporting order.
+pub static RULES: [&dyn Rule; 15] = [
+    &MissingFxVersion,
+    &ScriptNotFound,
+    &FileNotFound,
+    &AssetNotFound,
+    &AbsoluteScriptPath,
+    &DuplicateScript,
+    &UiPageNotInFiles,
+    &UnknownDataFileType,
+    &InvalidAwc,
+    &Escrowed,
+    &Lua54MissingForEscrow,
+    &Rdr3WarningMissing,
+    &NameMismatch,
+    &EmptyStream,
+    &InvalidIcon,
+];
+
+pub fn find_rule(name: &str) -> Option<&'static dyn Rule> {
+    RULES.iter().copied().find(|rule| rule.name() == name)
+}
+
+/// Findings of one rule, at the severity it was configured to.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RuleReport {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub count: usize,
+    pub findings: Vec<String>,
+}
+
+/// Runs every rule that is not turned off by `overrides`, later overrides
+/// taking precedence, and returns the rules that found something.
+pub fn run_rules(context: &LintContext, overrides: &[&LintConfig]) -> CfxResult<Vec<RuleReport>> {
+    let mut reports = vec![];
+    for rule in RULES {
+        let severity = overrides
+            .iter()
+            .rev()
+            .find_map(|config| config.get(rule.name()).copied())
+            .unwrap_or(rule.default_severity());
+        if severity == Severity::Off {
+            continue;
+        }
+
+        let findings = rule.check(context)?;
+        if !findings.is_empty() {
+            reports.push(RuleReport {
+                rule: rule.name(),
+                severity,
+                count: findings.len(),
+                findings,
+            });
+        }
+    }
+
+    Ok(reports)
+}
+
+/// Messages for `[lint]` entries that do not name a rule.
+pub fn unknown_rules(config: &LintConfig, source: &str) -> Vec<String> {
+    config
+        .keys()
+        .filter(|name| find_rule(name).is_none())
+        .map(|name| tf("lint.unknown_rule", &[("rule", name), ("source", source)]))
+        .collect()
+}
+
+/// Streamed and audio assets that are added after a resource is created, so
+/// that missing ones are reported apart from other files.
+fn is_asset_pattern(pattern: &str) -> bool {
+    pattern.starts_with("stream/") || pattern.ends_with(".awc") || pattern.ends_with(".rel")
+}
+
+/// The resource icon, whose problems are reported apart from other files.
+fn is_icon_pattern(pattern: &str) -> bool {
+    pattern == ICON_FILE
+}
+
+struct MissingFxVersion;
+
+impl Rule for MissingFxVersion {
+    fn name(&self) -> &'static str {
+        "missing-fx-version"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "The server refuses to start a resource whose manifest has no `fx_version`.\n\
+         Fix: add `fx_version 'cerulean'` at the top of fxmanifest.lua."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        Ok(match context.manifest.value("fx_version") {
+            Some(_) => vec![],
+            None => vec![t("lint.fx_version").to_owned()],
+        })
+    }
+}
+
+struct ScriptNotFound;
+
+impl Rule for ScriptNotFound {
+    fn name(&self) -> &'static str {
+        "script-not-found"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "A script listed in a *_script(s) directive matches no file, so the\n\
+         resource fails to load it.\n\
+         Fix: correct the path or glob, or create the script."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let scripts = context.local_scripts();
+        Ok(context
+            .missing
+            .iter()
+            .filter(|pattern| scripts.contains(&pattern.as_str()))
+            .map(|pattern| tf("lint.not_found", &[("pattern", pattern)]))
+            .collect())
+    }
+}
+
+struct FileNotFound;
+
+impl Rule for FileNotFound {
+    fn name(&self) -> &'static str {
+        "file-not-found"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "A path listed in `files`, `ui_page`, `loadscreen` or a `data_file` entry\n\
+         matches no file, so clients never receive it.\n\
+         Fix: correct the path or glob, or add the file."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let scripts = context.local_scripts();
+        Ok(context
+            .missing
+            .iter()
+            .filter(|pattern| {
+                !scripts.contains(&pattern.as_str())
+                    && !is_asset_pattern(pattern)
+                    && !is_icon_pattern(pattern)
+            })
+            .map(|pattern| tf("lint.not_found", &[("pattern", pattern)]))
+            .collect())
+    }
+}
+
+struct AssetNotFound;
+
+impl Rule for AssetNotFound {
+    fn name(&self) -> &'static str {
+        "asset-not-found"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "A streamed or audio asset the manifest references is not there yet.\n\
+         Freshly created add-ons expect them to be dropped in later.\n\
+         Fix: add the asset files, e.g. the models and textures below stream/."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        Ok(context
+            .missing
+            .iter()
+            .filter(|pattern| is_asset_pattern(pattern))
+            .map(|pattern| tf("lint.not_found_yet", &[("pattern", pattern)]))
+            .collect())
+    }
+}
+
+struct AbsoluteScriptPath;
+
+impl Rule for AbsoluteScriptPath {
+    fn name(&self) -> &'static str {
+        "absolute-script-path"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "Script paths are relative to the resource folder; an absolute path only\n\
+         works on the machine it was written on, if at all.\n\
+         Fix: make the path relative to the folder holding fxmanifest.lua."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        Ok(context
+            .local_scripts()
+            .into_iter()
+            .filter(|script| {
+                let bytes = script.as_bytes();
+                script.starts_with(['/', '\\'])
+                    || (bytes.len() > 1 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+            })
+            .map(|script| tf("lint.absolute_script", &[("script", script)]))
+            .collect())
+    }
+}
+
+struct DuplicateScript;
+
+impl Rule for DuplicateScript {
+    fn name(&self) -> &'static str {
+        "duplicate-script"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "A script is listed more than once for the same runtime, which loads it\n\
+         twice and runs its top-level code twice.\n\
+         Fix: remove the repeated entry."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let mut result = vec![];
+        for runtime in ["client", "server", "shared"] {
+            let names = [format!("{runtime}_script"), format!("{runtime}_scripts")];
+            let mut seen = BTreeSet::new();
+            let mut reported = BTreeSet::new();
+            for script in context
+                .manifest
+                .values(&[names[0].as_str(), names[1].as_str()])
+            {
+                if !seen.insert(script) && reported.insert(script) {
+                    result.push(tf(
+                        "lint.duplicate_script",
+                        &[("script", script), ("runtime", runtime)],
+                    ));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+struct UiPageNotInFiles;
+
+impl Rule for UiPageNotInFiles {
+    fn name(&self) -> &'static str {
+        "ui-page-not-in-files"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "Clients only download what `files` lists, so a `ui_page` missing from it\n\
+         shows a blank NUI frame.\n\
+         Fix: add the page, or a glob covering it such as 'html/**', to `files`."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let files = context.manifest.values(&["file", "files"]);
+        let covered = |page: &str| {
+            files.iter().any(|file| {
+                *file == page
+                    || glob::Pattern::new(file).is_ok_and(|pattern| pattern.matches(page))
+                    || page.starts_with(&format!("{}/", file.trim_end_matches('/')))
+            })
+        };
+
+        Ok(context
+            .manifest
+            .values(&["ui_page"])
+            .into_iter()
+            .filter(|page| is_local(page) && !covered(page))
+            .map(|page| tf("lint.ui_page", &[("page", page)]))
+            .collect())
+    }
+}
+
+/// `data_file` types the game and FXServer understand.
+const DATA_FILE_TYPES: [&str; 43] = [
+    "AUDIO_DYNAMIXDATA",
+    "AUDIO_GAMEDATA",
+    "AUDIO_SOUNDDATA",
+    "AUDIO_SPEECHDATA",
+    "AUDIO_SYNTHDATA",
+    "AUDIO_WAVEPACK",
+    "CARCOLS_FILE",
+    "CARCOLS_GEN9_FILE",
+    "CARMODCOLS_FILE",
+    "CARMODCOLS_GEN9_FILE",
+    "CONTENT_UNLOCKING_META_FILE",
+    "DLC_ITYP_REQUEST",
+    "DLC_POP_GROUPS",
+    "DLC_TEXT_FILE",
+    "DLC_WEAPON_PICKUPS",
+    "EXPLOSION_INFO_FILE",
+    "FIVEM_LOVES_YOU_341B23A2F0E0F131",
+    "FIVEM_LOVES_YOU_4B38E96CC036038F",
+    "GTXD_PARENTING_DATA",
+    "HANDLING_FILE",
+    "LOADOUTS_FILE",
+    "PED_METADATA_FILE",
+    "PED_PERSONALITY_FILE",
+    "POPSCHED_FILE",
+    "SCALEFORM_DLC_FILE",
+    "SCENARIO_POINTS_FILE",
+    "SCENARIO_POINTS_OVERRIDE_FILE",
+    "SCENARIO_POINTS_OVERRIDE_PSO_FILE",
+    "SCENARIO_POINTS_PSO_FILE",
+    "SHOP_PED_APPAREL_META_FILE",
+    "TATTOO_SHOP_DLC_FILE",
+    "TIMECYCLEMOD_FILE",
+    "VEHICLE_LAYOUTS_FILE",
+    "VEHICLE_METADATA_FILE",
+    "VEHICLE_SHOP_DLC_FILE",
+    "VEHICLE_VARIATION_FILE",
+    "WEAPON_ANIMATIONS_FILE",
+    "WEAPON_METADATA_FILE",
+    "WEAPONCOMPONENTSINFO_FILE",
+    "WEAPONINFO_FILE",
+    "WORLD_HEIGHTMAP_FILE",
+    "ZONEBIND_FILE",
+    "NAVMESH_INDEXREMAPPING_FILE",
+];
+
+struct UnknownDataFileType;
+
+impl Rule for UnknownDataFileType {
+    fn name(&self) -> &'static str {
+        "unknown-data-file-type"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "A `data_file` entry uses a type the game does not know, so the file is\n\
+         silently never loaded. Types are case-sensitive.\n\
+         Fix: use the documented type, e.g. HANDLING_FILE or DLC_ITYP_REQUEST."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        Ok(context
+            .manifest
+            .directives
+            .iter()
+            .filter(|directive| directive.name == "data_file")
+            .filter_map(|directive| directive.values.first())
+            .filter(|kind| !DATA_FILE_TYPES.contains(&kind.as_str()))
+            .map(|kind| tf("lint.data_file_type", &[("kind", kind)]))
+            .collect())
+    }
+}
+
+struct InvalidAwc;
+
+impl Rule for InvalidAwc {
+    fn name(&self) -> &'static str {
+        "invalid-awc"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "An audio container the manifest references does not parse as AWC.\n\
+         Fix: re-export the wavepack from your audio tool."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let mut result = vec![];
+        for file in &context.containers {
+            if let Err(err) = crate::awc::AwcHeader::parse(&std::fs::read(file)?) {
+                let relative = file.strip_prefix(context.resource_dir).unwrap_or(file);
+                result.push(tf(
+                    "lint.invalid_awc",
+                    &[
+                        ("file", &relative.display().to_string()),
+                        ("error", &err.to_string()),
+                    ],
+                ));
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+struct Escrowed;
+
+impl Rule for Escrowed {
+    fn name(&self) -> &'static str {
+        "escrowed"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "Some or all scripts are protected by Cfx escrow and cannot be reviewed.\n\
+         Fix: nothing to fix; set the rule to `off` for resources you trust."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        Ok(context
+            .escrow
+            .map(|escrow| match escrow.status {
+                EscrowStatus::Full => tf(
+                    "lint.escrowed",
+                    &[("count", &escrow.protected.len().to_string())],
+                ),
+                EscrowStatus::Partial => tf(
+                    "lint.partially_escrowed",
+                    &[
+                        ("protected", &escrow.protected.len().to_string()),
+                        ("plain", &escrow.plain.len().to_string()),
+                    ],
+                ),
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+struct Lua54MissingForEscrow;
+
+impl Rule for Lua54MissingForEscrow {
+    fn name(&self) -> &'static str {
+        "lua54-missing-for-escrow"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn explanation(&self) -> &'static str {
+        "Escrowed scripts are compiled for Lua 5.4 and fail to load without it.\n\
+         Fix: add `lua54 'yes'` to fxmanifest.lua."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let lua54 = context.manifest.value("lua54") == Some("yes");
+        Ok(match context.escrow {
+            Some(_) if !lua54 => vec![t("lint.lua54_escrow").to_owned()],
+            _ => vec![],
+        })
+    }
+}
+
+struct Rdr3WarningMissing;
+
+impl Rule for Rdr3WarningMissing {
+    fn name(&self) -> &'static str {
+        "rdr3-warning-missing"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "RedM only starts resources targeting rdr3 that acknowledge its prerelease\n\
+         state with an `rdr3_warning` directive.\n\
+         Fix: add rdr3_warning 'I acknowledge that this is a prerelease build of\n\
+         RedM, and I am aware my resources *will* become incompatible once RedM ships.'"
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let rdr3 = context.manifest.games().contains(&"rdr3");
+        Ok(match context.manifest.get("rdr3_warning") {
+            None if rdr3 => vec![t("lint.rdr3_warning").to_owned()],
+            _ => vec![],
+        })
+    }
+}
+
+struct NameMismatch;
+
+impl Rule for NameMismatch {
+    fn name(&self) -> &'static str {
+        "name-mismatch"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "The manifest's `name` differs from the folder name, but the server\n\
+         ensures resources by their folder.\n\
+         Fix: rename the folder or the `name` directive so they agree."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let Some(name) = context.manifest.name() else {
+            return Ok(vec![]);
+        };
+
+        let dir = context.resource_dir.canonicalize()?;
+        Ok(match dir.file_name().and_then(|name| name.to_str()) {
+            Some(dir_name) if dir_name != name => vec![tf(
+                "lint.name_mismatch",
+                &[("name", name), ("folder", dir_name)],
+            )],
+            _ => vec![],
+        })
+    }
+}
+
+struct EmptyStream;
+
+impl Rule for EmptyStream {
+    fn name(&self) -> &'static str {
+        "empty-stream"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "The stream/ folder exists but is empty, so the resource streams nothing.\n\
+         Fix: drop the models and textures into stream/, or remove the folder."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        let stream_dir = context.resource_dir.join("stream");
+        let empty = stream_dir.is_dir() && std::fs::read_dir(&stream_dir)?.next().is_none();
+        Ok(match empty {
+            true => vec![t("lint.empty_stream").to_owned()],
+            false => vec![],
+        })
+    }
+}
+
+struct InvalidIcon;
+
+impl Rule for InvalidIcon {
+    fn name(&self) -> &'static str {
+        "invalid-icon"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn explanation(&self) -> &'static str {
+        "Marketplace listings and txAdmin show the icon.png listed in `files`, and\n\
+         expect a square PNG of 64x64 to 1024x1024 pixels.\n\
+         Fix: add a fitting icon.png, e.g. `cfx create --icon` makes a placeholder."
+    }
+
+    fn check(&self, context: &LintContext) -> CfxResult<Vec<String>> {
+        if !context
+            .manifest
+            .values(&["file", "files"])
+            .contains(&ICON_FILE)
+        {
+            return Ok(vec![]);
+        }
+
+        let path = context.resource_dir.join(ICON_FILE);
+        if !path.is_file() {
+            return Ok(vec![tf("lint.icon_missing", &[("file", ICON_FILE)])]);
+        }
+
+        Ok(match check_icon(&std::fs::read(path)?) {
+            Ok(()) => vec![],
+            Err(problem) => vec![tf(
+                "lint.invalid_icon",
+                &[("file", ICON_FILE), ("problem", &problem.to_string())],
+            )],
+        })
+    }
+}
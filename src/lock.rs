@@ -0,0 +1,105 @@
+use std::fs::{create_dir_all, read_to_string, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::CfxResult;
+
+/// How long to wait for another cfx process before giving up.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(3);
+const RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Advisory lock on a `.lock` file, held until dropped. The file records the
+/// pid of the holder so others can say who they are waiting for.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Locks `path`, retrying for a few seconds while another process holds it.
+    pub fn acquire(path: &Path) -> CfxResult<Self> {
+        Self::acquire_within(path, LOCK_TIMEOUT)
+    }
+
+    pub fn acquire_within(path: &Path, timeout: Duration) -> CfxResult<Self> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        // Not truncated on open, that would wipe the pid of the holder
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                    sleep(RETRY_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => return Err(held_error(path).into()),
+                Err(TryLockError::Error(err)) => {
+                    return Err(format!("Could not lock {}: {err}", path.display()).into())
+                }
+            }
+        }
+
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+
+        Ok(Self { _file: file })
+    }
+}
+
+fn held_error(path: &Path) -> String {
+    let pid = read_to_string(path)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok());
+
+    match pid {
+        Some(pid) => format!(
+            "Another cfx process (pid {pid}) holds the lock on {}",
+            path.display()
+        ),
+        None => format!("Another cfx process holds the lock on {}", path.display()),
+    }
+}
+
+/// The lock file guarding `path`, next to it.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut result = path.as_os_str().to_owned();
+    result.push(".lock");
+    PathBuf::from(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_lock_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(&dir.path().join("config.toml"));
+
+        let held = FileLock::acquire(&path).unwrap();
+        let Err(err) = FileLock::acquire_within(&path, Duration::ZERO) else {
+            panic!("the lock was acquired twice");
+        };
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Another cfx process (pid {}) holds the lock on {}",
+                std::process::id(),
+                path.display()
+            )
+        );
+
+        drop(held);
+        assert!(FileLock::acquire_within(&path, Duration::ZERO).is_ok());
+    }
+}
@@ -0,0 +1,909 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// A syntax error with the 1-based line and column, in bytes, it was found at.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Checks that `source` is syntactically valid Lua 5.4, accepting the CfxLua
+/// extensions after [`substitute_cfx`] rewrote them.
+pub fn check_syntax(source: &str) -> Result<(), SyntaxError> {
+    let source = substitute_cfx(source);
+    let tokens = tokenize(&source)?;
+    Parser {
+        tokens,
+        position: 0,
+        loops: 0,
+    }
+    .chunk()
+}
+
+/// Rewrites the CfxLua extensions to plain Lua of the same length, so that
+/// lines and columns of errors still point into the original source:
+///
+/// - backtick hashes (`` `prop_chair` ``) become a number literal
+/// - compound assignments (`x += 1`) drop the operator (`x  = 1`)
+/// - safe navigation (`a?.b`, `a?[k]`) drops the `?`
+///
+/// Vector values are built by ordinary calls such as `vector3(1, 2, 3)` and
+/// need no rewriting. Strings and comments are left alone.
+pub fn substitute_cfx(source: &str) -> String {
+    let bytes = source.as_bytes();
+    let mut result = bytes.to_vec();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i += 2;
+                match long_bracket_level(bytes, i) {
+                    Some(level) => i = skip_long_bracket(bytes, i, level).unwrap_or(bytes.len()),
+                    None => {
+                        while i < bytes.len() && bytes[i] != b'\n' {
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            b'[' => match long_bracket_level(bytes, i) {
+                Some(level) => i = skip_long_bracket(bytes, i, level).unwrap_or(bytes.len()),
+                None => i += 1,
+            },
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'`' => {
+                let end = bytes[i + 1..]
+                    .iter()
+                    .position(|&byte| byte == b'`' || byte == b'\n')
+                    .map(|offset| i + 1 + offset);
+                match end {
+                    Some(end) if bytes[end] == b'`' => {
+                        result[i] = b'0';
+                        result[i + 1..=end].fill(b' ');
+                        i = end + 1;
+                    }
+                    _ => i += 1,
+                }
+            }
+            b'?' if matches!(bytes.get(i + 1), Some(b'.' | b'[')) => {
+                result[i] = b' ';
+                i += 1;
+            }
+            b'<' | b'>'
+                if bytes.get(i + 1) == Some(&bytes[i]) && bytes.get(i + 2) == Some(&b'=') =>
+            {
+                result[i..i + 2].fill(b' ');
+                i += 3;
+            }
+            b'+' | b'-' | b'*' | b'/' | b'%' | b'^' | b'&' | b'|'
+                if bytes.get(i + 1) == Some(&b'=') && bytes.get(i + 2) != Some(&b'=') =>
+            {
+                result[i] = b' ';
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    // Only ASCII bytes were replaced, by ASCII bytes
+    String::from_utf8(result).expect("substitution keeps UTF-8 intact")
+}
+
+/// The level of a long bracket (`[[` is 0, `[==[` is 2) opening at `start`.
+fn long_bracket_level(bytes: &[u8], start: usize) -> Option<usize> {
+    if bytes.get(start) != Some(&b'[') {
+        return None;
+    }
+
+    let level = bytes[start + 1..]
+        .iter()
+        .take_while(|&&byte| byte == b'=')
+        .count();
+    (bytes.get(start + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+/// Skips the long bracket opening at `start`, returning the position after
+/// its closing bracket, or `None` when it is not closed.
+fn skip_long_bracket(bytes: &[u8], start: usize, level: usize) -> Option<usize> {
+    let close = format!("]{}]", "=".repeat(level));
+    let content = start + level + 2;
+    bytes[content..]
+        .windows(close.len())
+        .position(|window| window == close.as_bytes())
+        .map(|offset| content + offset + close.len())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Keyword(&'static str),
+    Number,
+    Str,
+    Op(&'static str),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Name(name) => write!(f, "`{name}`"),
+            Token::Keyword(keyword) | Token::Op(keyword) => write!(f, "`{keyword}`"),
+            Token::Number => f.write_str("a number"),
+            Token::Str => f.write_str("a string"),
+            Token::Eof => f.write_str("the end of the file"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+const KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Operators, longest first so that the first match is the longest one.
+const OPERATORS: [&str; 33] = [
+    "...", "..", "==", "~=", "<=", ">=", "<<", ">>", "//", "::", "+", "-", "*", "/", "%", "^", "#",
+    "&", "~", "|", "<", ">", "=", "(", ")", "{", "}", "[", "]", ";", ":", ",", ".",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>, SyntaxError> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    let mut line = 1;
+    let mut line_start = 0;
+
+    // A leading `#!` line is skipped by the interpreter
+    if bytes.starts_with(b"#") {
+        while i < bytes.len() && bytes[i] != b'\n' {
+            i += 1;
+        }
+    }
+
+    let error = |line: usize, column: usize, message: String| SyntaxError {
+        line,
+        column,
+        message,
+    };
+
+    while i < bytes.len() {
+        let byte = bytes[i];
+        let column = i - line_start + 1;
+        let start_line = line;
+
+        if byte.is_ascii_whitespace() {
+            advance(bytes, i, i + 1, &mut line, &mut line_start);
+            i += 1;
+            continue;
+        }
+
+        if bytes[i..].starts_with(b"--") {
+            let start = i + 2;
+            let end = match long_bracket_level(bytes, start) {
+                Some(level) => skip_long_bracket(bytes, start, level)
+                    .ok_or_else(|| error(line, column, "unfinished long comment".to_owned()))?,
+                None => bytes[start..]
+                    .iter()
+                    .position(|&byte| byte == b'\n')
+                    .map_or(bytes.len(), |offset| start + offset),
+            };
+            advance(bytes, i, end, &mut line, &mut line_start);
+            i = end;
+            continue;
+        }
+
+        let token = if let Some(level) = long_bracket_level(bytes, i) {
+            let end = skip_long_bracket(bytes, i, level)
+                .ok_or_else(|| error(line, column, "unfinished long string".to_owned()))?;
+            advance(bytes, i, end, &mut line, &mut line_start);
+            i = end;
+            Token::Str
+        } else if byte == b'"' || byte == b'\'' {
+            i += 1;
+            loop {
+                match bytes.get(i) {
+                    None | Some(b'\n') => {
+                        return Err(error(line, column, "unfinished string".to_owned()))
+                    }
+                    Some(&quote) if quote == byte => break,
+                    Some(b'\\') => match bytes.get(i + 1) {
+                        // An escaped newline continues the string on the next
+                        // line, `\r\n` and `\n\r` counting as one newline
+                        Some(&first @ (b'\n' | b'\r')) => {
+                            i += 2;
+                            let second = bytes.get(i).copied();
+                            if matches!(second, Some(b'\n' | b'\r')) && second != Some(first) {
+                                i += 1;
+                            }
+                            line += 1;
+                            line_start = i;
+                        }
+                        // `\z` skips the whitespace that follows, newlines included
+                        Some(b'z') => {
+                            let start = i + 2;
+                            let end = bytes[start..]
+                                .iter()
+                                .position(|byte| !byte.is_ascii_whitespace())
+                                .map_or(bytes.len(), |offset| start + offset);
+                            advance(bytes, start, end, &mut line, &mut line_start);
+                            i = end;
+                        }
+                        _ => i += 2,
+                    },
+                    Some(_) => i += 1,
+                }
+            }
+            i += 1;
+            Token::Str
+        } else if byte.is_ascii_digit()
+            || (byte == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            let start = i;
+            let hex = bytes[i..].starts_with(b"0x") || bytes[i..].starts_with(b"0X");
+            let exponent: &[u8] = if hex { b"pP" } else { b"eE" };
+            if hex {
+                i += 2;
+            }
+
+            while let Some(&byte) = bytes.get(i) {
+                if exponent.contains(&byte) && matches!(bytes.get(i + 1), Some(b'+' | b'-')) {
+                    i += 2;
+                } else if byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'_' {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let text = &source[start..i];
+            if !valid_number(text, hex) {
+                return Err(error(
+                    line,
+                    column,
+                    format!("malformed number near `{text}`"),
+                ));
+            }
+            Token::Number
+        } else if byte.is_ascii_alphabetic() || byte == b'_' || !byte.is_ascii() {
+            let start = i;
+            while bytes.get(i).is_some_and(|&byte| {
+                byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii()
+            }) {
+                i += 1;
+            }
+
+            let name = &source[start..i];
+            match KEYWORDS.iter().find(|&&keyword| keyword == name) {
+                Some(keyword) => Token::Keyword(keyword),
+                None => Token::Name(name.to_owned()),
+            }
+        } else if let Some(op) = OPERATORS
+            .iter()
+            .find(|op| bytes[i..].starts_with(op.as_bytes()))
+        {
+            i += op.len();
+            Token::Op(op)
+        } else {
+            let symbol = source[i..].chars().next().unwrap_or_default();
+            return Err(error(line, column, format!("unexpected symbol `{symbol}`")));
+        };
+
+        tokens.push(Spanned {
+            token,
+            line: start_line,
+            column,
+        });
+    }
+
+    tokens.push(Spanned {
+        token: Token::Eof,
+        line,
+        column: i - line_start + 1,
+    });
+    Ok(tokens)
+}
+
+/// Moves `line` and `line_start` past the newlines in `bytes[from..to]`.
+fn advance(bytes: &[u8], from: usize, to: usize, line: &mut usize, line_start: &mut usize) {
+    for (offset, &byte) in bytes[from..to].iter().enumerate() {
+        if byte == b'\n' {
+            *line += 1;
+            *line_start = from + offset + 1;
+        }
+    }
+}
+
+fn valid_number(text: &str, hex: bool) -> bool {
+    let (digits, exponent) = if hex {
+        (&text[2..], ['p', 'P'])
+    } else {
+        (text, ['e', 'E'])
+    };
+
+    let (mantissa, power) = match digits.split_once(exponent) {
+        Some((mantissa, power)) => (mantissa, Some(power)),
+        None => (digits, None),
+    };
+
+    let mut parts = mantissa.splitn(2, '.');
+    let whole = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let is_digit = |c: char| {
+        if hex {
+            c.is_ascii_hexdigit()
+        } else {
+            c.is_ascii_digit()
+        }
+    };
+
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.chars().all(is_digit)
+        && fraction.chars().all(is_digit)
+        && power.is_none_or(|power| {
+            let power = power.trim_start_matches(['+', '-']);
+            !power.is_empty() && power.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// Binary operators with their left and right priority, as in Lua's parser.
+fn binary_priority(token: &Token) -> Option<(u8, u8)> {
+    Some(match token {
+        Token::Keyword("or") => (1, 1),
+        Token::Keyword("and") => (2, 2),
+        Token::Op("<" | ">" | "<=" | ">=" | "~=" | "==") => (3, 3),
+        Token::Op("|") => (4, 4),
+        Token::Op("~") => (5, 5),
+        Token::Op("&") => (6, 6),
+        Token::Op("<<" | ">>") => (7, 7),
+        Token::Op("..") => (9, 8),
+        Token::Op("+" | "-") => (10, 10),
+        Token::Op("*" | "/" | "//" | "%") => (11, 11),
+        Token::Op("^") => (14, 13),
+        _ => return None,
+    })
+}
+
+const UNARY_PRIORITY: u8 = 12;
+
+/// A recursive descent parser following the Lua 5.4 grammar, which only
+/// checks the syntax and builds nothing.
+struct Parser {
+    tokens: Vec<Spanned>,
+    position: usize,
+    /// Depth of enclosing loops, for `break`
+    loops: usize,
+}
+
+type ParseResult = Result<(), SyntaxError>;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].token
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.position].line
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token != Token::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> SyntaxError {
+        let token = &self.tokens[self.position];
+        SyntaxError {
+            line: token.line,
+            column: token.column,
+            message: format!("{} near {}", message.into(), token.token),
+        }
+    }
+
+    fn accept(&mut self, token: Token) -> bool {
+        if *self.peek() == token {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> ParseResult {
+        if self.accept(token.clone()) {
+            Ok(())
+        } else {
+            Err(self.error(format!("{token} expected")))
+        }
+    }
+
+    /// Expects the `close` token ending a construct opened by `open` at `line`.
+    fn expect_closing(&mut self, close: Token, open: &str, line: usize) -> ParseResult {
+        if self.accept(close.clone()) {
+            Ok(())
+        } else if line == self.line() {
+            Err(self.error(format!("{close} expected")))
+        } else {
+            Err(self.error(format!(
+                "{close} expected (to close `{open}` at line {line})"
+            )))
+        }
+    }
+
+    fn name(&mut self) -> ParseResult {
+        match self.peek() {
+            Token::Name(_) => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error("name expected")),
+        }
+    }
+
+    fn chunk(&mut self) -> ParseResult {
+        self.block()?;
+        match self.peek() {
+            Token::Eof => Ok(()),
+            _ => Err(self.error("`<eof>` expected")),
+        }
+    }
+
+    fn block_follows(&self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Eof | Token::Keyword("end" | "else" | "elseif" | "until")
+        )
+    }
+
+    fn block(&mut self) -> ParseResult {
+        while !self.block_follows() {
+            if self.accept(Token::Keyword("return")) {
+                if !self.block_follows() && *self.peek() != Token::Op(";") {
+                    self.expression_list()?;
+                }
+                self.accept(Token::Op(";"));
+                if !self.block_follows() {
+                    return Err(self.error("`return` must be the last statement of a block"));
+                }
+                return Ok(());
+            }
+
+            self.statement()?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(&mut self) -> ParseResult {
+        let line = self.line();
+        match self.peek().clone() {
+            Token::Op(";") => {
+                self.next();
+            }
+            Token::Op("::") => {
+                self.next();
+                self.name()?;
+                self.expect(Token::Op("::"))?;
+            }
+            Token::Keyword("break") => {
+                if self.loops == 0 {
+                    return Err(self.error("`break` outside a loop"));
+                }
+                self.next();
+            }
+            Token::Keyword("goto") => {
+                self.next();
+                self.name()?;
+            }
+            Token::Keyword("do") => {
+                self.next();
+                self.block()?;
+                self.expect_closing(Token::Keyword("end"), "do", line)?;
+            }
+            Token::Keyword("while") => {
+                self.next();
+                self.expression()?;
+                self.expect(Token::Keyword("do"))?;
+                self.loop_block()?;
+                self.expect_closing(Token::Keyword("end"), "while", line)?;
+            }
+            Token::Keyword("repeat") => {
+                self.next();
+                self.loop_block()?;
+                self.expect_closing(Token::Keyword("until"), "repeat", line)?;
+                self.expression()?;
+            }
+            Token::Keyword("if") => {
+                self.next();
+                self.expression()?;
+                self.expect(Token::Keyword("then"))?;
+                self.block()?;
+                while self.accept(Token::Keyword("elseif")) {
+                    self.expression()?;
+                    self.expect(Token::Keyword("then"))?;
+                    self.block()?;
+                }
+                if self.accept(Token::Keyword("else")) {
+                    self.block()?;
+                }
+                self.expect_closing(Token::Keyword("end"), "if", line)?;
+            }
+            Token::Keyword("for") => {
+                self.next();
+                self.name()?;
+                if self.accept(Token::Op("=")) {
+                    self.expression()?;
+                    self.expect(Token::Op(","))?;
+                    self.expression()?;
+                    if self.accept(Token::Op(",")) {
+                        self.expression()?;
+                    }
+                } else {
+                    while self.accept(Token::Op(",")) {
+                        self.name()?;
+                    }
+                    if !self.accept(Token::Keyword("in")) {
+                        return Err(self.error("`=` or `in` expected"));
+                    }
+                    self.expression_list()?;
+                }
+                self.expect(Token::Keyword("do"))?;
+                self.loop_block()?;
+                self.expect_closing(Token::Keyword("end"), "for", line)?;
+            }
+            Token::Keyword("function") => {
+                self.next();
+                self.name()?;
+                while self.accept(Token::Op(".")) {
+                    self.name()?;
+                }
+                if self.accept(Token::Op(":")) {
+                    self.name()?;
+                }
+                self.function_body(line)?;
+            }
+            Token::Keyword("local") => {
+                self.next();
+                if self.accept(Token::Keyword("function")) {
+                    self.name()?;
+                    self.function_body(line)?;
+                } else {
+                    self.attribute_name()?;
+                    while self.accept(Token::Op(",")) {
+                        self.attribute_name()?;
+                    }
+                    if self.accept(Token::Op("=")) {
+                        self.expression_list()?;
+                    }
+                }
+            }
+            _ => self.expression_statement()?,
+        }
+
+        Ok(())
+    }
+
+    /// A local name with an optional `<const>` or `<close>` attribute.
+    fn attribute_name(&mut self) -> ParseResult {
+        self.name()?;
+        if self.accept(Token::Op("<")) {
+            match self.next() {
+                Token::Name(attribute) if attribute == "const" || attribute == "close" => {}
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("unknown attribute"));
+                }
+            }
+            self.expect(Token::Op(">"))?;
+        }
+
+        Ok(())
+    }
+
+    fn loop_block(&mut self) -> ParseResult {
+        self.loops += 1;
+        let result = self.block();
+        self.loops -= 1;
+        result
+    }
+
+    /// A call, or an assignment to one or more variables.
+    fn expression_statement(&mut self) -> ParseResult {
+        let mut assignable = self.suffixed_expression()?;
+        if matches!(self.peek(), Token::Op("=" | ",")) {
+            while self.accept(Token::Op(",")) {
+                assignable &= self.suffixed_expression()?;
+            }
+            if !assignable {
+                return Err(self.error("cannot assign to a call"));
+            }
+            self.expect(Token::Op("="))?;
+            return self.expression_list();
+        }
+
+        if assignable {
+            return Err(self.error("syntax error"));
+        }
+
+        Ok(())
+    }
+
+    /// Parses a primary expression with its suffixes, returning whether it
+    /// can be assigned to (it does not end in a call).
+    fn suffixed_expression(&mut self) -> Result<bool, SyntaxError> {
+        let line = self.line();
+        match self.peek() {
+            Token::Name(_) => {
+                self.next();
+            }
+            Token::Op("(") => {
+                self.next();
+                self.expression()?;
+                self.expect_closing(Token::Op(")"), "(", line)?;
+            }
+            _ => return Err(self.error("unexpected symbol")),
+        }
+
+        let mut assignable = true;
+        loop {
+            match self.peek() {
+                Token::Op(".") => {
+                    self.next();
+                    self.name()?;
+                    assignable = true;
+                }
+                Token::Op("[") => {
+                    self.next();
+                    self.expression()?;
+                    self.expect(Token::Op("]"))?;
+                    assignable = true;
+                }
+                Token::Op(":") => {
+                    self.next();
+                    self.name()?;
+                    self.call_arguments()?;
+                    assignable = false;
+                }
+                Token::Op("(" | "{") | Token::Str => {
+                    self.call_arguments()?;
+                    assignable = false;
+                }
+                _ => return Ok(assignable),
+            }
+        }
+    }
+
+    fn call_arguments(&mut self) -> ParseResult {
+        let line = self.line();
+        match self.peek() {
+            Token::Str => {
+                self.next();
+                Ok(())
+            }
+            Token::Op("{") => self.table_constructor(),
+            Token::Op("(") => {
+                self.next();
+                if *self.peek() != Token::Op(")") {
+                    self.expression_list()?;
+                }
+                self.expect_closing(Token::Op(")"), "(", line)
+            }
+            _ => Err(self.error("function arguments expected")),
+        }
+    }
+
+    fn table_constructor(&mut self) -> ParseResult {
+        let line = self.line();
+        self.expect(Token::Op("{"))?;
+        while *self.peek() != Token::Op("}") {
+            if self.accept(Token::Op("[")) {
+                self.expression()?;
+                self.expect(Token::Op("]"))?;
+                self.expect(Token::Op("="))?;
+            } else if matches!(self.peek(), Token::Name(_))
+                && self.tokens[self.position + 1].token == Token::Op("=")
+            {
+                self.position += 2;
+            }
+            self.expression()?;
+
+            if !self.accept(Token::Op(",")) && !self.accept(Token::Op(";")) {
+                break;
+            }
+        }
+
+        self.expect_closing(Token::Op("}"), "{", line)
+    }
+
+    fn function_body(&mut self, line: usize) -> ParseResult {
+        self.expect(Token::Op("("))?;
+        if *self.peek() != Token::Op(")") {
+            loop {
+                if self.accept(Token::Op("...")) {
+                    break;
+                }
+                self.name()?;
+                if !self.accept(Token::Op(",")) {
+                    break;
+                }
+            }
+        }
+        self.expect(Token::Op(")"))?;
+
+        // A function body starts a new loop context for `break`
+        let loops = std::mem::take(&mut self.loops);
+        let result = self.block();
+        self.loops = loops;
+        result?;
+
+        self.expect_closing(Token::Keyword("end"), "function", line)
+    }
+
+    fn expression_list(&mut self) -> ParseResult {
+        self.expression()?;
+        while self.accept(Token::Op(",")) {
+            self.expression()?;
+        }
+
+        Ok(())
+    }
+
+    fn expression(&mut self) -> ParseResult {
+        self.subexpression(0)
+    }
+
+    /// Parses operands joined by binary operators binding tighter than `limit`.
+    fn subexpression(&mut self, limit: u8) -> ParseResult {
+        if matches!(
+            self.peek(),
+            Token::Keyword("not") | Token::Op("-" | "#" | "~")
+        ) {
+            self.next();
+            self.subexpression(UNARY_PRIORITY)?;
+        } else {
+            self.simple_expression()?;
+        }
+
+        while let Some((left, right)) = binary_priority(self.peek()) {
+            if left <= limit {
+                break;
+            }
+            self.next();
+            self.subexpression(right)?;
+        }
+
+        Ok(())
+    }
+
+    fn simple_expression(&mut self) -> ParseResult {
+        let line = self.line();
+        match self.peek() {
+            Token::Number
+            | Token::Str
+            | Token::Keyword("nil" | "true" | "false")
+            | Token::Op("...") => {
+                self.next();
+                Ok(())
+            }
+            Token::Op("{") => self.table_constructor(),
+            Token::Keyword("function") => {
+                self.next();
+                self.function_body(line)
+            }
+            _ => self.suffixed_expression().map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitute_cfx_test() {
+        assert_eq!(
+            substitute_cfx("local h = `prop_chair`"),
+            format!("local h = 0{}", " ".repeat(11))
+        );
+        assert_eq!(substitute_cfx("x += 1 y <<= 2"), "x  = 1 y   = 2");
+        assert_eq!(substitute_cfx("a = b?.c?[1]"), "a = b .c [1]");
+
+        // Comparisons and the contents of strings and comments stay as they are
+        let plain = "if a <= b and c >= d and e ~= f then end -- x += `y`\nlocal s = 'x += `y`' .. [[a?.b]]";
+        assert_eq!(substitute_cfx(plain), plain);
+
+        let source = "local h = `hash` print(h += 1)";
+        assert_eq!(substitute_cfx(source).len(), source.len());
+    }
+
+    #[test]
+    fn valid_syntax_test() {
+        let source = r#"#!/usr/bin/env lua
+local lib <const> = require 'lib'
+local a, b = 7 // 2, 1 << 4 | 3 & ~0
+for i = 1, 10, 2 do
+    if i % 2 == 0 then goto continue end
+    print(("%d"):format(i), #a, -b ^ 2)
+    ::continue::
+end
+for k, v in pairs({ 1, 2, x = 3, ["y"] = 4; 5 }) do break end
+local t = setmetatable({}, { __index = function(self, key, ...) return ... end })
+t.x.y[1] = function() end
+local model = `prop_chair`
+model += 1
+print [[long
+string]] --[==[ long
+comment ]==]
+repeat local done = true until done
+while false do end
+return t:method "arg"
+"#;
+        assert_eq!(check_syntax(source), Ok(()));
+        assert_eq!(check_syntax("local x = 0x1Fp-2 + .5e3 + 3."), Ok(()));
+        assert_eq!(
+            check_syntax("local s = 'a\\z\n    b' .. \"c\\\r\nd\" .. 'e\\\n\rf'"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn syntax_error_test() {
+        let error = |source: &str| check_syntax(source).unwrap_err();
+
+        assert_eq!(
+            error("function f()\n  print('x')\n"),
+            SyntaxError {
+                line: 3,
+                column: 1,
+                message: "`end` expected (to close `function` at line 1) near the end of the file"
+                    .to_owned()
+            }
+        );
+        assert_eq!(error("local x = 'open\n").line, 1);
+        assert_eq!(error("x = = 1").column, 5);
+        assert_eq!(
+            error("print('a') = 1").message,
+            "cannot assign to a call near `=`"
+        );
+        assert_eq!(error("x").message, "syntax error near the end of the file");
+        assert_eq!(
+            error("break").message,
+            "`break` outside a loop near `break`"
+        );
+        assert_eq!(
+            error("local x <nope> = 1").message,
+            "unknown attribute near `nope`"
+        );
+        assert_eq!(error("local n = 3x").message, "malformed number near `3x`");
+        assert_eq!(error("return 1\nprint()").line, 2);
+        assert_eq!(error("local s = [[open").message, "unfinished long string");
+        assert_eq!(error("local s = 'a\\z\n\n  b'\nx = = 1").line, 4);
+        assert_eq!(error("local s = 'a\\\r\nb'\nx = = 1").line, 3);
+        assert_eq!(error("local s = 'a\\z\n").message, "unfinished string");
+    }
+}
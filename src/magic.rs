@@ -0,0 +1,213 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::manifest::{expand_pattern, Manifest, SCRIPT_DIRECTIVES};
+use crate::CfxResult;
+
+/// `RSC7` read as a little-endian dword.
+pub const RSC7_MAGIC: u32 = 0x37435352;
+/// `RSC8` read as a little-endian dword.
+pub const RSC8_MAGIC: u32 = 0x38435352;
+/// `RPF7` read as a little-endian dword, stored as `7FPR` on disk.
+pub const RPF7_MAGIC: u32 = 0x52504637;
+/// `ADAT` read as a little-endian dword.
+pub const AWC_MAGIC: u32 = 0x54414441;
+/// `TADA`, the byte-swapped magic of big-endian (console) AWC containers.
+pub const AWC_MAGIC_BIG_ENDIAN: u32 = 0x41444154;
+pub const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+/// Leading bytes of the `.fxap` key file and of escrow-encrypted scripts.
+pub const FXAP_MAGIC: [u8; 4] = *b"FXAP";
+/// Leading bytes of a zstd frame, which some newer builds compress resource
+/// pages with instead of deflate.
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// File formats the tool recognizes by their leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    Rsc7,
+    Rsc8,
+    Rpf7,
+    Awc,
+    Gzip,
+    Fxap,
+}
+
+impl Format {
+    pub const ALL: [Format; 6] = [
+        Format::Rsc7,
+        Format::Rsc8,
+        Format::Rpf7,
+        Format::Awc,
+        Format::Gzip,
+        Format::Fxap,
+    ];
+
+    /// Leading bytes identifying the format.
+    pub fn magic(&self) -> Vec<u8> {
+        match self {
+            Format::Rsc7 => RSC7_MAGIC.to_le_bytes().to_vec(),
+            Format::Rsc8 => RSC8_MAGIC.to_le_bytes().to_vec(),
+            Format::Rpf7 => RPF7_MAGIC.to_le_bytes().to_vec(),
+            Format::Awc => AWC_MAGIC.to_le_bytes().to_vec(),
+            Format::Gzip => GZIP_MAGIC.to_vec(),
+            Format::Fxap => FXAP_MAGIC.to_vec(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Format::Rsc7 => "Compressed resource (GTA V)",
+            Format::Rsc8 => "Compressed resource (RDR2)",
+            Format::Rpf7 => "Package archive",
+            Format::Awc => "Audio wave container",
+            Format::Gzip => "Gzip stream",
+            Format::Fxap => "Escrow-protected file",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Rsc7 => "RSC7",
+            Format::Rsc8 => "RSC8",
+            Format::Rpf7 => "RPF7",
+            Format::Awc => "AWC",
+            Format::Gzip => "gzip",
+            Format::Fxap => "FXAP",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Identifies the format of `bytes` from its magic, if it is a known one.
+pub fn detect_format(bytes: &[u8]) -> Option<Format> {
+    Format::ALL
+        .into_iter()
+        .find(|format| bytes.starts_with(&format.magic()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    Full,
+    Partial,
+}
+
+/// Which scripts of an escrowed resource can and cannot be reviewed.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EscrowReport {
+    pub status: EscrowStatus,
+    /// Scripts relative to the resource, starting with [`FXAP_MAGIC`]
+    pub protected: Vec<String>,
+    pub plain: Vec<String>,
+}
+
+/// Detects Cfx escrow protection from the `.fxap` file, the `escrow_ignore`
+/// directive and the magic of each script, without decrypting anything.
+/// Returns `None` for resources showing none of these.
+pub fn detect_escrow(resource_dir: &Path, manifest: &Manifest) -> CfxResult<Option<EscrowReport>> {
+    let marked = resource_dir.join(".fxap").is_file() || manifest.get("escrow_ignore").is_some();
+
+    let mut protected = vec![];
+    let mut plain = vec![];
+    for pattern in manifest.values(&SCRIPT_DIRECTIVES) {
+        if pattern.starts_with('@') {
+            continue;
+        }
+
+        for file in expand_pattern(resource_dir, pattern)? {
+            let mut magic = Vec::with_capacity(FXAP_MAGIC.len());
+            File::open(&file)?
+                .take(FXAP_MAGIC.len() as u64)
+                .read_to_end(&mut magic)?;
+
+            let relative = file.strip_prefix(resource_dir).unwrap_or(&file);
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let list = if detect_format(&magic) == Some(Format::Fxap) {
+                &mut protected
+            } else {
+                &mut plain
+            };
+
+            if !list.contains(&relative) {
+                list.push(relative);
+            }
+        }
+    }
+
+    if !marked && protected.is_empty() {
+        return Ok(None);
+    }
+
+    let status = if plain.is_empty() {
+        EscrowStatus::Full
+    } else {
+        EscrowStatus::Partial
+    };
+    Ok(Some(EscrowReport {
+        status,
+        protected,
+        plain,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn detect_format_test() {
+        for format in Format::ALL {
+            let mut data = format.magic();
+            data.extend_from_slice(&[0u8; 12]);
+
+            assert_eq!(detect_format(&data), Some(format), "{format}");
+        }
+
+        assert_eq!(detect_format(b"RSC7"), Some(Format::Rsc7));
+        assert_eq!(detect_format(b"7FPR"), Some(Format::Rpf7));
+        assert_eq!(detect_format(b"ADAT"), Some(Format::Awc));
+        assert_eq!(detect_format(b"RSC"), None);
+        assert_eq!(detect_format(&[]), None);
+    }
+
+    #[test]
+    fn detect_escrow_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+
+        // Dummy data following the container layout: the magic, then opaque bytes
+        let mut encrypted = FXAP_MAGIC.to_vec();
+        encrypted.extend_from_slice(&[0xA5; 28]);
+        write(dir.path().join(".fxap"), &encrypted).unwrap();
+        write(dir.path().join("client/main.lua"), &encrypted).unwrap();
+        write(dir.path().join("config.lua"), "Config = {}\n").unwrap();
+
+        let manifest = Manifest::parse(
+            "shared_script 'config.lua'\nclient_script 'client/*.lua'\nescrow_ignore { 'config.lua' }\n",
+        )
+        .unwrap();
+        let report = detect_escrow(dir.path(), &manifest).unwrap().unwrap();
+
+        assert_eq!(
+            report,
+            EscrowReport {
+                status: EscrowStatus::Partial,
+                protected: vec!["client/main.lua".to_owned()],
+                plain: vec!["config.lua".to_owned()],
+            }
+        );
+
+        let manifest = Manifest::parse("shared_script 'config.lua'\n").unwrap();
+        std::fs::remove_file(dir.path().join(".fxap")).unwrap();
+        assert_eq!(detect_escrow(dir.path(), &manifest).unwrap(), None);
+    }
+}
@@ -1,42 +1,285 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use simple_logger::SimpleLogger;
 
 mod archive;
+mod atomic;
+mod awc;
+mod batch;
+mod cache;
+mod cancel;
 mod commands;
+mod config;
+mod diff;
+mod errors;
+mod i18n;
+mod icon;
+mod joaat;
+mod lint;
+mod lock;
+mod lua;
+mod magic;
+mod manifest;
+mod map;
+mod math;
+mod meta;
+mod naming;
+mod nav;
+mod network;
+mod output;
+mod parse;
+mod passthrough;
+mod progress;
+mod project;
+mod scan;
+mod template;
+mod texture;
+mod watch;
+mod wizard;
+mod writer;
 
-use crate::commands::create::handle_create_command;
-use crate::commands::unpack::handle_unpack_command;
+use crate::cancel::{ctrlc_pressed, install_ctrlc, is_cancellation};
+use crate::commands::add::{handle_add_command, AddArgs};
+use crate::commands::audit_map::{handle_audit_map_command, AuditMapArgs};
+use crate::commands::bump::{handle_bump_command, BumpArgs};
+use crate::commands::cache::{handle_cache_command, CacheCommand};
+use crate::commands::check::{handle_check_command, CheckArgs};
+use crate::commands::check_events::{handle_check_events_command, CheckEventsArgs};
+use crate::commands::check_exports::{handle_check_exports_command, CheckExportsArgs};
+use crate::commands::compare_manifests::{handle_compare_manifests_command, CompareManifestsArgs};
+use crate::commands::create::{handle_create_command, CreateArgs};
+use crate::commands::dev::{handle_dev_command, DevCommand};
+use crate::commands::doctor::handle_doctor_command;
+use crate::commands::explain::{handle_explain_command, ExplainArgs};
+use crate::commands::explain_flags::{handle_explain_flags_command, ExplainFlagsArgs};
+use crate::commands::formats::handle_formats_command;
+use crate::commands::init::{handle_init_command, InitArgs};
+use crate::commands::manifest::{handle_manifest_command, ManifestArgs};
+use crate::commands::pack_resource::{handle_pack_resource_command, PackResourceArgs};
+use crate::commands::repack::{handle_repack_command, RepackArgs};
+use crate::commands::strings::{handle_strings_command, StringsArgs};
+use crate::commands::template::{handle_template_command, TemplateCommand};
+use crate::commands::unpack::{handle_unpack_command, UnpackArgs};
+use crate::commands::upgrade_manifest::{handle_upgrade_manifest_command, UpgradeManifestArgs};
+use crate::commands::validate::{handle_validate_command, ValidateArgs};
+use crate::commands::watch::{handle_watch_command, WatchArgs};
+use crate::errors::ErrorCode;
+use crate::i18n::{t, tf};
+use crate::output::{init_logger, summary, take_warnings, Envelope};
+use crate::progress::cli_progress;
 
 pub type CfxResult<T> = Result<T, Box<dyn std::error::Error>>;
 
 #[derive(Parser)]
-struct Cli {
+pub(crate) struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output for other programs: never wait for enter before exiting, log to
+    /// stderr without colors or timestamps, print a JSON envelope with the
+    /// command result on stdout and exit with status 1 when the command fails
+    #[arg(long, global = true)]
+    machine: bool,
+
+    /// Same as --machine
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Enable hidden commands that are still in development
+    #[arg(long, global = true, hide = true)]
+    experimental: bool,
+
+    /// Flush written files and their directories to disk before moving on,
+    /// so they survive a power loss right after the command returns
+    #[arg(long, global = true)]
+    durable: bool,
+
+    /// Never touch the network: remote templates come from the cache or the
+    /// command fails naming what it needed
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Answer yes to every confirmation, and accept the answers of `create`
+    /// without reviewing them
+    #[arg(long, short, global = true)]
+    yes: bool,
+
+    /// Language of the prompts and messages (en or es), by default the `lang`
+    /// config key or else the LANG environment variable
+    #[arg(long, global = true, value_name = "CODE", value_parser = i18n::parse_language)]
+    lang: Option<i18n::Language>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Create,
-    Unpack { name: String },
+    /// Scaffold a new resource. Esc goes back to the previous question and
+    /// Ctrl-C is safe at any prompt, leaving no project directory behind
+    Create(Box<CreateArgs>),
+    /// Adopt an existing resource by writing a cfx.toml for it
+    Init(InitArgs),
+    /// Import a library into the resource's manifest
+    Add(AddArgs),
+    Unpack(UnpackArgs),
+    /// Recompress an RSC7 file at another deflate level
+    Repack(RepackArgs),
+    /// Print the printable strings in the decompressed pages of an RSC7 file
+    Strings(StringsArgs),
+    /// Report undefined archetypes, duplicate entity GUIDs and interior counts
+    /// across the .ymap and .ytyp files of a map resource
+    AuditMap(AuditMapArgs),
+    /// Manage the local template registry
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Manage the cache of downloaded templates
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Validate a resource, or the resources of a server directory
+    Validate(ValidateArgs),
+    /// Check the syntax of the Lua files a resource's manifest references
+    Check(CheckArgs),
+    /// Re-run the checks of a resource whenever its files change, until Ctrl-C
+    Watch(WatchArgs),
+    /// Report export calls that target unknown resources or exports
+    CheckExports(CheckExportsArgs),
+    /// Report orphaned, colliding and unprefixed events between resources
+    CheckEvents(CheckEventsArgs),
+    /// Validate a resource and zip the files its manifest references
+    PackResource(PackResourceArgs),
+    /// Generate an fxmanifest.lua from a JSON spec
+    Manifest(ManifestArgs),
+    /// Report the scripts, files and directives that differ between two
+    /// manifests, ignoring formatting
+    CompareManifests(CompareManifestsArgs),
+    /// Increment the version in the resource's manifest
+    Bump(BumpArgs),
+    /// Upgrade an old manifest to fx_version cerulean and Lua 5.4, keeping
+    /// its comments and layout
+    UpgradeManifest(UpgradeManifestArgs),
+    /// List the file formats recognized by their magic
+    Formats,
+    /// Decode a resource page flags dword, or encode one for a page size
+    ExplainFlags(ExplainFlagsArgs),
+    /// Check for optional tooling and report what is missing
+    Doctor,
+    /// Describe an error code, its common causes and how to fix it
+    Explain(ExplainArgs),
+    /// Developer tooling, requires --experimental
+    #[command(hide = true)]
+    Dev {
+        #[command(subcommand)]
+        command: DevCommand,
+    },
 }
 
 fn main() {
-    SimpleLogger::new().init().unwrap();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let command = matches.subcommand_name().unwrap_or_default();
+    let machine = cli.machine || cli.json;
+
+    // Commands may raise the max level for tracing, so the logger accepts debug
+    // records while the global filter starts out at info.
+    let logger = SimpleLogger::new().with_level(log::LevelFilter::Debug);
+    if machine {
+        init_logger(logger.without_timestamps().with_colors(false)).unwrap();
+    } else {
+        init_logger(logger).unwrap();
+    }
+
+    log::set_max_level(log::LevelFilter::Info);
+    output::set_machine(machine);
+    atomic::set_durable(cli.durable);
+    wizard::set_assume_yes(cli.yes);
+    let config = config::Config::load().unwrap_or_default();
+    network::set_offline(cli.offline || config.offline);
+    i18n::set_language(i18n::select_language(
+        cli.lang,
+        config.lang.as_deref(),
+        std::env::var("LANG").ok().as_deref(),
+    ));
 
-    let cli = Cli::parse();
     let result = match &cli.command {
-        Commands::Create => handle_create_command(),
-        Commands::Unpack { name } => handle_unpack_command(name),
+        Commands::Create(args) => handle_create_command(args, &install_ctrlc()).and_then(summary),
+        Commands::Init(args) => handle_init_command(args).and_then(summary),
+        Commands::Add(args) => handle_add_command(args).and_then(summary),
+        Commands::Unpack(args) => {
+            handle_unpack_command(args, cli_progress(machine).as_mut(), &install_ctrlc())
+                .and_then(summary)
+        }
+        Commands::Repack(args) => handle_repack_command(args).and_then(summary),
+        Commands::Strings(args) => handle_strings_command(args).and_then(summary),
+        Commands::AuditMap(args) => handle_audit_map_command(args).and_then(summary),
+        Commands::Template { command } => handle_template_command(command).and_then(summary),
+        Commands::Cache { command } => handle_cache_command(command).and_then(summary),
+        Commands::Validate(args) => handle_validate_command(args).and_then(summary),
+        Commands::Check(args) => handle_check_command(args).and_then(summary),
+        Commands::Watch(args) => handle_watch_command(args, &install_ctrlc()).and_then(summary),
+        Commands::CheckExports(args) => handle_check_exports_command(args).and_then(summary),
+        Commands::CheckEvents(args) => handle_check_events_command(args).and_then(summary),
+        Commands::PackResource(args) => handle_pack_resource_command(args).and_then(summary),
+        Commands::Manifest(args) => handle_manifest_command(args).and_then(summary),
+        Commands::CompareManifests(args) => {
+            handle_compare_manifests_command(args).and_then(summary)
+        }
+        Commands::Bump(args) => handle_bump_command(args).and_then(summary),
+        Commands::UpgradeManifest(args) => handle_upgrade_manifest_command(args).and_then(summary),
+        Commands::Formats => handle_formats_command().and_then(summary),
+        Commands::ExplainFlags(args) => handle_explain_flags_command(args).and_then(summary),
+        Commands::Doctor => handle_doctor_command().and_then(summary),
+        Commands::Explain(args) => handle_explain_command(args).and_then(summary),
+        Commands::Dev { .. } if !cli.experimental => {
+            Err("Dev commands are experimental, pass --experimental to use them".into())
+        }
+        Commands::Dev { command } => handle_dev_command(command).and_then(summary),
     };
 
+    // Like shells, report an interrupted run with 128 + SIGINT
+    let cancelled = result
+        .as_ref()
+        .is_err_and(|err| is_cancellation(err.as_ref()));
+    if machine {
+        let envelope = Envelope::new(command, result, take_warnings());
+        println!("{}", envelope.to_json());
+        if cancelled {
+            std::process::exit(130);
+        } else if !envelope.ok {
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let failed = result.is_err();
     match result {
-        Ok(_) => log::info!("Command completed successfully"),
-        Err(err) => log::error!("Command failed: {}", err),
+        Ok(_) => log::info!("{}", t("main.completed")),
+        Err(err) if cancelled => {
+            log::warn!("{err}");
+            std::process::exit(130);
+        }
+        Err(err) => match ErrorCode::of(err.as_ref()) {
+            Some(code) => log::error!(
+                "{}",
+                tf(
+                    "main.failed_code",
+                    &[("code", &code.to_string()), ("error", &err.to_string())]
+                )
+            ),
+            None => log::error!("{}", tf("main.failed", &[("error", &err.to_string())])),
+        },
     }
 
-    log::info!("Press enter to exit...");
+    // Ctrl-C already said the user is done with the window
+    if !ctrlc_pressed() {
+        log::info!("{}", t("main.press_enter"));
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).unwrap();
+    }
 
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
+    if failed {
+        std::process::exit(1);
+    }
 }
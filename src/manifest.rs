@@ -0,0 +1,540 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::icon::ICON_FILE;
+use crate::CfxResult;
+
+/// Directives listing scripts, in both their singular and plural forms.
+pub const SCRIPT_DIRECTIVES: [&str; 6] = [
+    "client_script",
+    "client_scripts",
+    "server_script",
+    "server_scripts",
+    "shared_script",
+    "shared_scripts",
+];
+
+/// Directives whose values are plain files the resource ships.
+pub const FILE_DIRECTIVES: [&str; 4] = ["file", "files", "ui_page", "loadscreen"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spanned {
+    token: Token,
+    span: Range<usize>,
+}
+
+/// A single `name value...` statement of a manifest, e.g. `data_file 'X' 'y'`
+/// or `client_scripts { 'a.lua', 'b.lua' }`, with table entries flattened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    pub name: String,
+    pub values: Vec<String>,
+    /// Byte range of the whole statement in the source
+    pub span: Range<usize>,
+    pub line: usize,
+}
+
+/// The directives of an `fxmanifest.lua`, in source order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Manifest {
+    pub directives: Vec<Directive>,
+}
+
+impl Manifest {
+    pub fn parse(content: &str) -> CfxResult<Self> {
+        let tokens = tokenize(content)?;
+        let mut directives = vec![];
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let Token::Ident(name) = &tokens[i].token else {
+                i += 1;
+                continue;
+            };
+
+            let start = tokens[i].span.start;
+            let mut end = tokens[i].span.end;
+            let mut values = vec![];
+            i += 1;
+
+            while i < tokens.len() {
+                match &tokens[i].token {
+                    Token::Str(value) => {
+                        values.push(value.clone());
+                        end = tokens[i].span.end;
+                        i += 1;
+                    }
+                    Token::Punct(open @ ('{' | '(')) => {
+                        let close = if *open == '{' { '}' } else { ')' };
+                        let mut depth = 0;
+                        while i < tokens.len() {
+                            match &tokens[i].token {
+                                Token::Punct(c) if *c == *open => depth += 1,
+                                Token::Punct(c) if *c == close => depth -= 1,
+                                Token::Str(value) => values.push(value.clone()),
+                                _ => {}
+                            }
+
+                            end = tokens[i].span.end;
+                            i += 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                    }
+                    _ => break,
+                }
+            }
+
+            directives.push(Directive {
+                name: name.clone(),
+                values,
+                line: content[..start].matches('\n').count() + 1,
+                span: start..end,
+            });
+        }
+
+        Ok(Self { directives })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Directive> {
+        self.directives
+            .iter()
+            .find(|directive| directive.name == name)
+    }
+
+    /// First value of the first `name` directive, e.g. the `"1.0.0"` of `version "1.0.0"`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.get(name)
+            .and_then(|directive| directive.values.first())
+            .map(String::as_str)
+    }
+
+    /// The `name` directive, which tooling may use instead of the folder name.
+    pub fn name(&self) -> Option<&str> {
+        self.value("name")
+    }
+
+    /// Games the resource targets, from either the singular `game` or the
+    /// `games` table, without duplicates.
+    pub fn games(&self) -> Vec<&str> {
+        let mut result = vec![];
+        for game in self.values(&["game", "games"]) {
+            if !result.contains(&game) {
+                result.push(game);
+            }
+        }
+
+        result
+    }
+
+    /// Values of every directive whose name is in `names`, in source order.
+    pub fn values(&self, names: &[&str]) -> Vec<&str> {
+        self.directives
+            .iter()
+            .filter(|directive| names.contains(&directive.name.as_str()))
+            .flat_map(|directive| directive.values.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Local path patterns the manifest references: scripts, files and the
+    /// paths of `data_file` entries. Imports from other resources (`@res/...`)
+    /// and URLs are left out. Audio data files are rewritten to the files the
+    /// game loads for them, see [`audio_data_pattern`].
+    pub fn referenced_patterns(&self) -> Vec<String> {
+        let mut result = self
+            .values(&SCRIPT_DIRECTIVES)
+            .into_iter()
+            .chain(self.values(&FILE_DIRECTIVES))
+            .map(str::to_owned)
+            .collect::<Vec<String>>();
+
+        for directive in &self.directives {
+            if let ("data_file", [kind, path, ..]) =
+                (directive.name.as_str(), directive.values.as_slice())
+            {
+                result.push(audio_data_pattern(kind, path).unwrap_or_else(|| path.clone()));
+            }
+        }
+
+        result.retain(|pattern| !pattern.starts_with('@') && !pattern.contains("://"));
+        result
+    }
+}
+
+/// Audio `data_file` entries name a wavepack folder of `.awc` containers, or a
+/// `.dat` path whose file on disk carries a `.dat<n>.rel` suffix.
+fn audio_data_pattern(kind: &str, path: &str) -> Option<String> {
+    match kind {
+        "AUDIO_WAVEPACK" => Some(format!("{path}/*.awc")),
+        _ if kind.starts_with("AUDIO_") && !path.ends_with(".rel") => Some(format!("{path}*.rel")),
+        _ => None,
+    }
+}
+
+/// Expands a manifest path pattern (`*` within a folder, `**` across folders)
+/// into the files it matches below `resource_dir`, sorted.
+pub fn expand_pattern(resource_dir: &Path, pattern: &str) -> CfxResult<Vec<PathBuf>> {
+    let escaped = glob::Pattern::escape(&resource_dir.to_string_lossy());
+    let full_pattern = format!("{escaped}/{pattern}");
+
+    let mut result = vec![];
+    for entry in glob::glob(&full_pattern)? {
+        let path = entry?;
+        if path.is_file() {
+            result.push(path);
+        } else if path.is_dir() {
+            for nested in glob::glob(&format!(
+                "{}/**/*",
+                glob::Pattern::escape(&path.to_string_lossy())
+            ))? {
+                let nested = nested?;
+                if nested.is_file() {
+                    result.push(nested);
+                }
+            }
+        }
+    }
+
+    result.sort();
+    result.dedup();
+    Ok(result)
+}
+
+/// Folders never shipped with a resource, whatever the manifest references.
+const EXCLUDED_DIRS: [&str; 4] = [".git", "node_modules", "tests", "test"];
+
+/// Every file a resource ships: the manifest itself, the expansion of each
+/// referenced pattern, everything below `stream/` and the icon, sorted and
+/// deduplicated.
+pub fn referenced_files(resource_dir: &Path, manifest: &Manifest) -> CfxResult<Vec<PathBuf>> {
+    let mut result = vec![resource_dir.join("fxmanifest.lua")];
+    for pattern in manifest.referenced_patterns() {
+        result.extend(expand_pattern(resource_dir, &pattern)?);
+    }
+
+    if resource_dir.join("stream").is_dir() {
+        result.extend(expand_pattern(resource_dir, "stream")?);
+    }
+
+    // Listings show the icon whether or not the manifest lists it
+    if resource_dir.join(ICON_FILE).is_file() {
+        result.push(resource_dir.join(ICON_FILE));
+    }
+
+    result.retain(|path| {
+        !path
+            .strip_prefix(resource_dir)
+            .unwrap_or(path)
+            .components()
+            .any(|part| EXCLUDED_DIRS.iter().any(|dir| part.as_os_str() == *dir))
+    });
+    result.sort();
+    result.dedup();
+
+    Ok(result)
+}
+
+fn tokenize(content: &str) -> CfxResult<Vec<Spanned>> {
+    let bytes = content.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        let start = i;
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if content[i..].starts_with("--") {
+            i += 2;
+            if let Some(level) = long_bracket_level(&content[i..]) {
+                i = skip_long_bracket(content, i, level, start)?;
+            } else {
+                i = content[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            }
+
+            continue;
+        }
+
+        let token = if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                let Some(next) = content[i..].chars().next() else {
+                    return Err(unterminated(content, start));
+                };
+
+                i += next.len_utf8();
+                match next {
+                    '\\' => {
+                        if let Some(escaped) = content[i..].chars().next() {
+                            i += escaped.len_utf8();
+                            value.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                        }
+                    }
+                    '\n' => return Err(unterminated(content, start)),
+                    _ if next == c => break,
+                    _ => value.push(next),
+                }
+            }
+
+            Token::Str(value)
+        } else if let Some(level) = long_bracket_level(&content[i..]) {
+            let open_len = level + 2;
+            let end = skip_long_bracket(content, i, level, start)?;
+            let value = &content[i + open_len..end - open_len];
+            i = end;
+
+            Token::Str(value.strip_prefix('\n').unwrap_or(value).to_owned())
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+
+            Token::Ident(content[start..i].to_owned())
+        } else if "{}(),;=".contains(c) {
+            i += 1;
+            Token::Punct(c)
+        } else {
+            i += content[i..].chars().next().map_or(1, char::len_utf8);
+            Token::Other
+        };
+
+        tokens.push(Spanned {
+            token,
+            span: start..i,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the level of a `[[`/`[==[` long bracket opening at the start of `text`.
+fn long_bracket_level(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix('[')?;
+    let level = rest.bytes().take_while(|&b| b == b'=').count();
+
+    rest[level..].starts_with('[').then_some(level)
+}
+
+/// Skips a long bracket opening at `i`, returning the offset after it closes.
+fn skip_long_bracket(content: &str, i: usize, level: usize, start: usize) -> CfxResult<usize> {
+    let close = format!("]{}]", "=".repeat(level));
+    let body = i + level + 2;
+
+    content[body..]
+        .find(&close)
+        .map(|end| body + end + close.len())
+        .ok_or_else(|| unterminated(content, start))
+}
+
+fn unterminated(content: &str, start: usize) -> Box<dyn std::error::Error> {
+    let line = content[..start].matches('\n').count() + 1;
+    format!("Unterminated string or comment on line {line}").into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    const MANIFEST: &str = r#"fx_version "cerulean"
+game 'gta5' -- trailing comment
+--[[ block
+comment ]]
+version "1.2.3"
+
+client_scripts {
+    "@ox_lib/init.lua",
+    'client/*.lua', -- the client
+}
+server_script "server/main.lua"
+files { "html/**" }
+ui_page('html/index.html')
+data_file 'DLC_ITYP_REQUEST' 'stream/props.ytyp'
+description [[Long
+description]]
+"#;
+
+    #[test]
+    fn parse_directives_test() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let names = manifest
+            .directives
+            .iter()
+            .map(|directive| directive.name.as_str())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            names,
+            vec![
+                "fx_version",
+                "game",
+                "version",
+                "client_scripts",
+                "server_script",
+                "files",
+                "ui_page",
+                "data_file",
+                "description"
+            ]
+        );
+        assert_eq!(manifest.value("version"), Some("1.2.3"));
+        assert_eq!(manifest.value("description"), Some("Long\ndescription"));
+        assert_eq!(manifest.name(), None);
+        assert_eq!(Manifest::parse("name 'bank'").unwrap().name(), Some("bank"));
+        assert_eq!(
+            manifest.values(&SCRIPT_DIRECTIVES),
+            vec!["@ox_lib/init.lua", "client/*.lua", "server/main.lua"]
+        );
+
+        let data_file = manifest.get("data_file").unwrap();
+        assert_eq!(
+            data_file.values,
+            vec!["DLC_ITYP_REQUEST", "stream/props.ytyp"]
+        );
+        assert_eq!(data_file.line, 14);
+        assert_eq!(
+            &MANIFEST[data_file.span.clone()],
+            "data_file 'DLC_ITYP_REQUEST' 'stream/props.ytyp'"
+        );
+    }
+
+    #[test]
+    fn games_test() {
+        let single = Manifest::parse("game 'gta5'").unwrap();
+        assert_eq!(single.games(), vec!["gta5"]);
+
+        let table = Manifest::parse("games { 'gta5', 'rdr3' }\ngame 'rdr3'").unwrap();
+        assert_eq!(table.games(), vec!["gta5", "rdr3"]);
+
+        assert!(Manifest::default().games().is_empty());
+    }
+
+    #[test]
+    fn referenced_patterns_test() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+
+        assert_eq!(
+            manifest.referenced_patterns(),
+            vec![
+                "client/*.lua",
+                "server/main.lua",
+                "html/**",
+                "html/index.html",
+                "stream/props.ytyp"
+            ]
+        );
+    }
+
+    #[test]
+    fn audio_data_patterns_test() {
+        let manifest = Manifest::parse(
+            "data_file 'AUDIO_GAMEDATA' 'audioconfig/siren_game.dat'\n\
+             data_file 'AUDIO_SOUNDDATA' 'audioconfig/siren_sounds.dat54.rel'\n\
+             data_file 'AUDIO_WAVEPACK' 'sfx/dlc_siren'",
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.referenced_patterns(),
+            vec![
+                "audioconfig/siren_game.dat*.rel",
+                "audioconfig/siren_sounds.dat54.rel",
+                "sfx/dlc_siren/*.awc"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unterminated_string_test() {
+        let result = Manifest::parse("fx_version 'cerulean'\ngame \"gta5\n");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unterminated string or comment on line 2"
+        );
+    }
+
+    #[test]
+    fn expand_pattern_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client/nested")).unwrap();
+        create_dir_all(dir.path().join("html/css")).unwrap();
+        for file in [
+            "client/a.lua",
+            "client/b.lua",
+            "client/nested/c.lua",
+            "html/css/x.css",
+        ] {
+            write(dir.path().join(file), "").unwrap();
+        }
+
+        let relative = |pattern: &str| {
+            expand_pattern(dir.path(), pattern)
+                .unwrap()
+                .iter()
+                .map(|path| path.strip_prefix(dir.path()).unwrap().to_path_buf())
+                .collect::<Vec<PathBuf>>()
+        };
+
+        assert_eq!(
+            relative("client/*.lua"),
+            vec![PathBuf::from("client/a.lua"), PathBuf::from("client/b.lua")]
+        );
+        assert_eq!(relative("client/**/*.lua").len(), 3);
+        assert_eq!(relative("html"), vec![PathBuf::from("html/css/x.css")]);
+        assert!(relative("missing.lua").is_empty());
+    }
+
+    #[test]
+    fn referenced_files_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("client")).unwrap();
+        create_dir_all(dir.path().join("stream/cars")).unwrap();
+        create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        for file in [
+            "fxmanifest.lua",
+            "client/main.lua",
+            "client/unused.txt",
+            "stream/cars/car.yft",
+            "node_modules/pkg/index.js",
+            "icon.png",
+        ] {
+            write(dir.path().join(file), "").unwrap();
+        }
+
+        let manifest =
+            Manifest::parse("client_script 'client/*.lua'\nfiles { '**/*.js' }").unwrap();
+        let files = referenced_files(dir.path(), &manifest)
+            .unwrap()
+            .iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect::<Vec<PathBuf>>();
+
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("client/main.lua"),
+                PathBuf::from("fxmanifest.lua"),
+                PathBuf::from("icon.png"),
+                PathBuf::from("stream/cars/car.yft"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use crate::archive::{read_array, FArchive, FArchiveExt, FResourceArchive, VIRTUAL_BASE};
+use crate::joaat::joaat_bytes;
+use crate::parse::ParseContext;
+use crate::CfxResult;
+
+const CMAP_DATA: u32 = joaat_bytes(b"CMapData");
+const CMAP_TYPES: u32 = joaat_bytes(b"CMapTypes");
+const CENTITY_DEF: u32 = joaat_bytes(b"CEntityDef");
+const CMLO_INSTANCE_DEF: u32 = joaat_bytes(b"CMloInstanceDef");
+const CBASE_ARCHETYPE_DEF: u32 = joaat_bytes(b"CBaseArchetypeDef");
+const CMLO_ARCHETYPE_DEF: u32 = joaat_bytes(b"CMloArchetypeDef");
+
+/// Size of a data block entry in the metadata header.
+const BLOCK_SIZE: usize = 0x10;
+/// Size of a `MetaPOINTER`, an item of a structure pointer array.
+const POINTER_SIZE: usize = 8;
+
+/// One data block of a metadata resource: every structure of one type,
+/// packed together.
+#[derive(Debug, Clone, Copy)]
+struct MetaBlock {
+    /// Case-sensitive hash of the structure name
+    name: u32,
+    length: u32,
+    pointer: u64,
+}
+
+/// The data blocks of a `ymap` or `ytyp`. Pointers inside the metadata name
+/// a block by its 1-based index and an offset into it, rather than holding a
+/// resource address.
+#[derive(Debug)]
+struct Meta {
+    blocks: Vec<MetaBlock>,
+    root: MetaBlock,
+}
+
+impl Meta {
+    fn read<Data>(
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<Self>
+    where
+        Data: AsRef<[u8]>,
+    {
+        archive.set_position(VIRTUAL_BASE + 0x1C)?;
+        let root = archive.read_int()?;
+        archive.set_position(VIRTUAL_BASE + 0x30)?;
+        let blocks_pointer = archive.read_ulong()?;
+        archive.set_position(VIRTUAL_BASE + 0x4C)?;
+        let mut count = [0u8; 2];
+        archive.read_bytes_exact(&mut count)?;
+        let count = u16::from_le_bytes(count) as usize;
+
+        let blocks = archive.read_at(blocks_pointer, |archive| {
+            read_array(archive, count, BLOCK_SIZE, context, |block| MetaBlock {
+                name: u32::from_le_bytes(block[0..4].try_into().unwrap()),
+                length: u32::from_le_bytes(block[4..8].try_into().unwrap()),
+                pointer: u64::from_le_bytes(block[8..16].try_into().unwrap()),
+            })
+        })?;
+
+        let root = match usize::try_from(root) {
+            Ok(index @ 1..) if index <= blocks.len() => blocks[index - 1],
+            _ => {
+                return Err(format!(
+                    "root block {root} is not one of the {} blocks",
+                    blocks.len()
+                )
+                .into())
+            }
+        };
+
+        Ok(Self { blocks, root })
+    }
+
+    /// The block and address a metadata pointer points to.
+    fn resolve(&self, pointer: u32, extra_offset: u32) -> CfxResult<(MetaBlock, u64)> {
+        let index = (pointer & 0xFFF) as usize;
+        let offset = ((pointer >> 12) & 0xFFFFF)
+            .checked_add(extra_offset)
+            .ok_or_else(|| format!("pointer {pointer:#x} offset {extra_offset:#x} overflows"))?;
+        let block = match index {
+            1.. if index <= self.blocks.len() => self.blocks[index - 1],
+            _ => {
+                return Err(format!(
+                    "pointer {pointer:#x} names block {index} of only {}",
+                    self.blocks.len()
+                )
+                .into())
+            }
+        };
+
+        if offset >= block.length {
+            return Err(format!(
+                "pointer {pointer:#x} is past the end of its {} byte block",
+                block.length
+            )
+            .into());
+        }
+
+        let address = block.pointer.checked_add(offset as u64).ok_or_else(|| {
+            format!(
+                "pointer {pointer:#x} overflows its block at {:#x}",
+                block.pointer
+            )
+        })?;
+
+        Ok((block, address))
+    }
+
+    /// The structure name and address of every item of the
+    /// `Array_StructurePointer` at `address`.
+    fn structure_pointers<Data>(
+        &self,
+        archive: &mut FResourceArchive<Data>,
+        address: u64,
+        context: &mut ParseContext,
+    ) -> CfxResult<Vec<(u32, u64)>>
+    where
+        Data: AsRef<[u8]>,
+    {
+        let (pointer, count) = array_header(archive, address)?;
+        if count == 0 {
+            return Ok(vec![]);
+        }
+
+        let (_, items) = self.resolve(pointer, 0)?;
+        let pointers = archive.read_at(items, |archive| {
+            read_array(archive, count as usize, POINTER_SIZE, context, |item| {
+                let pointer = u32::from_le_bytes(item[0..4].try_into().unwrap());
+                let extra_offset = u32::from_le_bytes(item[4..8].try_into().unwrap());
+                (pointer, extra_offset)
+            })
+        })?;
+
+        pointers
+            .into_iter()
+            .map(|(pointer, extra_offset)| {
+                let (block, address) = self.resolve(pointer, extra_offset)?;
+                Ok((block.name, address))
+            })
+            .collect()
+    }
+}
+
+/// Pointer and item count of the array at `address`, laid out the same for
+/// arrays of structures and of structure pointers.
+fn array_header<Data>(archive: &mut FResourceArchive<Data>, address: u64) -> CfxResult<(u32, u16)>
+where
+    Data: AsRef<[u8]>,
+{
+    archive.read_at(address, |archive| {
+        let pointer = archive.read_uint()?;
+        archive.skip(4)?;
+        let mut count = [0u8; 2];
+        archive.read_bytes_exact(&mut count)?;
+        Ok((pointer, u16::from_le_bytes(count)))
+    })
+}
+
+/// Address of the field at `offset` into the structure at `address`.
+fn field(address: u64, offset: u64) -> CfxResult<u64> {
+    address
+        .checked_add(offset)
+        .ok_or_else(|| format!("structure at {address:#x} runs past the address space").into())
+}
+
+fn read_uint_at<Data>(archive: &mut FResourceArchive<Data>, address: u64) -> CfxResult<u32>
+where
+    Data: AsRef<[u8]>,
+{
+    archive.read_at(address, |archive| archive.read_uint())
+}
+
+/// An entity placed by a `ymap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapEntity {
+    pub archetype: u32,
+    /// Zero when the map editor left it unset
+    pub guid: u32,
+    /// Whether the entity is an interior instance
+    pub mlo: bool,
+}
+
+/// The entities of a `.ymap`.
+#[derive(Debug, PartialEq)]
+pub struct Ymap {
+    pub name: u32,
+    pub entities: Vec<MapEntity>,
+}
+
+impl Ymap {
+    /// Reads the `CMapData` at the root of a metadata resource. Entities of
+    /// other structure types are a deviation and skipped.
+    pub fn read<Data>(
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<Self>
+    where
+        Data: AsRef<[u8]>,
+    {
+        let meta = Meta::read(archive, context)?;
+        if meta.root.name != CMAP_DATA {
+            return Err(format!(
+                "the root structure {:#010x} is not CMapData",
+                meta.root.name
+            )
+            .into());
+        }
+
+        let name = read_uint_at(archive, field(meta.root.pointer, 0x08)?)?;
+        let mut entities = vec![];
+        for (structure, address) in
+            meta.structure_pointers(archive, field(meta.root.pointer, 0x60)?, context)?
+        {
+            if structure != CENTITY_DEF && structure != CMLO_INSTANCE_DEF {
+                context.deviation(
+                    address,
+                    format!(
+                        "entity structure {structure:#010x} is not CEntityDef or CMloInstanceDef"
+                    ),
+                )?;
+                continue;
+            }
+
+            // `CMloInstanceDef` starts with a `CEntityDef`
+            entities.push(MapEntity {
+                archetype: read_uint_at(archive, field(address, 0x08)?)?,
+                guid: read_uint_at(archive, field(address, 0x10)?)?,
+                mlo: structure == CMLO_INSTANCE_DEF,
+            });
+        }
+
+        Ok(Self { name, entities })
+    }
+}
+
+/// Rooms, portals and entities of an interior archetype.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mlo {
+    pub room_count: u16,
+    pub portal_count: u16,
+    /// Archetypes of the entities inside
+    pub entities: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archetype {
+    pub name: u32,
+    /// `Some` for interior archetypes
+    pub mlo: Option<Mlo>,
+}
+
+/// The archetypes a `.ytyp` defines.
+#[derive(Debug, PartialEq)]
+pub struct Ytyp {
+    pub name: u32,
+    pub archetypes: Vec<Archetype>,
+}
+
+impl Ytyp {
+    /// Reads the `CMapTypes` at the root of a metadata resource. Archetypes
+    /// other than `CBaseArchetypeDef` and `CMloArchetypeDef`, such as time
+    /// archetypes, are skipped.
+    pub fn read<Data>(
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<Self>
+    where
+        Data: AsRef<[u8]>,
+    {
+        let meta = Meta::read(archive, context)?;
+        if meta.root.name != CMAP_TYPES {
+            return Err(format!(
+                "the root structure {:#010x} is not CMapTypes",
+                meta.root.name
+            )
+            .into());
+        }
+
+        let name = read_uint_at(archive, field(meta.root.pointer, 0x28)?)?;
+        let mut archetypes = vec![];
+        for (structure, address) in
+            meta.structure_pointers(archive, field(meta.root.pointer, 0x18)?, context)?
+        {
+            if structure != CBASE_ARCHETYPE_DEF && structure != CMLO_ARCHETYPE_DEF {
+                continue;
+            }
+
+            // Every archetype starts with a `CBaseArchetypeDef`
+            let name = read_uint_at(archive, field(address, 0x58)?)?;
+            let mlo = match structure == CMLO_ARCHETYPE_DEF {
+                true => {
+                    let mut entities = vec![];
+                    for (structure, entity) in
+                        meta.structure_pointers(archive, field(address, 0x98)?, context)?
+                    {
+                        if structure != CENTITY_DEF {
+                            context.deviation(
+                                entity,
+                                format!(
+                                    "interior entity structure {structure:#010x} is not CEntityDef"
+                                ),
+                            )?;
+                            continue;
+                        }
+
+                        entities.push(read_uint_at(archive, field(entity, 0x08)?)?);
+                    }
+
+                    Some(Mlo {
+                        room_count: array_header(archive, field(address, 0xA8)?)?.1,
+                        portal_count: array_header(archive, field(address, 0xB8)?)?.1,
+                        entities,
+                    })
+                }
+                false => None,
+            };
+
+            archetypes.push(Archetype { name, mlo });
+        }
+
+        Ok(Self { name, archetypes })
+    }
+}
+
+/// Lays out a metadata virtual page with `blocks`, each a structure name and
+/// its data, and the 1-based `root` block.
+pub fn meta_page(root: usize, blocks: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let blocks_offset = 0x70;
+    let mut page = vec![0u8; blocks_offset + blocks.len() * BLOCK_SIZE];
+    page[0x1C..0x20].copy_from_slice(&(root as i32).to_le_bytes());
+    page[0x30..0x38].copy_from_slice(&(VIRTUAL_BASE + blocks_offset as u64).to_le_bytes());
+    page[0x4C..0x4E].copy_from_slice(&(blocks.len() as u16).to_le_bytes());
+
+    for (index, (name, data)) in blocks.iter().enumerate() {
+        page.resize(page.len().next_multiple_of(16), 0);
+        let entry = blocks_offset + index * BLOCK_SIZE;
+        page[entry..entry + 4].copy_from_slice(&name.to_le_bytes());
+        page[entry + 4..entry + 8].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        let pointer = VIRTUAL_BASE + page.len() as u64;
+        page[entry + 8..entry + 16].copy_from_slice(&pointer.to_le_bytes());
+        page.extend(data);
+    }
+
+    page
+}
+
+/// A metadata pointer to `offset` in the 1-based `block`.
+fn meta_pointer(block: usize, offset: usize) -> u32 {
+    block as u32 | (offset as u32) << 12
+}
+
+fn array(pointer: u32, count: usize) -> [u8; 16] {
+    let mut array = [0u8; 16];
+    array[0..4].copy_from_slice(&pointer.to_le_bytes());
+    array[8..10].copy_from_slice(&(count as u16).to_le_bytes());
+    array[10..12].copy_from_slice(&(count as u16).to_le_bytes());
+    array
+}
+
+/// Pointers to structures of `size` bytes, one after another at the start of
+/// `block`.
+fn pointers(block: usize, size: usize, count: usize) -> Vec<u8> {
+    (0..count)
+        .flat_map(|index| (meta_pointer(block, index * size) as u64).to_le_bytes())
+        .collect()
+}
+
+fn entity_defs(entities: &[(u32, u32)], size: usize) -> Vec<u8> {
+    let mut data = vec![0u8; entities.len() * size];
+    for (index, (archetype, guid)) in entities.iter().enumerate() {
+        let start = index * size;
+        data[start + 0x08..start + 0x0C].copy_from_slice(&archetype.to_le_bytes());
+        data[start + 0x10..start + 0x14].copy_from_slice(&guid.to_le_bytes());
+    }
+    data
+}
+
+/// A `.ymap` virtual page placing `entities`, each an archetype and GUID.
+/// Interior instances, `mlo_instances`, come after the plain entities.
+pub fn ymap_page(name: u32, entities: &[(u32, u32)], mlo_instances: &[(u32, u32)]) -> Vec<u8> {
+    let mut map_data = vec![0u8; 0x200];
+    map_data[0x08..0x0C].copy_from_slice(&name.to_le_bytes());
+    let count = entities.len() + mlo_instances.len();
+    map_data[0x60..0x70].copy_from_slice(&array(meta_pointer(4, 0), count));
+
+    let mut items = pointers(2, 0x80, entities.len());
+    items.extend(pointers(3, 0x90, mlo_instances.len()));
+    meta_page(
+        1,
+        &[
+            (CMAP_DATA, map_data),
+            (CENTITY_DEF, entity_defs(entities, 0x80)),
+            (CMLO_INSTANCE_DEF, entity_defs(mlo_instances, 0x90)),
+            (joaat_bytes(b"POINTER"), items),
+        ],
+    )
+}
+
+/// A `.ytyp` virtual page defining `archetypes`, followed by `mlos`, each an
+/// interior name, room and portal counts and the archetypes of its entities.
+pub fn ytyp_page(name: u32, archetypes: &[u32], mlos: &[(u32, u16, u16, &[u32])]) -> Vec<u8> {
+    let mut map_types = vec![0u8; 0x50];
+    map_types[0x28..0x2C].copy_from_slice(&name.to_le_bytes());
+    let count = archetypes.len() + mlos.len();
+    map_types[0x18..0x28].copy_from_slice(&array(meta_pointer(4, 0), count));
+
+    let mut base = vec![0u8; archetypes.len() * 0x90];
+    for (index, archetype) in archetypes.iter().enumerate() {
+        base[index * 0x90 + 0x58..index * 0x90 + 0x5C].copy_from_slice(&archetype.to_le_bytes());
+    }
+
+    let mut interiors = vec![0u8; mlos.len() * 0xF0];
+    let mut entities = vec![];
+    for (index, (archetype, rooms, portals, inside)) in mlos.iter().enumerate() {
+        let start = index * 0xF0;
+        interiors[start + 0x58..start + 0x5C].copy_from_slice(&archetype.to_le_bytes());
+        let first = entities.len();
+        entities.extend(inside.iter().map(|&archetype| (archetype, 0)));
+        let pointer = meta_pointer(6, first * POINTER_SIZE);
+        interiors[start + 0x98..start + 0xA8].copy_from_slice(&array(pointer, inside.len()));
+        interiors[start + 0xA8..start + 0xB8].copy_from_slice(&array(0, *rooms as usize));
+        interiors[start + 0xB8..start + 0xC8].copy_from_slice(&array(0, *portals as usize));
+    }
+
+    let mut items = pointers(2, 0x90, archetypes.len());
+    items.extend(pointers(3, 0xF0, mlos.len()));
+    meta_page(
+        1,
+        &[
+            (CMAP_TYPES, map_types),
+            (CBASE_ARCHETYPE_DEF, base),
+            (CMLO_ARCHETYPE_DEF, interiors),
+            (joaat_bytes(b"POINTER"), items),
+            (CENTITY_DEF, entity_defs(&entities, 0x80)),
+            (joaat_bytes(b"POINTER"), pointers(5, 0x80, entities.len())),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joaat::joaat;
+
+    fn archive(page: Vec<u8>) -> FResourceArchive<Vec<u8>> {
+        FResourceArchive::new(page, vec![])
+    }
+
+    #[test]
+    fn ymap_test() {
+        let page = ymap_page(
+            joaat("bank_exterior"),
+            &[(joaat("prop_bench_01a"), 11), (joaat("prop_atm_01"), 0)],
+            &[(joaat("bank_vault"), 12)],
+        );
+        let mut context = ParseContext::default();
+        let ymap = Ymap::read(&mut archive(page), &mut context).unwrap();
+        assert_eq!(ymap.name, joaat("bank_exterior"));
+        assert_eq!(
+            ymap.entities,
+            [
+                MapEntity {
+                    archetype: joaat("prop_bench_01a"),
+                    guid: 11,
+                    mlo: false
+                },
+                MapEntity {
+                    archetype: joaat("prop_atm_01"),
+                    guid: 0,
+                    mlo: false
+                },
+                MapEntity {
+                    archetype: joaat("bank_vault"),
+                    guid: 12,
+                    mlo: true
+                },
+            ]
+        );
+        assert!(context.warnings.is_empty());
+
+        let empty = ymap_page(joaat("empty"), &[], &[]);
+        let ymap = Ymap::read(&mut archive(empty), &mut context).unwrap();
+        assert!(ymap.entities.is_empty());
+    }
+
+    #[test]
+    fn ytyp_test() {
+        let page = ytyp_page(
+            joaat("bank"),
+            &[joaat("bank_counter")],
+            &[(
+                joaat("bank_vault"),
+                3,
+                4,
+                &[joaat("bank_counter"), joaat("prop_safe_01")],
+            )],
+        );
+        let mut context = ParseContext::default();
+        let ytyp = Ytyp::read(&mut archive(page), &mut context).unwrap();
+        assert_eq!(ytyp.name, joaat("bank"));
+        assert_eq!(
+            ytyp.archetypes,
+            [
+                Archetype {
+                    name: joaat("bank_counter"),
+                    mlo: None
+                },
+                Archetype {
+                    name: joaat("bank_vault"),
+                    mlo: Some(Mlo {
+                        room_count: 3,
+                        portal_count: 4,
+                        entities: vec![joaat("bank_counter"), joaat("prop_safe_01")],
+                    })
+                },
+            ]
+        );
+        assert!(context.warnings.is_empty());
+    }
+
+    #[test]
+    fn meta_errors_test() {
+        let ytyp = ytyp_page(joaat("bank"), &[], &[]);
+        let err = Ymap::read(&mut archive(ytyp), &mut ParseContext::default()).unwrap_err();
+        assert!(err.to_string().ends_with("is not CMapData"), "{err}");
+
+        let mut page = ymap_page(joaat("bank"), &[(joaat("prop_atm_01"), 1)], &[]);
+        page[0x1C] = 9;
+        let err = Ymap::read(&mut archive(page.clone()), &mut ParseContext::default()).unwrap_err();
+        assert_eq!(err.to_string(), "root block 9 is not one of the 4 blocks");
+
+        // Point the entities array into block 7
+        page[0x1C] = 1;
+        let map_data = u64::from_le_bytes(page[0x78..0x80].try_into().unwrap()) - VIRTUAL_BASE;
+        page[map_data as usize + 0x60] = 7;
+        let err = Ymap::read(&mut archive(page), &mut ParseContext::default()).unwrap_err();
+        assert!(
+            err.to_string().ends_with("names block 7 of only 4"),
+            "{err}"
+        );
+
+        // A root block at the top of the address space
+        let mut page = ymap_page(joaat("bank"), &[], &[]);
+        page[0x78..0x80].copy_from_slice(&0xFFFF_FFFF_FFFF_FFFCu64.to_le_bytes());
+        let err = Ymap::read(&mut archive(page), &mut ParseContext::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "structure at 0xfffffffffffffffc runs past the address space"
+        );
+
+        let block = MetaBlock {
+            name: CENTITY_DEF,
+            length: 0x100,
+            pointer: u64::MAX - 0x10,
+        };
+        let meta = Meta {
+            blocks: vec![block],
+            root: block,
+        };
+        assert!(meta.resolve(meta_pointer(1, 0x08), 0).is_ok());
+        let err = meta.resolve(meta_pointer(1, 0x20), 0).unwrap_err();
+        assert!(err.to_string().contains("overflows its block"), "{err}");
+        let err = meta
+            .resolve(meta_pointer(1, 0xFFFFF), u32::MAX)
+            .unwrap_err();
+        assert!(err.to_string().ends_with("overflows"), "{err}");
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+/// Three `f32`s as stored in resources, e.g. an entity position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Four `f32`s as stored in resources, e.g. a rotation quaternion or a
+/// 16-byte aligned position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from([x, y, z]: [f32; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<Vec3> for [f32; 3] {
+    fn from(value: Vec3) -> Self {
+        [value.x, value.y, value.z]
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
+impl From<Vec4> for [f32; 4] {
+    fn from(value: Vec4) -> Self {
+        [value.x, value.y, value.z, value.w]
+    }
+}
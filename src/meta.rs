@@ -0,0 +1,195 @@
+use quick_xml::se::Serializer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::CfxResult;
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+/// `peds.meta`, registering add-on ped models.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "CPedModelInfo__InitDataList")]
+pub struct PedsMeta {
+    #[serde(rename = "InitDatas")]
+    pub init_datas: PedInitDatas,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PedInitDatas {
+    #[serde(rename = "Item", default)]
+    pub items: Vec<PedInitData>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PedInitData {
+    pub name: String,
+    pub clip_dictionary_name: String,
+    pub expression_set_name: String,
+    pub pedtype: String,
+    pub movement_clip_set: String,
+    pub default_task_data_set_name: String,
+    pub ped_capsule_name: String,
+    pub relationship_group: String,
+    pub nav_capabilities_name: String,
+    pub perception_info: String,
+    pub default_brawling_style: String,
+    pub default_unarmed_weapon: String,
+    pub personality: String,
+}
+
+impl PedInitData {
+    /// An ambient male civilian, the usual starting point for add-on peds.
+    pub fn civilian(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            clip_dictionary_name: "move_m@generic".to_owned(),
+            expression_set_name: "expr_set_ambient_male".to_owned(),
+            pedtype: "CIVMALE".to_owned(),
+            movement_clip_set: "move_m@generic".to_owned(),
+            default_task_data_set_name: "STANDARD_PED".to_owned(),
+            ped_capsule_name: "STANDARD_MALE".to_owned(),
+            relationship_group: "CIVMALE".to_owned(),
+            nav_capabilities_name: "STANDARD_PED".to_owned(),
+            perception_info: "DEFAULT_PERCEPTION".to_owned(),
+            default_brawling_style: "BS_AI".to_owned(),
+            default_unarmed_weapon: "WEAPON_UNARMED".to_owned(),
+            personality: "Streamed_Male".to_owned(),
+        }
+    }
+}
+
+/// Shop meta of a clothing pack, naming the ped and DLC its components belong to.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "ShopPedApparel", rename_all = "camelCase")]
+pub struct ShopPedApparel {
+    pub ped_name: String,
+    pub dlc_name: String,
+    pub full_dlc_name: String,
+    #[serde(rename = "eCharacter")]
+    pub character: String,
+    pub creature_meta_data: String,
+}
+
+impl ShopPedApparel {
+    pub fn new(ped_name: &str, dlc_name: &str) -> Self {
+        Self {
+            ped_name: ped_name.to_owned(),
+            dlc_name: dlc_name.to_owned(),
+            full_dlc_name: format!("{ped_name}_{dlc_name}"),
+            character: "SCR_CHAR_MULTIPLAYER".to_owned(),
+            creature_meta_data: format!("MP_CreatureMetadata_{dlc_name}"),
+        }
+    }
+}
+
+/// `vehicles.meta`, registering add-on vehicle models. Like the other
+/// vehicle metas below, only the names `validate --vehicles` cross-checks
+/// are read and everything else is ignored.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename = "CVehicleModelInfo__InitDataList")]
+pub struct VehiclesMeta {
+    #[serde(rename = "InitDatas", default)]
+    pub init_datas: VehicleInitDatas,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct VehicleInitDatas {
+    #[serde(rename = "Item", default)]
+    pub items: Vec<VehicleInitData>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleInitData {
+    pub model_name: String,
+    #[serde(default)]
+    pub txd_name: String,
+    #[serde(default)]
+    pub handling_id: String,
+}
+
+/// `handling.meta`, the handling entries vehicles refer to by `handlingId`.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename = "CHandlingDataMgr")]
+pub struct HandlingMeta {
+    #[serde(rename = "HandlingData", default)]
+    pub handling_data: HandlingItems,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct HandlingItems {
+    #[serde(rename = "Item", default)]
+    pub items: Vec<HandlingItem>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlingItem {
+    pub handling_name: String,
+}
+
+/// `carvariations.meta`, the colours and mod kits of each model.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename = "CVehicleModelInfoVariation")]
+pub struct CarVariationsMeta {
+    #[serde(rename = "variationData", default)]
+    pub variation_data: VariationItems,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct VariationItems {
+    #[serde(rename = "Item", default)]
+    pub items: Vec<VariationItem>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariationItem {
+    pub model_name: String,
+}
+
+/// Serializes a meta file with two-space indentation and an XML declaration.
+pub fn to_xml<T: Serialize>(value: &T) -> CfxResult<String> {
+    let mut result = XML_DECLARATION.to_owned();
+    let mut serializer = Serializer::new(&mut result);
+    serializer.indent(' ', 2);
+    value.serialize(serializer)?;
+
+    result.push('\n');
+    Ok(result)
+}
+
+pub fn from_xml<T: DeserializeOwned>(content: &str) -> CfxResult<T> {
+    Ok(quick_xml::de::from_str(content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peds_meta_test() {
+        let meta = PedsMeta {
+            init_datas: PedInitDatas {
+                items: vec![PedInitData::civilian("a_m_y_custom")],
+            },
+        };
+        let xml = to_xml(&meta).unwrap();
+
+        assert!(xml.starts_with(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<CPedModelInfo__InitDataList>\n  <InitDatas>\n    <Item>\n      <Name>a_m_y_custom</Name>\n"
+        ));
+        assert_eq!(from_xml::<PedsMeta>(&xml).unwrap(), meta);
+    }
+
+    #[test]
+    fn shop_ped_apparel_test() {
+        let meta = ShopPedApparel::new("mp_m_freemode_01", "mypack");
+        let xml = to_xml(&meta).unwrap();
+
+        assert!(xml.contains("  <fullDlcName>mp_m_freemode_01_mypack</fullDlcName>\n"));
+        assert!(xml.contains("  <eCharacter>SCR_CHAR_MULTIPLAYER</eCharacter>\n"));
+        assert_eq!(from_xml::<ShopPedApparel>(&xml).unwrap(), meta);
+    }
+}
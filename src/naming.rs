@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+/// Variables an output name template can use, with what they expand to.
+pub const NAME_VARIABLES: [(&str, &str); 5] = [
+    ("name", "file name of the input"),
+    ("stem", "file name of the input without its extension"),
+    ("ext", "extension of the input"),
+    (
+        "kind",
+        "what was extracted, such as `virtual` or `physical`",
+    ),
+    ("index", "position of the input on the command line, from 0"),
+];
+
+/// Names outputs `<input file name>.<kind>`.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{name}.{kind}";
+
+/// Where outputs go below the output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NameLayout {
+    /// Mirror the input paths below the directory all inputs share
+    #[default]
+    PreservePaths,
+    /// Put every output directly in the output directory
+    Flat,
+}
+
+/// One output of one input, what a name template is rendered from.
+pub struct NameInput<'a> {
+    pub path: &'a Path,
+    pub kind: &'a str,
+    pub index: usize,
+}
+
+/// Expands the `{variable}` placeholders of `template`. Unknown variables are
+/// an error listing the valid ones.
+pub fn render_name(template: &str, input: &NameInput) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            return Err(format!("Unclosed `{{` in name template `{template}`"));
+        };
+
+        let variable = &rest[start + 1..start + end];
+        result.push_str(&rest[..start]);
+        result.push_str(&expand(variable, input).ok_or_else(|| {
+            let valid = NAME_VARIABLES
+                .iter()
+                .map(|(name, _)| format!("{{{name}}}"))
+                .collect::<Vec<String>>();
+            format!(
+                "Unknown variable `{{{variable}}}` in name template, valid ones are {}",
+                valid.join(", ")
+            )
+        })?);
+        rest = &rest[start + end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn expand(variable: &str, input: &NameInput) -> Option<String> {
+    let lossy = |value: Option<&OsStr>| value.unwrap_or_default().to_string_lossy().to_string();
+    Some(match variable {
+        "name" => lossy(input.path.file_name()),
+        "stem" => lossy(input.path.file_stem()),
+        "ext" => lossy(input.path.extension()),
+        "kind" => input.kind.to_owned(),
+        "index" => input.index.to_string(),
+        _ => return None,
+    })
+}
+
+/// Replaces characters that are invalid in file names on any platform with
+/// `_`, and trailing dots and spaces that Windows drops.
+pub fn sanitize(name: &str) -> String {
+    let result = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    let trimmed = result.trim_end_matches(['.', ' ']);
+    match trimmed {
+        "" | "." | ".." => "_".to_owned(),
+        trimmed => trimmed.to_owned(),
+    }
+}
+
+/// The deepest directory every path is in, empty when they share none.
+pub fn common_base(paths: &[&Path]) -> PathBuf {
+    let mut parents = paths
+        .iter()
+        .map(|path| path.parent().unwrap_or(Path::new("")).components());
+    let Some(first) = parents.next() else {
+        return PathBuf::new();
+    };
+
+    let mut base = first.collect::<Vec<Component>>();
+    for components in parents {
+        let shared = base
+            .iter()
+            .zip(components)
+            .take_while(|(a, b)| *a == b)
+            .count();
+        base.truncate(shared);
+    }
+
+    base.iter().collect()
+}
+
+/// Picks the output path for `input` below `output_dir`, suffixing the name
+/// with `_2`, `_3`, ... before its extension when it is already in `used`.
+pub fn output_path(
+    output_dir: &Path,
+    base: &Path,
+    layout: NameLayout,
+    template: &str,
+    input: &NameInput,
+    used: &HashSet<PathBuf>,
+) -> Result<PathBuf, String> {
+    let mut dir = output_dir.to_path_buf();
+    if layout == NameLayout::PreservePaths {
+        let parent = input.path.parent().unwrap_or(Path::new(""));
+        for component in parent.strip_prefix(base).unwrap_or(parent).components() {
+            if let Component::Normal(part) = component {
+                dir.push(sanitize(&part.to_string_lossy()));
+            }
+        }
+    }
+
+    let name = sanitize(&render_name(template, input)?);
+    let mut path = dir.join(&name);
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name.as_str(), ""),
+    };
+
+    let mut suffix = 2;
+    while used.contains(&path) {
+        path = dir.join(format!("{stem}_{suffix}{extension}"));
+        suffix += 1;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(path: &'a str, kind: &'a str, index: usize) -> NameInput<'a> {
+        NameInput {
+            path: Path::new(path),
+            kind,
+            index,
+        }
+    }
+
+    #[test]
+    fn render_name_test() {
+        let sample = input("stream/cars/adder.ytd", "virtual", 3);
+
+        assert_eq!(
+            render_name(DEFAULT_NAME_TEMPLATE, &sample).unwrap(),
+            "adder.ytd.virtual"
+        );
+        assert_eq!(
+            render_name("{stem}_{index}.{ext}", &sample).unwrap(),
+            "adder_3.ytd"
+        );
+
+        let err = render_name("{stem}_{size}", &sample).unwrap_err();
+        assert_eq!(
+            err,
+            "Unknown variable `{size}` in name template, valid ones are {name}, {stem}, {ext}, {kind}, {index}"
+        );
+        assert!(render_name("{stem", &sample).is_err());
+    }
+
+    #[test]
+    fn sanitize_test() {
+        assert_eq!(sanitize("adder.ytd"), "adder.ytd");
+        assert_eq!(sanitize("a:b/c\\d?*.ytd"), "a_b_c_d__.ytd");
+        assert_eq!(sanitize("tab\there. "), "tab_here");
+        assert_eq!(sanitize(".."), "_");
+    }
+
+    #[test]
+    fn common_base_test() {
+        let paths = [
+            Path::new("stream/cars/adder.ytd"),
+            Path::new("stream/peds/a_m_y.ytd"),
+        ];
+        assert_eq!(common_base(&paths), PathBuf::from("stream"));
+        assert_eq!(
+            common_base(&[Path::new("stream/cars/adder.ytd")]),
+            PathBuf::from("stream/cars")
+        );
+        assert_eq!(
+            common_base(&[Path::new("a/x.ytd"), Path::new("b/x.ytd")]),
+            PathBuf::new()
+        );
+    }
+
+    #[test]
+    fn output_path_test() {
+        let out = Path::new("out");
+        let base = Path::new("stream");
+        let adder = input("stream/cars/adder.ytd", "virtual", 0);
+
+        let preserved = output_path(
+            out,
+            base,
+            NameLayout::PreservePaths,
+            DEFAULT_NAME_TEMPLATE,
+            &adder,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(preserved, PathBuf::from("out/cars/adder.ytd.virtual"));
+
+        let flat = |used: &HashSet<PathBuf>| {
+            output_path(
+                out,
+                base,
+                NameLayout::Flat,
+                DEFAULT_NAME_TEMPLATE,
+                &adder,
+                used,
+            )
+            .unwrap()
+        };
+        let mut used = HashSet::new();
+        for expected in [
+            "out/adder.ytd.virtual",
+            "out/adder.ytd_2.virtual",
+            "out/adder.ytd_3.virtual",
+        ] {
+            let path = flat(&used);
+            assert_eq!(path, PathBuf::from(expected));
+            used.insert(path);
+        }
+    }
+}
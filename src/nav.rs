@@ -0,0 +1,401 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::archive::{read_array, FArchive, FArchiveExt, FResourceArchive, VIRTUAL_BASE};
+use crate::math::Vec3;
+use crate::parse::ParseContext;
+use crate::CfxResult;
+
+/// Size of a path node in a `.ynd`.
+const NODE_SIZE: usize = 0x28;
+/// Path areas form a 32x32 grid over the map.
+const PATH_AREA_COUNT: u16 = 32 * 32;
+/// Polygons index their vertices with `u16`s.
+const MAX_NAVMESH_VERTICES: u32 = 1 << 16;
+
+/// Navigation files `unpack` summarizes, told apart by extension since the
+/// navmesh resource version is shared with other metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavKind {
+    /// `.ynd` path nodes
+    Ynd,
+    /// `.ynv` navmesh
+    Ynv,
+}
+
+impl NavKind {
+    /// The kind of `path` by its extension, looking through a `.gz` one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let path = match path.extension() {
+            Some(extension) if extension == "gz" => Path::new(path.file_stem()?),
+            _ => path,
+        };
+
+        match path.extension()?.to_str()? {
+            "ynd" => Some(Self::Ynd),
+            "ynv" => Some(Self::Ynv),
+            _ => None,
+        }
+    }
+
+    /// Reads the summary from the structure at the start of the virtual page.
+    pub fn summarize<Data>(
+        self,
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<NavSummary>
+    where
+        Data: AsRef<[u8]>,
+    {
+        Ok(match self {
+            Self::Ynd => NavSummary::Ynd(YndSummary::read(archive, context)?),
+            Self::Ynv => NavSummary::Ynv(YnvSummary::read(archive, context)?),
+        })
+    }
+}
+
+/// Counts from a navigation file, for taking inventory of custom map data
+/// without exporting its geometry.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NavSummary {
+    Ynd(YndSummary),
+    Ynv(YnvSummary),
+}
+
+/// Node dictionary of a `.ynd`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct YndSummary {
+    pub node_count: u32,
+    pub vehicle_node_count: u32,
+    pub ped_node_count: u32,
+    pub link_count: u32,
+    pub junction_count: u32,
+    /// Distinct areas the nodes are in, in ascending order
+    pub area_ids: Vec<u16>,
+}
+
+impl YndSummary {
+    fn read<Data>(
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<Self>
+    where
+        Data: AsRef<[u8]>,
+    {
+        // Past the VFT and pages info pointer
+        archive.set_position(VIRTUAL_BASE + 0x10)?;
+        let nodes_pointer = archive.read_ulong()?;
+        let node_count = archive.read_uint()?;
+        let vehicle_node_count = archive.read_uint()?;
+        let ped_node_count = archive.read_uint()?;
+        archive.skip(4)?;
+        archive.skip(8)?; // links pointer
+        let link_count = archive.read_uint()?;
+        archive.skip(4)?;
+        archive.skip(16)?; // junctions and junction heightmaps pointers
+        archive.skip(4)?;
+        let junction_count = archive.read_uint()?;
+
+        if vehicle_node_count as u64 + ped_node_count as u64 != node_count as u64 {
+            context.deviation(
+                VIRTUAL_BASE + 0x18,
+                format!(
+                    "{vehicle_node_count} vehicle and {ped_node_count} ped nodes do not add up to the {node_count} nodes"
+                ),
+            )?;
+        }
+
+        let nodes = match node_count {
+            0 => vec![],
+            _ => archive.read_at(nodes_pointer, |archive| {
+                read_array(archive, node_count as usize, NODE_SIZE, context, |node| {
+                    let area_id = u16::from_le_bytes([node[0x10], node[0x11]]);
+                    let link_id = u16::from_le_bytes([node[0x1A], node[0x1B]]);
+                    (area_id, link_id, node[0x25] >> 3)
+                })
+            })?,
+        };
+
+        let mut area_ids = BTreeSet::new();
+        for (index, &(area_id, link_id, links)) in nodes.iter().enumerate() {
+            let offset = nodes_pointer
+                .checked_add((index * NODE_SIZE) as u64)
+                .ok_or_else(|| format!("node {index} at {nodes_pointer:#x} overflows"))?;
+            if area_id >= PATH_AREA_COUNT {
+                context.deviation(
+                    offset,
+                    format!(
+                        "node {index} is in area {area_id}, past the last area {}",
+                        PATH_AREA_COUNT - 1
+                    ),
+                )?;
+            }
+
+            let end = link_id as u32 + links as u32;
+            if end > link_count {
+                context.deviation(
+                    offset,
+                    format!("node {index} uses links {link_id} to {end} of only {link_count}"),
+                )?;
+            }
+
+            area_ids.insert(area_id);
+        }
+
+        Ok(Self {
+            node_count,
+            vehicle_node_count,
+            ped_node_count,
+            link_count,
+            junction_count,
+            area_ids: area_ids.into_iter().collect(),
+        })
+    }
+}
+
+/// Header of a `.ynv` navmesh.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct YnvSummary {
+    pub area_id: u32,
+    pub vertex_count: u32,
+    pub poly_count: u32,
+    pub portal_count: u32,
+    /// Corners of the sector tree's root, `None` without a sector tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<[Vec3; 2]>,
+}
+
+impl YnvSummary {
+    fn read<Data>(
+        archive: &mut FResourceArchive<Data>,
+        context: &mut ParseContext,
+    ) -> CfxResult<Self>
+    where
+        Data: AsRef<[u8]>,
+    {
+        // Past the VFT, pages info pointer, flags and the transform matrix
+        archive.set_position(VIRTUAL_BASE + 0x60)?;
+        let size = archive.read_vec3()?;
+        if ![size.x, size.y, size.z]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            context.deviation(
+                VIRTUAL_BASE + 0x60,
+                format!("the bounding box size {size:?} is not finite"),
+            )?;
+        }
+
+        archive.set_position(VIRTUAL_BASE + 0x120)?;
+        let sector_tree_pointer = archive.read_ulong()?;
+        archive.set_position(VIRTUAL_BASE + 0x138)?;
+        let vertex_count = archive.read_uint()?;
+        let poly_count = archive.read_uint()?;
+        let area_id = archive.read_uint()?;
+        archive.skip(8)?; // total bytes and points count
+        let portal_count = archive.read_uint()?;
+
+        if vertex_count > MAX_NAVMESH_VERTICES {
+            context.deviation(
+                VIRTUAL_BASE + 0x138,
+                format!(
+                    "{vertex_count} vertices are more than polygons can address ({MAX_NAVMESH_VERTICES})"
+                ),
+            )?;
+        }
+
+        if vertex_count > 0 && poly_count == 0 {
+            context.deviation(
+                VIRTUAL_BASE + 0x13C,
+                format!("{vertex_count} vertices but no polygons"),
+            )?;
+        }
+
+        let bounds = match sector_tree_pointer {
+            0 => None,
+            pointer => {
+                let (min, max) = archive.read_at(pointer, |archive| {
+                    let min = archive.read_vec4()?;
+                    let max = archive.read_vec4()?;
+                    Ok((
+                        Vec3::from([min.x, min.y, min.z]),
+                        Vec3::from([max.x, max.y, max.z]),
+                    ))
+                })?;
+                check_bounds(pointer, min, max, context)?;
+                Some([min, max])
+            }
+        };
+
+        Ok(Self {
+            area_id,
+            vertex_count,
+            poly_count,
+            portal_count,
+            bounds,
+        })
+    }
+}
+
+/// Reports bounds with NaN or infinite corners, or a minimum past the maximum.
+fn check_bounds(offset: u64, min: Vec3, max: Vec3, context: &mut ParseContext) -> CfxResult<()> {
+    let (min, max) = (<[f32; 3]>::from(min), <[f32; 3]>::from(max));
+    if !min.iter().chain(&max).all(|value| value.is_finite()) {
+        return context.deviation(
+            offset,
+            format!("the bounds {min:?} to {max:?} are not finite"),
+        );
+    }
+
+    if min.iter().zip(&max).any(|(min, max)| min > max) {
+        return context.deviation(
+            offset,
+            format!("the bounds minimum {min:?} is past the maximum {max:?}"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Virtual page of a `.ynd` with `nodes` as (area id, link id, link
+/// count) after the header, and `links` links.
+pub fn ynd_page(nodes: &[(u16, u16, u8)], vehicle_nodes: u32, links: u32) -> Vec<u8> {
+    let mut page = vec![0u8; 0x70];
+    let count = nodes.len() as u32;
+    page[0x10..0x18].copy_from_slice(&(VIRTUAL_BASE + 0x70).to_le_bytes());
+    page[0x18..0x1C].copy_from_slice(&count.to_le_bytes());
+    page[0x1C..0x20].copy_from_slice(&vehicle_nodes.to_le_bytes());
+    page[0x20..0x24].copy_from_slice(&(count - vehicle_nodes).to_le_bytes());
+    page[0x30..0x34].copy_from_slice(&links.to_le_bytes());
+    page[0x4C..0x50].copy_from_slice(&1u32.to_le_bytes());
+
+    for &(area_id, link_id, link_count) in nodes {
+        let mut node = [0u8; NODE_SIZE];
+        node[0x10..0x12].copy_from_slice(&area_id.to_le_bytes());
+        node[0x1A..0x1C].copy_from_slice(&link_id.to_le_bytes());
+        node[0x25] = link_count << 3;
+        page.extend(node);
+    }
+
+    page
+}
+
+/// Virtual page of a `.ynv` whose sector tree spans `min` to `max`.
+pub fn ynv_page(vertices: u32, polys: u32, min: [f32; 3], max: [f32; 3]) -> Vec<u8> {
+    let mut page = vec![0u8; 0x170];
+    for (index, value) in max.iter().zip(min).map(|(max, min)| max - min).enumerate() {
+        page[0x60 + index * 4..0x64 + index * 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    page[0x120..0x128].copy_from_slice(&(VIRTUAL_BASE + 0x170).to_le_bytes());
+    page[0x138..0x13C].copy_from_slice(&vertices.to_le_bytes());
+    page[0x13C..0x140].copy_from_slice(&polys.to_le_bytes());
+    page[0x140..0x144].copy_from_slice(&5050u32.to_le_bytes());
+    page[0x14C..0x150].copy_from_slice(&2u32.to_le_bytes());
+
+    for corner in [min, max] {
+        for value in corner.into_iter().chain([0.0]) {
+            page.extend(value.to_le_bytes());
+        }
+    }
+
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summarize(
+        kind: NavKind,
+        page: Vec<u8>,
+        context: &mut ParseContext,
+    ) -> CfxResult<NavSummary> {
+        kind.summarize(&mut FResourceArchive::new(page, vec![]), context)
+    }
+
+    #[test]
+    fn nav_kind_test() {
+        assert_eq!(
+            NavKind::from_path(Path::new("nodes42.ynd")),
+            Some(NavKind::Ynd)
+        );
+        assert_eq!(
+            NavKind::from_path(Path::new("maps/navmesh[50][50].ynv.gz")),
+            Some(NavKind::Ynv)
+        );
+        assert_eq!(NavKind::from_path(Path::new("hei_bank.ymap")), None);
+        assert_eq!(NavKind::from_path(Path::new("nodes.gz")), None);
+    }
+
+    #[test]
+    fn ynd_summary_test() {
+        let page = ynd_page(&[(42, 0, 2), (42, 2, 1), (43, 3, 1)], 2, 4);
+        let mut context = ParseContext::default();
+        assert_eq!(
+            summarize(NavKind::Ynd, page, &mut context).unwrap(),
+            NavSummary::Ynd(YndSummary {
+                node_count: 3,
+                vehicle_node_count: 2,
+                ped_node_count: 1,
+                link_count: 4,
+                junction_count: 1,
+                area_ids: vec![42, 43],
+            })
+        );
+        assert!(context.warnings.is_empty());
+
+        let mut page = ynd_page(&[(1024, 0, 1), (7, 1, 3)], 2, 2);
+        page[0x20..0x24].copy_from_slice(&5u32.to_le_bytes());
+        let mut context = ParseContext::default();
+        summarize(NavKind::Ynd, page.clone(), &mut context).unwrap();
+        let messages = context
+            .warnings
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "2 vehicle and 5 ped nodes do not add up to the 2 nodes",
+                "node 0 is in area 1024, past the last area 1023",
+                "node 1 uses links 1 to 4 of only 2",
+            ]
+        );
+        assert!(summarize(NavKind::Ynd, page, &mut ParseContext::strict()).is_err());
+    }
+
+    #[test]
+    fn ynv_summary_test() {
+        let page = ynv_page(300, 120, [-10.0, -20.0, 0.0], [140.0, 130.0, 35.5]);
+        let mut context = ParseContext::default();
+        assert_eq!(
+            summarize(NavKind::Ynv, page, &mut context).unwrap(),
+            NavSummary::Ynv(YnvSummary {
+                area_id: 5050,
+                vertex_count: 300,
+                poly_count: 120,
+                portal_count: 2,
+                bounds: Some([
+                    Vec3::from([-10.0, -20.0, 0.0]),
+                    Vec3::from([140.0, 130.0, 35.5])
+                ]),
+            })
+        );
+        assert!(context.warnings.is_empty());
+
+        let page = ynv_page(70000, 0, [f32::NAN, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        let mut context = ParseContext::default();
+        summarize(NavKind::Ynv, page, &mut context).unwrap();
+        assert_eq!(context.warnings.len(), 4);
+        assert!(context.warnings[3].message.contains("not finite"));
+
+        let page = ynv_page(3, 1, [5.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        let mut context = ParseContext::default();
+        summarize(NavKind::Ynv, page, &mut context).unwrap();
+        assert!(context.warnings[0].message.contains("past the maximum"));
+    }
+}
@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Makes every later network access fail with [`OfflineError`], set from
+/// `--offline` or the `offline` config key.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Error of a feature that needed the network while offline mode is on.
+#[derive(Debug, PartialEq)]
+pub struct OfflineError {
+    /// What the network was needed for, such as `cloning https://...`
+    pub needed: String,
+}
+
+impl fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "offline mode is on, {} needs the network and nothing is cached for it",
+            self.needed
+        )
+    }
+}
+
+impl Error for OfflineError {}
+
+/// Fails fast with an [`OfflineError`] naming `needed` in offline mode.
+pub fn ensure_online(needed: &str) -> Result<(), OfflineError> {
+    if is_offline() {
+        return Err(OfflineError {
+            needed: needed.to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+/// A command that talks to the network, such as `git clone`. Every network
+/// access goes through here, so offline mode is enforced in one place and
+/// nothing is spawned while it is on.
+pub fn remote_command(program: &str, needed: &str) -> Result<Command, OfflineError> {
+    ensure_online(needed)?;
+    Ok(Command::new(program))
+}
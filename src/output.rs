@@ -0,0 +1,461 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{Log, Metadata, Record};
+use serde::Serialize;
+use serde_json::Value;
+use simple_logger::SimpleLogger;
+
+use crate::atomic::atomic_write;
+use crate::errors::ErrorCode;
+use crate::CfxResult;
+
+lazy_static::lazy_static! {
+    static ref WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+}
+
+static MACHINE: AtomicBool = AtomicBool::new(false);
+
+/// Keeps stdout for the envelope alone, set from `--machine` or `--json`.
+/// Commands then leave out the plain listings they print, which the envelope
+/// carries as data anyway.
+pub fn set_machine(machine: bool) {
+    MACHINE.store(machine, Ordering::Relaxed);
+}
+
+pub fn is_machine() -> bool {
+    MACHINE.load(Ordering::Relaxed)
+}
+
+/// The JSON document every command prints with `--json`; commands only
+/// contribute `data`.
+#[derive(Debug, Serialize)]
+pub struct Envelope {
+    pub tool: &'static str,
+    pub version: &'static str,
+    pub command: String,
+    pub ok: bool,
+    pub data: Option<Value>,
+    pub warnings: Vec<String>,
+    pub error: Option<EnvelopeError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvelopeError {
+    pub message: String,
+    /// Stable code of the failure, see `cfx explain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl Envelope {
+    pub fn new(command: &str, result: CfxResult<Option<Value>>, warnings: Vec<String>) -> Self {
+        let (data, error) = match result {
+            Ok(data) => (data, None),
+            Err(err) => (
+                err.downcast_ref::<FailedWith>()
+                    .map(|failed| failed.data.clone()),
+                Some(EnvelopeError {
+                    message: err.to_string(),
+                    code: ErrorCode::of(err.as_ref()).map(ErrorCode::code),
+                }),
+            ),
+        };
+
+        Self {
+            tool: "cfx",
+            version: env!("CARGO_PKG_VERSION"),
+            command: command.to_owned(),
+            ok: error.is_none(),
+            data,
+            warnings,
+            error,
+        }
+    }
+
+    /// The line `--json` prints.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// A failure that still has findings to report, such as the diagnostics of a
+/// failed check. The envelope carries them as `data` next to the error.
+#[derive(Debug)]
+pub struct FailedWith {
+    pub message: String,
+    pub data: Value,
+}
+
+impl FailedWith {
+    pub fn new<T: Serialize>(message: impl Into<String>, summary: &T) -> Self {
+        Self {
+            message: message.into(),
+            data: serde_json::to_value(summary).unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for FailedWith {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for FailedWith {}
+
+/// Turns what a command handler returns into the `data` of its envelope.
+pub fn summary<T: Serialize>(value: T) -> CfxResult<Option<Value>> {
+    Ok(Some(serde_json::to_value(value)?))
+}
+
+/// Forwards to the stderr logger while keeping a copy of every warning, so
+/// they end up in the envelope as well.
+struct CapturingLogger {
+    inner: SimpleLogger,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() == log::Level::Warn && self.enabled(record.metadata()) {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+pub fn init_logger(logger: SimpleLogger) -> CfxResult<()> {
+    log::set_boxed_logger(Box::new(CapturingLogger { inner: logger }))?;
+    Ok(())
+}
+
+/// Warnings logged so far, leaving none behind.
+pub fn take_warnings() -> Vec<String> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap())
+}
+
+/// Number of warnings logged so far, to pass to [`warnings_since`].
+pub fn warning_count() -> usize {
+    WARNINGS.lock().unwrap().len()
+}
+
+/// Warnings logged after [`warning_count`] returned `start`, leaving them in
+/// place for the envelope.
+pub fn warnings_since(start: usize) -> Vec<String> {
+    WARNINGS
+        .lock()
+        .unwrap()
+        .get(start..)
+        .map(<[String]>::to_vec)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    Ok,
+    Warning,
+    Failed,
+}
+
+impl ItemStatus {
+    fn label(self) -> &'static str {
+        match self {
+            ItemStatus::Ok => "ok",
+            ItemStatus::Warning => "warning",
+            ItemStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Outcome of one item of a batch, such as a file or a resource.
+#[derive(Debug, Serialize)]
+pub struct ReportItem {
+    pub item: String,
+    pub status: ItemStatus,
+    pub duration_ms: u64,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl ReportItem {
+    /// The status follows from the messages: failed with errors, warning with
+    /// warnings only.
+    pub fn new(
+        item: impl Into<String>,
+        duration: Duration,
+        warnings: Vec<String>,
+        errors: Vec<String>,
+    ) -> Self {
+        let status = if !errors.is_empty() {
+            ItemStatus::Failed
+        } else if !warnings.is_empty() {
+            ItemStatus::Warning
+        } else {
+            ItemStatus::Ok
+        };
+
+        Self {
+            item: item.into(),
+            status,
+            duration_ms: duration.as_millis() as u64,
+            warnings,
+            errors,
+        }
+    }
+}
+
+/// Per-item summary of a batch run that `--report` writes, as JSON or as a
+/// Markdown table depending on the file extension.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub command: String,
+    pub items: Vec<ReportItem>,
+}
+
+impl RunReport {
+    pub fn new(command: &str, items: Vec<ReportItem>) -> Self {
+        Self {
+            command: command.to_owned(),
+            items,
+        }
+    }
+
+    fn count(&self, status: ItemStatus) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.status == status)
+            .count()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "## cfx {}\n\n{} item(s): {} ok, {} with warnings, {} failed\n\n",
+            self.command,
+            self.items.len(),
+            self.count(ItemStatus::Ok),
+            self.count(ItemStatus::Warning),
+            self.count(ItemStatus::Failed)
+        );
+        markdown.push_str("| Item | Status | Duration | Warnings | Errors |\n");
+        markdown.push_str("| --- | --- | --- | --- | --- |\n");
+
+        for item in &self.items {
+            markdown.push_str(&format!(
+                "| {} | {} | {} ms | {} | {} |\n",
+                table_cell(std::slice::from_ref(&item.item)),
+                item.status.label(),
+                item.duration_ms,
+                table_cell(&item.warnings),
+                table_cell(&item.errors)
+            ));
+        }
+
+        markdown
+    }
+
+    /// Writes the report to a `.json` or `.md` file.
+    pub fn write(&self, path: &Path) -> CfxResult<()> {
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => serde_json::to_string_pretty(self)? + "\n",
+            Some("md") => self.to_markdown(),
+            _ => {
+                return Err(
+                    format!("Report {path:?} must end in .json or .md to pick a format").into(),
+                )
+            }
+        };
+
+        atomic_write(path, contents)?;
+        log::info!("Wrote report to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Joins messages into one table cell, escaping what would break the row.
+fn table_cell(messages: &[String]) -> String {
+    messages
+        .iter()
+        .map(|message| message.replace('|', "\\|").replace('\n', " "))
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    use crate::cancel::CancelToken;
+    use crate::commands::explain_flags::{handle_explain_flags_command, ExplainFlagsArgs};
+    use crate::commands::formats::handle_formats_command;
+    use crate::commands::unpack::{self, handle_unpack_command};
+    use crate::errors::CodedError;
+    use crate::magic::Format;
+    use crate::progress::NoProgress;
+    use crate::Cli;
+
+    const FIELDS: [&str; 7] = [
+        "tool", "version", "command", "ok", "data", "warnings", "error",
+    ];
+
+    fn report() -> RunReport {
+        RunReport::new(
+            "unpack",
+            vec![
+                ReportItem::new("a.ydr", Duration::from_millis(12), vec![], vec![]),
+                ReportItem::new(
+                    "b.ydr",
+                    Duration::from_millis(3),
+                    vec!["Found the RSC7 magic at offset 0x10".to_owned()],
+                    vec![],
+                ),
+                ReportItem::new(
+                    "c.ydr",
+                    Duration::from_millis(1),
+                    vec![],
+                    vec!["Invalid magic | expected RSC7".to_owned()],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn markdown_report_test() {
+        assert_eq!(
+            report().to_markdown(),
+            "## cfx unpack\n\
+             \n\
+             3 item(s): 1 ok, 1 with warnings, 1 failed\n\
+             \n\
+             | Item | Status | Duration | Warnings | Errors |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | a.ydr | ok | 12 ms |  |  |\n\
+             | b.ydr | warning | 3 ms | Found the RSC7 magic at offset 0x10 |  |\n\
+             | c.ydr | failed | 1 ms |  | Invalid magic \\| expected RSC7 |\n"
+        );
+    }
+
+    #[test]
+    fn json_report_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        report().write(&path).unwrap();
+
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "command": "unpack",
+                "items": [
+                    { "item": "a.ydr", "status": "ok", "duration_ms": 12, "warnings": [], "errors": [] },
+                    {
+                        "item": "b.ydr",
+                        "status": "warning",
+                        "duration_ms": 3,
+                        "warnings": ["Found the RSC7 magic at offset 0x10"],
+                        "errors": []
+                    },
+                    {
+                        "item": "c.ydr",
+                        "status": "failed",
+                        "duration_ms": 1,
+                        "warnings": [],
+                        "errors": ["Invalid magic | expected RSC7"]
+                    }
+                ]
+            })
+        );
+
+        let err = report().write(&dir.path().join("report.txt")).unwrap_err();
+        assert!(err.to_string().contains("must end in .json or .md"));
+    }
+
+    /// The printed envelope of `result`, as `main` builds it, parsed back.
+    fn printed(command: &str, result: CfxResult<Option<Value>>) -> Value {
+        let line = Envelope::new(command, result, take_warnings()).to_json();
+        let value: Value = serde_json::from_str(&line).unwrap();
+        for field in FIELDS {
+            assert!(value.get(field).is_some(), "{command}: missing `{field}`");
+        }
+
+        assert_eq!(value["tool"], "cfx");
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(value["command"], command);
+        assert_eq!(value["error"].is_null(), value["ok"] == true);
+        value
+    }
+
+    #[test]
+    fn envelope_schema_test() {
+        let names = Cli::command()
+            .get_subcommands()
+            .map(|command| command.get_name().to_owned())
+            .collect::<Vec<_>>();
+        for name in ["formats", "explain-flags", "unpack"] {
+            assert!(names.iter().any(|known| known == name), "no `{name}`");
+        }
+
+        let formats = printed("formats", handle_formats_command().and_then(summary));
+        assert_eq!(formats["ok"], true);
+        let formats = formats["data"].as_array().unwrap();
+        assert_eq!(formats.len(), Format::ALL.len());
+        assert!(formats.iter().all(|format| format["magic"].is_string()));
+
+        let args = ExplainFlagsArgs {
+            value: Some("0x20020001".to_owned()),
+            for_size: None,
+            version: 0,
+        };
+        let flags = printed(
+            "explain-flags",
+            handle_explain_flags_command(&args).and_then(summary),
+        );
+        assert_eq!(flags["ok"], true);
+        assert_eq!(flags["data"]["base_size"], 0x400);
+        assert_eq!(flags["data"]["total_size"], 0x4000);
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("broken.ytd");
+        std::fs::write(&file, b"\0not a resource").unwrap();
+        let args = unpack::args(vec![file.to_string_lossy().to_string()]);
+        let result = handle_unpack_command(&args, &mut NoProgress, &CancelToken::new());
+        let unpack = printed("unpack", result.and_then(summary));
+        assert_eq!(unpack["ok"], false);
+        assert!(unpack["data"].is_null());
+        assert_eq!(unpack["error"]["code"], "E001");
+        assert!(unpack["error"]["message"].is_string());
+
+        // Failures with findings keep them as data
+        let failed = FailedWith::new(
+            "Check found 1 error(s)",
+            &serde_json::json!({ "checked": 1 }),
+        );
+        let value = printed("check", Err(failed.into()));
+        assert_eq!(value["ok"], false);
+        assert_eq!(value["data"]["checked"], 1);
+        assert_eq!(value["error"]["message"], "Check found 1 error(s)");
+
+        // Errors without a code leave it out
+        let uncoded = printed("unpack", Err("broken".into()));
+        assert!(uncoded["error"].get("code").is_none());
+        let coded = CodedError::new(ErrorCode::InvalidMagic, "Invalid magic");
+        assert_eq!(
+            printed("repack", Err(coded.into()))["error"]["code"],
+            "E001"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+use crate::archive::MAX_ARRAY_LEN;
+use crate::CfxResult;
+
+/// How parsers treat data that is slightly out of spec.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    /// Fail on the first deviation instead of recording it and salvaging
+    /// what can be read
+    pub strict: bool,
+    /// Most elements an array may claim, in either mode
+    pub max_items: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            max_items: MAX_ARRAY_LEN,
+        }
+    }
+}
+
+/// A deviation from the format a lenient parse carried on past.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseWarning {
+    pub offset: u64,
+    pub message: String,
+}
+
+/// The options of a parse and the warnings it collected, passed to every
+/// parser so lenient and strict parsing share one code path.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    pub options: ParseOptions,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseContext {
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            warnings: vec![],
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn strict() -> Self {
+        Self::new(ParseOptions {
+            strict: true,
+            ..Default::default()
+        })
+    }
+
+    /// Reports data at `offset` that is out of spec: an error in strict mode,
+    /// otherwise a logged warning the parser salvages past.
+    pub fn deviation(&mut self, offset: u64, message: impl Into<String>) -> CfxResult<()> {
+        let message = message.into();
+        if self.options.strict {
+            return Err(format!("{message} (at {offset:#x}, rejected by --strict)").into());
+        }
+
+        log::warn!("{message} (at {offset:#x})");
+        self.warnings.push(ParseWarning { offset, message });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deviation_test() {
+        let mut lenient = ParseContext::default();
+        lenient.deviation(0x10, "7 bytes of trailing data").unwrap();
+        assert_eq!(
+            lenient.warnings,
+            vec![ParseWarning {
+                offset: 0x10,
+                message: "7 bytes of trailing data".to_owned()
+            }]
+        );
+
+        let err = ParseContext::strict()
+            .deviation(0x10, "7 bytes of trailing data")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "7 bytes of trailing data (at 0x10, rejected by --strict)"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+use serde::Serialize;
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+
+/// What a plain text file mixed in with binary resources holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextKind {
+    /// XML such as `.meta` files, starting with `<`
+    Xml,
+    Text,
+}
+
+/// Sniffs `bytes` for plain text: valid UTF-8 after an optional BOM, without
+/// control characters other than tabs and line breaks. Text whose first
+/// non-blank character is `<` is XML.
+pub fn sniff_text(bytes: &[u8]) -> Option<TextKind> {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    let text = std::str::from_utf8(bytes).ok()?;
+    if text.trim().is_empty()
+        || text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+    {
+        return None;
+    }
+
+    Some(match text.trim_start().starts_with('<') {
+        true => TextKind::Xml,
+        false => TextKind::Text,
+    })
+}
+
+/// Re-indents XML by two spaces per level, dropping the blank text between
+/// elements. Malformed XML is an error naming the byte it was found at.
+pub fn pretty_xml(bytes: &[u8]) -> Result<String, String> {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    let text = std::str::from_utf8(bytes).map_err(|err| err.to_string())?;
+
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(vec![], b' ', 2);
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|err| format!("{err} at byte {}", reader.error_position()))?;
+        if event == Event::Eof {
+            break;
+        }
+
+        writer.write_event(event).map_err(|err| err.to_string())?;
+    }
+
+    let mut result = String::from_utf8(writer.into_inner()).map_err(|err| err.to_string())?;
+    result.push('\n');
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_text_test() {
+        assert_eq!(
+            sniff_text(b"\xef\xbb\xbf\r\n  <?xml version=\"1.0\"?>\n<CVehicleModelInfo />"),
+            Some(TextKind::Xml)
+        );
+        assert_eq!(sniff_text(b"model\tsultanrs2\n"), Some(TextKind::Text));
+        assert_eq!(sniff_text(b"RSC7\x0d\x00\x00\x00"), None);
+        assert_eq!(sniff_text(b"<\xff>"), None);
+        assert_eq!(sniff_text(b" \n"), None);
+    }
+
+    #[test]
+    fn pretty_xml_test() {
+        let xml = b"<?xml version=\"1.0\"?><CHandlingDataMgr><HandlingData>\n  <Item type=\"CHandlingData\"><handlingName>ADDER</handlingName></Item></HandlingData></CHandlingDataMgr>";
+        assert_eq!(
+            pretty_xml(xml).unwrap(),
+            r#"<?xml version="1.0"?>
+<CHandlingDataMgr>
+  <HandlingData>
+    <Item type="CHandlingData">
+      <handlingName>ADDER</handlingName>
+    </Item>
+  </HandlingData>
+</CHandlingDataMgr>
+"#
+        );
+
+        let err = pretty_xml(b"<Item><a></b></Item>").unwrap_err();
+        assert!(err.contains("at byte"), "{err}");
+    }
+}
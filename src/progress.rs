@@ -0,0 +1,289 @@
+use std::io::Read;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Receives progress of long operations such as decompression. Kept free of
+/// any UI dependency so other front ends can implement it.
+pub trait ProgressSink: Send {
+    fn start(&mut self, total: u64);
+    fn advance(&mut self, n: u64);
+    fn message(&mut self, message: &str);
+    fn finish(&mut self);
+}
+
+/// Ignores all progress, for machine output and tests.
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn start(&mut self, _total: u64) {}
+    fn advance(&mut self, _n: u64) {}
+    fn message(&mut self, _message: &str) {}
+    fn finish(&mut self) {}
+}
+
+/// Coalesces `advance` calls so that `inner` sees at most one per `interval`,
+/// however tight the loop reporting them is.
+pub struct Throttled<Sink: ProgressSink> {
+    inner: Sink,
+    interval: Duration,
+    pending: u64,
+    last: Option<Instant>,
+}
+
+impl<Sink: ProgressSink> Throttled<Sink> {
+    pub fn new(inner: Sink, interval: Duration) -> Self {
+        Self {
+            inner,
+            interval,
+            pending: 0,
+            last: None,
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pending > 0 {
+            self.inner.advance(self.pending);
+            self.pending = 0;
+        }
+
+        self.last = Some(Instant::now());
+    }
+}
+
+impl<Sink: ProgressSink> ProgressSink for Throttled<Sink> {
+    fn start(&mut self, total: u64) {
+        self.pending = 0;
+        self.last = Some(Instant::now());
+        self.inner.start(total);
+    }
+
+    fn advance(&mut self, n: u64) {
+        self.pending += n;
+        if self.last.is_none_or(|last| last.elapsed() >= self.interval) {
+            self.flush();
+        }
+    }
+
+    fn message(&mut self, message: &str) {
+        self.inner.message(message);
+    }
+
+    fn finish(&mut self) {
+        self.flush();
+        self.inner.finish();
+    }
+}
+
+/// Terminal progress bar on stderr.
+pub struct BarProgress {
+    bar: ProgressBar,
+}
+
+impl BarProgress {
+    pub fn new() -> Self {
+        Self {
+            bar: ProgressBar::hidden(),
+        }
+    }
+}
+
+impl ProgressSink for BarProgress {
+    fn start(&mut self, total: u64) {
+        self.bar = ProgressBar::new(total);
+        if let Ok(style) =
+            ProgressStyle::with_template("{msg} [{bar:30}] {bytes}/{total_bytes} ({eta})")
+        {
+            self.bar.set_style(style.progress_chars("=> "));
+        }
+    }
+
+    fn advance(&mut self, n: u64) {
+        self.bar.inc(n);
+    }
+
+    fn message(&mut self, message: &str) {
+        self.bar.set_message(message.to_owned());
+    }
+
+    fn finish(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// The sink commands report to: a throttled bar for people, nothing for
+/// `--machine` output.
+pub fn cli_progress(machine: bool) -> Box<dyn ProgressSink> {
+    if machine {
+        Box::new(NoProgress)
+    } else {
+        Box::new(Throttled::new(
+            BarProgress::new(),
+            Duration::from_millis(50),
+        ))
+    }
+}
+
+/// Lets threads working side by side report to one sink, each through its own
+/// [`SharedProgress::handle`].
+pub struct SharedProgress<'a> {
+    sink: Mutex<&'a mut dyn ProgressSink>,
+}
+
+impl<'a> SharedProgress<'a> {
+    pub fn new(sink: &'a mut dyn ProgressSink) -> Self {
+        Self {
+            sink: Mutex::new(sink),
+        }
+    }
+
+    pub fn handle(&self) -> ProgressHandle<'_, 'a> {
+        ProgressHandle { shared: self }
+    }
+}
+
+/// A thread's way to report to a [`SharedProgress`].
+pub struct ProgressHandle<'s, 'a> {
+    shared: &'s SharedProgress<'a>,
+}
+
+impl ProgressHandle<'_, '_> {
+    fn with(&self, report: impl FnOnce(&mut dyn ProgressSink)) {
+        // A thread panicking mid-report leaves nothing to clean up
+        let mut sink = self
+            .shared
+            .sink
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        report(&mut **sink);
+    }
+}
+
+impl ProgressSink for ProgressHandle<'_, '_> {
+    fn start(&mut self, total: u64) {
+        self.with(|sink| sink.start(total));
+    }
+
+    fn advance(&mut self, n: u64) {
+        self.with(|sink| sink.advance(n));
+    }
+
+    fn message(&mut self, message: &str) {
+        self.with(|sink| sink.message(message));
+    }
+
+    fn finish(&mut self) {
+        self.with(|sink| sink.finish());
+    }
+}
+
+/// Reports the bytes read through it to a sink.
+pub struct ProgressReader<'a, Inner: Read> {
+    inner: Inner,
+    sink: &'a mut dyn ProgressSink,
+}
+
+impl<'a, Inner: Read> ProgressReader<'a, Inner> {
+    pub fn new(inner: Inner, sink: &'a mut dyn ProgressSink) -> Self {
+        Self { inner, sink }
+    }
+}
+
+impl<Inner: Read> Read for ProgressReader<'_, Inner> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.sink.advance(read as u64);
+        }
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recording {
+        calls: Vec<String>,
+    }
+
+    impl ProgressSink for &mut Recording {
+        fn start(&mut self, total: u64) {
+            self.calls.push(format!("start {total}"));
+        }
+
+        fn advance(&mut self, n: u64) {
+            self.calls.push(format!("advance {n}"));
+        }
+
+        fn message(&mut self, message: &str) {
+            self.calls.push(format!("message {message}"));
+        }
+
+        fn finish(&mut self) {
+            self.calls.push("finish".to_owned());
+        }
+    }
+
+    #[test]
+    fn throttled_progress_test() {
+        let mut recording = Recording::default();
+        let mut sink = Throttled::new(&mut recording, Duration::from_secs(3600));
+
+        sink.start(1000);
+        for _ in 0..1000 {
+            sink.advance(1);
+        }
+        sink.finish();
+
+        // Only the final flush gets through within the interval
+        assert_eq!(
+            recording.calls,
+            vec!["start 1000", "advance 1000", "finish"]
+        );
+    }
+
+    #[test]
+    fn progress_reader_test() {
+        let mut recording = Recording::default();
+        let mut sink = &mut recording;
+        let mut reader = ProgressReader::new(&[7u8; 10][..], &mut sink);
+
+        let mut buffer = [0u8; 4];
+        while reader.read(&mut buffer).unwrap() > 0 {}
+
+        assert_eq!(recording.calls, vec!["advance 4", "advance 4", "advance 2"]);
+    }
+
+    #[test]
+    fn shared_progress_test() {
+        let mut recording = Recording::default();
+        let mut sink = &mut recording;
+        let shared = SharedProgress::new(&mut sink);
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                let mut handle = shared.handle();
+                scope.spawn(move || {
+                    for _ in 0..50 {
+                        handle.advance(2);
+                    }
+                });
+            }
+        });
+        shared.handle().finish();
+
+        assert_eq!(recording.calls.len(), 101);
+        assert_eq!(
+            recording
+                .calls
+                .iter()
+                .filter(|call| *call == "advance 2")
+                .count(),
+            100
+        );
+        assert_eq!(recording.calls.last().unwrap(), "finish");
+    }
+}
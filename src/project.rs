@@ -0,0 +1,85 @@
+use std::fs::{read_to_string, File};
+use std::io::Write;
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::create::CreateAnswers;
+use crate::lint::LintConfig;
+use crate::CfxResult;
+
+pub const PROJECT_FILE: &str = "cfx.toml";
+
+/// Per-resource metadata kept next to the manifest in `cfx.toml`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    pub name: String,
+    pub author: String,
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    /// Convars the server has to set for the resource to work
+    #[serde(default)]
+    pub required_convars: Vec<String>,
+    #[serde(default)]
+    pub language: Language,
+    /// Severity overrides for `validate` rules, taking precedence over the
+    /// user config
+    #[serde(default, skip_serializing_if = "LintConfig::is_empty")]
+    pub lint: LintConfig,
+    /// Answers the resource was created from, usable with `create --answers`
+    #[serde(default, skip_serializing_if = "CreateAnswers::is_empty")]
+    pub create: CreateAnswers,
+}
+
+/// Language the resource's scripts are written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    Lua,
+    Js,
+    Ts,
+}
+
+impl ProjectConfig {
+    pub fn load(resource_dir: &Path) -> CfxResult<Self> {
+        let path = resource_dir.join(PROJECT_FILE);
+        let content = read_to_string(&path)?;
+
+        toml::from_str(&content).map_err(|err| format!("Invalid {path:?}: {err}").into())
+    }
+
+    pub fn save(&self, resource_dir: &Path) -> CfxResult<()> {
+        let mut file = File::create(resource_dir.join(PROJECT_FILE))?;
+        file.write_all(toml::to_string_pretty(self)?.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::Severity;
+
+    #[test]
+    fn project_round_trip_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = ProjectConfig {
+            name: "example".to_owned(),
+            author: "cfx".to_owned(),
+            libraries: vec!["oxmysql".to_owned()],
+            required_convars: vec!["mysql_connection_string".to_owned()],
+            language: Language::Lua,
+            lint: LintConfig::from([("escrowed".to_owned(), Severity::Off)]),
+            create: CreateAnswers {
+                author: Some("cfx".to_owned()),
+                ..Default::default()
+            },
+        };
+        project.save(dir.path()).unwrap();
+
+        assert_eq!(ProjectConfig::load(dir.path()).unwrap(), project);
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt;
+use std::fs::{read, read_dir};
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::CfxResult;
+
+pub const MANIFEST_NAMES: [&str; 2] = ["fxmanifest.lua", "__resource.lua"];
+const SCRIPT_EXTENSIONS: [&str; 3] = ["lua", "js", "ts"];
+const IGNORED_DIRS: [&str; 3] = [".git", "node_modules", "dist"];
+
+lazy_static! {
+    /// `exports('name', ...)` registrations in Lua and JS.
+    pub static ref EXPORT_REGISTRATION: Regex =
+        Regex::new(r#"\bexports\s*\(\s*['"]([\w\-]+)['"]"#).unwrap();
+    /// `exports['resource']:name(`, `exports.resource:name(` and the JS `.name(` forms.
+    pub static ref EXPORT_CALL: Regex = Regex::new(
+        r#"\bexports\s*(?:\[\s*['"]([\w\-]+)['"]\s*\]|\.([\w\-]+))\s*[:.]\s*(\w+)\s*\("#
+    )
+    .unwrap();
+    /// `exports { ... }` and `server_exports { ... }` blocks, or their singular forms.
+    pub static ref MANIFEST_EXPORTS: Regex = Regex::new(
+        r#"(?s)\b(?:server_)?exports?\s*(?:\{([^}]*)\}|\(?\s*(['"][^'"]*['"]))"#
+    )
+    .unwrap();
+    /// Event handler registrations, capturing the registering function and event name.
+    pub static ref EVENT_REGISTRATION: Regex = Regex::new(
+        r#"\b(RegisterNetEvent|RegisterServerEvent|AddEventHandler|onNet)\s*\(\s*['"]([^'"]+)['"]"#
+    )
+    .unwrap();
+    /// Event triggers, capturing the triggering function and event name.
+    pub static ref EVENT_TRIGGER: Regex = Regex::new(
+        r#"\b(TriggerEvent|TriggerServerEvent|TriggerClientEvent|TriggerLatentServerEvent|TriggerLatentClientEvent|emitNet)\s*\(\s*['"]([^'"]+)['"]"#
+    )
+    .unwrap();
+    static ref QUOTED: Regex = Regex::new(r#"['"]([^'"]*)['"]"#).unwrap();
+}
+
+/// A resource folder found while walking a resources tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Resource {
+    pub fn manifest_path(&self) -> Option<PathBuf> {
+        MANIFEST_NAMES
+            .iter()
+            .map(|name| self.path.join(name))
+            .find(|path| path.is_file())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// A regex match within a script, with every capture group in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanMatch {
+    pub location: Location,
+    pub captures: Vec<Option<String>>,
+}
+
+/// Finds resources below `root`, descending into `[category]` and other
+/// plain folders but never into a resource itself.
+pub fn find_resources(root: &Path) -> CfxResult<Vec<Resource>> {
+    let mut result = vec![];
+    collect_resources(root, &mut result)?;
+
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(result)
+}
+
+fn collect_resources(dir: &Path, result: &mut Vec<Resource>) -> CfxResult<()> {
+    if MANIFEST_NAMES.iter().any(|name| dir.join(name).is_file()) {
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        result.push(Resource {
+            name,
+            path: dir.to_path_buf(),
+        });
+        return Ok(());
+    }
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && !is_ignored(&path) {
+            collect_resources(&path, result)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is a folder that walks over resources skip.
+pub fn is_ignored(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| IGNORED_DIRS.iter().any(|ignored| name == *ignored))
+}
+
+/// Lists the Lua, JS and TS files of a resource in a stable order.
+pub fn script_files(resource_dir: &Path) -> CfxResult<Vec<PathBuf>> {
+    let mut result = vec![];
+    collect_scripts(resource_dir, &mut result)?;
+
+    result.sort();
+    Ok(result)
+}
+
+fn collect_scripts(dir: &Path, result: &mut Vec<PathBuf>) -> CfxResult<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if !is_ignored(&path) {
+                collect_scripts(&path, result)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| SCRIPT_EXTENSIONS.iter().any(|known| ext == *known))
+        {
+            result.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Matches `regex` against every line of `content`, skipping lines that are
+/// entirely a Lua or JS comment.
+pub fn scan_content(file: &Path, content: &str, regex: &Regex) -> Vec<ScanMatch> {
+    let mut result = vec![];
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("--") || trimmed.starts_with("//") {
+            continue;
+        }
+
+        for captures in regex.captures_iter(line) {
+            result.push(ScanMatch {
+                location: Location {
+                    file: file.to_path_buf(),
+                    line: index + 1,
+                },
+                captures: captures
+                    .iter()
+                    .skip(1)
+                    .map(|group| group.map(|group| group.as_str().to_owned()))
+                    .collect(),
+            });
+        }
+    }
+
+    result
+}
+
+/// Reads a script as text. Scripts saved in a legacy encoding such as
+/// Latin-1 are common, so bytes that are not UTF-8 are replaced rather than
+/// failing the read.
+pub fn read_script(file: &Path) -> CfxResult<String> {
+    let bytes = read(file).map_err(|err| format!("Could not read {}: {err}", file.display()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub fn scan_file(file: &Path, regex: &Regex) -> CfxResult<Vec<ScanMatch>> {
+    let content = read_script(file)?;
+    Ok(scan_content(file, &content, regex))
+}
+
+/// Matches `regex` against every script of every resource, pairing each
+/// match with the name of the resource it was found in.
+pub fn scan_resources(
+    resources: &[Resource],
+    regex: &Regex,
+) -> CfxResult<Vec<(String, ScanMatch)>> {
+    let mut result = vec![];
+    for resource in resources {
+        for script in script_files(&resource.path)? {
+            for found in scan_file(&script, regex)? {
+                result.push((resource.name.clone(), found));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Export names declared in a manifest through `exports`/`server_exports`.
+pub fn manifest_exports(content: &str) -> Vec<String> {
+    let mut result = vec![];
+    for captures in MANIFEST_EXPORTS.captures_iter(content) {
+        let Some(body) = captures.get(1).or_else(|| captures.get(2)) else {
+            continue;
+        };
+
+        for quoted in QUOTED.captures_iter(body.as_str()) {
+            result.push(quoted[1].to_owned());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn find_resources_test() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [
+            "[core]/bank",
+            "shop",
+            "shop/nested",
+            "[core]/node_modules/x",
+        ] {
+            create_dir_all(root.path().join(dir)).unwrap();
+        }
+        write(root.path().join("[core]/bank/fxmanifest.lua"), "").unwrap();
+        write(root.path().join("shop/__resource.lua"), "").unwrap();
+        write(root.path().join("shop/nested/fxmanifest.lua"), "").unwrap();
+
+        let names = find_resources(root.path())
+            .unwrap()
+            .into_iter()
+            .map(|resource| resource.name)
+            .collect::<Vec<String>>();
+
+        assert_eq!(names, vec!["bank", "shop"]);
+    }
+
+    #[test]
+    fn latin1_script_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("client.lua");
+        write(&file, b"-- Versi\xf3n 2\nexports['ox_lib']:notify({})\n").unwrap();
+
+        let matches = scan_file(&file, &EXPORT_CALL).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].location.line, 2);
+
+        let err = scan_file(&dir.path().join("gone.lua"), &EXPORT_CALL).unwrap_err();
+        assert!(err.to_string().contains("gone.lua"), "{err}");
+    }
+
+    #[test]
+    fn export_call_test() {
+        let content = r#"local a = exports['ox_lib']:notify({})
+-- exports.bank:hidden()
+exports.bank:getMoney(1) exports["qb-core"].GetCoreObject()"#;
+
+        let matches = scan_content(Path::new("x.lua"), content, &EXPORT_CALL);
+        let calls = matches
+            .iter()
+            .map(|m| {
+                let resource = m.captures[0].clone().or(m.captures[1].clone()).unwrap();
+                (m.location.line, resource, m.captures[2].clone().unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            calls,
+            vec![
+                (1, "ox_lib".to_owned(), "notify".to_owned()),
+                (3, "bank".to_owned(), "getMoney".to_owned()),
+                (3, "qb-core".to_owned(), "GetCoreObject".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn manifest_exports_test() {
+        let content = r#"exports {
+    'getFoo',
+    "getBar"
+}
+server_exports { 'setBaz' }
+export 'legacy'"#;
+
+        assert_eq!(
+            manifest_exports(content),
+            vec!["getFoo", "getBar", "setBaz", "legacy"]
+        );
+    }
+}
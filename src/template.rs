@@ -0,0 +1,503 @@
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read, read_dir, remove_dir_all, rename, write, File};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::atomic::temp_path;
+use crate::cache::Cache;
+use crate::config::{cache_dir, TemplateEntry};
+use crate::lock::{lock_path, FileLock};
+use crate::network::remote_command;
+use crate::CfxResult;
+
+/// Answers used to check that a template renders before it is registered.
+pub const DUMMY_VARIABLES: [(&str, &str); 2] =
+    [("project_name", "example_resource"), ("author", "cfx")];
+
+/// Parses a `--var KEY=VALUE` for a custom template variable. Keys are
+/// letters, digits and underscores, and cannot replace the built-in
+/// [`DUMMY_VARIABLES`].
+pub fn parse_variable(value: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = value.split_once('=') else {
+        return Err(format!("`{value}` is not KEY=VALUE"));
+    };
+
+    let key = key.trim();
+    let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(format!("`{key}` is not a valid template variable"));
+    }
+
+    if DUMMY_VARIABLES.iter().any(|&(builtin, _)| builtin == key) {
+        return Err(format!("`{key}` is a built-in template variable"));
+    }
+
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+/// Parses the name a template is registered under. It names the template's
+/// folder in the cache, so it cannot be empty, absolute or leave that folder.
+pub fn parse_template_name(value: &str) -> Result<String, String> {
+    let valid = !value.is_empty()
+        && !value.contains(['/', '\\'])
+        && !value.contains("..")
+        && !Path::new(value).is_absolute();
+    if !valid {
+        return Err(format!(
+            "`{value}` is not a valid template name, it cannot be empty or contain `/`, `\\` or `..`"
+        ));
+    }
+
+    Ok(value.to_owned())
+}
+
+/// The built-in `project_name` and `author` variables followed by `custom`
+/// ones, where a later value for a key wins.
+pub fn variables(
+    project_name: &str,
+    author: &str,
+    custom: &[(String, String)],
+) -> HashMap<String, String> {
+    let mut variables = HashMap::from([
+        ("project_name".to_owned(), project_name.to_owned()),
+        ("author".to_owned(), author.to_owned()),
+    ]);
+    variables.extend(custom.iter().cloned());
+    variables
+}
+
+pub fn is_remote(source: &str) -> bool {
+    source.starts_with("https://")
+        || source.starts_with("http://")
+        || source.starts_with("git@")
+        || source.ends_with(".git")
+}
+
+/// Written into a template directory restored from the cache, holding the
+/// hash of the snapshot it came from. Never rendered.
+const RESTORED_MARKER: &str = ".cfx-snapshot";
+
+/// Cache folder of the template `name`. Fails for names that would point
+/// outside the template cache, which registering them already rejects.
+pub fn template_cache_dir(name: &str) -> CfxResult<PathBuf> {
+    parse_template_name(name)?;
+    Ok(cache_dir()?.join("templates").join(name))
+}
+
+/// Cache name of a snapshot of `source`, at `rev` or the latest fetched one.
+pub fn snapshot_name(source: &str, rev: Option<&str>) -> String {
+    match rev {
+        Some(rev) => format!("template:{source}@{rev}"),
+        None => format!("template:{source}"),
+    }
+}
+
+/// Clones a remote template into the cache, replacing any previous copy.
+/// The clone goes to a temp directory first, so a cache entry is either
+/// complete or missing even when git fails or is interrupted. A snapshot of
+/// the clone is also stored in the asset cache, see [`resolve`].
+pub fn fetch(name: &str, source: &str) -> CfxResult<PathBuf> {
+    let mut git = remote_command("git", &format!("cloning template {source}"))?;
+    let target = template_cache_dir(name)?;
+    let _lock = FileLock::acquire(&lock_path(&target))?;
+
+    let staging = temp_path(&target);
+    let status = git
+        .args(["clone", "--depth", "1", "--quiet", source])
+        .arg(&staging)
+        .status();
+
+    let failure = match status {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!("git clone of {source} failed ({status})")),
+        Err(err) => Some(format!("Could not run git: {err}")),
+    };
+
+    if let Some(failure) = failure {
+        if staging.exists() {
+            remove_dir_all(&staging)?;
+        }
+
+        return Err(failure.into());
+    }
+
+    if let Err(err) = store_snapshot(source, &staging) {
+        log::warn!("Could not cache a snapshot of {source}: {err}");
+    }
+
+    if target.exists() {
+        remove_dir_all(&target)?;
+    }
+
+    rename(&staging, &target)?;
+    Ok(target)
+}
+
+/// Stores the checkout in `dir` under both its revision and the source alone.
+fn store_snapshot(source: &str, dir: &Path) -> CfxResult<()> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .output()?;
+    let rev = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    let snapshot = snapshot(dir)?;
+    let cache = Cache::open()?;
+    if output.status.success() && !rev.is_empty() {
+        cache.put(&snapshot_name(source, Some(&rev)), &snapshot)?;
+    }
+
+    cache.put(&snapshot_name(source, None), &snapshot)?;
+    Ok(())
+}
+
+/// Zips the files of a template directory, leaving out `.git`.
+pub fn snapshot(dir: &Path) -> CfxResult<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries = read_dir(&current)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+
+        for path in entries {
+            let name = path.file_name().unwrap_or_default();
+            if name == ".git" || name == RESTORED_MARKER {
+                continue;
+            }
+
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let relative = path.strip_prefix(dir)?.to_string_lossy().replace('\\', "/");
+            writer.start_file(relative, options)?;
+            writer.write_all(&read(&path)?)?;
+        }
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Unpacks a snapshot into `target` the same way [`fetch`] places a clone.
+fn restore(blob: &Path, target: &Path) -> CfxResult<()> {
+    let staging = temp_path(target);
+    let restored = ZipArchive::new(File::open(blob)?)
+        .and_then(|mut archive| archive.extract(&staging))
+        .map_err(|err| err.into())
+        .and_then(|_| {
+            write(
+                staging.join(RESTORED_MARKER),
+                blob.file_name().unwrap_or_default().as_encoded_bytes(),
+            )
+        });
+
+    if let Err(err) = restored {
+        if staging.exists() {
+            remove_dir_all(&staging)?;
+        }
+
+        return Err(format!("Could not restore template snapshot {blob:?}: {err}").into());
+    }
+
+    if target.exists() {
+        remove_dir_all(target)?;
+    }
+
+    rename(&staging, target)?;
+    Ok(())
+}
+
+/// Resolves the directory a template renders from. Remote templates whose
+/// cached copy has been deleted or is incomplete are restored from the last
+/// snapshot in the asset cache, so they work offline, and only fetched again
+/// when there is none.
+pub fn resolve(name: &str, entry: &TemplateEntry) -> CfxResult<PathBuf> {
+    if !is_remote(&entry.source) {
+        return Ok(PathBuf::from(&entry.source));
+    }
+
+    let cached = template_cache_dir(name)?;
+    if cached.join(".git").is_dir() || cached.join(RESTORED_MARKER).is_file() {
+        return Ok(cached);
+    }
+
+    if let Some(blob) = Cache::open()?.get(&snapshot_name(&entry.source, None))? {
+        let _lock = FileLock::acquire(&lock_path(&cached))?;
+        restore(&blob, &cached)?;
+        return Ok(cached);
+    }
+
+    fetch(name, &entry.source)
+}
+
+/// Replaces every `{{variable}}` placeholder in `content`, returning the name
+/// of the first variable that has no value.
+pub fn substitute(content: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+
+        let key = rest[start + 2..start + end].trim();
+        let value = variables.get(key).ok_or_else(|| key.to_owned())?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Copies a template directory into `output_dir`, substituting variables in
+/// file names and text contents. Binary files are copied verbatim.
+pub fn render(
+    template_dir: &Path,
+    output_dir: &Path,
+    variables: &HashMap<String, String>,
+) -> CfxResult<Vec<PathBuf>> {
+    let mut written = vec![];
+    render_dir(
+        template_dir,
+        template_dir,
+        output_dir,
+        variables,
+        &mut written,
+    )?;
+
+    Ok(written)
+}
+
+fn render_dir(
+    root: &Path,
+    dir: &Path,
+    output_dir: &Path,
+    variables: &HashMap<String, String>,
+    written: &mut Vec<PathBuf>,
+) -> CfxResult<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name == ".git" || name == RESTORED_MARKER)
+        {
+            continue;
+        }
+
+        let relative = path.strip_prefix(root)?.to_string_lossy().to_string();
+        let relative = substitute(&relative, variables)
+            .map_err(|key| format!("{relative}: missing template variable `{key}`"))?;
+
+        if path.is_dir() {
+            render_dir(root, &path, output_dir, variables, written)?;
+            continue;
+        }
+
+        let target = output_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let bytes = read(&path)?;
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) => substitute(&text, variables)
+                .map_err(|key| format!("{relative}: missing template variable `{key}`"))?
+                .into_bytes(),
+            Err(err) => err.into_bytes(),
+        };
+
+        File::create(&target)?.write_all(&contents)?;
+        written.push(target);
+    }
+
+    Ok(())
+}
+
+/// Renders a template with [`DUMMY_VARIABLES`] and the `custom` variables
+/// into a throwaway directory.
+pub fn validate(template_dir: &Path, custom: &[(String, String)]) -> CfxResult<()> {
+    if !template_dir.is_dir() {
+        return Err(format!("Template directory {template_dir:?} does not exist").into());
+    }
+
+    let [(_, project_name), (_, author)] = DUMMY_VARIABLES;
+    let variables = variables(project_name, author, custom);
+
+    let output = tempfile::tempdir()?;
+    render(template_dir, output.path(), &variables)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::read_to_string;
+
+    fn variables() -> HashMap<String, String> {
+        DUMMY_VARIABLES
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn substitute_test() {
+        let result = substitute("name {{ project_name }} by {{author}}", &variables());
+        assert_eq!(result.unwrap(), "name example_resource by cfx");
+    }
+
+    #[test]
+    fn substitute_missing_variable_test() {
+        let result = substitute("prefix {{org}}", &variables());
+        assert_eq!(result, Err("org".to_owned()));
+    }
+
+    #[test]
+    fn render_test() {
+        let template = tempfile::tempdir().unwrap();
+        create_dir_all(template.path().join("src")).unwrap();
+        write(
+            template.path().join("src/{{project_name}}.lua"),
+            "-- {{author}}",
+        )
+        .unwrap();
+
+        let output = tempfile::tempdir().unwrap();
+        render(template.path(), output.path(), &variables()).unwrap();
+
+        let rendered = read_to_string(output.path().join("src/example_resource.lua")).unwrap();
+        assert_eq!(rendered, "-- cfx");
+    }
+
+    #[test]
+    fn validate_reports_file_and_variable_test() {
+        let template = tempfile::tempdir().unwrap();
+        write(template.path().join("fxmanifest.lua"), "author \"{{org}}\"").unwrap();
+
+        let err = validate(template.path(), &[]).unwrap_err().to_string();
+        assert_eq!(err, "fxmanifest.lua: missing template variable `org`");
+
+        let org = [("org".to_owned(), "acme".to_owned())];
+        validate(template.path(), &org).unwrap();
+    }
+
+    #[test]
+    fn custom_variable_test() {
+        let template = tempfile::tempdir().unwrap();
+        create_dir_all(template.path().join("server")).unwrap();
+        write(
+            template.path().join("server/{{prefix}}_main.lua"),
+            "-- {{project_name}} by {{ org }}\nlocal PREFIX = '{{prefix}}'",
+        )
+        .unwrap();
+
+        let custom = [
+            parse_variable("org=Acme Roleplay").unwrap(),
+            parse_variable("prefix=acme").unwrap(),
+        ];
+        let output = tempfile::tempdir().unwrap();
+        render(
+            template.path(),
+            output.path(),
+            &super::variables("my_bank", "cfx", &custom),
+        )
+        .unwrap();
+
+        let rendered = read_to_string(output.path().join("server/acme_main.lua")).unwrap();
+        assert_eq!(
+            rendered,
+            "-- my_bank by Acme Roleplay\nlocal PREFIX = 'acme'"
+        );
+    }
+
+    #[test]
+    fn parse_variable_test() {
+        assert_eq!(
+            parse_variable("org=a=b"),
+            Ok(("org".to_owned(), "a=b".to_owned()))
+        );
+        assert_eq!(parse_variable("org=").unwrap().1, "");
+        assert_eq!(parse_variable("org").unwrap_err(), "`org` is not KEY=VALUE");
+        assert_eq!(
+            parse_variable("my-org=x").unwrap_err(),
+            "`my-org` is not a valid template variable"
+        );
+        assert_eq!(
+            parse_variable("author=x").unwrap_err(),
+            "`author` is a built-in template variable"
+        );
+    }
+
+    #[test]
+    fn template_name_test() {
+        assert_eq!(parse_template_name("esx-base").unwrap(), "esx-base");
+        for name in ["", "..", "a/../b", "/tmp/victim", "a\\b", "nested/name"] {
+            assert!(parse_template_name(name).is_err(), "{name:?}");
+            assert!(template_cache_dir(name).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn snapshot_restore_test() {
+        let template = tempfile::tempdir().unwrap();
+        create_dir_all(template.path().join(".git")).unwrap();
+        create_dir_all(template.path().join("client")).unwrap();
+        write(template.path().join(".git/HEAD"), "ref").unwrap();
+        write(template.path().join("fxmanifest.lua"), "-- {{author}}").unwrap();
+        write(template.path().join("client/main.lua"), "print(1)").unwrap();
+
+        let blob_dir = tempfile::tempdir().unwrap();
+        let blob = blob_dir.path().join("snapshot");
+        write(&blob, snapshot(template.path()).unwrap()).unwrap();
+
+        let output = tempfile::tempdir().unwrap();
+        let target = output.path().join("starter");
+        restore(&blob, &target).unwrap();
+
+        assert!(!target.join(".git").exists());
+        assert!(target.join(RESTORED_MARKER).is_file());
+        assert_eq!(
+            read_to_string(target.join("client/main.lua")).unwrap(),
+            "print(1)"
+        );
+
+        let rendered = tempfile::tempdir().unwrap();
+        let written = render(&target, rendered.path(), &variables()).unwrap();
+        assert_eq!(written.len(), 2);
+    }
+
+    #[test]
+    fn offline_fetch_test() {
+        crate::network::set_offline(true);
+        let result = fetch("offline", "https://example.invalid/template.git");
+        crate::network::set_offline(false);
+
+        let err = result.unwrap_err();
+        let offline = err.downcast_ref::<crate::network::OfflineError>().unwrap();
+        assert_eq!(
+            offline.needed,
+            "cloning template https://example.invalid/template.git"
+        );
+    }
+
+    #[test]
+    fn is_remote_test() {
+        assert!(is_remote("https://github.com/example/template"));
+        assert!(is_remote("git@github.com:example/template.git"));
+        assert!(!is_remote("./templates/starter"));
+    }
+}
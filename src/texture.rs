@@ -0,0 +1,177 @@
+use crate::archive::{PHYSICAL_BASE, VIRTUAL_BASE};
+use crate::joaat::joaat;
+use crate::CfxResult;
+
+/// Size of the `pgDictionary<grcTexture>` at the start of a `.ytd`.
+const DICTIONARY_SIZE: usize = 0x40;
+/// Size of a PC `grcTexture`.
+const TEXTURE_SIZE: usize = 0x90;
+/// `D3DFMT_DXT1`, the four characters `DXT1`.
+const FORMAT_DXT1: u32 = u32::from_le_bytes(*b"DXT1");
+/// Bytes of a DXT1 block, which covers 4x4 pixels.
+const DXT1_BLOCK_SIZE: usize = 8;
+
+/// A single-level DXT1 texture of a texture dictionary.
+#[derive(Clone, Copy)]
+pub struct Texture<'a> {
+    pub name: &'a str,
+    pub width: u16,
+    pub height: u16,
+    /// Compressed blocks, row by row
+    pub data: &'a [u8],
+}
+
+impl Texture<'_> {
+    /// Bytes of DXT1 blocks a texture of this size takes.
+    pub fn data_size(width: u16, height: u16) -> usize {
+        (width as usize).div_ceil(4) * (height as usize).div_ceil(4) * DXT1_BLOCK_SIZE
+    }
+}
+
+/// Lays out the virtual and physical pages of a `.ytd` holding `textures`:
+/// the dictionary, its sorted name hashes and texture pointers, the textures
+/// and their names in the virtual page, the texture data in the physical one.
+pub fn texture_dictionary_pages(textures: &[Texture]) -> CfxResult<(Vec<u8>, Vec<u8>)> {
+    let count = u16::try_from(textures.len())
+        .map_err(|_| format!("{} textures do not fit in a dictionary", textures.len()))?;
+
+    // The game binary searches the hashes, so textures go in hash order
+    let mut sorted = textures.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|texture| joaat(texture.name));
+    for pair in sorted.windows(2) {
+        if joaat(pair[0].name) == joaat(pair[1].name) {
+            return Err(format!("Texture `{}` is in the dictionary twice", pair[1].name).into());
+        }
+    }
+
+    let hashes_offset = DICTIONARY_SIZE;
+    let pointers_offset = (hashes_offset + textures.len() * 4).next_multiple_of(16);
+    let textures_offset = (pointers_offset + textures.len() * 8).next_multiple_of(16);
+    let mut page = vec![0u8; textures_offset + textures.len() * TEXTURE_SIZE];
+    let list = |pointer: usize| {
+        let mut list = [0u8; 16];
+        list[0..8].copy_from_slice(&(VIRTUAL_BASE + pointer as u64).to_le_bytes());
+        list[8..10].copy_from_slice(&count.to_le_bytes());
+        list[10..12].copy_from_slice(&count.to_le_bytes());
+        list
+    };
+    page[0x04..0x08].copy_from_slice(&1u32.to_le_bytes());
+    page[0x18..0x1C].copy_from_slice(&1u32.to_le_bytes());
+    page[0x20..0x30].copy_from_slice(&list(hashes_offset));
+    page[0x30..0x40].copy_from_slice(&list(pointers_offset));
+
+    let mut physical = vec![];
+    for (index, texture) in sorted.into_iter().enumerate() {
+        let expected = Texture::data_size(texture.width, texture.height);
+        if texture.width == 0 || texture.height == 0 || texture.data.len() != expected {
+            return Err(format!(
+                "Texture `{}` is {}x{} and needs {expected} bytes of DXT1 data, not {}",
+                texture.name,
+                texture.width,
+                texture.height,
+                texture.data.len()
+            )
+            .into());
+        }
+
+        let hash = hashes_offset + index * 4;
+        page[hash..hash + 4].copy_from_slice(&joaat(texture.name).to_le_bytes());
+        let start = textures_offset + index * TEXTURE_SIZE;
+        let pointer = pointers_offset + index * 8;
+        page[pointer..pointer + 8].copy_from_slice(&(VIRTUAL_BASE + start as u64).to_le_bytes());
+
+        let name = (VIRTUAL_BASE + page.len() as u64).to_le_bytes();
+        page.extend(texture.name.as_bytes());
+        page.push(0);
+        page.resize(page.len().next_multiple_of(16), 0);
+
+        physical.resize(physical.len().next_multiple_of(16), 0);
+        let data = (PHYSICAL_BASE + physical.len() as u64).to_le_bytes();
+        physical.extend(texture.data);
+
+        let stride = (texture.width as usize).div_ceil(4) * DXT1_BLOCK_SIZE;
+        let fields = &mut page[start..start + TEXTURE_SIZE];
+        fields[0x04..0x08].copy_from_slice(&1u32.to_le_bytes());
+        fields[0x28..0x30].copy_from_slice(&name);
+        fields[0x30..0x32].copy_from_slice(&1u16.to_le_bytes());
+        fields[0x50..0x52].copy_from_slice(&texture.width.to_le_bytes());
+        fields[0x52..0x54].copy_from_slice(&texture.height.to_le_bytes());
+        fields[0x54..0x56].copy_from_slice(&1u16.to_le_bytes());
+        fields[0x56..0x58].copy_from_slice(&(stride as u16).to_le_bytes());
+        fields[0x58..0x5C].copy_from_slice(&FORMAT_DXT1.to_le_bytes());
+        fields[0x5D] = 1;
+        fields[0x70..0x78].copy_from_slice(&data);
+    }
+
+    Ok((page, physical))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u64_at(page: &[u8], offset: usize) -> usize {
+        let value = u64::from_le_bytes(page[offset..offset + 8].try_into().unwrap());
+        (value & 0x0FFF_FFFF) as usize
+    }
+
+    #[test]
+    fn texture_dictionary_test() {
+        let (logo, stripes) = ([0x11; 8], [0x22; 32]);
+        let textures = [
+            Texture {
+                name: "logo",
+                width: 4,
+                height: 4,
+                data: &logo,
+            },
+            Texture {
+                name: "stripes",
+                width: 8,
+                height: 8,
+                data: &stripes,
+            },
+        ];
+        let (page, physical) = texture_dictionary_pages(&textures).unwrap();
+
+        let hashes = u64_at(&page, 0x20);
+        let hashes = [0, 4].map(|offset| {
+            u32::from_le_bytes(
+                page[hashes + offset..hashes + offset + 4]
+                    .try_into()
+                    .unwrap(),
+            )
+        });
+        assert!(hashes[0] < hashes[1]);
+        assert_eq!(u16::from_le_bytes([page[0x38], page[0x39]]), 2);
+
+        let pointers = u64_at(&page, 0x30);
+        for (index, hash) in hashes.into_iter().enumerate() {
+            let texture = textures.iter().find(|t| joaat(t.name) == hash).unwrap();
+            let start = u64_at(&page, pointers + index * 8);
+            let name = u64_at(&page, start + 0x28);
+            assert_eq!(
+                &page[name..name + texture.name.len() + 1],
+                [texture.name.as_bytes(), &[0]].concat()
+            );
+            assert_eq!(
+                u16::from_le_bytes([page[start + 0x50], page[start + 0x51]]),
+                texture.width
+            );
+            assert_eq!(&page[start + 0x58..start + 0x5C], b"DXT1");
+            let data = u64_at(&page, start + 0x70);
+            assert_eq!(&physical[data..data + texture.data.len()], texture.data);
+        }
+
+        let wrong_size = Texture {
+            name: "logo",
+            width: 4,
+            height: 4,
+            data: &stripes,
+        };
+        let err = texture_dictionary_pages(&[wrong_size]).unwrap_err();
+        assert!(err.to_string().contains("needs 8 bytes"), "{err}");
+        let twice = [textures[0], textures[0]];
+        assert!(texture_dictionary_pages(&twice).is_err());
+    }
+}
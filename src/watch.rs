@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::cancel::CancelToken;
+use crate::scan::is_ignored;
+use crate::CfxResult;
+
+/// How often the watched tree is compared against the last snapshot.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Suffixes of the backup and swap files editors write next to the file
+/// being edited, which never count as changes.
+const IGNORED_SUFFIXES: [&str; 4] = ["~", ".swp", ".swx", ".tmp"];
+
+/// Modification time and size of every watched file.
+type Snapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+/// Polls a directory tree for changed files. Folders `scan` skips, such as
+/// `.git` and `node_modules`, and editor backup files are left out.
+pub struct Watcher {
+    root: PathBuf,
+    debounce: Duration,
+    snapshot: Snapshot,
+}
+
+impl Watcher {
+    pub fn new(root: &Path, debounce: Duration) -> CfxResult<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            debounce,
+            snapshot: snapshot(root)?,
+        })
+    }
+
+    /// Every file currently watched, in a stable order.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.snapshot.keys().cloned().collect()
+    }
+
+    /// Blocks until files change and then stay unchanged for the debounce
+    /// delay, returning the ones added, modified or removed since the last
+    /// call. Returns `None` once `cancel` is cancelled.
+    pub fn wait(&mut self, cancel: &CancelToken) -> CfxResult<Option<Vec<PathBuf>>> {
+        let mut pending = BTreeSet::new();
+        let mut last_change = None;
+        loop {
+            if cancel.is_cancelled() {
+                return Ok(None);
+            }
+
+            sleep(POLL_INTERVAL.min(self.debounce));
+            let current = snapshot(&self.root)?;
+            let changed = changed_files(&self.snapshot, &current);
+            if !changed.is_empty() {
+                pending.extend(changed);
+                self.snapshot = current;
+                last_change = Some(Instant::now());
+            } else if last_change.is_some_and(|at: Instant| at.elapsed() >= self.debounce) {
+                return Ok(Some(pending.into_iter().collect()));
+            }
+        }
+    }
+}
+
+fn snapshot(root: &Path) -> CfxResult<Snapshot> {
+    let mut result = Snapshot::new();
+    collect_files(root, &mut result)?;
+    Ok(result)
+}
+
+fn collect_files(dir: &Path, result: &mut Snapshot) -> CfxResult<()> {
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            if !is_ignored(&path) {
+                collect_files(&path, result)?;
+            }
+
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        if IGNORED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+            continue;
+        }
+
+        // A file removed between listing and reading it shows up as removed
+        // on the next poll
+        if let Ok(metadata) = entry.metadata() {
+            result.insert(path, (metadata.modified()?, metadata.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Files added, modified or removed between `old` and `new`.
+fn changed_files(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    let modified = new
+        .iter()
+        .filter(|(path, state)| old.get(*path) != Some(state))
+        .map(|(path, _)| path);
+    let removed = old.keys().filter(|path| !new.contains_key(*path));
+
+    let mut changed = modified.chain(removed).cloned().collect::<Vec<_>>();
+    changed.sort();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_file, write};
+    use std::thread;
+
+    #[test]
+    fn changed_files_test() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        write(dir.path().join("node_modules/pkg/index.js"), "").unwrap();
+        write(dir.path().join("client.lua"), "print(1)").unwrap();
+        write(dir.path().join("server.lua"), "").unwrap();
+        write(dir.path().join("client.lua~"), "").unwrap();
+
+        let old = snapshot(dir.path()).unwrap();
+        assert_eq!(
+            old.keys().collect::<Vec<_>>(),
+            [
+                &dir.path().join("client.lua"),
+                &dir.path().join("server.lua")
+            ]
+        );
+
+        write(dir.path().join("client.lua"), "print(12)").unwrap();
+        write(dir.path().join("shared.lua"), "").unwrap();
+        remove_file(dir.path().join("server.lua")).unwrap();
+        write(dir.path().join("node_modules/pkg/index.js"), "changed").unwrap();
+
+        let new = snapshot(dir.path()).unwrap();
+        assert_eq!(
+            changed_files(&old, &new),
+            ["client.lua", "server.lua", "shared.lua"].map(|name| dir.path().join(name))
+        );
+        assert!(changed_files(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn watcher_debounce_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("client.lua");
+        write(&file, "").unwrap();
+
+        let mut watcher = Watcher::new(dir.path(), Duration::from_millis(300)).unwrap();
+        assert_eq!(watcher.files(), std::slice::from_ref(&file));
+
+        // Saves in quick succession come back as one change
+        let writer = {
+            let dir = dir.path().to_path_buf();
+            thread::spawn(move || {
+                for (name, content) in [
+                    ("client.lua", "a"),
+                    ("server.lua", ""),
+                    ("client.lua", "ab"),
+                ] {
+                    sleep(Duration::from_millis(50));
+                    write(dir.join(name), content).unwrap();
+                }
+            })
+        };
+
+        let changed = watcher.wait(&CancelToken::new()).unwrap().unwrap();
+        writer.join().unwrap();
+        assert_eq!(changed, [file, dir.path().join("server.lua")]);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(watcher.wait(&cancel).unwrap(), None);
+    }
+}
@@ -0,0 +1,463 @@
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use inquire::validator::Validation;
+use inquire::{Confirm, InquireError, MultiSelect, Select, Text};
+
+use crate::cancel::Interrupted;
+use crate::i18n::{t, tf};
+use crate::CfxResult;
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Makes every later confirmation accept without prompting, set from `--yes`.
+pub fn set_assume_yes(assume_yes: bool) {
+    ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Asks a yes/no question on the terminal, or answers yes under `--yes`.
+pub fn confirm(message: &str, default: bool) -> CfxResult<bool> {
+    if assume_yes() {
+        log::info!("{message} yes (--yes)");
+        return Ok(true);
+    }
+
+    Ok(Confirm::new(message).with_default(default).prompt()?)
+}
+
+/// The prompter for interactive commands: the terminal, accepting every
+/// confirmation under `--yes`.
+pub fn terminal_prompter() -> Box<dyn Prompter> {
+    match assume_yes() {
+        true => Box::new(AssumeYes(InquirePrompter)),
+        false => Box::new(InquirePrompter),
+    }
+}
+
+/// What the user answered at a prompt, or that they asked to go back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer<T> {
+    Value(T),
+    Back,
+}
+
+impl<T> Answer<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Answer<U> {
+        match self {
+            Answer::Value(value) => Answer::Value(f(value)),
+            Answer::Back => Answer::Back,
+        }
+    }
+}
+
+/// The prompts a wizard step can show. Options are passed as labels and
+/// picked by index, so a step works the same against a terminal and against
+/// scripted answers.
+pub trait Prompter {
+    /// Asks for a non-empty line of text, pre-filled with `initial`.
+    fn text(&mut self, message: &str, initial: Option<&str>) -> CfxResult<Answer<String>>;
+    fn confirm(&mut self, message: &str, default: bool) -> CfxResult<Answer<bool>>;
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        cursor: usize,
+    ) -> CfxResult<Answer<usize>>;
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        selected: &[usize],
+    ) -> CfxResult<Answer<Vec<usize>>>;
+
+    /// Asks whether to go on with the answers listed in `options`, the first
+    /// of which continues and the others change an answer.
+    fn confirm_answers(&mut self, options: &[String]) -> CfxResult<Answer<usize>> {
+        self.select(t("wizard.review"), options, 0)
+    }
+}
+
+/// Accepts every confirmation, including the answers of a wizard, without
+/// prompting, and asks everything else through the inner prompter.
+pub struct AssumeYes<P: Prompter>(pub P);
+
+impl<P: Prompter> Prompter for AssumeYes<P> {
+    fn text(&mut self, message: &str, initial: Option<&str>) -> CfxResult<Answer<String>> {
+        self.0.text(message, initial)
+    }
+
+    fn confirm(&mut self, _: &str, _: bool) -> CfxResult<Answer<bool>> {
+        Ok(Answer::Value(true))
+    }
+
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        cursor: usize,
+    ) -> CfxResult<Answer<usize>> {
+        self.0.select(message, options, cursor)
+    }
+
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        selected: &[usize],
+    ) -> CfxResult<Answer<Vec<usize>>> {
+        self.0.multi_select(message, options, selected)
+    }
+
+    fn confirm_answers(&mut self, _: &[String]) -> CfxResult<Answer<usize>> {
+        Ok(Answer::Value(0))
+    }
+}
+
+/// Prompts on the terminal. Esc goes back a step, Ctrl-C fails with
+/// [`Interrupted`].
+pub struct InquirePrompter;
+
+impl Prompter for InquirePrompter {
+    fn text(&mut self, message: &str, initial: Option<&str>) -> CfxResult<Answer<String>> {
+        let mut prompt = Text::new(message)
+            .with_help_message(t("wizard.help"))
+            .with_validator(|input: &str| match input.trim().is_empty() {
+                true => Ok(Validation::Invalid(t("wizard.invalid_input").into())),
+                false => Ok(Validation::Valid),
+            });
+        if let Some(initial) = initial {
+            prompt = prompt.with_initial_value(initial);
+        }
+
+        escape_as_back(prompt.prompt())
+    }
+
+    fn confirm(&mut self, message: &str, default: bool) -> CfxResult<Answer<bool>> {
+        escape_as_back(
+            Confirm::new(message)
+                .with_default(default)
+                .with_help_message(t("wizard.help"))
+                .prompt(),
+        )
+    }
+
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        cursor: usize,
+    ) -> CfxResult<Answer<usize>> {
+        let answer = Select::new(message, options.to_vec())
+            .with_starting_cursor(cursor)
+            .with_help_message(t("wizard.help"))
+            .raw_prompt();
+        escape_as_back(answer.map(|option| option.index))
+    }
+
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        selected: &[usize],
+    ) -> CfxResult<Answer<Vec<usize>>> {
+        let answer = MultiSelect::new(message, options.to_vec())
+            .with_default(selected)
+            .with_help_message(t("wizard.help"))
+            .raw_prompt();
+        escape_as_back(
+            answer.map(|options| options.into_iter().map(|option| option.index).collect()),
+        )
+    }
+}
+
+fn escape_as_back<T>(result: Result<T, InquireError>) -> CfxResult<Answer<T>> {
+    match result {
+        Ok(value) => Ok(Answer::Value(value)),
+        Err(InquireError::OperationCanceled) => Ok(Answer::Back),
+        Err(InquireError::OperationInterrupted) => Err(Interrupted.into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+/// A canned reply for [`ScriptedPrompter`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scripted {
+    Text(String),
+    Confirm(bool),
+    Select(usize),
+    MultiSelect(Vec<usize>),
+    Back,
+}
+
+#[cfg(test)]
+/// Replies to prompts from a list, in order, so wizards can run without a
+/// terminal. A prompt of another kind than the next reply, or a prompt after
+/// the last one, is an error naming the prompt.
+pub struct ScriptedPrompter {
+    replies: VecDeque<Scripted>,
+    /// Every message prompted so far
+    pub asked: Vec<String>,
+}
+
+#[cfg(test)]
+impl ScriptedPrompter {
+    pub fn new(replies: impl IntoIterator<Item = Scripted>) -> Self {
+        Self {
+            replies: replies.into_iter().collect(),
+            asked: vec![],
+        }
+    }
+
+    fn next<T>(
+        &mut self,
+        message: &str,
+        reply: impl FnOnce(Scripted) -> Option<T>,
+    ) -> CfxResult<Answer<T>> {
+        self.asked.push(message.to_owned());
+        match self.replies.pop_front() {
+            Some(Scripted::Back) => Ok(Answer::Back),
+            Some(scripted) => reply(scripted.clone())
+                .map(Answer::Value)
+                .ok_or_else(|| format!("Scripted {scripted:?} does not answer `{message}`").into()),
+            None => Err(format!("No scripted answer left for `{message}`").into()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Prompter for ScriptedPrompter {
+    fn text(&mut self, message: &str, _: Option<&str>) -> CfxResult<Answer<String>> {
+        self.next(message, |scripted| match scripted {
+            Scripted::Text(text) => Some(text),
+            _ => None,
+        })
+    }
+
+    fn confirm(&mut self, message: &str, _: bool) -> CfxResult<Answer<bool>> {
+        self.next(message, |scripted| match scripted {
+            Scripted::Confirm(yes) => Some(yes),
+            _ => None,
+        })
+    }
+
+    fn select(&mut self, message: &str, options: &[String], _: usize) -> CfxResult<Answer<usize>> {
+        self.next(message, |scripted| match scripted {
+            Scripted::Select(index) if index < options.len() => Some(index),
+            _ => None,
+        })
+    }
+
+    fn multi_select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        _: &[usize],
+    ) -> CfxResult<Answer<Vec<usize>>> {
+        self.next(message, |scripted| match scripted {
+            Scripted::MultiSelect(indices) if indices.iter().all(|&i| i < options.len()) => {
+                Some(indices)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// One question of a wizard, filling in part of the state `S`.
+pub trait Step<S> {
+    /// Short name shown on the confirmation screen
+    fn title(&self) -> &'static str;
+
+    /// Prompts for the answer and stores it in `state`. Asked again after
+    /// going back, so the previous answer should be the default.
+    fn ask(&self, prompter: &mut dyn Prompter, state: &mut S) -> CfxResult<Answer<()>>;
+
+    /// Whether the step is asked at all given the earlier answers.
+    fn applies(&self, _state: &S) -> bool {
+        true
+    }
+
+    /// The current answer for the confirmation screen, `None` while it is
+    /// unanswered.
+    fn summary(&self, state: &S) -> Option<String>;
+}
+
+/// Asks every applicable unanswered step in order. Going back re-asks the
+/// previous step, and going back from the first one fails with
+/// [`Interrupted`]. Once everything is answered a confirmation screen lists
+/// the answers, and picking one re-asks that step, then any step that
+/// became applicable but is unanswered, before showing the screen again.
+/// Nothing is shown when `state` starts out fully answered.
+pub fn run_wizard<S>(
+    steps: &[&dyn Step<S>],
+    prompter: &mut dyn Prompter,
+    state: &mut S,
+) -> CfxResult<()> {
+    let mut history = vec![];
+    let mut current = next_open(steps, state, 0);
+    if current.is_none() {
+        return Ok(());
+    }
+
+    loop {
+        let Some(index) = current else {
+            let answered = (0..steps.len())
+                .filter(|&index| steps[index].applies(state))
+                .filter_map(|index| Some((index, steps[index].summary(state)?)))
+                .collect::<Vec<_>>();
+            let options = [t("wizard.continue").to_owned()]
+                .into_iter()
+                .chain(answered.iter().map(|(index, summary)| {
+                    tf(
+                        "wizard.change",
+                        &[("step", steps[*index].title()), ("answer", summary)],
+                    )
+                }))
+                .collect::<Vec<_>>();
+
+            match prompter.confirm_answers(&options)? {
+                Answer::Value(0) => return Ok(()),
+                Answer::Value(choice) => {
+                    let index = answered[choice - 1].0;
+                    if steps[index].ask(prompter, state)? == Answer::Value(()) {
+                        current = next_open(steps, state, index + 1);
+                    }
+                }
+                Answer::Back => current = Some(history.pop().ok_or(Interrupted)?),
+            }
+
+            continue;
+        };
+
+        match steps[index].ask(prompter, state)? {
+            Answer::Value(()) => {
+                history.push(index);
+                current = next_open(steps, state, index + 1);
+            }
+            Answer::Back => current = Some(history.pop().ok_or(Interrupted)?),
+        }
+    }
+}
+
+/// First step from `start` on that applies and is unanswered.
+fn next_open<S>(steps: &[&dyn Step<S>], state: &S, start: usize) -> Option<usize> {
+    (start..steps.len())
+        .find(|&index| steps[index].applies(state) && steps[index].summary(state).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Order {
+        size: Option<String>,
+        extra: Option<bool>,
+        topping: Option<String>,
+    }
+
+    enum OrderStep {
+        Size,
+        Extra,
+        Topping,
+    }
+
+    impl Step<Order> for OrderStep {
+        fn title(&self) -> &'static str {
+            match self {
+                OrderStep::Size => "size",
+                OrderStep::Extra => "extra",
+                OrderStep::Topping => "topping",
+            }
+        }
+
+        fn ask(&self, prompter: &mut dyn Prompter, order: &mut Order) -> CfxResult<Answer<()>> {
+            Ok(match self {
+                OrderStep::Size => prompter
+                    .text("Size?", order.size.as_deref())?
+                    .map(|size| order.size = Some(size)),
+                OrderStep::Extra => prompter
+                    .confirm("Extra?", false)?
+                    .map(|extra| order.extra = Some(extra)),
+                OrderStep::Topping => prompter
+                    .text("Topping?", None)?
+                    .map(|topping| order.topping = Some(topping)),
+            })
+        }
+
+        fn applies(&self, order: &Order) -> bool {
+            !matches!(self, OrderStep::Topping) || order.extra == Some(true)
+        }
+
+        fn summary(&self, order: &Order) -> Option<String> {
+            match self {
+                OrderStep::Size => order.size.clone(),
+                OrderStep::Extra => order.extra.map(|extra| extra.to_string()),
+                OrderStep::Topping => order.topping.clone(),
+            }
+        }
+    }
+
+    const STEPS: [&dyn Step<Order>; 3] = [&OrderStep::Size, &OrderStep::Extra, &OrderStep::Topping];
+
+    #[test]
+    fn wizard_back_test() {
+        let mut prompter = ScriptedPrompter::new([
+            Scripted::Text("small".into()),
+            Scripted::Back,
+            Scripted::Text("large".into()),
+            Scripted::Confirm(false),
+            Scripted::Select(0),
+        ]);
+        let mut order = Order::default();
+        run_wizard(&STEPS, &mut prompter, &mut order).unwrap();
+
+        assert_eq!(order.size.as_deref(), Some("large"));
+        assert_eq!(order.extra, Some(false));
+        assert_eq!(order.topping, None);
+        assert_eq!(
+            prompter.asked,
+            [
+                "Size?",
+                "Extra?",
+                "Size?",
+                "Extra?",
+                "Continue with these answers?"
+            ]
+        );
+    }
+
+    #[test]
+    fn wizard_jump_back_test() {
+        let mut prompter = ScriptedPrompter::new([
+            Scripted::Text("small".into()),
+            Scripted::Confirm(false),
+            // Changing `extra` makes `topping` apply, so it is asked next
+            Scripted::Select(2),
+            Scripted::Confirm(true),
+            Scripted::Text("cheese".into()),
+            Scripted::Select(0),
+        ]);
+        let mut order = Order::default();
+        run_wizard(&STEPS, &mut prompter, &mut order).unwrap();
+
+        assert_eq!(order.extra, Some(true));
+        assert_eq!(order.topping.as_deref(), Some("cheese"));
+    }
+
+    #[test]
+    fn wizard_cancel_test() {
+        let mut prompter = ScriptedPrompter::new([Scripted::Back]);
+        let err = run_wizard(&STEPS, &mut prompter, &mut Order::default()).unwrap_err();
+        assert!(err.downcast_ref::<Interrupted>().is_some());
+
+        let mut prompter = ScriptedPrompter::new([Scripted::Confirm(true)]);
+        let err = run_wizard(&STEPS, &mut prompter, &mut Order::default()).unwrap_err();
+        assert!(err.to_string().contains("`Size?`"));
+    }
+}
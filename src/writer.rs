@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::commands::unpack::{ResourceChunkFlags, BUCKETS_CAPACITY, BUCKETS_SHIFTS};
+use crate::magic::RSC7_MAGIC;
+use crate::math::{Vec3, Vec4};
+use crate::CfxResult;
+
+/// Little-endian writers mirroring the typed `FArchiveExt` readers.
+#[allow(dead_code)]
+pub trait FWriterExt {
+    fn write_vec3(&mut self, value: Vec3);
+    fn write_vec4(&mut self, value: Vec4);
+    fn write_array_u32(&mut self, values: &[u32]);
+    fn write_array_u16(&mut self, values: &[u16]);
+}
+
+impl FWriterExt for Vec<u8> {
+    fn write_vec3(&mut self, value: Vec3) {
+        for component in <[f32; 3]>::from(value) {
+            self.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+
+    fn write_vec4(&mut self, value: Vec4) {
+        for component in <[f32; 4]>::from(value) {
+            self.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+
+    fn write_array_u32(&mut self, values: &[u32]) {
+        for value in values {
+            self.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn write_array_u16(&mut self, values: &[u16]) {
+        for value in values {
+            self.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Deflate level [`write_rsc7`] compresses pages with.
+pub const DEFAULT_COMPRESSION: u32 = 6;
+
+/// Builds an RSC7 file whose virtual and physical pages hold the deflated
+/// `virtual_data` and `physical_data`, each zero-padded to the page size
+/// described by its flags. Like the game, the flags' high nibbles repeat
+/// `version`.
+pub fn write_rsc7(version: u8, virtual_data: &[u8], physical_data: &[u8]) -> CfxResult<Vec<u8>> {
+    write_rsc7_with_level(version, virtual_data, physical_data, DEFAULT_COMPRESSION)
+}
+
+/// [`write_rsc7`] with a deflate level from 0 (stored) to 9 (smallest).
+pub fn write_rsc7_with_level(
+    version: u8,
+    virtual_data: &[u8],
+    physical_data: &[u8],
+    level: u32,
+) -> CfxResult<Vec<u8>> {
+    let mut result = vec![];
+    let (virtual_flags, virtual_page) = build_page(virtual_data, level)?;
+    let (physical_flags, physical_page) = build_page(physical_data, level)?;
+
+    let version = version as u32;
+    let virtual_flags = virtual_flags | (version >> 4) << 28;
+    let physical_flags = physical_flags | (version & 0xF) << 28;
+
+    for value in [RSC7_MAGIC, 0, virtual_flags, physical_flags, version] {
+        result.extend_from_slice(&value.to_le_bytes());
+    }
+
+    result.extend_from_slice(&virtual_page);
+    result.extend_from_slice(&physical_page);
+    Ok(result)
+}
+
+/// Deflates `data` and returns the page flags, computed from the size of the
+/// compressed output, alongside the padded page bytes.
+pub fn build_page(data: &[u8], level: u32) -> CfxResult<(u32, Vec<u8>)> {
+    if level > 9 {
+        return Err(format!("compression level {level} is not between 0 and 9").into());
+    }
+
+    let mut encoder = DeflateEncoder::new(vec![], Compression::new(level));
+    encoder.write_all(data)?;
+    let mut page = encoder.finish()?;
+    let flags = page_flags(page.len())?;
+
+    page.resize(ResourceChunkFlags::new(flags).get_size()? as usize, 0);
+    Ok((flags, page))
+}
+
+/// Encodes the smallest base shift whose buckets can hold `size` bytes,
+/// filling the buckets greedily from the largest chunk size down. Sizes are
+/// counted in u64 and the page has to stay below 4 GiB like any page read.
+pub fn page_flags(size: usize) -> CfxResult<u32> {
+    let too_big = || format!("{size} bytes do not fit in a single resource page").into();
+    let size = size as u64;
+    for base_shift in 0..=0xF {
+        let base_size = 0x200u64 << base_shift;
+        let Some(rounded) = size.div_ceil(base_size).checked_mul(base_size) else {
+            return Err(too_big());
+        };
+
+        if rounded > u32::MAX as u64 {
+            return Err(too_big());
+        }
+
+        let mut remaining = rounded;
+        let mut value = base_shift as u32;
+        for (i, (&shift, &capacity)) in BUCKETS_SHIFTS.iter().zip(&BUCKETS_CAPACITY).enumerate() {
+            let chunk_size = base_size << (8 - i);
+            let count = (remaining / chunk_size).min(capacity as u64);
+
+            remaining -= count * chunk_size;
+            value |= (count as u32) << shift;
+        }
+
+        if remaining == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(too_big())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_flags_test() {
+        for size in [0, 1, 0x200, 0x201, 0x12345, 0x100000] {
+            let flags = page_flags(size).unwrap();
+            let page_size = ResourceChunkFlags::new(flags).get_size().unwrap() as usize;
+
+            assert!(page_size >= size, "{size:#x} -> {page_size:#x}");
+            assert_eq!(page_size % 0x200, 0);
+        }
+
+        for size in [u32::MAX as usize, usize::MAX] {
+            let err = page_flags(size).unwrap_err().to_string();
+            assert!(
+                err.contains("do not fit in a single resource page"),
+                "{err}"
+            );
+        }
+    }
+}
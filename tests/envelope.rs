@@ -0,0 +1,111 @@
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+const FIELDS: [&str; 7] = [
+    "tool", "version", "command", "ok", "data", "warnings", "error",
+];
+
+/// Runs `cfx --json <args>` with its config and cache below `home`, checks
+/// that stdout holds the envelope and nothing else, and returns it parsed.
+fn run(home: &Path, args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_cfx-cli"))
+        .arg("--json")
+        .args(args)
+        .env("CFX_CONFIG_DIR", home.join("config"))
+        .env("CFX_CACHE_DIR", home.join("cache"))
+        .env_remove("CFX_RESOURCES_ROOT")
+        .env("LANG", "C")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1, "{args:?}: stdout is not one line\n{stdout}");
+    let value: Value = serde_json::from_str(lines[0]).unwrap();
+    for field in FIELDS {
+        assert!(value.get(field).is_some(), "{args:?}: missing `{field}`");
+    }
+
+    assert_eq!(value["tool"], "cfx");
+    assert_eq!(value["command"], args[0]);
+    assert_eq!(value["error"].is_null(), value["ok"] == true);
+    assert_eq!(output.status.success(), value["ok"] == true, "{args:?}");
+    value
+}
+
+#[test]
+fn envelope_stdout_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = dir.path();
+    let shop = home.join("shop");
+    create_dir_all(&shop).unwrap();
+    write(
+        shop.join("fxmanifest.lua"),
+        "fx_version \"cerulean\"\ngame \"gta5\"\n\nclient_script \"client.lua\"\n",
+    )
+    .unwrap();
+    write(shop.join("client.lua"), "print('shop')\n").unwrap();
+    let shop = shop.to_str().unwrap();
+
+    let check = run(home, &["check", shop]);
+    assert_eq!(check["data"]["checked"], 1);
+    let validate = run(home, &["validate", shop]);
+    assert_eq!(validate["ok"], true);
+
+    // Failures with findings keep them as data
+    write(home.join("shop/client.lua"), "print('shop'\n").unwrap();
+    let check = run(home, &["check", shop]);
+    assert_eq!(check["ok"], false);
+    assert_eq!(check["data"]["errors"][0]["file"], "client.lua");
+    write(
+        home.join("shop/fxmanifest.lua"),
+        "fx_version \"cerulean\"\ngame \"gta5\"\n\nclient_script \"missing.lua\"\n",
+    )
+    .unwrap();
+    let validate = run(home, &["validate", shop]);
+    assert_eq!(validate["ok"], false);
+    assert_eq!(validate["data"]["errors"].as_array().unwrap().len(), 1);
+    let missing = home.join("missing");
+    let validate = run(home, &["validate", missing.to_str().unwrap()]);
+    assert!(validate["data"].is_null());
+
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/minimal.rsc7");
+    let strings = run(home, &["strings", fixture]);
+    assert!(!strings["data"]["strings"].as_array().unwrap().is_empty());
+    let not_rsc7 = home.join("shop/client.lua");
+    let strings = run(home, &["strings", not_rsc7.to_str().unwrap()]);
+    assert_eq!(strings["ok"], false);
+
+    let template = home.join("template");
+    create_dir_all(&template).unwrap();
+    let added = run(
+        home,
+        &["template", "add", "bank", template.to_str().unwrap()],
+    );
+    assert_eq!(added["data"]["action"], "add");
+    let listed = run(home, &["template", "list"]);
+    assert_eq!(listed["data"]["templates"][0]["name"], "bank");
+    let removed = run(home, &["template", "remove", "jobs"]);
+    assert_eq!(removed["ok"], false);
+
+    let store = home.join("cache/store");
+    create_dir_all(&store).unwrap();
+    write(
+        store.join("index.json"),
+        r#"{"template:bank@1":{"hash":"00","size":1,"last_access":1}}"#,
+    )
+    .unwrap();
+    let cache = run(home, &["cache", "list"]);
+    assert_eq!(cache["data"].as_array().unwrap().len(), 1);
+
+    // A cache directory that is a file fails the command
+    let blocked = home.join("blocked");
+    create_dir_all(&blocked).unwrap();
+    write(blocked.join("cache"), "").unwrap();
+    let cache = run(&blocked, &["cache", "clear"]);
+    assert_eq!(cache["ok"], false);
+}